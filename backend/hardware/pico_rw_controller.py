@@ -4,8 +4,9 @@ Controls physical motors to visualize reaction wheel rotation speeds.
 Supports up to 3 axes (X, Y, Z) using separate Pico boards.
 
 HID Protocol:
-  Output Report (Host → Pico): [speed_normalized] (int16_t, little-endian)
-    - Normalized speed: -32767 = -100%, 0 = stop, +32767 = +100%
+  Output Report (Host → Pico, report ID 1): [speed_normalized, checksum]
+    - Normalized speed: int16_t little-endian, -32767 = -100%, 0 = stop, +32767 = +100%
+    - Checksum: CRC-8/SMBUS (poly 0x07) of the two speed bytes; mismatches are dropped
     - Pico maps this to motor duty cycle (0-100%)
 
 Multi-Axis Configuration:
@@ -33,6 +34,19 @@ except ImportError:
 VID = 0x2E8A
 PID = 0x0B33
 
+# HID report ID of the speed output report (see rw-core/src/protocol.rs)
+SPEED_REPORT_ID = 1
+
+
+def _crc8(data: bytes) -> int:
+    """CRC-8/SMBUS (poly 0x07, init 0x00), matching rw-core/src/crc.rs."""
+    crc = 0
+    for byte in data:
+        crc ^= byte
+        for _ in range(8):
+            crc = ((crc << 1) ^ 0x07) & 0xFF if crc & 0x80 else (crc << 1) & 0xFF
+    return crc
+
 
 class PicoRWController:
     """Controls up to 3 Raspberry Pi Picos for 3-axis RW visualization."""
@@ -206,9 +220,10 @@ class PicoRWController:
         normalized = max(-32767, min(32767, normalized))
         speed_normalized = int(normalized)
 
-        # Pack as int16_t little-endian
-        # HID report: [report_id, speed_normalized_low, speed_normalized_high]
-        report = struct.pack("<Bh", 0, speed_normalized)
+        # Pack as int16_t little-endian followed by its checksum
+        # HID report: [report_id, speed_normalized_low, speed_normalized_high, checksum]
+        payload = struct.pack("<h", speed_normalized)
+        report = bytes([SPEED_REPORT_ID]) + payload + bytes([_crc8(payload)])
 
         try:
             device.write(report)
@@ -0,0 +1,159 @@
+//! HID report descriptor of a motor interface
+//!
+//! Report IDs 1 (speed command and status) and 9 (percent command) are laid
+//! out the same on every motor interface, so `motor_descriptor!` writes them
+//! and the interface adds its own report groups. By default every field is a
+//! vendor-defined byte. The `standard_usages` build describes the commanded
+//! and applied speed as signed 16-bit (percent: 8-bit) Generic Desktop Vbrz
+//! values instead, so HID debuggers and WebHID can decode them. The report
+//! bytes are the same in both builds.
+
+/// `gen_hid_descriptor` struct `$name` for one motor interface (application collection usage `$usage`)
+///
+/// `{ groups }` are the remaining report groups and `{ fields }` their struct fields.
+#[cfg(not(feature = "standard_usages"))]
+macro_rules! motor_descriptor {
+    (
+        $(#[$attr:meta])* $vis:vis struct $name:ident, usage = $usage:tt,
+        { $($groups:tt)* },
+        { $($fields:tt)* }
+    ) => {
+        #[gen_hid_descriptor(
+            (collection = APPLICATION, usage_page = VENDOR_DEFINED_START, usage = $usage) = {
+                (report_id = 0x01,) = {
+                    speed_normalized_low=output;
+                    speed_normalized_high=output;
+                    speed_checksum=output;
+                    status_speed_normalized_low=input;
+                    status_speed_normalized_high=input;
+                    status_duty=input;
+                    status_flags=input;
+                    status_rpm_low=input;
+                    status_rpm_high=input;
+                    status_temperature_low=input;
+                    status_temperature_high=input;
+                    status_heartbeat_0=input;
+                    status_heartbeat_1=input;
+                    status_heartbeat_2=input;
+                    status_heartbeat_3=input;
+                    status_uptime_ms_0=input;
+                    status_uptime_ms_1=input;
+                    status_uptime_ms_2=input;
+                    status_uptime_ms_3=input;
+                    status_faults_low=input;
+                    status_faults_high=input;
+                };
+                (report_id = 0x09,) = {
+                    speed_percent=output;
+                    speed_percent_checksum=output;
+                };
+                $($groups)*
+            }
+        )]
+        $(#[$attr])*
+        $vis struct $name {
+            speed_normalized_low: u8,
+            speed_normalized_high: u8,
+            speed_checksum: u8,
+            status_speed_normalized_low: u8,
+            status_speed_normalized_high: u8,
+            status_duty: u8,
+            status_flags: u8,
+            status_rpm_low: u8,
+            status_rpm_high: u8,
+            status_temperature_low: u8,
+            status_temperature_high: u8,
+            status_heartbeat_0: u8,
+            status_heartbeat_1: u8,
+            status_heartbeat_2: u8,
+            status_heartbeat_3: u8,
+            status_uptime_ms_0: u8,
+            status_uptime_ms_1: u8,
+            status_uptime_ms_2: u8,
+            status_uptime_ms_3: u8,
+            status_faults_low: u8,
+            status_faults_high: u8,
+            speed_percent: u8,
+            speed_percent_checksum: u8,
+            $($fields)*
+        }
+    };
+}
+
+#[cfg(feature = "standard_usages")]
+macro_rules! motor_descriptor {
+    (
+        $(#[$attr:meta])* $vis:vis struct $name:ident, usage = $usage:tt,
+        { $($groups:tt)* },
+        { $($fields:tt)* }
+    ) => {
+        // 0x45: Generic Desktop Vbrz, rotation about the wheel's own axis
+        #[gen_hid_descriptor(
+            (collection = APPLICATION, usage_page = VENDOR_DEFINED_START, usage = $usage) = {
+                (report_id = 0x01,) = {
+                    (usage_page = GENERIC_DESKTOP, usage = 0x45,) = {
+                        speed_normalized=output;
+                    };
+                    (usage_page = VENDOR_DEFINED_START, usage = $usage,) = {
+                        speed_checksum=output;
+                    };
+                    (usage_page = GENERIC_DESKTOP, usage = 0x45,) = {
+                        status_speed_normalized=input;
+                    };
+                    (usage_page = VENDOR_DEFINED_START, usage = $usage,) = {
+                        status_duty=input;
+                        status_flags=input;
+                        status_rpm_low=input;
+                        status_rpm_high=input;
+                        status_temperature_low=input;
+                        status_temperature_high=input;
+                        status_heartbeat_0=input;
+                        status_heartbeat_1=input;
+                        status_heartbeat_2=input;
+                        status_heartbeat_3=input;
+                        status_uptime_ms_0=input;
+                        status_uptime_ms_1=input;
+                        status_uptime_ms_2=input;
+                        status_uptime_ms_3=input;
+                        status_faults_low=input;
+                        status_faults_high=input;
+                    };
+                };
+                (report_id = 0x09,) = {
+                    (usage_page = GENERIC_DESKTOP, usage = 0x45,) = {
+                        speed_percent=output;
+                    };
+                    (usage_page = VENDOR_DEFINED_START, usage = $usage,) = {
+                        speed_percent_checksum=output;
+                    };
+                };
+                $($groups)*
+            }
+        )]
+        $(#[$attr])*
+        $vis struct $name {
+            speed_normalized: i16,
+            speed_checksum: u8,
+            status_speed_normalized: i16,
+            status_duty: u8,
+            status_flags: u8,
+            status_rpm_low: u8,
+            status_rpm_high: u8,
+            status_temperature_low: u8,
+            status_temperature_high: u8,
+            status_heartbeat_0: u8,
+            status_heartbeat_1: u8,
+            status_heartbeat_2: u8,
+            status_heartbeat_3: u8,
+            status_uptime_ms_0: u8,
+            status_uptime_ms_1: u8,
+            status_uptime_ms_2: u8,
+            status_uptime_ms_3: u8,
+            status_faults_low: u8,
+            status_faults_high: u8,
+            speed_percent: i8,
+            speed_percent_checksum: u8,
+            $($fields)*
+        }
+    };
+}
@@ -0,0 +1,86 @@
+//! Quadrature encoder on GPIO19 (A) / GPIO20 (B)
+//!
+//! Both channels interrupt on every edge; IO_IRQ_BANK0 decodes the transition
+//! and accumulates a signed count that the main loop turns into RPM. With the
+//! polarity reversed (swapped A/B wiring) each step is counted the other way.
+
+use core::cell::{Cell, RefCell};
+
+use critical_section::Mutex;
+use embedded_hal::digital::InputPin;
+use rp_pico::hal::gpio::bank0::{Gpio19, Gpio20};
+use rp_pico::hal::gpio::{FunctionSioInput, Interrupt, Pin, PullUp};
+use rp_pico::hal::pac::{self, interrupt};
+
+use rw_core::encoder::quadrature_step;
+
+pub type PinA = Pin<Gpio19, FunctionSioInput, PullUp>;
+pub type PinB = Pin<Gpio20, FunctionSioInput, PullUp>;
+
+const EDGES: [Interrupt; 2] = [Interrupt::EdgeHigh, Interrupt::EdgeLow];
+
+struct EncoderPins {
+    a: PinA,
+    b: PinB,
+    // Last 2-bit state (A << 1 | B)
+    state: u8,
+}
+
+impl EncoderPins {
+    fn read(&mut self) -> u8 {
+        let a = self.a.is_high().unwrap_or(false) as u8;
+        let b = self.b.is_high().unwrap_or(false) as u8;
+        (a << 1) | b
+    }
+}
+
+static ENCODER: Mutex<RefCell<Option<EncoderPins>>> = Mutex::new(RefCell::new(None));
+static COUNT: Mutex<Cell<i32>> = Mutex::new(Cell::new(0));
+static REVERSED: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+
+/// Hand the encoder pins to the interrupt handler and start counting
+pub fn init(a: PinA, b: PinB) {
+    for edge in EDGES {
+        a.set_interrupt_enabled(edge, true);
+        b.set_interrupt_enabled(edge, true);
+    }
+
+    let mut pins = EncoderPins { a, b, state: 0 };
+    pins.state = pins.read();
+    critical_section::with(|cs| ENCODER.borrow_ref_mut(cs).replace(pins));
+
+    // SAFETY: the handler only touches state guarded by critical sections
+    unsafe {
+        pac::NVIC::unmask(pac::Interrupt::IO_IRQ_BANK0);
+    }
+}
+
+/// Decode with A and B swapped from now on; the count continues from where it is
+pub fn set_reversed(reversed: bool) {
+    critical_section::with(|cs| REVERSED.borrow(cs).set(reversed));
+}
+
+/// Accumulated signed encoder count (wraps)
+pub fn count() -> i32 {
+    critical_section::with(|cs| COUNT.borrow(cs).get())
+}
+
+#[interrupt]
+fn IO_IRQ_BANK0() {
+    critical_section::with(|cs| {
+        if let Some(pins) = ENCODER.borrow_ref_mut(cs).as_mut() {
+            for edge in EDGES {
+                pins.a.clear_interrupt(edge);
+                pins.b.clear_interrupt(edge);
+            }
+
+            let state = pins.read();
+            let step = quadrature_step(pins.state, state);
+            let step = if REVERSED.borrow(cs).get() { -step } else { step };
+            pins.state = state;
+
+            let count = COUNT.borrow(cs);
+            count.set(count.get().wrapping_add(step as i32));
+        }
+    });
+}
@@ -0,0 +1,80 @@
+//! Readable feature reports (HID GET_REPORT)
+//!
+//! usbd-hid rejects GET_REPORT, so this class sits in front of `HIDClass` in the
+//! poll list and answers GET_REPORT(Feature) from a table of published reports.
+//! Everything else falls through to `HIDClass`.
+
+use usb_device::class_prelude::*;
+use usb_device::control::{Recipient, RequestType};
+
+const HID_REQ_GET_REPORT: u8 = 0x01;
+const HID_REPORT_TYPE_FEATURE: u8 = 3;
+
+/// Readable reports that can be published at once
+const MAX_REPORTS: usize = 5;
+/// Largest payload (without the report ID byte); usb-device's control buffer
+/// holds 128 bytes and sends longer replies than the 64-byte EP0 in several packets
+const MAX_PAYLOAD: usize = 127;
+
+#[derive(Clone, Copy)]
+struct Slot {
+    id: u8,
+    len: usize,
+    // Report ID followed by the payload
+    data: [u8; MAX_PAYLOAD + 1],
+}
+
+pub struct FeatureReports {
+    interface: u16,
+    slots: [Option<Slot>; MAX_REPORTS],
+}
+
+impl FeatureReports {
+    /// `interface` is the HID interface number the requests are addressed to
+    pub fn new(interface: u8) -> Self {
+        Self {
+            interface: interface as u16,
+            slots: [None; MAX_REPORTS],
+        }
+    }
+
+    /// Publish (or replace) the contents returned for feature report `id`
+    ///
+    /// `payload` excludes the report ID byte.
+    pub fn set(&mut self, id: u8, payload: &[u8]) {
+        let len = payload.len().min(MAX_PAYLOAD);
+        let mut data = [0; MAX_PAYLOAD + 1];
+        data[0] = id;
+        data[1..=len].copy_from_slice(&payload[..len]);
+        let slot = Slot { id, len: len + 1, data };
+
+        let index = self
+            .slots
+            .iter()
+            .position(|s| matches!(s, Some(s) if s.id == id))
+            .or_else(|| self.slots.iter().position(Option::is_none));
+        match index {
+            Some(index) => self.slots[index] = Some(slot),
+            None => defmt::error!("Feature report table full, dropping report ID {}", id),
+        }
+    }
+}
+
+impl<B: UsbBus> UsbClass<B> for FeatureReports {
+    fn control_in(&mut self, xfer: ControlIn<B>) {
+        let req = *xfer.request();
+        if req.request_type != RequestType::Class
+            || req.recipient != Recipient::Interface
+            || req.index != self.interface
+            || req.request != HID_REQ_GET_REPORT
+            || (req.value >> 8) as u8 != HID_REPORT_TYPE_FEATURE
+        {
+            return;
+        }
+
+        let id = req.value as u8;
+        if let Some(slot) = self.slots.iter().flatten().find(|s| s.id == id) {
+            xfer.accept_with(&slot.data[..slot.len]).ok();
+        }
+    }
+}
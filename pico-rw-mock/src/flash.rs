@@ -0,0 +1,87 @@
+//! Config persistence in the last 4KB flash sector
+//!
+//! Erasing and programming go through the boot ROM with XIP disabled, so the
+//! sequence runs from RAM with interrupts off. boot2 is copied to RAM first and
+//! re-run afterwards to restore fast XIP (as the Pico SDK does).
+
+use core::ptr::addr_of_mut;
+
+use rp_pico::hal::rom_data;
+
+const XIP_BASE: usize = 0x1000_0000;
+const FLASH_SIZE: usize = 2048 * 1024;
+const SECTOR_SIZE: usize = 4096;
+/// Smallest programmable unit
+const PAGE_SIZE: usize = 256;
+/// Offset of the config sector from the start of flash (kept clear of the firmware image)
+const CONFIG_OFFSET: usize = FLASH_SIZE - SECTOR_SIZE;
+/// 4KB sector erase command
+const SECTOR_ERASE_CMD: u8 = 0x20;
+
+/// Copy of boot2 (first 256 bytes of flash), callable while XIP is down
+static mut BOOT2_COPY: [u32; 64] = [0; 64];
+
+/// Boot ROM entry points, looked up before XIP goes away
+struct RomFunctions {
+    connect_internal_flash: unsafe extern "C" fn(),
+    flash_exit_xip: unsafe extern "C" fn(),
+    flash_range_erase: unsafe extern "C" fn(u32, usize, u32, u8),
+    flash_range_program: unsafe extern "C" fn(u32, *const u8, usize),
+    flash_flush_cache: unsafe extern "C" fn(),
+    boot2: unsafe extern "C" fn(),
+}
+
+/// First page of the config sector (memory-mapped through XIP)
+pub fn read() -> &'static [u8] {
+    unsafe { core::slice::from_raw_parts((XIP_BASE + CONFIG_OFFSET) as *const u8, PAGE_SIZE) }
+}
+
+/// Replace the config sector with `data` (truncated to a page, padded with 0xFF)
+///
+/// Blocks for roughly 50ms with interrupts disabled.
+pub fn write(data: &[u8]) {
+    let mut page = [0xFF; PAGE_SIZE];
+    let len = data.len().min(PAGE_SIZE);
+    page[..len].copy_from_slice(&data[..len]);
+    update_sector(Some(&page));
+}
+
+/// Erase the config sector
+pub fn erase() {
+    update_sector(None);
+}
+
+fn update_sector(page: Option<&[u8; PAGE_SIZE]>) {
+    critical_section::with(|_| unsafe {
+        let boot2 = &mut *addr_of_mut!(BOOT2_COPY);
+        core::ptr::copy_nonoverlapping(XIP_BASE as *const u32, boot2.as_mut_ptr(), boot2.len());
+
+        let rom = RomFunctions {
+            connect_internal_flash: rom_data::connect_internal_flash::ptr(),
+            flash_exit_xip: rom_data::flash_exit_xip::ptr(),
+            flash_range_erase: rom_data::flash_range_erase::ptr(),
+            flash_range_program: rom_data::flash_range_program::ptr(),
+            flash_flush_cache: rom_data::flash_flush_cache::ptr(),
+            // Thumb bit set
+            boot2: core::mem::transmute::<usize, unsafe extern "C" fn()>(boot2.as_ptr() as usize | 1),
+        };
+        let data = page.map_or(core::ptr::null(), |page| page.as_ptr());
+        update_sector_in_ram(&rom, data);
+    });
+}
+
+/// Erase the config sector and program `data` (one page, or erase only if null)
+///
+/// Must not touch flash: everything it calls is in ROM or RAM.
+#[inline(never)]
+#[link_section = ".data.ram_func"]
+unsafe fn update_sector_in_ram(rom: &RomFunctions, data: *const u8) {
+    (rom.connect_internal_flash)();
+    (rom.flash_exit_xip)();
+    (rom.flash_range_erase)(CONFIG_OFFSET as u32, SECTOR_SIZE, SECTOR_SIZE as u32, SECTOR_ERASE_CMD);
+    if !data.is_null() {
+        (rom.flash_range_program)(CONFIG_OFFSET as u32, data, PAGE_SIZE);
+    }
+    (rom.flash_flush_cache)();
+    (rom.boot2)();
+}
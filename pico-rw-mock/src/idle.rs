@@ -0,0 +1,100 @@
+//! HID idle rates (SET_IDLE / GET_IDLE)
+//!
+//! usbd-hid accepts SET_IDLE without storing it and rejects GET_IDLE, so this
+//! class sits in front of `HIDClass` in the poll list and keeps the rates the
+//! host set for one interface. The main loop reads them to decide when to
+//! repeat unchanged input reports.
+
+use usb_device::class_prelude::*;
+use usb_device::control::{Recipient, RequestType};
+
+use rw_core::idle::{IdleRate, DEFAULT_IDLE_RATE};
+
+const HID_REQ_GET_IDLE: u8 = 0x02;
+const HID_REQ_SET_IDLE: u8 = 0x0a;
+
+/// Report IDs that can have their own rate
+const MAX_REPORTS: usize = 4;
+
+pub struct IdleRates {
+    interface: u16,
+    /// Rate of every report without its own entry (SET_IDLE with report ID 0)
+    all: IdleRate,
+    per_report: [Option<(u8, IdleRate)>; MAX_REPORTS],
+}
+
+impl IdleRates {
+    /// `interface` is the HID interface number the requests are addressed to
+    pub fn new(interface: u8) -> Self {
+        Self {
+            interface: interface as u16,
+            all: DEFAULT_IDLE_RATE,
+            per_report: [None; MAX_REPORTS],
+        }
+    }
+
+    /// Idle rate of input report `id`
+    pub fn rate(&self, id: u8) -> IdleRate {
+        self.per_report
+            .iter()
+            .flatten()
+            .find(|(report, _)| *report == id)
+            .map_or(self.all, |&(_, rate)| rate)
+    }
+
+    fn set(&mut self, id: u8, rate: IdleRate) -> bool {
+        if id == 0 {
+            self.all = rate;
+            self.per_report = [None; MAX_REPORTS];
+            return true;
+        }
+        let index = self
+            .per_report
+            .iter()
+            .position(|r| matches!(r, Some((report, _)) if *report == id))
+            .or_else(|| self.per_report.iter().position(Option::is_none));
+        match index {
+            Some(index) => {
+                self.per_report[index] = Some((id, rate));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn is_for_us(&self, req: &usb_device::control::Request) -> bool {
+        req.request_type == RequestType::Class && req.recipient == Recipient::Interface && req.index == self.interface
+    }
+}
+
+impl<B: UsbBus> UsbClass<B> for IdleRates {
+    /// Hosts expect the default rates again after re-enumerating
+    fn reset(&mut self) {
+        self.all = DEFAULT_IDLE_RATE;
+        self.per_report = [None; MAX_REPORTS];
+    }
+
+    fn control_in(&mut self, xfer: ControlIn<B>) {
+        let req = *xfer.request();
+        if !self.is_for_us(&req) || req.request != HID_REQ_GET_IDLE {
+            return;
+        }
+        xfer.accept_with(&[self.rate(req.value as u8).raw()]).ok();
+    }
+
+    fn control_out(&mut self, xfer: ControlOut<B>) {
+        let req = *xfer.request();
+        if !self.is_for_us(&req) || req.request != HID_REQ_SET_IDLE {
+            return;
+        }
+        let id = req.value as u8;
+        let rate = IdleRate::from_raw((req.value >> 8) as u8);
+        if self.set(id, rate) {
+            defmt::println!("HID SET_IDLE: interface {} report {} -> {}", self.interface, id, rate);
+            xfer.accept().ok();
+        } else {
+            defmt::warn!("HID SET_IDLE: no room for report ID {}", id);
+            xfer.reject().ok();
+        }
+    }
+}
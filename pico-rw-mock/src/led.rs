@@ -0,0 +1,40 @@
+//! Onboard LED status patterns
+
+/// Blink pattern shown on the onboard LED
+#[derive(Debug, Clone, Copy, PartialEq, Eq, defmt::Format)]
+pub enum LedPattern {
+    /// Solid off: motor stopped
+    Off,
+    /// 1Hz blink: spinning forward
+    SlowBlink,
+    /// 5Hz blink: spinning in reverse
+    FastBlink,
+    /// Two short blinks per second: fault / command timeout
+    DoubleBlink,
+    /// Solid on: emergency stop latched
+    On,
+    /// Three short blinks, then a pause: halted on an unstable axis strap
+    #[cfg(feature = "axis-auto")]
+    TripleBlink,
+}
+
+impl LedPattern {
+    /// LED level for this pattern at `now_ms` (derived from the timer, never blocks)
+    pub fn is_on(self, now_ms: u64) -> bool {
+        match self {
+            LedPattern::Off => false,
+            LedPattern::On => true,
+            LedPattern::SlowBlink => now_ms % 1000 < 500,
+            LedPattern::FastBlink => now_ms % 200 < 100,
+            LedPattern::DoubleBlink => {
+                let t = now_ms % 1000;
+                t < 100 || (200..300).contains(&t)
+            }
+            #[cfg(feature = "axis-auto")]
+            LedPattern::TripleBlink => {
+                let t = now_ms % 1900;
+                t < 900 && t % 300 < 100
+            }
+        }
+    }
+}
@@ -1,18 +1,38 @@
 #![no_main]
 #![no_std]
 
+use core::mem::discriminant;
+
 use rp_pico::hal;
+use hal::fugit::ExtU32;
 use hal::pac;
 
-use panic_halt as _;
-
-use embedded_hal::delay::DelayNs;
-use embedded_hal::digital::OutputPin;
+use embedded_hal::digital::{OutputPin, StatefulOutputPin};
 use embedded_hal::pwm::SetDutyCycle;
+use embedded_hal_0_2::adc::OneShot;
 use embedded_hal_0_2::digital::v2::InputPin;
 
 use defmt_rtt as _;
 
+#[macro_use]
+mod descriptor;
+mod encoder;
+mod feature;
+mod flash;
+mod idle;
+mod led;
+#[cfg(feature = "dual_motor")]
+mod motor_b;
+mod panic;
+#[cfg(feature = "uart_telemetry")]
+mod uart;
+#[macro_use]
+mod pinout;
+mod usb;
+use feature::FeatureReports;
+use pinout::PINOUT;
+use led::LedPattern;
+
 // USB HID
 use hal::usb::UsbBus;
 use usb_device::{class_prelude::*, prelude::*};
@@ -20,77 +40,445 @@ use usbd_hid::descriptor::generator_prelude::*;
 use usbd_hid::hid_class::{
     HIDClass, HidClassSettings, HidCountryCode, HidProtocol, HidSubClass, ProtocolModeConfig,
 };
-use zerocopy::{FromBytes, Immutable, KnownLayout};
+use zerocopy::{FromBytes, IntoBytes};
 
-/// USB bus allocator (needs static lifetime)
-static mut USB_BUS: Option<UsbBusAllocator<UsbBus>> = None;
+use rw_core::axis::{apply_axis_override, Axis};
+use rw_core::coast::{CoastTest, CoastTestCommand, CoastTestEnd};
+use rw_core::command::{handle_command, CommandGate, CommandResult};
+use rw_core::config::{Config, ConfigCommand, ControlMode, FaultResponse};
+use rw_core::control::{MotorController, MotorOutput, CONTROL_TICK_US};
+use rw_core::current::{adc_to_current_ma, OvercurrentMonitor};
+use rw_core::deadline::Deadline;
+use rw_core::demo::{DemoButton, DemoSpin};
+use rw_core::driver::{DriverEnable, IdleSleep, DRIVER_WAKE_US};
+use rw_core::encoder::RpmEstimator;
+use rw_core::failsafe::{is_command_timed_out, EStopCommand, EStopRamp, COMMAND_TIMEOUT_MS, ESTOP_RAMP_MS};
+use rw_core::fault::{drive_response, Fault, FaultLog};
+use rw_core::feedback::FeedbackMonitor;
+use rw_core::idle::IdleTimer;
+use rw_core::lograte::{LogLimiter, LogLine};
+use rw_core::looptime::{LoopTimer, LOOP_OVERRUN_US};
+use rw_core::min_duty::{MinDutyCalibration, MinDutyCommand, MinDutyResult, MIN_DUTY_STEP_MS};
+use rw_core::motor::{normalized_to_percent, MotorSpeed, PWM_TOP};
+use rw_core::protocol::{
+    OutputReportError, RWAxisOverrideReport, RWCoastTestReport, RWConfigCommandReport,
+    RWConfigReport, RWConfigStatusReport, RWDescriptorInfoReport, RWDeviceInfoReport, RWEStopReport, RWEffectiveConfigReport, RWEncoderCalibrationReport,
+    RWFaultClearReport, RWMinDutyCalibrationReport, RWSequenceCommandReport, RWSequenceReport, RWSpinUpTestReport, RWStatusReport,
+    RWTelemetryReport,
+    AXIS_OVERRIDE_REPORT_ID, COAST_TEST_REPORT_ID, CONFIG_COMMAND_REPORT_ID, CONFIG_REPORT_ID, CONFIG_STATUS_REPORT_ID,
+    DESCRIPTOR_INFO_REPORT_ID, DEVICE_INFO_REPORT_ID, EFFECTIVE_CONFIG_REPORT_ID, ENCODER_CALIBRATION_REPORT_ID, ESTOP_REPORT_ID,
+    FAULT_CLEAR_REPORT_ID, MIN_DUTY_CALIBRATION_REPORT_ID, SEQUENCE_COMMAND_REPORT_ID, SEQUENCE_REPORT_ID, SPEED_PERCENT_REPORT_ID, SPEED_REPORT_ID,
+    SPIN_UP_TEST_REPORT_ID, TELEMETRY_REPORT_ID,
+};
+use rw_core::polarity::{PolarityCalibration, PolarityCheck, PolarityCommand, POLARITY_CHECK_MS};
+use rw_core::pwm::PwmTiming;
+use rw_core::selftest::{SelfTest, SELF_TEST_SEQUENCE};
+use rw_core::sequence::{Sequence, SequenceCommand, SequencePlayer};
+use rw_core::spinup::{SpinUpCommand, SpinUpEnd, SpinUpTest};
+use rw_core::stall::{StallEvent, StallMonitor};
+use rw_core::storage::{StoredConfig, StoredConfigError};
+use rw_core::supply::{adc_to_supply_mv, UndervoltageMonitor};
+#[cfg(feature = "axis-auto")]
+use rw_core::strap::{
+    strap_majority, StrapPolarity, STRAP_MAJORITY, STRAP_SAMPLES, STRAP_SAMPLE_INTERVAL_MS, STRAP_SETTLE_MS,
+};
+use rw_core::thermal::{max_duty_for_temperature, TemperatureFilter, TEMP_SAMPLE_MS};
+use rw_core::wakeup::RemoteWakeup;
 
-/// HID Report descriptor for RW speed control
-/// Output: speed_normalized (int16_t, little-endian)
-/// Range: -32767 = -100%, 0 = stop, +32767 = +100%
-#[gen_hid_descriptor(
-    (collection = APPLICATION, usage_page = VENDOR_DEFINED_START, usage = 0x01) = {
-        speed_normalized_low=output;
-        speed_normalized_high=output;
-    }
-)]
-struct RWSpeedReport {
-    speed_normalized_low: u8,
-    speed_normalized_high: u8,
-}
+#[cfg(not(any(feature = "axis-auto", feature = "axis-x", feature = "axis-y", feature = "axis-z")))]
+compile_error!("select an axis feature: `axis-auto` (default), `axis-x`, `axis-y` or `axis-z`");
 
-/// Output report from host (normalized speed)
-#[derive(FromBytes, KnownLayout, Immutable)]
-#[repr(C)]
-struct OutputReport {
-    speed_normalized: i16,  // Normalized speed: -32767 to +32767 (-100% to +100%)
-}
+#[cfg(any(
+    all(feature = "axis-auto", any(feature = "axis-x", feature = "axis-y", feature = "axis-z")),
+    all(feature = "axis-x", any(feature = "axis-y", feature = "axis-z")),
+    all(feature = "axis-y", feature = "axis-z"),
+))]
+compile_error!("axis features are mutually exclusive (fixed axis: `--no-default-features --features axis-x`)");
 
-/// Motor speed state
-#[derive(Clone, Copy)]
-struct MotorSpeed {
-    speed_normalized: i16,  // Normalized speed (-32767 to +32767)
-}
+/// Axis fixed at build time by `axis-x` / `axis-y` / `axis-z`; the strap pins are not read
+#[cfg(feature = "axis-x")]
+const FIXED_AXIS: Axis = Axis::X;
+#[cfg(feature = "axis-y")]
+const FIXED_AXIS: Axis = Axis::Y;
+#[cfg(feature = "axis-z")]
+const FIXED_AXIS: Axis = Axis::Z;
 
-impl MotorSpeed {
-    fn to_duty_and_direction(&self) -> (u8, bool) {
-        // Convert normalized speed (-32767 to +32767) to duty cycle (0-100%)
-        // -32767 -> 100% reverse
-        // 0 -> 0% (stop)
-        // +32767 -> 100% forward
+/// Strap pin bias of the board revision, `strap_pull_down` for boards strapping to 3V3
+#[cfg(all(feature = "axis-auto", not(feature = "strap_pull_down")))]
+const STRAP_POLARITY: StrapPolarity = StrapPolarity::PullUp;
+#[cfg(all(feature = "axis-auto", feature = "strap_pull_down"))]
+const STRAP_POLARITY: StrapPolarity = StrapPolarity::PullDown;
 
-        let abs_speed = self.speed_normalized.abs();
-        let is_forward = self.speed_normalized >= 0;
+/// Hardware watchdog period; the main loop must feed it at least this often
+const WATCHDOG_TIMEOUT_MS: u32 = 1000;
 
-        // Scale: 32767 -> 100% duty
-        // Use min duty of 40% when speed > 0
-        let duty = if abs_speed == 0 {
-            0
-        } else {
-            let scaled = (abs_speed as u32 * 100 / 32767).min(100) as u8;
-            scaled.max(MIN_DUTY)
+/// USB bus allocator (needs static lifetime)
+static mut USB_BUS: Option<UsbBusAllocator<UsbBus>> = None;
+
+motor_descriptor! {
+    /// HID Report descriptor for RW speed control
+    /// Report IDs match `rw_core::protocol::*_REPORT_ID`; every report starts with its ID byte.
+    /// ID 1 output: speed_normalized (int16_t, little-endian), CRC-8 of the speed bytes
+    /// Range: -32767 = -100%, 0 = stop, +32767 = +100%
+    /// ID 1 input: status, laid out as `rw_core::protocol::RWStatusReport`
+    /// ID 2 feature: runtime config, laid out as `rw_core::protocol::RWConfigReport`
+    /// ID 3 feature: emergency stop, laid out as `rw_core::protocol::RWEStopReport`
+    /// ID 4 feature (read-only): device info, laid out as `rw_core::protocol::RWDeviceInfoReport`
+    /// ID 5 feature: axis override, laid out as `rw_core::protocol::RWAxisOverrideReport`
+    /// ID 6 feature: config storage command, laid out as `rw_core::protocol::RWConfigCommandReport`
+    /// ID 7 input: command path telemetry, laid out as `rw_core::protocol::RWTelemetryReport`
+    /// ID 8 feature: clear latched faults, laid out as `rw_core::protocol::RWFaultClearReport`
+    /// ID 9 output: speed percent (int8_t, -100..=100), CRC-8 of the percent byte; same path as ID 1
+    /// ID 10 feature: coast test, laid out as `rw_core::protocol::RWCoastTestReport`
+    /// ID 11 feature (read-only): config in effect, laid out as `rw_core::protocol::RWEffectiveConfigReport`
+    /// ID 12 feature: speed sequence, laid out as `rw_core::protocol::RWSequenceReport`
+    /// ID 13 feature: sequence playback, laid out as `rw_core::protocol::RWSequenceCommandReport`
+    /// ID 14 feature: encoder polarity calibration, laid out as `rw_core::protocol::RWEncoderCalibrationReport`
+    /// ID 15 feature: minimum duty calibration, laid out as `rw_core::protocol::RWMinDutyCalibrationReport`
+    /// ID 16 feature (read-only): outcome of the last config write, laid out as `rw_core::protocol::RWConfigStatusReport`
+    /// ID 17 feature (read-only): descriptor length and CRC, laid out as `rw_core::protocol::RWDescriptorInfoReport`
+    /// ID 18 feature: spin-up test, laid out as `rw_core::protocol::RWSpinUpTestReport`
+    struct RWSpeedReport, usage = 0x01,
+    {
+        (report_id = 0x02,) = {
+            config_stop_mode=feature;
+            config_min_duty=feature;
+            config_kickstart_duty=feature;
+            config_kickstart_ms_low=feature;
+            config_kickstart_ms_high=feature;
+            config_slew_step_low=feature;
+            config_slew_step_high=feature;
+            config_control_mode=feature;
+            config_max_current_ma_low=feature;
+            config_max_current_ma_high=feature;
+            config_throttle_start_c=feature;
+            config_throttle_limit_c=feature;
+            config_curve=feature;
+            config_fwd_scale_low=feature;
+            config_fwd_scale_high=feature;
+            config_rev_scale_low=feature;
+            config_rev_scale_high=feature;
+            config_deadband_low=feature;
+            config_deadband_high=feature;
+            config_stop_ramp_step_low=feature;
+            config_stop_ramp_step_high=feature;
+            config_pwm_freq_hz_low=feature;
+            config_pwm_freq_hz_high=feature;
+            config_ramp_profile=feature;
+            config_ramp_tau_ms_low=feature;
+            config_ramp_tau_ms_high=feature;
+            config_max_speed_normalized_low=feature;
+            config_max_speed_normalized_high=feature;
+            config_stall_window_ms_low=feature;
+            config_stall_window_ms_high=feature;
+            config_stall_rpm_low=feature;
+            config_stall_rpm_high=feature;
+            config_stall_retry=feature;
+            config_kickstart_rpm_low=feature;
+            config_kickstart_rpm_high=feature;
+            config_reversal_kick_threshold_low=feature;
+            config_reversal_kick_threshold_high=feature;
+            config_reversal_brake_ms_low=feature;
+            config_reversal_brake_ms_high=feature;
+            config_reversal_brake_rpm_low=feature;
+            config_reversal_brake_rpm_high=feature;
+            config_telemetry_period_ms_low=feature;
+            config_telemetry_period_ms_high=feature;
+            config_dither_amplitude=feature;
+            config_dither_freq_hz=feature;
+            config_dither_max_speed_low=feature;
+            config_dither_max_speed_high=feature;
+            config_min_dwell_ms_low=feature;
+            config_min_dwell_ms_high=feature;
+            config_log_interval_ms_low=feature;
+            config_log_interval_ms_high=feature;
+            config_encoder_reversed=feature;
+            config_direction_hysteresis_low=feature;
+            config_direction_hysteresis_high=feature;
+            config_max_rpm_low=feature;
+            config_max_rpm_high=feature;
+            config_min_supply_mv_low=feature;
+            config_min_supply_mv_high=feature;
+            config_kickstart_current_ma_low=feature;
+            config_kickstart_current_ma_high=feature;
+            config_feedback_timeout_ms_low=feature;
+            config_feedback_timeout_ms_high=feature;
+            config_overcurrent_response=feature;
+            config_over_temperature_response=feature;
+            config_undervoltage_response=feature;
+            config_stall_response=feature;
+            config_current_limit_ma_low=feature;
+            config_current_limit_ma_high=feature;
+            config_current_limit_gain_low=feature;
+            config_current_limit_gain_high=feature;
+            config_idle_sleep_ms_low=feature;
+            config_idle_sleep_ms_high=feature;
+        };
+        (report_id = 0x03,) = {
+            estop_command=feature;
+        };
+        (report_id = 0x04,) = {
+            info_axis=feature;
+            info_version_major=feature;
+            info_version_minor=feature;
+            info_version_patch=feature;
+            info_strap_levels=feature;
+            info_strap_axis=feature;
+        };
+        (report_id = 0x05,) = {
+            override_axis=feature;
+            override_force=feature;
+        };
+        (report_id = 0x06,) = {
+            config_command=feature;
+        };
+        (report_id = 0x07,) = {
+            telemetry_received_low=input;
+            telemetry_received_high=input;
+            telemetry_target_low=input;
+            telemetry_target_high=input;
+            telemetry_effective_low=input;
+            telemetry_effective_high=input;
+            telemetry_flags=input;
+            telemetry_pwm_errors_low=input;
+            telemetry_pwm_errors_high=input;
+            telemetry_encoder_reversed=input;
+            telemetry_min_duty=input;
+            telemetry_min_duty_ramp=input;
+            telemetry_loop_max_us_low=input;
+            telemetry_loop_max_us_high=input;
+            telemetry_supply_mv_low=input;
+            telemetry_supply_mv_high=input;
+            telemetry_control_mode=input;
+            telemetry_spin_up_state=input;
+            telemetry_spin_up_ms_low=input;
+            telemetry_spin_up_ms_high=input;
+            telemetry_pwm_top_low=input;
+            telemetry_pwm_top_high=input;
+            telemetry_pwm_div_int=input;
+            telemetry_min_duty_effective=input;
+            telemetry_duty_saturated=input;
+            telemetry_current_limited=input;
+            telemetry_idle_asleep=input;
+        };
+        (report_id = 0x08,) = {
+            fault_clear_mask_low=feature;
+            fault_clear_mask_high=feature;
+        };
+        (report_id = 0x0A,) = {
+            coast_test_command=feature;
+        };
+        (report_id = 0x0B,) = {
+            effective_layout_version=feature;
+            effective_inverted=feature;
+            effective_config=feature;
+        };
+        (report_id = 0x0C,) = {
+            sequence_count=feature;
+            sequence_steps=feature;
+        };
+        (report_id = 0x0D,) = {
+            sequence_command=feature;
+        };
+        (report_id = 0x0E,) = {
+            encoder_calibration_command=feature;
+        };
+        (report_id = 0x0F,) = {
+            min_duty_calibration_command=feature;
+        };
+        (report_id = 0x10,) = {
+            config_status_writes=feature;
+            config_status_result=feature;
+            config_status_field=feature;
+            config_status_value_low=feature;
+            config_status_value_high=feature;
+        };
+        (report_id = 0x11,) = {
+            descriptor_length_low=feature;
+            descriptor_length_high=feature;
+            descriptor_crc32_0=feature;
+            descriptor_crc32_1=feature;
+            descriptor_crc32_2=feature;
+            descriptor_crc32_3=feature;
         };
+        (report_id = 0x12,) = {
+            spin_up_command=feature;
+            spin_up_speed_low=feature;
+            spin_up_speed_high=feature;
+        };
+    },
+    {
+        config_stop_mode: u8,
+        config_min_duty: u8,
+        config_kickstart_duty: u8,
+        config_kickstart_ms_low: u8,
+        config_kickstart_ms_high: u8,
+        config_slew_step_low: u8,
+        config_slew_step_high: u8,
+        config_control_mode: u8,
+        config_max_current_ma_low: u8,
+        config_max_current_ma_high: u8,
+        config_throttle_start_c: u8,
+        config_throttle_limit_c: u8,
+        config_curve: u8,
+        config_fwd_scale_low: u8,
+        config_fwd_scale_high: u8,
+        config_rev_scale_low: u8,
+        config_rev_scale_high: u8,
+        config_deadband_low: u8,
+        config_deadband_high: u8,
+        config_stop_ramp_step_low: u8,
+        config_stop_ramp_step_high: u8,
+        config_pwm_freq_hz_low: u8,
+        config_pwm_freq_hz_high: u8,
+        config_ramp_profile: u8,
+        config_ramp_tau_ms_low: u8,
+        config_ramp_tau_ms_high: u8,
+        config_max_speed_normalized_low: u8,
+        config_max_speed_normalized_high: u8,
+        config_stall_window_ms_low: u8,
+        config_stall_window_ms_high: u8,
+        config_stall_rpm_low: u8,
+        config_stall_rpm_high: u8,
+        config_stall_retry: u8,
+        config_kickstart_rpm_low: u8,
+        config_kickstart_rpm_high: u8,
+        config_reversal_kick_threshold_low: u8,
+        config_reversal_kick_threshold_high: u8,
+        config_reversal_brake_ms_low: u8,
+        config_reversal_brake_ms_high: u8,
+        config_reversal_brake_rpm_low: u8,
+        config_reversal_brake_rpm_high: u8,
+        config_telemetry_period_ms_low: u8,
+        config_telemetry_period_ms_high: u8,
+        config_dither_amplitude: u8,
+        config_dither_freq_hz: u8,
+        config_dither_max_speed_low: u8,
+        config_dither_max_speed_high: u8,
+        config_min_dwell_ms_low: u8,
+        config_min_dwell_ms_high: u8,
+        config_log_interval_ms_low: u8,
+        config_log_interval_ms_high: u8,
+        config_encoder_reversed: u8,
+        config_direction_hysteresis_low: u8,
+        config_direction_hysteresis_high: u8,
+        config_max_rpm_low: u8,
+        config_max_rpm_high: u8,
+        config_min_supply_mv_low: u8,
+        config_min_supply_mv_high: u8,
+        config_kickstart_current_ma_low: u8,
+        config_kickstart_current_ma_high: u8,
+        config_feedback_timeout_ms_low: u8,
+        config_feedback_timeout_ms_high: u8,
+        config_overcurrent_response: u8,
+        config_over_temperature_response: u8,
+        config_undervoltage_response: u8,
+        config_stall_response: u8,
+        config_current_limit_ma_low: u8,
+        config_current_limit_ma_high: u8,
+        config_current_limit_gain_low: u8,
+        config_current_limit_gain_high: u8,
+        config_idle_sleep_ms_low: u8,
+        config_idle_sleep_ms_high: u8,
+        estop_command: u8,
+        info_axis: u8,
+        info_version_major: u8,
+        info_version_minor: u8,
+        info_version_patch: u8,
+        info_strap_levels: u8,
+        info_strap_axis: u8,
+        override_axis: u8,
+        override_force: u8,
+        config_command: u8,
+        telemetry_received_low: u8,
+        telemetry_received_high: u8,
+        telemetry_target_low: u8,
+        telemetry_target_high: u8,
+        telemetry_effective_low: u8,
+        telemetry_effective_high: u8,
+        telemetry_flags: u8,
+        telemetry_pwm_errors_low: u8,
+        telemetry_pwm_errors_high: u8,
+        telemetry_encoder_reversed: u8,
+        telemetry_min_duty: u8,
+        telemetry_min_duty_ramp: u8,
+        telemetry_loop_max_us_low: u8,
+        telemetry_loop_max_us_high: u8,
+        telemetry_supply_mv_low: u8,
+        telemetry_supply_mv_high: u8,
+        telemetry_control_mode: u8,
+        telemetry_spin_up_state: u8,
+        telemetry_spin_up_ms_low: u8,
+        telemetry_spin_up_ms_high: u8,
+        telemetry_pwm_top_low: u8,
+        telemetry_pwm_top_high: u8,
+        telemetry_pwm_div_int: u8,
+        telemetry_min_duty_effective: u8,
+        telemetry_duty_saturated: u8,
+        telemetry_current_limited: u8,
+        telemetry_idle_asleep: u8,
+        fault_clear_mask_low: u8,
+        fault_clear_mask_high: u8,
+        coast_test_command: u8,
+        effective_layout_version: u8,
+        effective_inverted: u8,
+        effective_config: [u8; 72],
+        sequence_count: u8,
+        sequence_steps: [u8; 48],
+        sequence_command: u8,
+        encoder_calibration_command: u8,
+        min_duty_calibration_command: u8,
+        config_status_writes: u8,
+        config_status_result: u8,
+        config_status_field: u8,
+        config_status_value_low: u8,
+        config_status_value_high: u8,
+        descriptor_length_low: u8,
+        descriptor_length_high: u8,
+        descriptor_crc32_0: u8,
+        descriptor_crc32_1: u8,
+        descriptor_crc32_2: u8,
+        descriptor_crc32_3: u8,
+        spin_up_command: u8,
+        spin_up_speed_low: u8,
+        spin_up_speed_high: u8,
+    }
+}
+
+// `effective_config` and `sequence_steps` above have to follow the report layouts
+const _: () = assert!(core::mem::size_of::<RWConfigReport>() == 72);
+const _: () = assert!(core::mem::size_of::<RWSequenceReport>() == 49);
 
-        (duty, is_forward)
+/// Whether the wheel on `axis` is mounted so that positive commands must spin it backwards
+const fn axis_is_inverted(axis: Axis) -> bool {
+    match axis {
+        Axis::X => false,
+        Axis::Y => false,
+        Axis::Z => false,
+        Axis::Test => false,
     }
 }
 
-/// Kickstart parameters
-const KICKSTART_DUTY: u8 = 100;
-const KICKSTART_MS: u32 = 150;
-const MIN_DUTY: u8 = 40;
-
-/// Axis identification for multi-Pico setup
-#[derive(Debug, Clone, Copy, defmt::Format)]
-enum Axis {
-    X,
-    Y,
-    Z,
+/// Device info report for `axis`, with the boot strap reading (`strap_levels` as in the report)
+fn device_info(axis: Axis, strapped_axis: Axis, strap_levels: u8) -> RWDeviceInfoReport {
+    RWDeviceInfoReport {
+        axis: axis as u8,
+        version_major: env!("CARGO_PKG_VERSION_MAJOR").parse().unwrap_or(0),
+        version_minor: env!("CARGO_PKG_VERSION_MINOR").parse().unwrap_or(0),
+        version_patch: env!("CARGO_PKG_VERSION_PATCH").parse().unwrap_or(0),
+        strap_levels,
+        strap_axis: strapped_axis as u8,
+    }
 }
 
 #[hal::entry]
 fn main() -> ! {
     let mut pac = pac::Peripherals::take().unwrap();
+
+    // REASON is cleared by the next reset, so check it before anything else
+    if pac.WATCHDOG.reason().read().timer().bit_is_set() {
+        defmt::warn!("Reset by watchdog (main loop stalled)");
+    }
     let mut watchdog = hal::Watchdog::new(pac.WATCHDOG);
 
     let clocks = hal::clocks::init_clocks_and_plls(
@@ -104,7 +492,7 @@ fn main() -> ! {
     )
     .unwrap();
 
-    let mut timer = hal::timer::Timer::new(pac.TIMER, &mut pac.RESETS, &clocks);
+    let timer = hal::timer::Timer::new(pac.TIMER, &mut pac.RESETS, &clocks);
     let sio = hal::Sio::new(pac.SIO);
     let pins = hal::gpio::Pins::new(
         pac.IO_BANK0,
@@ -113,49 +501,134 @@ fn main() -> ! {
         &mut pac.RESETS,
     );
 
+    // Motor A bridge inputs, nSLEEP and their PWM slice (`pinout::PINOUT`, default GPIO16/17/18 on PWM0)
+    let mut pwm_slices = hal::pwm::Slices::new(pac.PWM, &mut pac.RESETS);
+    let (ain1_pin, ain2_pin, nsleep_pin, motor_pwm) = motor_a_pins!(pins, pwm_slices);
+
+    // nSLEEP: keep LOW (driver disabled) until the bring-up in the control loop
+    let mut motor_sleep = nsleep_pin.into_push_pull_output();
+    motor_sleep.set_low().unwrap();
+
+    // Onboard LED (GPIO25)
+    let mut led = pins.gpio25.into_push_pull_output();
+    led.set_low().unwrap();
+
+    // Wake button (GPIO21, to GND): asks a suspended host to resume the bus
+    let wake_button = pins.gpio21.into_pull_up_input();
+
+    // Demo button (GPIO22, to GND): toggles the standalone demo spin
+    let demo_pin = pins.gpio22.into_pull_up_input();
+
     // Detect axis from GPIO0 and GPIO1
-    // Read GPIO pins with pull-up (LOW=0, HIGH=1)
-    let id0 = pins.gpio0.into_pull_up_input();
-    let id1 = pins.gpio1.into_pull_up_input();
-    let bit0 = if id0.is_low().unwrap() { 0 } else { 1 };
-    let bit1 = if id1.is_low().unwrap() { 0 } else { 1 };
-    let axis_id = (bit1 << 1) | bit0;
-
-    let axis = match axis_id {
-        0b11 => Axis::X,  // Both HIGH (floating) → X-axis
-        0b10 => Axis::Y,  // GPIO0=LOW, GPIO1=HIGH → Y-axis
-        0b01 => Axis::Z,  // GPIO0=HIGH, GPIO1=LOW → Z-axis
-        0b00 => panic!("Invalid axis ID: both GPIO0 and GPIO1 are LOW"),
-        _ => unreachable!(),
+    // Read GPIO pins pulled to their idle level (fitted strap = 0), after a settle delay and by majority vote
+    #[cfg(feature = "axis-auto")]
+    let (strapped_axis, strap_levels) = {
+        #[cfg(not(feature = "strap_pull_down"))]
+        let (id0, id1) = (pins.gpio0.into_pull_up_input(), pins.gpio1.into_pull_up_input());
+        #[cfg(feature = "strap_pull_down")]
+        let (id0, id1) = (pins.gpio0.into_pull_down_input(), pins.gpio1.into_pull_down_input());
+        wait_ms(&timer, STRAP_SETTLE_MS);
+        let mut samples = [0u8; STRAP_SAMPLES];
+        for sample in samples.iter_mut() {
+            *sample = STRAP_POLARITY.strap_bits(id0.is_high().unwrap(), id1.is_high().unwrap());
+            wait_ms(&timer, STRAP_SAMPLE_INTERVAL_MS);
+        }
+        defmt::println!("Axis strap samples ({}): {=[u8]:#04b}", STRAP_POLARITY, samples);
+        let Some(axis_id) = strap_majority(&samples) else {
+            defmt::error!("Axis strap unstable (no {}/{} majority)", STRAP_MAJORITY, STRAP_SAMPLES);
+            strap_fault_halt(&mut led, &timer);
+        };
+        // The levels behind the majority value, for diagnosing a strap from the host
+        let levels = STRAP_POLARITY.pin_levels(axis_id);
+        defmt::println!(
+            "Axis strap levels: GPIO0={} GPIO1={} -> {}",
+            if levels & 0b01 != 0 { "HIGH" } else { "LOW" },
+            if levels & 0b10 != 0 { "HIGH" } else { "LOW" },
+            Axis::from_strap(axis_id)
+        );
+        (Axis::from_strap(axis_id), levels)
+    };
+    #[cfg(not(feature = "axis-auto"))]
+    let (strapped_axis, strap_levels) = {
+        defmt::println!("Axis fixed at build time, strap pins not read");
+        (FIXED_AXIS, RWDeviceInfoReport::STRAP_NOT_READ)
     };
 
-    let serial = match axis {
-        Axis::X => "RW-X",
-        Axis::Y => "RW-Y",
-        Axis::Z => "RW-Z",
+    // The strap result is kept so overrides can be checked against it
+    let mut axis = strapped_axis;
+    let serial = axis.serial();
+    let mut inverted = axis_is_inverted(axis);
+    defmt::println!("Detected axis: {}, Serial: {}, inverted: {}", axis, serial, inverted);
+
+    // Tuning written by the host survives resets; a blank or corrupt sector means defaults
+    let mut config = match StoredConfig::load(flash::read()) {
+        Ok(stored) => {
+            defmt::println!("Config loaded from flash: {}", stored);
+            stored
+        }
+        Err(StoredConfigError::Magic(_)) => Config::default(),
+        Err(e) => {
+            defmt::warn!("Stored config ignored ({}), using defaults", e);
+            Config::default()
+        }
     };
-    defmt::println!("Detected axis: {}, Serial: {}", axis, serial);
 
-    // nSLEEP pin: set HIGH to enable motor driver
-    let mut motor_sleep = pins.gpio18.into_push_pull_output();
-    motor_sleep.set_high().unwrap();
+    // Configure the motor A PWM slice at the configured frequency (default 10kHz)
+    // freq = 125MHz / (divider * (TOP + 1)) = 125MHz / (1 * 12500) = 10kHz
+    let timing = config.pwm_timing();
+    motor_pwm.set_top(timing.top);
+    motor_pwm.set_div_int(timing.div_int);
+    // Zero duty before the pins are routed, so the bridge inputs never see a pulse
+    motor_pwm.channel_a.set_duty_cycle_fully_off().unwrap();
+    motor_pwm.channel_b.set_duty_cycle_fully_off().unwrap();
+    motor_pwm.enable();
 
-    // Configure PWM slice 0
-    // Target: ~10kHz PWM frequency
-    // freq = 125MHz / (divider * TOP) = 125MHz / (5 * 2500) = 10kHz
-    let mut pwm_slices = hal::pwm::Slices::new(pac.PWM, &mut pac.RESETS);
-    let pwm0 = &mut pwm_slices.pwm0;
-    pwm0.set_top(2500);
-    pwm0.set_div_int(5u8);
-    pwm0.enable();
+    // AIN1: channel A, AIN2: channel B
+    motor_pwm.channel_a.output_to(ain1_pin);
+    motor_pwm.channel_b.output_to(ain2_pin);
+    defmt::println!(
+        "Motor A pins: AIN1 GPIO{}, AIN2 GPIO{} (PWM{}), nSLEEP GPIO{}",
+        PINOUT.ain1,
+        PINOUT.ain2,
+        PINOUT.pwm_slice,
+        PINOUT.nsleep
+    );
 
-    // AIN1: GPIO16 (PWM0 channel A)
-    let ain1 = &mut pwm0.channel_a;
-    ain1.output_to(pins.gpio16);
+    // Motor B: BIN1 GPIO14 / BIN2 GPIO15 (PWM7), on the same driver and nSLEEP as motor A
+    #[cfg(feature = "dual_motor")]
+    let mut motor_b = {
+        let mut pwm7 = pwm_slices.pwm7;
+        pwm7.channel_a.output_to(pins.gpio14);
+        pwm7.channel_b.output_to(pins.gpio15);
+        motor_b::MotorB::new(pwm7, timing)
+    };
+    defmt::println!("Driver bring-up: PWM outputs at zero duty, nSLEEP low");
+
+    // Framed telemetry: UART1 TX on GPIO4, 8N1, nothing received
+    #[cfg(feature = "uart_telemetry")]
+    let mut uart_telemetry = {
+        use hal::uart::{DataBits, StopBits, UartConfig, UartPeripheral};
+        use hal::Clock;
+        let uart_pins = hal::uart::Pins::default().tx(pins.gpio4.into_function());
+        let config = UartConfig::new(hal::fugit::HertzU32::from_raw(uart::UART_BAUD), DataBits::Eight, None, StopBits::One);
+        let uart = UartPeripheral::new(pac.UART1, uart_pins, &mut pac.RESETS)
+            .enable(config, clocks.peripheral_clock.freq())
+            .unwrap();
+        defmt::println!("UART telemetry: GPIO4 at {} baud", uart::UART_BAUD);
+        uart::UartTelemetry::new(uart)
+    };
+
+    // Quadrature encoder: GPIO19 (A), GPIO20 (B)
+    encoder::init(pins.gpio19.into_pull_up_input(), pins.gpio20.into_pull_up_input());
+    encoder::set_reversed(config.encoder_reversed);
 
-    // AIN2: GPIO17 (PWM0 channel B)
-    let ain2 = &mut pwm0.channel_b;
-    ain2.output_to(pins.gpio17);
+    // Motor current sense: DRV8833 AISEN resistor voltage on GPIO26 (ADC0)
+    let mut adc = hal::Adc::new(pac.ADC, &mut pac.RESETS);
+    let mut current_sense = hal::adc::AdcPin::new(pins.gpio26.into_floating_input()).unwrap();
+    // Supply voltage: VSYS / 3 on GPIO29 (ADC3)
+    let mut supply_sense = hal::adc::AdcPin::new(pins.gpio29.into_floating_input()).unwrap();
+    // Internal temperature sensor (ADC4)
+    let mut temp_sense = adc.take_temp_sensor().unwrap();
 
     // Set up USB HID
     let usb_bus: &'static _ = unsafe {
@@ -166,10 +639,10 @@ fn main() -> ! {
             true,
             &mut pac.RESETS,
         )));
-        USB_BUS.as_ref().unwrap()
+        (*core::ptr::addr_of!(USB_BUS)).as_ref().unwrap()
     };
 
-    let mut hid = HIDClass::new_with_settings(
+    let hid = HIDClass::new_with_settings(
         usb_bus,
         RWSpeedReport::desc(),
         10, // poll interval ms
@@ -181,7 +654,35 @@ fn main() -> ! {
         },
     );
 
-    let mut usb_dev = UsbDeviceBuilder::new(usb_bus, UsbVidPid(0x2E8A, 0x0B33))
+    // Motor B's wheel gets its own interface (1), with the same report layout
+    #[cfg(feature = "dual_motor")]
+    let hid_b = HIDClass::new_with_settings(
+        usb_bus,
+        motor_b::MotorBReport::desc(),
+        10, // poll interval ms
+        HidClassSettings {
+            subclass: HidSubClass::NoSubClass,
+            protocol: HidProtocol::Generic,
+            config: ProtocolModeConfig::ForceReport,
+            locale: HidCountryCode::NotSupported,
+        },
+    );
+
+    // Readable feature reports; all feature reports are on the first HID interface
+    let mut features = FeatureReports::new(0);
+    features.set(DEVICE_INFO_REPORT_ID, device_info(axis, strapped_axis, strap_levels).as_bytes());
+    features.set(EFFECTIVE_CONFIG_REPORT_ID, RWEffectiveConfigReport::new(&config, inverted).as_bytes());
+    features.set(SEQUENCE_REPORT_ID, Sequence::new().to_report().as_bytes());
+    features.set(CONFIG_STATUS_REPORT_ID, RWConfigStatusReport::new().as_bytes());
+    let descriptor_info = RWDescriptorInfoReport::new(RWSpeedReport::desc());
+    defmt::println!(
+        "HID descriptor: {} bytes, CRC-32 {=u32:#010x}",
+        descriptor_info.length.get(),
+        descriptor_info.crc32.get()
+    );
+    features.set(DESCRIPTOR_INFO_REPORT_ID, descriptor_info.as_bytes());
+
+    let usb_dev = UsbDeviceBuilder::new(usb_bus, UsbVidPid(0x2E8A, 0x0B33))
         .strings(&[StringDescriptors::default()
             .manufacturer("sksat")
             .product("Reaction Wheel Visualizer")
@@ -189,90 +690,1245 @@ fn main() -> ! {
         .unwrap()
         .max_packet_size_0(64)
         .unwrap()
+        .supports_remote_wakeup(true)
         .build();
+    usb::init(
+        usb_dev,
+        hid,
+        #[cfg(feature = "dual_motor")]
+        hid_b,
+        features,
+    );
 
     defmt::println!("Reaction Wheel Visualizer Started (HID)");
 
-    let mut current_speed = MotorSpeed { speed_normalized: 0 };
-    let mut last_speed = current_speed;
-    let mut usb_buf = [0u8; 64];
+    let mut controller = MotorController::new();
+    controller.set_wheel_spec(axis.wheel_spec());
+    let mut motor_output = MotorOutput::Coast;
+    // Failed PWM duty-set calls since boot, reported in telemetry
+    let mut pwm_errors: u16 = 0;
+    let mut next_tick = Deadline::at(timer.get_counter().ticks());
+    // Timer timestamp (us) of the last valid command, None until the first one
+    let mut last_command_us: Option<u64> = None;
+    let mut command_timed_out = false;
+    let mut usb_state = usb::state();
+    let mut rpm = RpmEstimator::new();
+    let mut overcurrent = OvercurrentMonitor::new();
+    let mut undervoltage = UndervoltageMonitor::new();
+    let mut stall = StallMonitor::new();
+    let mut feedback = FeedbackMonitor::new();
+    let mut coast_test = CoastTest::new();
+    // Time to target of a speed step, for tuning the ramp, kickstart and PID
+    let mut spin_up = SpinUpTest::new();
+    // Uploaded speed sequence, played back against the timer instead of host commands
+    let mut sequence = SequencePlayer::new();
+    // Encoder polarity check, driving the wheel instead of the controller while it runs
+    let mut polarity = PolarityCalibration::new();
+    // Minimum duty calibration, ramping the wheel instead of the controller while it runs
+    let mut min_duty_calibration = MinDutyCalibration::new();
+    // Config reports received since boot, echoed in the config status report (wraps)
+    let mut config_writes: u8 = 0;
+    // Hot-path log lines: repeated commands and ramp steps are coalesced
+    let mut recv_log = LogLimiter::new();
+    let mut motor_log = LogLimiter::new();
+    let mut temperature = TemperatureFilter::new();
+    let mut next_temp_sample = next_tick;
+    let mut throttling = false;
+    // Duty held down by the soft current limit on the last tick
+    let mut current_limiting = false;
+    // Throttled all the way down (at the limit temperature), answered like a tripped fault
+    let mut overheated = false;
+    // Drive response to the faults cutting the drive, None while none is active
+    let mut fault_response: Option<FaultResponse> = None;
+    // Emergency stop latch: driver disabled until an explicit release
+    let mut estopped = false;
+    // Duty ramp of a ramped e-stop, the driver disabled when it ends
+    let mut estop_ramp = EStopRamp::new();
+    // nSLEEP stays low until the host has enumerated the device and the driver has woken up
+    let mut driver = DriverEnable::new();
+    // Driver put to sleep while the wheel sits idle (`Config::idle_sleep_ms`), woken by the next host report
+    let mut idle_sleep = IdleSleep::new();
+    let mut enumerated = false;
+    // Faults reported to the host, latched until it clears them
+    let mut faults = FaultLog::new();
+    let mut wakeup = RemoteWakeup::new();
+    // Control tick counter reported as a heartbeat (wraps)
+    let mut heartbeat: u32 = 0;
+    // Worst iteration time since the last telemetry report
+    let mut loop_timer = LoopTimer::new();
+    // Last host speed before clamping, for telemetry
+    let mut received_speed: i16 = 0;
+    // Status and telemetry share the IN endpoint and take turns when both are due
+    let mut telemetry_next = false;
+    // Unchanged input reports are only repeated at the host's idle rate
+    let mut status_idle = IdleTimer::new();
+    let mut telemetry_idle = IdleTimer::new();
+    #[cfg(feature = "dual_motor")]
+    let mut status_b_idle = IdleTimer::new();
+    #[cfg(feature = "panic_test")]
+    let mut panic_test = panic::PanicTest::new();
+    // Bench self-test drives the target instead of the host
+    let mut self_test = (axis == Axis::Test).then(SelfTest::new);
+    if self_test.is_some() {
+        defmt::println!("Self-test mode: host speed commands are ignored");
+    }
+    // Demo spin runs from a button press until the next (held at boot: starts once debounced)
+    let mut demo_button = DemoButton::new();
+    let mut demo: Option<DemoSpin> = None;
 
-    // Stop motor initially
-    ain1.set_duty_cycle_fully_off().unwrap();
-    ain2.set_duty_cycle_fully_off().unwrap();
+    // Last-resort recovery: a stalled loop resets the chip, and startup leaves the motor stopped.
+    // Started only now so the blocking strap fault halt never trips it.
+    watchdog.pause_on_debug(true);
+    watchdog.start(WATCHDOG_TIMEOUT_MS.millis());
 
     loop {
-        // Poll USB
-        usb_dev.poll(&mut [&mut hid]);
-
-        // Read output report from host
-        if let Ok(len) = hid.pull_raw_output(&mut usb_buf) {
-            if let Some(report) = OutputReport::ref_from_bytes(&usb_buf[..len]).ok() {
-                current_speed.speed_normalized = report.speed_normalized;
-                let percentage = (current_speed.speed_normalized as i32 * 100 / 32767) as i16;
-                defmt::println!("HID recv: speed={}% ({})", percentage, current_speed.speed_normalized);
-
-                // Apply motor command if speed changed
-                if current_speed.speed_normalized != last_speed.speed_normalized {
-                    apply_motor_speed(
-                        last_speed,
-                        current_speed,
-                        ain1,
-                        ain2,
-                        &mut timer,
+        // Fixed-rate control loop; USBCTRL_IRQ services the bus in the meantime
+        while !next_tick.is_elapsed(timer.get_counter().ticks()) {}
+        let now_us = timer.get_counter().ticks();
+        let now_ms = now_us / 1000;
+        // Resync instead of bursting through missed ticks (e.g. after a flash write)
+        next_tick = next_tick.next(CONTROL_TICK_US, now_us);
+
+        watchdog.feed();
+        heartbeat = heartbeat.wrapping_add(1);
+
+        // Stop on bus suspend (host asleep) or bus reset (replug, driver reload),
+        // honor commands again once configured
+        let state = usb::state();
+        if state != usb_state {
+            defmt::println!("USB state: {} -> {}", usb_state, state);
+            if state == UsbDeviceState::Default && usb_state != UsbDeviceState::Default {
+                defmt::println!("USB bus reset, stopping motor until the host commands again");
+                controller.set_target(MotorSpeed::STOP);
+                last_command_us = None;
+                sequence.stop();
+                spin_up.abort();
+                polarity.abort();
+                min_duty_calibration.abort();
+                // A command queued before the reset is from the previous session
+                let _ = usb::take_output();
+                #[cfg(feature = "dual_motor")]
+                {
+                    motor_b.suspend();
+                    let _ = usb::take_output_b();
+                }
+            }
+            if state == UsbDeviceState::Suspend {
+                defmt::println!("USB suspended, stopping motor");
+                wakeup.suspended(now_us);
+                controller.set_target(MotorSpeed::STOP);
+                last_command_us = None;
+                sequence.stop();
+                spin_up.abort();
+                polarity.abort();
+                min_duty_calibration.abort();
+                #[cfg(feature = "dual_motor")]
+                motor_b.suspend();
+            } else {
+                wakeup.resumed();
+            }
+            if state == UsbDeviceState::Configured {
+                enumerated = true;
+                // Give a newly (re)attached host the current state at once
+                status_idle.reset();
+                telemetry_idle.reset();
+                #[cfg(feature = "dual_motor")]
+                status_b_idle.reset();
+            }
+            usb_state = state;
+        }
+
+        // A fault that keeps the driver disabled also holds off the wake from idle sleep
+        let wake_blocked = fault_response == Some(FaultResponse::DisableDriver);
+        if wake_blocked {
+            idle_sleep.block_wake();
+        }
+        // Driver bring-up (again after an e-stop release, a fault that disabled it or an idle sleep ended by
+        // the host); self-test and the demo have no host to wait for
+        if driver.is_asleep()
+            && !estopped
+            && !wake_blocked
+            && !idle_sleep.is_asleep()
+            && (enumerated || self_test.is_some() || demo.is_some())
+        {
+            motor_sleep.set_high().unwrap();
+            driver.wake(now_us);
+            defmt::println!("Driver bring-up: nSLEEP high, waiting {}us for wake-up", DRIVER_WAKE_US);
+        }
+        if driver.update(now_us) {
+            defmt::println!("Driver bring-up: driver awake, accepting commands (idle sleep ended: {})", idle_sleep.is_waking());
+            idle_sleep.awake();
+        }
+
+        // Latest output report from host
+        if let Some(packet) = usb::take_output() {
+            // Commands are dropped while e-stopped, until the driver is awake
+            // and until an overcurrent, undervoltage or stall fault clears. A driver
+            // asleep for idleness is woken instead: the command waits for the wake time.
+            if idle_sleep.activity(wake_blocked) {
+                defmt::println!("Idle sleep: ended by a speed command");
+            }
+            let gate = CommandGate {
+                host_locked_out: self_test.is_some() || demo.is_some(),
+                estopped,
+                driver_awake: driver.is_awake() || idle_sleep.is_waking(),
+                overcurrent: overcurrent.is_tripped(),
+                undervoltage: undervoltage.is_tripped(),
+                stalled: stall.is_stalled(),
+            };
+            match handle_command(packet.data(), &gate, &mut controller, &config) {
+                CommandResult::Ignored if self_test.is_some() => {
+                    defmt::warn!("HID recv: speed command ignored in self-test mode");
+                }
+                CommandResult::Ignored => {
+                    defmt::warn!("HID recv: speed command ignored during the demo spin");
+                }
+                CommandResult::Command(command) => {
+                    last_command_us = Some(now_us);
+                    received_speed = command.raw_speed_normalized;
+                    if coast_test.abort() {
+                        defmt::println!("Coast test aborted by a speed command");
+                    }
+                    if sequence.stop() {
+                        defmt::println!("Sequence stopped by a speed command");
+                    }
+                    if spin_up.abort() {
+                        defmt::println!("Spin-up test aborted by a speed command");
+                    }
+                    if polarity.abort() {
+                        defmt::println!("Encoder calibration aborted by a speed command");
+                    }
+                    if min_duty_calibration.abort() {
+                        defmt::println!("Minimum duty calibration aborted by a speed command");
+                    }
+                    if command_timed_out {
+                        defmt::println!("Command timeout cleared, resuming");
+                        command_timed_out = false;
+                    }
+
+                    // A stop command re-arms stall detection
+                    if command.target == MotorSpeed::STOP {
+                        if stall.is_stalled() {
+                            defmt::println!("Stall cleared by stop command");
+                        }
+                        stall.clear();
+                    }
+                    let speed_normalized = command.target.speed_normalized;
+                    if let Some(line) = recv_log.offer(now_us, speed_normalized, command, &config) {
+                        let logged = line.value;
+                        defmt::println!(
+                            "HID recv: speed={}% ({}) -> duty={}% {} (kickstart={}{}), {} more not logged",
+                            normalized_to_percent(speed_normalized),
+                            speed_normalized,
+                            logged.duty,
+                            if logged.is_forward { "fwd" } else { "rev" },
+                            logged.kickstart,
+                            if logged.accepted { "" } else { ", dropped" },
+                            line.suppressed
+                        );
+                    }
+                }
+                // Keep the previous speed; a garbled command must not make the wheel jump
+                CommandResult::Invalid(OutputReportError::Checksum { expected, received }) => {
+                    defmt::warn!(
+                        "HID recv: checksum mismatch (expected {=u8:#x}, got {=u8:#x})",
+                        expected,
+                        received
                     );
-                    last_speed = current_speed;
+                    faults.record(Fault::INVALID_REPORT);
                 }
+                CommandResult::Invalid(OutputReportError::Malformed) => {
+                    defmt::warn!("HID recv: truncated output report (len={})", packet.data().len());
+                    faults.record(Fault::INVALID_REPORT);
+                }
+                CommandResult::Invalid(OutputReportError::UnknownReportId(id)) => {
+                    defmt::warn!("HID recv: report ID {} is not a speed command, ignored", id);
+                    faults.record(Fault::INVALID_REPORT);
+                }
+            }
+        }
+
+        #[cfg(feature = "dual_motor")]
+        if let Some(packet) = usb::take_output_b() {
+            if idle_sleep.activity(wake_blocked) {
+                defmt::println!("Idle sleep: ended by a motor B speed command");
+            }
+            let blocked = estopped
+                || !(driver.is_awake() || idle_sleep.is_waking())
+                || overcurrent.is_tripped()
+                || undervoltage.is_tripped();
+            if !motor_b.command(packet.data(), now_us, blocked, &config) {
+                faults.record(Fault::INVALID_REPORT);
+            }
+        }
+
+        // Queued feature reports (runtime config, emergency stop, axis override, config commands) from host
+        let dropped = usb::take_dropped_features();
+        if dropped > 0 {
+            defmt::warn!("Feature reports: {} dropped (queue full)", dropped);
+        }
+        while let Some(packet) = usb::take_feature() {
+            // Any host report ends an idle sleep, so the tests and calibrations below can start
+            if idle_sleep.activity(wake_blocked) {
+                defmt::println!("Idle sleep: ended by feature report {}", packet.report_id);
+            }
+            let payload = packet.payload();
+            let valid = match packet.report_id {
+                CONFIG_REPORT_ID => {
+                    // Validated whole into a new config; a rejected report leaves the old one in effect
+                    config_writes = config_writes.wrapping_add(1);
+                    let (valid, status) = match RWConfigReport::read_from_bytes(payload) {
+                        Ok(report) => match Config::from_report(&report) {
+                            Ok(new_config) => {
+                                // Skip identical writes to spare the flash
+                                if new_config != config {
+                                    flash::write(StoredConfig::new(&new_config).as_bytes());
+                                }
+                                if new_config.pwm_timing() != config.pwm_timing() {
+                                    let driven = if inverted { motor_output.mirrored() } else { motor_output };
+                                    set_pwm_timing(motor_pwm, new_config.pwm_timing(), driven, &mut pwm_errors);
+                                    #[cfg(feature = "dual_motor")]
+                                    motor_b.set_pwm_timing(new_config.pwm_timing());
+                                }
+                                config = new_config;
+                                // A config write is the host's cue to try closed loop again
+                                feedback.clear();
+                                controller.set_feedback_lost(false);
+                                defmt::println!("Config: {}", config);
+                                if config.effective_min_duty() > config.min_duty {
+                                    defmt::println!(
+                                        "Min duty raised to {}% for the driver's minimum pulse at {}Hz",
+                                        config.effective_min_duty(),
+                                        config.pwm_freq_hz
+                                    );
+                                }
+                                (true, RWConfigStatusReport::accepted(config_writes))
+                            }
+                            Err(e) => {
+                                defmt::warn!("Config: rejected ({}), keeping the previous config", e);
+                                (false, RWConfigStatusReport::rejected(config_writes, &e))
+                            }
+                        },
+                        Err(_) => {
+                            defmt::warn!("Config: invalid report length {}", packet.data().len());
+                            (false, RWConfigStatusReport::invalid_length(config_writes, payload.len()))
+                        }
+                    };
+                    usb::set_feature(CONFIG_STATUS_REPORT_ID, status.as_bytes());
+                    valid
+                }
+                ESTOP_REPORT_ID => {
+                    let command = RWEStopReport::read_from_bytes(payload)
+                        .ok()
+                        .and_then(|report| EStopCommand::from_report(&report));
+                    match command {
+                        Some(command @ (EStopCommand::Engage | EStopCommand::EngageRamped)) => {
+                            // A ramped stop needs the driver awake to brake (a running ramp carries on);
+                            // otherwise it is the same as the immediate one
+                            let ramped = command == EStopCommand::EngageRamped && (estop_ramp.is_running() || driver.is_awake());
+                            if ramped {
+                                estop_ramp.start(now_us);
+                            } else {
+                                // Disable the driver first, then make sure nothing re-enables the PWM
+                                estop_ramp.abort();
+                                motor_sleep.set_low().unwrap();
+                                driver.sleep();
+                            }
+                            controller.stop_now();
+                            coast_test.abort();
+                            sequence.stop();
+                            spin_up.abort();
+                            polarity.abort();
+                            min_duty_calibration.abort();
+                            if !ramped {
+                                motor_output = MotorOutput::Coast;
+                                set_motor_output(motor_output, &mut motor_pwm.channel_a, &mut motor_pwm.channel_b, &mut pwm_errors);
+                            }
+                            #[cfg(feature = "dual_motor")]
+                            motor_b.stop_now();
+                            if !estopped {
+                                if ramped {
+                                    defmt::warn!("E-stop engaged, braking up over {}ms, ignoring speed commands until released", ESTOP_RAMP_MS);
+                                } else {
+                                    defmt::warn!("E-stop engaged, ignoring speed commands until released");
+                                }
+                            }
+                            estopped = true;
+                            true
+                        }
+                        Some(EStopCommand::Release) => {
+                            // A ramp still running ends here, with the driver latched disabled as usual;
+                            // the driver is woken up again by the bring-up sequence
+                            if estop_ramp.abort() {
+                                motor_sleep.set_low().unwrap();
+                                driver.sleep();
+                            }
+                            if estopped {
+                                defmt::println!("E-stop released");
+                                estopped = false;
+                            }
+                            true
+                        }
+                        None => {
+                            defmt::warn!("E-stop: invalid report (len={})", packet.data().len());
+                            false
+                        }
+                    }
+                }
+                AXIS_OVERRIDE_REPORT_ID => match RWAxisOverrideReport::read_from_bytes(payload) {
+                    // The USB serial stays as enumerated; the host should read the device info report
+                    Ok(report) => match apply_axis_override(strapped_axis, &report) {
+                        Ok(new_axis) => {
+                            if new_axis != axis {
+                                defmt::println!("Axis override: {} -> {}", axis, new_axis);
+                                axis = new_axis;
+                                inverted = axis_is_inverted(axis);
+                                controller.set_wheel_spec(axis.wheel_spec());
+                                usb::set_feature(DEVICE_INFO_REPORT_ID, device_info(axis, strapped_axis, strap_levels).as_bytes());
+                                if self_test.take().is_some() {
+                                    defmt::println!("Self-test stopped, accepting host speed commands");
+                                    controller.set_target(MotorSpeed::STOP);
+                                }
+                            }
+                            true
+                        }
+                        Err(e) => {
+                            defmt::warn!("Axis override: rejected ({})", e);
+                            false
+                        }
+                    },
+                    Err(_) => {
+                        defmt::warn!("Axis override: invalid report length {}", packet.data().len());
+                        false
+                    }
+                },
+                CONFIG_COMMAND_REPORT_ID => {
+                    let command = RWConfigCommandReport::read_from_bytes(payload)
+                        .ok()
+                        .and_then(|report| ConfigCommand::from_report(&report));
+                    match command {
+                        Some(ConfigCommand::ResetToDefaults) => {
+                            flash::erase();
+                            if Config::default().pwm_timing() != config.pwm_timing() {
+                                let driven = if inverted { motor_output.mirrored() } else { motor_output };
+                                set_pwm_timing(motor_pwm, Config::default().pwm_timing(), driven, &mut pwm_errors);
+                                #[cfg(feature = "dual_motor")]
+                                motor_b.set_pwm_timing(Config::default().pwm_timing());
+                            }
+                            config = Config::default();
+                            feedback.clear();
+                            controller.set_feedback_lost(false);
+                            defmt::println!("Config reset to defaults: {}", config);
+                            true
+                        }
+                        None => {
+                            defmt::warn!("Config command: invalid report (len={})", packet.data().len());
+                            false
+                        }
+                    }
+                }
+                FAULT_CLEAR_REPORT_ID => match RWFaultClearReport::read_from_bytes(payload) {
+                    Ok(report) => {
+                        faults.clear(Fault::from_bits(report.mask.get()));
+                        defmt::println!("Faults cleared: {} (still latched: {})", report.mask.get(), faults.latched());
+                        true
+                    }
+                    Err(_) => {
+                        defmt::warn!("Fault clear: invalid report length {}", packet.data().len());
+                        false
+                    }
+                },
+                COAST_TEST_REPORT_ID => {
+                    let command = RWCoastTestReport::read_from_bytes(payload)
+                        .ok()
+                        .and_then(|report| CoastTestCommand::from_report(&report));
+                    match command {
+                        Some(CoastTestCommand::Start) if estopped || self_test.is_some() || demo.is_some() => {
+                            defmt::warn!("Coast test: not started while e-stopped, in self-test or during the demo spin");
+                            true
+                        }
+                        Some(CoastTestCommand::Start) => {
+                            // Free spin-down: no stop ramp and no brake, whatever the stop mode
+                            controller.stop_now();
+                            coast_test.start(now_us);
+                            if sequence.stop() {
+                                defmt::println!("Sequence stopped by the coast test");
+                            }
+                            if spin_up.abort() {
+                                defmt::println!("Spin-up test aborted by the coast test");
+                            }
+                            if polarity.abort() {
+                                defmt::println!("Encoder calibration aborted by the coast test");
+                            }
+                            if min_duty_calibration.abort() {
+                                defmt::println!("Minimum duty calibration aborted by the coast test");
+                            }
+                            defmt::println!("Coast test started at {}rpm", rpm.rpm());
+                            true
+                        }
+                        Some(CoastTestCommand::Abort) => {
+                            if coast_test.abort() {
+                                defmt::println!("Coast test aborted");
+                            }
+                            true
+                        }
+                        None => {
+                            defmt::warn!("Coast test: invalid report (len={})", packet.data().len());
+                            false
+                        }
+                    }
+                }
+                SEQUENCE_REPORT_ID => match RWSequenceReport::read_from_bytes(payload) {
+                    Ok(report) => match Sequence::from_report(&report) {
+                        Ok(new_sequence) => {
+                            if sequence.load(new_sequence) {
+                                defmt::println!("Sequence stopped by a new upload");
+                            }
+                            usb::set_feature(SEQUENCE_REPORT_ID, new_sequence.to_report().as_bytes());
+                            defmt::println!("Sequence loaded: {} steps", new_sequence.steps().len());
+                            true
+                        }
+                        Err(e) => {
+                            defmt::warn!("Sequence: rejected ({})", e);
+                            false
+                        }
+                    },
+                    Err(_) => {
+                        defmt::warn!("Sequence: invalid report length {}", packet.data().len());
+                        false
+                    }
+                },
+                SEQUENCE_COMMAND_REPORT_ID => {
+                    let command = RWSequenceCommandReport::read_from_bytes(payload)
+                        .ok()
+                        .and_then(|report| SequenceCommand::from_report(&report));
+                    match command {
+                        Some(SequenceCommand::Start) if estopped || self_test.is_some() || demo.is_some() => {
+                            defmt::warn!("Sequence: not started while e-stopped, in self-test or during the demo spin");
+                            true
+                        }
+                        Some(SequenceCommand::Start) => {
+                            if sequence.start(now_us) {
+                                if coast_test.abort() {
+                                    defmt::println!("Coast test aborted by the sequence");
+                                }
+                                if spin_up.abort() {
+                                    defmt::println!("Spin-up test aborted by the sequence");
+                                }
+                                if polarity.abort() {
+                                    defmt::println!("Encoder calibration aborted by the sequence");
+                                }
+                                if min_duty_calibration.abort() {
+                                    defmt::println!("Minimum duty calibration aborted by the sequence");
+                                }
+                                defmt::println!("Sequence started ({} steps)", sequence.sequence().steps().len());
+                            } else {
+                                defmt::warn!("Sequence: not started, no steps loaded");
+                            }
+                            true
+                        }
+                        Some(SequenceCommand::Stop) => {
+                            if sequence.stop() {
+                                defmt::println!("Sequence stopped");
+                            }
+                            true
+                        }
+                        None => {
+                            defmt::warn!("Sequence command: invalid report (len={})", packet.data().len());
+                            false
+                        }
+                    }
+                }
+                ENCODER_CALIBRATION_REPORT_ID => {
+                    let command = RWEncoderCalibrationReport::read_from_bytes(payload)
+                        .ok()
+                        .and_then(|report| PolarityCommand::from_report(&report));
+                    let blocked = estopped
+                        || self_test.is_some()
+                        || demo.is_some()
+                        || !(driver.is_awake() || idle_sleep.is_waking())
+                        || overcurrent.is_tripped()
+                        || undervoltage.is_tripped()
+                        || stall.is_stalled();
+                    match command {
+                        Some(PolarityCommand::Start) if blocked => {
+                            defmt::warn!("Encoder calibration: not started while the drive is blocked, in self-test or during the demo spin");
+                            true
+                        }
+                        Some(PolarityCommand::Start) => {
+                            controller.stop_now();
+                            coast_test.abort();
+                            sequence.stop();
+                            spin_up.abort();
+                            min_duty_calibration.abort();
+                            polarity.start(now_us);
+                            defmt::println!("Encoder calibration: driving forward for {}ms", POLARITY_CHECK_MS);
+                            true
+                        }
+                        Some(PolarityCommand::Abort) => {
+                            if polarity.abort() {
+                                defmt::println!("Encoder calibration aborted");
+                            }
+                            true
+                        }
+                        None => {
+                            defmt::warn!("Encoder calibration: invalid report (len={})", packet.data().len());
+                            false
+                        }
+                    }
+                }
+                MIN_DUTY_CALIBRATION_REPORT_ID => {
+                    let command = RWMinDutyCalibrationReport::read_from_bytes(payload)
+                        .ok()
+                        .and_then(|report| MinDutyCommand::from_report(&report));
+                    let blocked = estopped
+                        || self_test.is_some()
+                        || demo.is_some()
+                        || !(driver.is_awake() || idle_sleep.is_waking())
+                        || overcurrent.is_tripped()
+                        || undervoltage.is_tripped()
+                        || stall.is_stalled();
+                    match command {
+                        Some(MinDutyCommand::Start) if blocked => {
+                            defmt::warn!("Minimum duty calibration: not started while the drive is blocked, in self-test or during the demo spin");
+                            true
+                        }
+                        Some(MinDutyCommand::Start) => {
+                            controller.stop_now();
+                            coast_test.abort();
+                            sequence.stop();
+                            spin_up.abort();
+                            polarity.abort();
+                            min_duty_calibration.start(now_us, encoder::count());
+                            defmt::println!("Minimum duty calibration: ramping 1% every {}ms", MIN_DUTY_STEP_MS);
+                            true
+                        }
+                        Some(MinDutyCommand::Abort) => {
+                            if min_duty_calibration.abort() {
+                                defmt::println!("Minimum duty calibration aborted");
+                            }
+                            true
+                        }
+                        None => {
+                            defmt::warn!("Minimum duty calibration: invalid report (len={})", packet.data().len());
+                            false
+                        }
+                    }
+                }
+                SPIN_UP_TEST_REPORT_ID => {
+                    let command = RWSpinUpTestReport::read_from_bytes(payload)
+                        .ok()
+                        .and_then(|report| SpinUpCommand::from_report(&report));
+                    let blocked = estopped
+                        || self_test.is_some()
+                        || demo.is_some()
+                        || !(driver.is_awake() || idle_sleep.is_waking())
+                        || overcurrent.is_tripped()
+                        || undervoltage.is_tripped()
+                        || stall.is_stalled();
+                    match command {
+                        Some(SpinUpCommand::Start(_)) if blocked => {
+                            defmt::warn!("Spin-up test: not started while the drive is blocked, in self-test or during the demo spin");
+                            true
+                        }
+                        Some(SpinUpCommand::Start(target)) => {
+                            // The step goes through the controller like a speed command, ramp and kickstart included
+                            coast_test.abort();
+                            sequence.stop();
+                            polarity.abort();
+                            min_duty_calibration.abort();
+                            controller.set_target(target);
+                            received_speed = target.speed_normalized;
+                            last_command_us = Some(now_us);
+                            command_timed_out = false;
+                            spin_up.start(now_us, target, rpm.rpm(), controller.max_rpm(&config));
+                            defmt::println!(
+                                "Spin-up test: step from {}rpm to speed={}% ({})",
+                                rpm.rpm(),
+                                normalized_to_percent(target.speed_normalized),
+                                target.speed_normalized
+                            );
+                            true
+                        }
+                        Some(SpinUpCommand::Abort) => {
+                            if spin_up.abort() {
+                                defmt::println!("Spin-up test aborted");
+                            }
+                            true
+                        }
+                        None => {
+                            defmt::warn!("Spin-up test: invalid report (len={})", packet.data().len());
+                            false
+                        }
+                    }
+                }
+                DEVICE_INFO_REPORT_ID | EFFECTIVE_CONFIG_REPORT_ID | CONFIG_STATUS_REPORT_ID | DESCRIPTOR_INFO_REPORT_ID => {
+                    defmt::warn!("Feature report {} is read-only", packet.report_id);
+                    false
+                }
+                // Speed commands only count on the OUT endpoint
+                SPEED_REPORT_ID | SPEED_PERCENT_REPORT_ID | TELEMETRY_REPORT_ID => {
+                    defmt::warn!("Feature report: ID {} is an input or output report, ignored", packet.report_id);
+                    false
+                }
+                id => {
+                    defmt::warn!("Feature report: unknown report ID {}", id);
+                    false
+                }
+            };
+            if !valid {
+                faults.record(Fault::INVALID_REPORT);
             }
+            // The config or the axis (and with it the inversion) may have changed
+            encoder::set_reversed(config.encoder_reversed);
+            usb::set_feature(EFFECTIVE_CONFIG_REPORT_ID, RWEffectiveConfigReport::new(&config, inverted).as_bytes());
+        }
+
+        // Sequence playback: a due step is applied like a host speed command
+        if let Some(speed_normalized) = sequence.update(now_us) {
+            let target = MotorSpeed::new(speed_normalized);
+            defmt::println!(
+                "Sequence step: speed={}% ({})",
+                normalized_to_percent(target.speed_normalized),
+                target.speed_normalized
+            );
+            received_speed = speed_normalized;
+            command_timed_out = false;
+            if target == MotorSpeed::STOP {
+                stall.clear();
+            }
+            if !estopped
+                && (driver.is_awake() || idle_sleep.is_waking())
+                && !overcurrent.is_tripped()
+                && !undervoltage.is_tripped()
+                && !stall.is_stalled()
+            {
+                controller.set_target(target);
+            }
+            last_command_us = Some(now_us);
+        }
+        // The host may stay silent while a sequence plays or a spin-up test runs; the timeout runs again from their end
+        if sequence.is_playing() || spin_up.is_running() {
+            last_command_us = Some(now_us);
+        }
+
+        // Failsafe: stop the wheel if the host went silent
+        if let Some(last_us) = last_command_us {
+            if !command_timed_out && is_command_timed_out(now_us, last_us) {
+                defmt::warn!("Command timeout ({}ms without command), stopping motor", COMMAND_TIMEOUT_MS);
+                command_timed_out = true;
+                controller.set_target(MotorSpeed::STOP);
+            }
+        }
+
+        // Self-test: the controller still applies kickstart, slew and the protection limits
+        if let Some(test) = self_test.as_mut().filter(|_| driver.is_awake()) {
+            if let Some((index, step)) = test.update(now_us) {
+                defmt::println!(
+                    "Self-test step {}/{}: speed={}% for {}ms",
+                    index + 1,
+                    SELF_TEST_SEQUENCE.len(),
+                    normalized_to_percent(step.speed.speed_normalized),
+                    step.duration_ms
+                );
+                if step.speed == MotorSpeed::STOP {
+                    stall.clear();
+                }
+                if !estopped && !overcurrent.is_tripped() && !undervoltage.is_tripped() && !stall.is_stalled() {
+                    controller.set_target(step.speed);
+                }
+            }
+        }
+
+        // Demo button: a press starts the demo spin (ending whatever the host had running), the next stops it
+        if demo_button.update(now_us, demo_pin.is_low().unwrap()) {
+            if demo.take().is_some() {
+                defmt::println!("Demo spin stopped, accepting host speed commands");
+                controller.set_target(MotorSpeed::STOP);
+            } else if self_test.is_some() {
+                defmt::warn!("Demo spin: not started in self-test mode");
+            } else {
+                coast_test.abort();
+                sequence.stop();
+                spin_up.abort();
+                polarity.abort();
+                min_duty_calibration.abort();
+                // The host's last command no longer applies, so neither does its timeout
+                last_command_us = None;
+                command_timed_out = false;
+                demo = Some(DemoSpin::new(now_us));
+                defmt::println!("Demo spin started, host speed commands are ignored until the next press");
+            }
+        }
+        // Demo spin: a new target every tick, through the controller like the self-test
+        if let Some(spin) = demo.as_ref().filter(|_| driver.is_awake()) {
+            let speed = spin.update(now_us);
+            if speed == MotorSpeed::STOP {
+                stall.clear();
+            }
+            if !estopped && !overcurrent.is_tripped() && !undervoltage.is_tripped() && !stall.is_stalled() {
+                controller.set_target(speed);
+            }
+        }
+
+        // Thermal throttling: scale the allowed duty down between the start and limit temperatures
+        if next_temp_sample.is_elapsed(now_us) {
+            next_temp_sample = Deadline::after_ms(now_us, TEMP_SAMPLE_MS);
+            let raw: u16 = adc.read(&mut temp_sense).unwrap();
+            let max_duty = max_duty_for_temperature(temperature.update(raw), &config);
+            if (max_duty < 100) != throttling {
+                throttling = max_duty < 100;
+                defmt::warn!("Thermal throttling: {} (temp={} x0.1C)", throttling, temperature.decicelsius());
+            }
+            overheated = max_duty == 0;
+            controller.set_duty_limit(max_duty);
+            #[cfg(feature = "dual_motor")]
+            motor_b.set_duty_limit(max_duty);
+        }
+
+        // Overcurrent: cut the drive at once, resume on the next command once cleared. With the soft
+        // current limit on, the cut only comes if the current stays high despite the limit.
+        let raw: u16 = adc.read(&mut current_sense).unwrap();
+        let current_ma = adc_to_current_ma(raw);
+        let was_tripped = overcurrent.is_tripped();
+        if overcurrent.update_with_trip(now_us, current_ma, config.max_current_ma, config.overcurrent_trip_ms()) != was_tripped {
+            if overcurrent.is_tripped() {
+                defmt::warn!("Overcurrent ({}mA > {}mA), stopping motor", current_ma, config.max_current_ma);
+                controller.stop_now();
+                polarity.abort();
+                min_duty_calibration.abort();
+                // Motor B shares the driver, so it stops too
+                #[cfg(feature = "dual_motor")]
+                motor_b.stop_now();
+            } else {
+                defmt::println!("Overcurrent cleared");
+            }
+        }
+        // Undervoltage: a sagging supply browns out the driver, so stop cleanly instead
+        let raw: u16 = adc.read(&mut supply_sense).unwrap();
+        let supply_mv = adc_to_supply_mv(raw);
+        let was_undervoltage = undervoltage.is_tripped();
+        if undervoltage.update(now_us, supply_mv, config.min_supply_mv) != was_undervoltage {
+            if undervoltage.is_tripped() {
+                defmt::warn!("Undervoltage ({}mV < {}mV), stopping motor", supply_mv, config.min_supply_mv);
+                controller.stop_now();
+                polarity.abort();
+                min_duty_calibration.abort();
+                #[cfg(feature = "dual_motor")]
+                motor_b.stop_now();
+            } else {
+                defmt::println!("Undervoltage cleared ({}mV)", supply_mv);
+            }
+        }
+        // Torque mode and the soft current limit: the current loops take the same sample
+        controller.set_measured_current(current_ma, &config);
+
+        // Fault response: coast, brake or disable the driver while a fault cuts the drive (the stall is
+        // the one latched last tick). The e-stop keeps the driver off regardless.
+        let mut cutting = Fault::NONE;
+        if overcurrent.is_tripped() {
+            cutting |= Fault::OVERCURRENT;
+        }
+        if overheated {
+            cutting |= Fault::OVER_TEMPERATURE;
+        }
+        if undervoltage.is_tripped() {
+            cutting |= Fault::UNDERVOLTAGE;
+        }
+        if stall.is_stalled() {
+            cutting |= Fault::STALL;
+        }
+        let response = drive_response(cutting, &config);
+        if response != fault_response {
+            match response {
+                Some(FaultResponse::DisableDriver) => {
+                    // Motor B shares the driver, so it is cut too
+                    motor_sleep.set_low().unwrap();
+                    driver.sleep();
+                    defmt::warn!("Fault response: driver disabled until the fault clears");
+                }
+                Some(response) => defmt::warn!("Fault response: {}", response),
+                None => defmt::println!("Fault response: cleared"),
+            }
+            fault_response = response;
+        }
+
+        // Slew toward the target and run the kickstart state machine (held coasting during a coast test
+        // and while the driver wakes from idle sleep, driven directly by a ramped e-stop and the encoder
+        // and minimum duty calibrations). The controller is not advanced while waking, so the waking
+        // command's ramp and kickstart start on the first awake tick.
+        let output = if let Some(output) = estop_ramp.output(now_us) {
+            output
+        } else if coast_test.is_running() || idle_sleep.is_waking() {
+            MotorOutput::Coast
+        } else if let Some(output) = polarity.output().or(min_duty_calibration.output()) {
+            output
+        } else {
+            controller.update(now_us, &config)
+        };
+        let output = estop_ramp.with_fault_response(output, fault_response);
+        if controller.is_current_limited() != current_limiting {
+            current_limiting = controller.is_current_limited();
+            defmt::warn!("Current limit: {} ({}mA, limit {}mA)", current_limiting, current_ma, config.current_limit_ma);
+        }
+        let kickstarting = controller.is_kickstarting();
+        let motor_line = if output != motor_output {
+            motor_log.offer(now_us, (discriminant(&output), kickstarting), (output, kickstarting), &config)
+        } else {
+            // Last step of a ramp that changed faster than the log interval
+            motor_log.flush(now_us, &config)
+        };
+        if let Some(LogLine { value: (logged, kickstart), suppressed }) = motor_line {
+            defmt::println!("Motor: {} (kickstart={}), {} more not logged", logged, kickstart, suppressed);
+        }
+        if output != motor_output {
+            // Inversion is applied at the driver only; status and LED keep the commanded direction
+            let driven = if inverted { output.mirrored() } else { output };
+            set_motor_output(driven, &mut motor_pwm.channel_a, &mut motor_pwm.channel_b, &mut pwm_errors);
+            motor_output = output;
+        }
+        #[cfg(feature = "panic_test")]
+        panic_test.update(now_us, motor_output);
+        #[cfg(feature = "dual_motor")]
+        if !idle_sleep.is_waking() {
+            // Braked on the same ramp as motor A, until the shared driver is disabled
+            motor_b.update(now_us, &config, estop_ramp.output(now_us));
+        }
+
+        // Measured wheel speed (drives the PID in closed-loop mode and ends kickstarts early)
+        let measured = rpm.update(now_us, encoder::count());
+        if let Some(measured) = measured {
+            let was_kickstarting = controller.is_kickstarting();
+            let was_braking = controller.is_reversal_braking();
+            controller.set_measured_rpm(measured, &config);
+            if was_kickstarting && !controller.is_kickstarting() {
+                defmt::println!("Kickstart ended early at {}rpm", measured);
+            }
+            if was_braking && !controller.is_reversal_braking() {
+                defmt::println!("Reversal brake ended early at {}rpm", measured);
+            }
+        }
+
+        // Idle sleep: nSLEEP low once the wheel has stood still with nothing to drive it for idle_sleep_ms
+        let idle = driver.is_awake()
+            && controller.target() == MotorSpeed::STOP
+            && motor_output.counts() == 0
+            && rpm.rpm() == 0
+            && self_test.is_none()
+            && demo.is_none()
+            && !coast_test.is_running()
+            && !polarity.is_running()
+            && !min_duty_calibration.is_running()
+            && !sequence.is_playing()
+            && !spin_up.is_running()
+            && !estop_ramp.is_running();
+        #[cfg(feature = "dual_motor")]
+        let idle = idle && motor_b.is_stopped();
+        if idle_sleep.update(now_us, idle, &config) {
+            motor_sleep.set_low().unwrap();
+            driver.sleep();
+            defmt::println!("Idle sleep: driver asleep after {}ms stopped, until the next host report", config.idle_sleep_ms);
+        }
+
+        // Ramped e-stop: the driver is latched disabled once the brake has stopped the wheel
+        if estop_ramp.update(now_us, measured) {
+            motor_sleep.set_low().unwrap();
+            driver.sleep();
+            defmt::println!("E-stop: ramped stop done at {}rpm, driver disabled", rpm.rpm());
+        }
+
+        // Coast test: the status report streams the spin-down until the encoder reads 0
+        match coast_test.update(now_us, measured) {
+            Some(CoastTestEnd::Stopped { elapsed_ms }) => {
+                defmt::println!("Coast test done: wheel stopped after {}ms", elapsed_ms);
+            }
+            Some(CoastTestEnd::TimedOut) => {
+                defmt::warn!("Coast test timed out with the wheel at {}rpm", rpm.rpm());
+            }
+            None => {}
+        }
+
+        // Spin-up test: timed on the RPM measurements, the wheel keeps the step target afterwards
+        match spin_up.update(now_us, measured) {
+            Some(SpinUpEnd::Reached { elapsed_ms }) => {
+                defmt::println!("Spin-up test done: {}rpm after {}ms", rpm.rpm(), elapsed_ms);
+            }
+            Some(SpinUpEnd::TimedOut) => {
+                defmt::warn!("Spin-up test timed out with the wheel at {}rpm", rpm.rpm());
+            }
+            None => {}
+        }
+
+        // Encoder calibration: a wheel driven forward must measure forward
+        match polarity.update(now_us, measured) {
+            Some(PolarityCheck::Matches { rpm }) => {
+                defmt::println!("Encoder calibration: polarity correct ({}rpm forward)", rpm);
+            }
+            Some(PolarityCheck::Reversed { rpm }) => {
+                config.encoder_reversed = !config.encoder_reversed;
+                encoder::set_reversed(config.encoder_reversed);
+                flash::write(StoredConfig::new(&config).as_bytes());
+                usb::set_feature(EFFECTIVE_CONFIG_REPORT_ID, RWEffectiveConfigReport::new(&config, inverted).as_bytes());
+                defmt::warn!(
+                    "Encoder calibration: measured {}rpm driving forward, decode polarity flipped (reversed={})",
+                    rpm,
+                    config.encoder_reversed
+                );
+            }
+            Some(PolarityCheck::NoRotation { rpm }) => {
+                defmt::warn!("Encoder calibration: no rotation measured ({}rpm), polarity unchanged", rpm);
+            }
+            None => {}
+        }
+
+        // Minimum duty calibration: the first duty the encoder sees motion at becomes the floor
+        match min_duty_calibration.update(now_us, encoder::count()) {
+            Some(MinDutyResult::Found { duty }) => {
+                config.min_duty = duty;
+                flash::write(StoredConfig::new(&config).as_bytes());
+                usb::set_feature(EFFECTIVE_CONFIG_REPORT_ID, RWEffectiveConfigReport::new(&config, inverted).as_bytes());
+                defmt::println!("Minimum duty calibration: wheel started at {}% duty, stored as min_duty", duty);
+            }
+            Some(MinDutyResult::NoRotation) => {
+                defmt::warn!("Minimum duty calibration: no motion up to 100% duty, min_duty unchanged");
+            }
+            None => {}
+        }
+
+        // Stall: driven but the encoder shows no rotation (not while the calibration ramp is still below breakaway)
+        let stall_duty = if min_duty_calibration.is_running() { 0 } else { motor_output.counts() };
+        match stall.update(now_us, stall_duty, rpm.rpm(), &config) {
+            Some(StallEvent::Retry) => {
+                defmt::warn!(
+                    "Stall ({}rpm after {}ms), retrying with a stronger kickstart",
+                    rpm.rpm(),
+                    config.stall_window_ms
+                );
+                controller.retry_kickstart();
+            }
+            Some(StallEvent::Stalled) => {
+                defmt::warn!(
+                    "Stall ({}rpm after {}ms), stopping motor until a stop command",
+                    rpm.rpm(),
+                    config.stall_window_ms
+                );
+                controller.stop_now();
+                polarity.abort();
+                min_duty_calibration.abort();
+            }
+            None => {}
+        }
+
+        // Closed loop without encoder counts: run open loop rather than let the PID wind up
+        if feedback.update(now_us, motor_output.counts(), rpm.rpm(), &config) {
+            defmt::warn!(
+                "No encoder feedback after {}ms in closed loop, falling back to open loop",
+                config.feedback_timeout_ms
+            );
+        }
+        controller.set_feedback_lost(feedback.is_lost());
+
+        // Conditions active this tick; everything is latched for the host
+        let mut active = Fault::NONE;
+        if overcurrent.is_tripped() {
+            active |= Fault::OVERCURRENT;
+        }
+        if throttling {
+            active |= Fault::OVER_TEMPERATURE;
+        }
+        if command_timed_out {
+            active |= Fault::COMMAND_TIMEOUT;
+        }
+        if stall.is_stalled() {
+            active |= Fault::STALL;
+        }
+        if undervoltage.is_tripped() {
+            active |= Fault::UNDERVOLTAGE;
+        }
+        if feedback.is_lost() {
+            active |= Fault::NO_FEEDBACK;
+        }
+        #[cfg(feature = "dual_motor")]
+        if motor_b.is_command_timed_out() {
+            active |= Fault::COMMAND_TIMEOUT;
+        }
+        faults.set_active(active);
+
+        // Ask a suspended host to resume so it sees an active fault (or on the wake button)
+        let wake_pending = !faults.active().is_empty() || wake_button.is_low().unwrap();
+        if wakeup.should_request(now_us, wake_pending) {
+            if usb::remote_wakeup() {
+                defmt::println!("USB remote wakeup requested (faults={})", faults.active());
+            } else {
+                defmt::warn!("USB remote wakeup not enabled by the host");
+            }
+        }
+
+        // Status LED
+        let (duty, is_forward) = controller.applied().to_duty_and_direction(&config);
+        let led_pattern = if estopped {
+            LedPattern::On
+        } else if !faults.active().is_empty() {
+            LedPattern::DoubleBlink
+        } else if duty == 0 {
+            LedPattern::Off
+        } else if is_forward {
+            LedPattern::SlowBlink
+        } else {
+            LedPattern::FastBlink
+        };
+        let _ = led.set_state(led_pattern.is_on(now_ms).into());
+
+        // Report applied state back to host on change or at the idle rate (or at the configured
+        // telemetry period), alternating with telemetry (dropped while the previous report is pending)
+        let mut flags = 0;
+        if controller.control_mode(&config) == ControlMode::ClosedLoop {
+            flags |= RWStatusReport::FLAG_CLOSED_LOOP;
+        }
+        if overcurrent.is_tripped() {
+            flags |= RWStatusReport::FLAG_OVERCURRENT;
+        }
+        if throttling {
+            flags |= RWStatusReport::FLAG_THROTTLING;
+        }
+        if estopped {
+            flags |= RWStatusReport::FLAG_ESTOP;
+        }
+        if coast_test.is_running() {
+            flags |= RWStatusReport::FLAG_COAST_TEST;
+        }
+        if sequence.is_playing() {
+            flags |= RWStatusReport::FLAG_SEQUENCE;
+        }
+        if polarity.is_running() {
+            flags |= RWStatusReport::FLAG_ENCODER_CALIBRATION;
+        }
+        let status = RWStatusReport::new(
+            controller.applied(),
+            motor_output,
+            rpm.rpm(),
+            temperature.decicelsius().unwrap_or(0),
+            flags,
+        )
+        .with_faults(faults.latched());
+        // Liveness counters change every tick and don't count as a change
+        let status_state = status.to_report();
+        // Both slices go to the same driver, so motor B's failures count too
+        #[cfg(feature = "dual_motor")]
+        let pwm_errors = pwm_errors.saturating_add(motor_b.pwm_errors());
+        let telemetry = RWTelemetryReport::new(received_speed, &controller, &config)
+            .with_driver_enabled(motor_sleep.is_set_high().unwrap())
+            .with_idle_asleep(idle_sleep.is_asleep())
+            .with_pwm_errors(pwm_errors)
+            .with_min_duty_ramp(min_duty_calibration.duty())
+            .with_spin_up(&spin_up);
+        // Loop timing and the supply voltage change every tick and don't count as a change either
+        let telemetry_state = telemetry.to_report();
+        let status_report = status.with_liveness(heartbeat, now_ms as u32).to_report();
+        let telemetry_report = telemetry.with_loop_max_us(loop_timer.max_us()).with_supply_mv(supply_mv).to_report();
+        let period = config.telemetry_period_ms;
+        let status_due = status_idle.is_due_at(period, usb::idle_rate(SPEED_REPORT_ID), now_us, &status_state);
+        let telemetry_due =
+            telemetry_idle.is_due_at(period, usb::idle_rate(TELEMETRY_REPORT_ID), now_us, &telemetry_state);
+        if telemetry_due && (telemetry_next || !status_due) {
+            if usb::push_input(&telemetry_report) {
+                telemetry_idle.sent(telemetry_state, now_us);
+                loop_timer.reset();
+                telemetry_next = false;
+            }
+        } else if status_due && usb::push_input(&status_report) {
+            status_idle.sent(status_state, now_us);
+            telemetry_next = true;
+        }
+        // The same two reports on the UART, whatever the USB state
+        #[cfg(feature = "uart_telemetry")]
+        {
+            if uart_telemetry.is_due(now_us) {
+                uart_telemetry.queue(&status_report);
+                uart_telemetry.queue(&telemetry_report);
+            }
+            uart_telemetry.poll();
+        }
+        #[cfg(feature = "dual_motor")]
+        {
+            let status_b = motor_b
+                .status(temperature.decicelsius().unwrap_or(0), flags)
+                .with_faults(faults.latched());
+            let status_b_state = status_b.to_report();
+            if status_b_idle.is_due_at(period, usb::idle_rate_b(SPEED_REPORT_ID), now_us, &status_b_state)
+                && usb::push_input_b(&status_b.with_liveness(heartbeat, now_ms as u32).to_report())
+            {
+                status_b_idle.sent(status_b_state, now_us);
+            }
+        }
+
+        // Loop timing watchpoint: this iteration's work, without the wait for the tick
+        if let Some(busy_us) = loop_timer.record(now_us, timer.get_counter().ticks()) {
+            faults.record(Fault::LOOP_OVERRUN);
+            defmt::warn!("Loop overrun: iteration took {}us (> {}us)", busy_us, LOOP_OVERRUN_US);
         }
     }
 }
 
-/// Apply motor speed with kickstart logic
-fn apply_motor_speed<A, B, T>(
-    last: MotorSpeed,
-    current: MotorSpeed,
-    ain1: &mut A,
-    ain2: &mut B,
-    timer: &mut T,
-)
+/// Halt on an unstable axis strap
+///
+/// The motor driver stays disabled and USB is never enumerated. The onboard LED
+/// shows `LedPattern::TripleBlink` so the fault is visible without RTT.
+#[cfg(feature = "axis-auto")]
+fn strap_fault_halt<P: OutputPin>(led: &mut P, timer: &hal::timer::Timer) -> ! {
+    loop {
+        let now_ms = timer.get_counter().ticks() / 1000;
+        let _ = led.set_state(LedPattern::TripleBlink.is_on(now_ms).into());
+    }
+}
+
+/// Spin until `ms` have passed on the timer; only for boot, before the control loop runs
+#[cfg(feature = "axis-auto")]
+fn wait_ms(timer: &hal::timer::Timer, ms: u32) {
+    let deadline = Deadline::after_ms(timer.get_counter().ticks(), ms);
+    while !deadline.is_elapsed(timer.get_counter().ticks()) {}
+}
+
+/// Retime the motor PWM slice and re-apply `driven`, whose compare values were relative to the old TOP
+fn set_pwm_timing<I>(
+    pwm: &mut hal::pwm::Slice<I, hal::pwm::FreeRunning>,
+    timing: PwmTiming,
+    driven: MotorOutput,
+    errors: &mut u16,
+) where
+    I: hal::pwm::SliceId,
+    hal::pwm::FreeRunning: hal::pwm::ValidSliceMode<I>,
+{
+    pwm.set_top(timing.top);
+    pwm.set_div_int(timing.div_int);
+    set_motor_output(driven, &mut pwm.channel_a, &mut pwm.channel_b, errors);
+    defmt::println!("PWM: {}Hz (div={}, top={})", timing.frequency_hz(), timing.div_int, timing.top);
+}
+
+/// Drive the motor driver inputs for `output`, counting a failed duty-set in `errors`
+fn set_motor_output<A, B>(output: MotorOutput, ain1: &mut A, ain2: &mut B, errors: &mut u16)
 where
     A: SetDutyCycle,
     B: SetDutyCycle,
-    T: DelayNs,
 {
-    let (duty, is_forward) = current.to_duty_and_direction();
-    let (last_duty, last_forward) = last.to_duty_and_direction();
-
-    // Check if kickstart needed (direction change or start from stop)
-    let needs_kickstart =
-        (last_duty == 0 && duty > 0) ||  // Starting from stop
-        (last_forward != is_forward && duty > 0);  // Direction change
-
-    if duty == 0 {
-        // Stop motor
-        defmt::println!("Motor: STOP");
-        let _ = ain1.set_duty_cycle_fully_off();
-        let _ = ain2.set_duty_cycle_fully_off();
-    } else if is_forward {
-        if needs_kickstart {
-            defmt::println!("Motor: FWD Kickstart -> {}%", duty);
-            let _ = ain2.set_duty_cycle_fully_off();
-            let _ = ain1.set_duty_cycle_percent(KICKSTART_DUTY);
-            timer.delay_ms(KICKSTART_MS);
-        }
-        defmt::println!("Motor: FWD {}%", duty);
-        let _ = ain2.set_duty_cycle_fully_off();
-        let _ = ain1.set_duty_cycle_percent(duty);
-    } else {
-        if needs_kickstart {
-            defmt::println!("Motor: REV Kickstart -> {}%", duty);
-            let _ = ain1.set_duty_cycle_fully_off();
-            let _ = ain2.set_duty_cycle_percent(KICKSTART_DUTY);
-            timer.delay_ms(KICKSTART_MS);
-        }
-        defmt::println!("Motor: REV {}%", duty);
-        let _ = ain1.set_duty_cycle_fully_off();
-        let _ = ain2.set_duty_cycle_percent(duty);
+    // `&` so the second input is still set when the first fails
+    let ok = match output {
+        MotorOutput::Coast => ain1.set_duty_cycle_fully_off().is_ok() & ain2.set_duty_cycle_fully_off().is_ok(),
+        MotorOutput::Brake => ain1.set_duty_cycle_fully_on().is_ok() & ain2.set_duty_cycle_fully_on().is_ok(),
+        // Both channels of the slice count together, so the inputs are high (shorting the windings) at the same time
+        MotorOutput::PartialBrake(counts) => {
+            ain1.set_duty_cycle_fraction(counts, PWM_TOP).is_ok() & ain2.set_duty_cycle_fraction(counts, PWM_TOP).is_ok()
+        }
+        // Counts are relative to PWM_TOP; the fraction maps PWM_TOP to fully on (TOP + 1)
+        MotorOutput::Forward(counts) => {
+            ain2.set_duty_cycle_fully_off().is_ok() & ain1.set_duty_cycle_fraction(counts, PWM_TOP).is_ok()
+        }
+        MotorOutput::Reverse(counts) => {
+            ain1.set_duty_cycle_fully_off().is_ok() & ain2.set_duty_cycle_fraction(counts, PWM_TOP).is_ok()
+        }
+    };
+    if !ok {
+        *errors = errors.saturating_add(1);
+        defmt::error!("PWM duty-set failed for {} ({} failures)", output, *errors);
     }
 }
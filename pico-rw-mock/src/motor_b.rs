@@ -0,0 +1,186 @@
+//! Second motor on the DRV8833 B bridge (`dual_motor` feature)
+//!
+//! Motor B is addressed through its own HID interface (interface 1) carrying the
+//! same speed output and status input reports as interface 0, so the host can
+//! drive both wheels independently with the existing report formats. It shares
+//! the runtime config, nSLEEP, e-stop, overcurrent and thermal limits with
+//! motor A, but has no encoder or current sense and always runs open loop.
+
+use core::mem::{discriminant, Discriminant};
+
+use rp_pico::hal::pwm::{FreeRunning, Pwm7, Slice};
+use usbd_hid::descriptor::generator_prelude::*;
+
+use rw_core::config::{Config, ControlMode};
+use rw_core::control::{MotorController, MotorOutput};
+use rw_core::failsafe::{is_command_timed_out, COMMAND_TIMEOUT_MS};
+use rw_core::lograte::{LogLimiter, LogLine};
+use rw_core::motor::{normalized_to_percent, MotorSpeed};
+use rw_core::protocol::{OutputReport, OutputReportError, RWStatusReport};
+use rw_core::pwm::PwmTiming;
+
+use crate::{set_motor_output, set_pwm_timing};
+
+/// BIN1: GPIO14 (PWM7 channel A), BIN2: GPIO15 (PWM7 channel B)
+pub type MotorBPwm = Slice<Pwm7, FreeRunning>;
+
+motor_descriptor! {
+    /// HID report descriptor of interface 1: same layout as report IDs 1 and 9 of interface 0
+    pub struct MotorBReport, usage = 0x02,
+    {},
+    {}
+}
+
+pub struct MotorB {
+    pwm: MotorBPwm,
+    controller: MotorController,
+    output: MotorOutput,
+    // Timer timestamp (us) of the last valid command, None until the first one
+    last_command_us: Option<u64>,
+    command_timed_out: bool,
+    // Failed duty-set calls on this slice
+    pwm_errors: u16,
+    recv_log: LogLimiter<i16, i8>,
+    motor_log: LogLimiter<(Discriminant<MotorOutput>, bool), (MotorOutput, bool)>,
+}
+
+impl MotorB {
+    /// Take over a PWM slice that is already routed to BIN1/BIN2, with the motor stopped
+    pub fn new(mut pwm: MotorBPwm, timing: PwmTiming) -> Self {
+        pwm.set_top(timing.top);
+        pwm.set_div_int(timing.div_int);
+        let mut pwm_errors = 0;
+        set_motor_output(MotorOutput::Coast, &mut pwm.channel_a, &mut pwm.channel_b, &mut pwm_errors);
+        pwm.enable();
+        Self {
+            pwm,
+            controller: MotorController::new(),
+            output: MotorOutput::Coast,
+            last_command_us: None,
+            command_timed_out: false,
+            pwm_errors,
+            recv_log: LogLimiter::new(),
+            motor_log: LogLimiter::new(),
+        }
+    }
+
+    /// Handle an output report from interface 1; `blocked` drops the speed (e-stop, overcurrent)
+    ///
+    /// Returns false if the report was invalid.
+    pub fn command(&mut self, data: &[u8], now_us: u64, blocked: bool, config: &Config) -> bool {
+        match OutputReport::parse_command(data) {
+            Ok(report) => {
+                self.last_command_us = Some(now_us);
+                if self.command_timed_out {
+                    defmt::println!("Motor B: command timeout cleared, resuming");
+                    self.command_timed_out = false;
+                }
+
+                let target = MotorSpeed::new(report.speed_normalized());
+                let percentage = normalized_to_percent(target.speed_normalized);
+                if let Some(line) = self.recv_log.offer(now_us / 1000, target.speed_normalized, percentage, config) {
+                    defmt::println!(
+                        "Motor B: HID recv speed={}% ({}), {} more not logged",
+                        line.value,
+                        target.speed_normalized,
+                        line.suppressed
+                    );
+                }
+                if !blocked {
+                    self.controller.set_target(target);
+                }
+                true
+            }
+            Err(OutputReportError::Checksum { expected, received }) => {
+                defmt::warn!(
+                    "Motor B: checksum mismatch (expected {=u8:#x}, got {=u8:#x})",
+                    expected,
+                    received
+                );
+                false
+            }
+            Err(OutputReportError::Malformed) => {
+                defmt::warn!("Motor B: truncated output report (len={})", data.len());
+                false
+            }
+            Err(OutputReportError::UnknownReportId(id)) => {
+                defmt::warn!("Motor B: report ID {} is not a speed command, ignored", id);
+                false
+            }
+        }
+    }
+
+    /// Stop at the configured ramp and wait for a new command (bus suspend or reset)
+    pub fn suspend(&mut self) {
+        self.controller.set_target(MotorSpeed::STOP);
+        self.last_command_us = None;
+    }
+
+    /// Cut the drive at once (e-stop, overcurrent)
+    pub fn stop_now(&mut self) {
+        self.controller.stop_now();
+        self.output = MotorOutput::Coast;
+        set_motor_output(self.output, &mut self.pwm.channel_a, &mut self.pwm.channel_b, &mut self.pwm_errors);
+    }
+
+    /// Target and output both at stop
+    pub fn is_stopped(&self) -> bool {
+        self.controller.target() == MotorSpeed::STOP && self.output.counts() == 0
+    }
+
+    pub fn is_command_timed_out(&self) -> bool {
+        self.command_timed_out
+    }
+
+    pub fn pwm_errors(&self) -> u16 {
+        self.pwm_errors
+    }
+
+    pub fn set_duty_limit(&mut self, limit: u8) {
+        self.controller.set_duty_limit(limit);
+    }
+
+    pub fn set_pwm_timing(&mut self, timing: PwmTiming) {
+        set_pwm_timing(&mut self.pwm, timing, self.output, &mut self.pwm_errors);
+    }
+
+    /// Run the command timeout and one control tick; `braking` is the ramped
+    /// e-stop output for this tick, driven instead of the controller's
+    pub fn update(&mut self, now_us: u64, config: &Config, braking: Option<MotorOutput>) {
+        if let Some(last_us) = self.last_command_us {
+            if !self.command_timed_out && is_command_timed_out(now_us, last_us) {
+                defmt::warn!("Motor B: command timeout ({}ms without command), stopping", COMMAND_TIMEOUT_MS);
+                self.command_timed_out = true;
+                self.controller.set_target(MotorSpeed::STOP);
+            }
+        }
+
+        // No encoder or current sense on motor B: closed-loop and torque mode would never get feedback
+        let config = Config { control_mode: ControlMode::OpenLoop, ..*config };
+        let output = braking.unwrap_or_else(|| self.controller.update(now_us, &config));
+        let kickstarting = self.controller.is_kickstarting();
+        let line = if output != self.output {
+            self.motor_log.offer(now_us, (discriminant(&output), kickstarting), (output, kickstarting), &config)
+        } else {
+            self.motor_log.flush(now_us, &config)
+        };
+        if let Some(LogLine { value: (logged, kickstart), suppressed }) = line {
+            defmt::println!("Motor B: {} (kickstart={}), {} more not logged", logged, kickstart, suppressed);
+        }
+        if output != self.output {
+            set_motor_output(output, &mut self.pwm.channel_a, &mut self.pwm.channel_b, &mut self.pwm_errors);
+            self.output = output;
+        }
+    }
+
+    /// Status report for interface 1 (`flags` as for motor A, without the closed-loop bit)
+    pub fn status(&self, temperature: i16, flags: u8) -> RWStatusReport {
+        RWStatusReport::new(
+            self.controller.applied(),
+            self.output,
+            0,
+            temperature,
+            flags & !RWStatusReport::FLAG_CLOSED_LOOP,
+        )
+    }
+}
@@ -0,0 +1,84 @@
+//! Panic handler that leaves the motor stopped
+//!
+//! A plain halt would freeze the PWM slices and nSLEEP in whatever state the
+//! loop last set, so a bug hit at full duty would keep the wheel spinning
+//! until the watchdog resets the chip. The handler drops nSLEEP and zeroes
+//! every PWM compare register first, then halts. It only does single register
+//! writes through stolen peripherals: no HAL pins, no locks, no bounds-checked
+//! indexing, nothing that could panic again. The watchdog keeps running, so
+//! outside a debug session the chip still resets after `WATCHDOG_TIMEOUT_MS`.
+
+use core::panic::PanicInfo;
+use core::sync::atomic::{compiler_fence, AtomicBool, Ordering};
+
+use rp_pico::hal::pac;
+
+use crate::pinout::PINOUT;
+
+#[cfg(feature = "panic_test")]
+use rw_core::control::MotorOutput;
+#[cfg(feature = "panic_test")]
+use rw_core::deadline::Deadline;
+
+/// GPIO of the DRV8833 nSLEEP input (low = driver disabled)
+pub const NSLEEP_PIN: u32 = PINOUT.nsleep as u32;
+
+/// Set on entry, so a panic while logging the first one goes straight to the halt
+static PANICKED: AtomicBool = AtomicBool::new(false);
+
+/// Disable the driver and zero all PWM outputs (both motor bridges included)
+fn stop_motor() {
+    // SAFETY: single write-only register accesses; the main loop is never resumed after this
+    let p = unsafe { pac::Peripherals::steal() };
+    p.SIO.gpio_out_clr().write(|w| unsafe { w.bits(1 << NSLEEP_PIN) });
+    for slice in p.PWM.ch_iter() {
+        slice.cc().write(|w| unsafe { w.bits(0) });
+    }
+}
+
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    // USBCTRL_IRQ must not get to run again: its report handlers can change the outputs
+    cortex_m::interrupt::disable();
+    stop_motor();
+    if !PANICKED.load(Ordering::Relaxed) {
+        PANICKED.store(true, Ordering::Relaxed);
+        defmt::error!("Panic, motor stopped: {}", defmt::Display2Format(info));
+    }
+    loop {
+        compiler_fence(Ordering::SeqCst);
+    }
+}
+
+/// Drive time after which the `panic_test` build panics on purpose [us]
+#[cfg(feature = "panic_test")]
+const PANIC_TEST_AFTER_US: u64 = 2_000_000;
+
+/// `panic_test` builds: panic once the motor has been driven for `PANIC_TEST_AFTER_US`
+///
+/// For checking on the bench that the wheel coasts down after a panic
+/// instead of holding its last duty.
+#[cfg(feature = "panic_test")]
+pub struct PanicTest {
+    // Timer timestamp (us) the current drive started, None while stopped
+    driving_since_us: Option<u64>,
+}
+
+#[cfg(feature = "panic_test")]
+impl PanicTest {
+    pub const fn new() -> Self {
+        Self { driving_since_us: None }
+    }
+
+    /// Call once per control tick with the output just applied
+    pub fn update(&mut self, now_us: u64, output: MotorOutput) {
+        if !matches!(output, MotorOutput::Forward(_) | MotorOutput::Reverse(_)) {
+            self.driving_since_us = None;
+            return;
+        }
+        let since_us = *self.driving_since_us.get_or_insert(now_us);
+        if Deadline::after_us(since_us, PANIC_TEST_AFTER_US).is_elapsed(now_us) {
+            panic!("panic_test: deliberate panic while driving at {}% duty", output.duty());
+        }
+    }
+}
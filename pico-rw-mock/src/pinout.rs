@@ -0,0 +1,57 @@
+//! Motor driver pinout, selected at build time
+//!
+//! `PINOUT` names the GPIOs of motor A's bridge inputs and nSLEEP for code that
+//! works on pin numbers (the panic handler, logs); `motor_a_pins!` takes the
+//! same pins and their PWM slice out of the HAL, selected by the same feature.
+//! On the RP2040 GPIO n is on PWM slice (n / 2) % 8, channel A for even n and B
+//! for odd, so both bridge inputs have to be one slice's A/B pair. The HAL
+//! rejects a pin routed to the wrong slice or channel at compile time
+//! (`ValidPwmOutputPin`), and the checks below catch a `PINOUT` that has
+//! drifted from the macro.
+
+/// GPIO numbers of motor A on the DRV8833
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pinout {
+    /// AIN1 (PWM channel A)
+    pub ain1: u8,
+    /// AIN2 (PWM channel B of the same slice)
+    pub ain2: u8,
+    /// nSLEEP (low = driver disabled)
+    pub nsleep: u8,
+    /// PWM slice of AIN1/AIN2
+    pub pwm_slice: u8,
+}
+
+impl Pinout {
+    /// PWM slice driving GPIO `gpio`
+    pub const fn slice_of(gpio: u8) -> u8 {
+        (gpio / 2) % 8
+    }
+}
+
+/// Original board: AIN1 GPIO16, AIN2 GPIO17 (PWM0), nSLEEP GPIO18
+#[cfg(not(feature = "pinout_rev2"))]
+pub const PINOUT: Pinout = Pinout { ain1: 16, ain2: 17, nsleep: 18, pwm_slice: 0 };
+
+/// Board revision 2: AIN1 GPIO10, AIN2 GPIO11 (PWM5), nSLEEP GPIO12
+#[cfg(feature = "pinout_rev2")]
+pub const PINOUT: Pinout = Pinout { ain1: 10, ain2: 11, nsleep: 12, pwm_slice: 5 };
+
+const _: () = assert!(PINOUT.ain1.is_multiple_of(2) && PINOUT.ain2 == PINOUT.ain1 + 1);
+const _: () = assert!(Pinout::slice_of(PINOUT.ain1) == PINOUT.pwm_slice);
+const _: () = assert!(PINOUT.nsleep != PINOUT.ain1 && PINOUT.nsleep != PINOUT.ain2);
+
+/// `(ain1, ain2, nsleep, slice)` of `PINOUT` from `hal::gpio::Pins` and `hal::pwm::Slices`
+#[cfg(not(feature = "pinout_rev2"))]
+macro_rules! motor_a_pins {
+    ($pins:ident, $slices:ident) => {
+        ($pins.gpio16, $pins.gpio17, $pins.gpio18, &mut $slices.pwm0)
+    };
+}
+
+#[cfg(feature = "pinout_rev2")]
+macro_rules! motor_a_pins {
+    ($pins:ident, $slices:ident) => {
+        ($pins.gpio10, $pins.gpio11, $pins.gpio12, &mut $slices.pwm5)
+    };
+}
@@ -0,0 +1,88 @@
+//! Framed telemetry on UART1 (`uart_telemetry` feature)
+//!
+//! A fallback for when USB is not available (or is in use by something else):
+//! the status and telemetry reports go out on UART1 TX as `rw_core::frame`
+//! frames every `UART_TELEMETRY_PERIOD_MS`, whether or not a USB host is
+//! connected. Nothing is received, so the UART is not a command path.
+//!
+//! At 115200 baud a status and telemetry pair takes about 5ms to send, longer
+//! than a control tick, so frames go through a queue that `poll` drains into
+//! the UART FIFO without blocking. A frame that doesn't fit in the queue is
+//! dropped whole, so the stream never carries a partial frame.
+
+use rp_pico::hal;
+use rw_core::deadline::Deadline;
+use rw_core::frame::Frame;
+
+use hal::pac::UART1;
+use hal::uart::{Enabled, UartPeripheral, ValidUartPinout};
+
+pub const UART_BAUD: u32 = 115_200;
+
+/// Interval between status/telemetry pairs (a few percent of the line at 115200 baud)
+pub const UART_TELEMETRY_PERIOD_MS: u32 = 100;
+
+/// Queued bytes not yet in the FIFO (room for several report pairs)
+const QUEUE_LEN: usize = 256;
+
+pub struct UartTelemetry<P: ValidUartPinout<UART1>> {
+    uart: UartPeripheral<Enabled, UART1, P>,
+    // Ring buffer: `queued` bytes from `head`
+    queue: [u8; QUEUE_LEN],
+    head: usize,
+    queued: usize,
+    next: Deadline,
+    dropped: u32,
+}
+
+impl<P: ValidUartPinout<UART1>> UartTelemetry<P> {
+    pub fn new(uart: UartPeripheral<Enabled, UART1, P>) -> Self {
+        Self {
+            uart,
+            queue: [0; QUEUE_LEN],
+            head: 0,
+            queued: 0,
+            next: Deadline::at(0),
+            dropped: 0,
+        }
+    }
+
+    /// True once per period; the caller then queues the reports
+    pub fn is_due(&mut self, now_us: u64) -> bool {
+        if !self.next.is_elapsed(now_us) {
+            return false;
+        }
+        self.next = Deadline::after_ms(now_us, UART_TELEMETRY_PERIOD_MS);
+        true
+    }
+
+    /// Queue `report` (as from `to_report()`) as one frame, or drop it if the queue is full
+    pub fn queue(&mut self, report: &[u8]) {
+        let Some(frame) = Frame::from_report(report) else {
+            return;
+        };
+        let bytes = frame.as_bytes();
+        if QUEUE_LEN - self.queued < bytes.len() {
+            self.dropped += 1;
+            if self.dropped.is_power_of_two() {
+                defmt::warn!("UART telemetry: {} frames dropped", self.dropped);
+            }
+            return;
+        }
+        for &byte in bytes {
+            self.queue[(self.head + self.queued) % QUEUE_LEN] = byte;
+            self.queued += 1;
+        }
+    }
+
+    /// Move queued bytes into the UART FIFO until it is full
+    pub fn poll(&mut self) {
+        while self.queued > 0 {
+            if self.uart.write_raw(&[self.queue[self.head]]).is_err() {
+                break;
+            }
+            self.head = (self.head + 1) % QUEUE_LEN;
+            self.queued -= 1;
+        }
+    }
+}
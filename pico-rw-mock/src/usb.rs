@@ -0,0 +1,237 @@
+//! USB HID device serviced from USBCTRL_IRQ
+//!
+//! The interrupt handler handles enumeration and report I/O, keeps the latest
+//! output report and queues feature reports. The main loop takes them on its
+//! control tick, so USB traffic never delays motor updates.
+//!
+//! With the `dual_motor` feature a second HID interface carries motor B's
+//! speed and status reports.
+//!
+//! Each interface keeps the idle rates set by the host (`idle.rs`); the main
+//! loop reads them to decide when to repeat unchanged input reports.
+
+use core::cell::{Cell, RefCell};
+
+use critical_section::Mutex;
+use rp_pico::hal::pac::{self, interrupt};
+use rp_pico::hal::usb::UsbBus;
+use usb_device::device::{UsbDevice, UsbDeviceState};
+use usbd_hid::hid_class::{HIDClass, ReportType};
+
+use rw_core::idle::{IdleRate, DEFAULT_IDLE_RATE};
+
+use crate::feature::FeatureReports;
+use crate::idle::IdleRates;
+
+/// Feature reports buffered between control ticks (SET_REPORT takes at least a frame each)
+const FEATURE_QUEUE_LEN: usize = 4;
+
+/// Report received from the host (starts with the report ID byte)
+#[derive(Clone, Copy)]
+pub struct Packet {
+    pub report_id: u8,
+    len: usize,
+    data: [u8; 64],
+}
+
+impl Packet {
+    /// Whole report, including the report ID byte
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+
+    /// Report without the report ID byte
+    pub fn payload(&self) -> &[u8] {
+        self.data.get(1..self.len).unwrap_or(&[])
+    }
+}
+
+struct Usb {
+    device: UsbDevice<'static, UsbBus>,
+    hid: HIDClass<'static, UsbBus>,
+    idle: IdleRates,
+    #[cfg(feature = "dual_motor")]
+    hid_b: HIDClass<'static, UsbBus>,
+    #[cfg(feature = "dual_motor")]
+    idle_b: IdleRates,
+    features: FeatureReports,
+}
+
+struct Inbox {
+    /// Latest output report; a newer speed command replaces an unread one
+    output: Option<Packet>,
+    /// Latest output report of motor B's interface
+    #[cfg(feature = "dual_motor")]
+    output_b: Option<Packet>,
+    features: [Option<Packet>; FEATURE_QUEUE_LEN],
+    /// Feature reports lost to a full queue since the last `take_dropped_features`
+    dropped_features: u32,
+}
+
+static USB: Mutex<RefCell<Option<Usb>>> = Mutex::new(RefCell::new(None));
+static INBOX: Mutex<RefCell<Inbox>> = Mutex::new(RefCell::new(Inbox {
+    output: None,
+    #[cfg(feature = "dual_motor")]
+    output_b: None,
+    features: [None; FEATURE_QUEUE_LEN],
+    dropped_features: 0,
+}));
+static STATE: Mutex<Cell<UsbDeviceState>> = Mutex::new(Cell::new(UsbDeviceState::Default));
+
+/// Hand the USB device to the interrupt handler and start servicing it
+pub fn init(
+    device: UsbDevice<'static, UsbBus>,
+    hid: HIDClass<'static, UsbBus>,
+    #[cfg(feature = "dual_motor")] hid_b: HIDClass<'static, UsbBus>,
+    features: FeatureReports,
+) {
+    critical_section::with(|cs| {
+        USB.borrow_ref_mut(cs).replace(Usb {
+            device,
+            hid,
+            idle: IdleRates::new(0),
+            #[cfg(feature = "dual_motor")]
+            hid_b,
+            #[cfg(feature = "dual_motor")]
+            idle_b: IdleRates::new(1),
+            features,
+        })
+    });
+
+    // SAFETY: the handler only touches state guarded by critical sections
+    unsafe {
+        pac::NVIC::unmask(pac::Interrupt::USBCTRL_IRQ);
+    }
+}
+
+/// Device state as of the last interrupt
+pub fn state() -> UsbDeviceState {
+    critical_section::with(|cs| STATE.borrow(cs).get())
+}
+
+/// Latest output report received since the last call
+pub fn take_output() -> Option<Packet> {
+    critical_section::with(|cs| INBOX.borrow_ref_mut(cs).output.take())
+}
+
+/// Latest output report of motor B received since the last call
+#[cfg(feature = "dual_motor")]
+pub fn take_output_b() -> Option<Packet> {
+    critical_section::with(|cs| INBOX.borrow_ref_mut(cs).output_b.take())
+}
+
+/// Oldest queued feature report (SET_REPORT)
+pub fn take_feature() -> Option<Packet> {
+    critical_section::with(|cs| {
+        let mut inbox = INBOX.borrow_ref_mut(cs);
+        let packet = inbox.features[0].take();
+        inbox.features.rotate_left(1);
+        packet
+    })
+}
+
+/// Number of feature reports dropped because the queue was full, reset on read
+pub fn take_dropped_features() -> u32 {
+    critical_section::with(|cs| core::mem::take(&mut INBOX.borrow_ref_mut(cs).dropped_features))
+}
+
+/// Queue an input report; false if it was dropped because the previous one has not been sent yet
+pub fn push_input(report: &[u8]) -> bool {
+    critical_section::with(|cs| match USB.borrow_ref_mut(cs).as_mut() {
+        Some(usb) => usb.hid.push_raw_input(report).is_ok(),
+        None => false,
+    })
+}
+
+/// Queue an input report on motor B's interface (false if dropped, see `push_input`)
+#[cfg(feature = "dual_motor")]
+pub fn push_input_b(report: &[u8]) -> bool {
+    critical_section::with(|cs| match USB.borrow_ref_mut(cs).as_mut() {
+        Some(usb) => usb.hid_b.push_raw_input(report).is_ok(),
+        None => false,
+    })
+}
+
+/// Signal resume on a suspended bus
+///
+/// False if the bus is not suspended or the host did not enable remote wakeup
+/// (SET_FEATURE DEVICE_REMOTE_WAKEUP before suspending).
+pub fn remote_wakeup() -> bool {
+    critical_section::with(|cs| match USB.borrow_ref(cs).as_ref() {
+        Some(usb) if usb.device.state() == UsbDeviceState::Suspend && usb.device.remote_wakeup_enabled() => {
+            usb.device.bus().remote_wakeup();
+            true
+        }
+        _ => false,
+    })
+}
+
+/// Idle rate the host set for input report `id`
+pub fn idle_rate(id: u8) -> IdleRate {
+    critical_section::with(|cs| match USB.borrow_ref(cs).as_ref() {
+        Some(usb) => usb.idle.rate(id),
+        None => DEFAULT_IDLE_RATE,
+    })
+}
+
+/// Idle rate the host set for input report `id` of motor B's interface
+#[cfg(feature = "dual_motor")]
+pub fn idle_rate_b(id: u8) -> IdleRate {
+    critical_section::with(|cs| match USB.borrow_ref(cs).as_ref() {
+        Some(usb) => usb.idle_b.rate(id),
+        None => DEFAULT_IDLE_RATE,
+    })
+}
+
+/// Publish the contents returned for readable feature report `id`
+pub fn set_feature(id: u8, payload: &[u8]) {
+    critical_section::with(|cs| {
+        if let Some(usb) = USB.borrow_ref_mut(cs).as_mut() {
+            usb.features.set(id, payload);
+        }
+    });
+}
+
+#[interrupt]
+fn USBCTRL_IRQ() {
+    critical_section::with(|cs| {
+        let mut usb = USB.borrow_ref_mut(cs);
+        let Some(usb) = usb.as_mut() else {
+            return;
+        };
+
+        // Feature GET_REPORT and idle rates first, HIDClass handles everything else
+        #[cfg(not(feature = "dual_motor"))]
+        usb.device.poll(&mut [&mut usb.features, &mut usb.idle, &mut usb.hid]);
+        #[cfg(feature = "dual_motor")]
+        usb.device.poll(&mut [
+            &mut usb.features,
+            &mut usb.idle,
+            &mut usb.hid,
+            &mut usb.idle_b,
+            &mut usb.hid_b,
+        ]);
+        STATE.borrow(cs).set(usb.device.state());
+
+        let mut inbox = INBOX.borrow_ref_mut(cs);
+        let mut data = [0; 64];
+        if let Ok(len) = usb.hid.pull_raw_output(&mut data) {
+            let report_id = data.first().copied().unwrap_or(0);
+            inbox.output = Some(Packet { report_id, len, data });
+        }
+        #[cfg(feature = "dual_motor")]
+        if let Ok(len) = usb.hid_b.pull_raw_output(&mut data) {
+            let report_id = data.first().copied().unwrap_or(0);
+            inbox.output_b = Some(Packet { report_id, len, data });
+        }
+        if let Ok(info) = usb.hid.pull_raw_report(&mut data) {
+            if info.report_type == ReportType::Feature {
+                let packet = Packet { report_id: info.report_id, len: info.len, data };
+                match inbox.features.iter_mut().find(|slot| slot.is_none()) {
+                    Some(slot) => *slot = Some(packet),
+                    None => inbox.dropped_features += 1,
+                }
+            }
+        }
+    });
+}
@@ -0,0 +1,268 @@
+//! Bench-test CLI for a single reaction wheel
+//!
+//! `rw-cli --serial RW-X --speed 50` holds 50% until Ctrl-C. Without `--speed`
+//! it starts interactively: Up/Right and Down/Left nudge the speed, Space stops,
+//! q or Esc quits. The command is resent every `RESEND_INTERVAL`, as the firmware
+//! stops the wheel when commands stop arriving for 500ms.
+//!
+//! `--calibrate-direction` spins the wheel slowly forward, asks which way it
+//! turned about its body axis and records that in the direction file
+//! (`rw_host::calibration`); speeds given to the other modes are body-frame
+//! speeds with the recorded direction applied.
+//!
+//! `--mock` drives a simulated wheel (`rw_host::mock`) instead of a device, to
+//! try the CLI without a Pico plugged in.
+
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::thread::sleep;
+use std::time::Duration;
+
+use clap::Parser;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use rw_host::calibration::{self, Direction, DirectionCalibration, PROBE_DURATION, PROBE_PERCENT};
+use rw_host::{percent_to_normalized, Error, ReactionWheel, Transport};
+
+/// Interval between repeated speed commands (well inside the firmware's command timeout)
+const RESEND_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Parser)]
+#[command(version, about = "Drive one reaction wheel over USB HID")]
+struct Args {
+    /// USB serial number of the wheel
+    #[arg(long, default_value = "RW-X")]
+    serial: String,
+
+    /// HID interface to open (dual-motor firmware: 0 = motor A, 1 = motor B)
+    #[arg(long)]
+    interface: Option<i32>,
+
+    /// Hold this speed in percent (-100..=100) until Ctrl-C; interactive mode if omitted
+    #[arg(long, allow_negative_numbers = true, value_parser = parse_percent)]
+    speed: Option<f32>,
+
+    /// Speed change per arrow key press in interactive mode, in percent
+    #[arg(long, default_value_t = 5.0, value_parser = parse_percent)]
+    step: f32,
+
+    /// Spin the wheel slowly forward, ask which way it turned and record its direction
+    #[arg(long, conflicts_with = "speed")]
+    calibrate_direction: bool,
+
+    /// With --calibrate-direction, take the direction from the encoder RPM sign without asking
+    /// (only if the encoder counts positive about the body axis)
+    #[arg(long, requires = "calibrate_direction")]
+    trust_encoder: bool,
+
+    /// Direction file [default: rw_host::calibration::default_path()]
+    #[arg(long)]
+    directions: Option<PathBuf>,
+
+    /// Drive a simulated wheel instead of a device (no hardware needed)
+    #[arg(long, conflicts_with = "interface")]
+    mock: bool,
+}
+
+fn parse_percent(s: &str) -> Result<f32, String> {
+    let percent: f32 = s.parse().map_err(|e| format!("{e}"))?;
+    if (-100.0..=100.0).contains(&percent) {
+        Ok(percent)
+    } else {
+        Err(format!("{percent} is outside -100..=100"))
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    if args.mock {
+        return drive(ReactionWheel::mock(&args.serial), args);
+    }
+    let opened = match args.interface {
+        Some(interface) => ReactionWheel::open_interface(&args.serial, interface),
+        None => ReactionWheel::open(&args.serial),
+    };
+    match opened {
+        Ok(wheel) => drive(wheel, args),
+        Err(e) => {
+            eprintln!("error: {e}");
+            if let Error::NotFound(_) = e {
+                print_connected();
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run the mode selected by `args` on `wheel`
+fn drive<T: Transport>(mut wheel: ReactionWheel<T>, args: Args) -> ExitCode {
+    let path = args.directions.unwrap_or_else(calibration::default_path);
+    let directions = match DirectionCalibration::load(&path) {
+        Ok(directions) => directions,
+        Err(e) => {
+            eprintln!("error: {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    if args.calibrate_direction {
+        return match calibrate(&mut wheel, directions, &path, args.trust_encoder) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+    if !directions.is_calibrated(wheel.serial()) {
+        eprintln!("{}: spin direction not calibrated (--calibrate-direction), driving as the firmware sees it", wheel.serial());
+    }
+    wheel.set_direction(directions.direction(wheel.serial()));
+
+    let result = match args.speed {
+        Some(percent) => hold(&mut wheel, percent),
+        None => interactive(&mut wheel, args.step),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn print_connected() {
+    match rw_host::list_serials() {
+        Ok(serials) if serials.is_empty() => eprintln!("no reaction wheels are connected"),
+        Ok(serials) => eprintln!("connected wheels: {}", serials.join(", ")),
+        Err(e) => eprintln!("could not enumerate wheels: {e}"),
+    }
+}
+
+/// Probe the wheel's direction, confirm it and store it in `directions` at `path`
+fn calibrate<T: Transport>(
+    wheel: &mut ReactionWheel<T>,
+    mut directions: DirectionCalibration,
+    path: &Path,
+    trust_encoder: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let serial = wheel.serial().to_owned();
+    println!("{serial}: spinning at {PROBE_PERCENT}% for {}ms, watch which way the wheel turns", PROBE_DURATION.as_millis());
+    let rpm = calibration::probe_rpm(wheel, PROBE_PERCENT, PROBE_DURATION)?;
+    let measured = rpm.and_then(Direction::from_probe_rpm);
+    match rpm {
+        Some(rpm) => println!("{serial}: encoder read {rpm:+}rpm"),
+        None => println!("{serial}: no status report during the spin"),
+    }
+
+    let direction = if trust_encoder {
+        measured.ok_or("the encoder showed no rotation, run without --trust-encoder")?
+    } else {
+        // No default: after the firmware's encoder calibration the RPM sign follows the drive, not the body
+        loop {
+            print!("Did it turn positive about the body axis (counterclockwise seen from the + end)? [y/n] ");
+            std::io::stdout().flush()?;
+            let mut answer = String::new();
+            if std::io::stdin().lock().read_line(&mut answer)? == 0 {
+                return Err("no answer, nothing recorded".into());
+            }
+            match answer.trim().to_ascii_lowercase().as_str() {
+                "y" | "yes" => break Direction::Normal,
+                "n" | "no" => break Direction::Reversed,
+                _ => {}
+            }
+        }
+    };
+
+    directions.set(&serial, direction);
+    directions.save(path)?;
+    println!("{serial}: recorded {} in {}", direction.as_str(), path.display());
+    Ok(())
+}
+
+/// Send `percent` until the process is killed (the firmware timeout stops the wheel after Ctrl-C)
+fn hold<T: Transport>(wheel: &mut ReactionWheel<T>, percent: f32) -> Result<(), Box<dyn std::error::Error>> {
+    println!(
+        "{}: holding {percent:+.1}% ({}), Ctrl-C to stop",
+        wheel.serial(),
+        percent_to_normalized(percent)
+    );
+    let mut connected = true;
+    loop {
+        let now_connected = send(wheel, percent)?;
+        if now_connected != connected {
+            let state = if now_connected { "reconnected" } else { "disconnected" };
+            eprintln!("{}: {state}", wheel.serial());
+            connected = now_connected;
+        }
+        sleep(RESEND_INTERVAL);
+    }
+}
+
+fn interactive<T: Transport>(wheel: &mut ReactionWheel<T>, step: f32) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}: Up/Right +{step}%, Down/Left -{step}%, Space stop, q quit", wheel.serial());
+
+    let raw = RawMode::enable()?;
+    let mut percent = 0.0f32;
+    let result = loop {
+        let connected = match send(wheel, percent) {
+            Ok(connected) => connected,
+            Err(e) => break Err(e),
+        };
+        print!("\r\x1b[K{percent:+6.1}% ({:+6})", percent_to_normalized(percent));
+        if !connected {
+            print!("  disconnected");
+        }
+        std::io::stdout().flush()?;
+
+        if !event::poll(RESEND_INTERVAL)? {
+            continue;
+        }
+        let Event::Key(KeyEvent { code, modifiers, kind: KeyEventKind::Press, .. }) = event::read()? else {
+            continue;
+        };
+        match code {
+            KeyCode::Up | KeyCode::Right => percent = (percent + step).min(100.0),
+            KeyCode::Down | KeyCode::Left => percent = (percent - step).max(-100.0),
+            KeyCode::Char(' ') => percent = 0.0,
+            KeyCode::Char('q') | KeyCode::Esc => break Ok(()),
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => break Ok(()),
+            _ => {}
+        }
+    };
+    drop(raw);
+    println!();
+
+    // Leave the wheel stopped rather than waiting for the firmware timeout
+    send(wheel, 0.0)?;
+    result
+}
+
+/// Send one command and return whether the wheel is connected
+///
+/// Disconnection is not an error here: the next send tries to reconnect.
+fn send<T: Transport>(wheel: &mut ReactionWheel<T>, percent: f32) -> Result<bool, Box<dyn std::error::Error>> {
+    match wheel.set_speed_percent(percent) {
+        Ok(()) => Ok(true),
+        Err(Error::Disconnected(_)) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Terminal raw mode, restored on drop (including on error paths)
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> std::io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
@@ -0,0 +1,103 @@
+//! Axis identity (GPIO0/1 strap), per-axis wheel parameters and host override
+
+use crate::pid::MAX_RPM;
+use crate::protocol::RWAxisOverrideReport;
+
+/// Physical parameters of the wheel mounted on one axis
+///
+/// Shared by the firmware (closed-loop RPM scaling) and the attitude simulator
+/// (wheel momentum), so a normalized command means the same wheel speed and
+/// momentum on both. The firmware's `max_rpm` can be overridden at runtime
+/// with `Config::max_rpm`, the simulator's through its own wheel parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WheelSpec {
+    /// Wheel speed commanded by a full-scale normalized speed in closed loop [RPM]
+    pub max_rpm: u16,
+    /// Rotor moment of inertia about the spin axis [kg·m²]
+    pub inertia_kg_m2: f32,
+}
+
+/// The wheel fitted to every axis of the reference build
+pub const DEFAULT_WHEEL_SPEC: WheelSpec = WheelSpec { max_rpm: MAX_RPM as u16, inertia_kg_m2: 3.33e-6 };
+
+/// Wheel on the X, Y and Z axis, in that order
+pub const WHEEL_SPECS: [WheelSpec; 3] = [DEFAULT_WHEEL_SPEC; 3];
+
+/// Axis identification for multi-Pico setup (discriminant is the device info report byte)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum Axis {
+    X = 0,
+    Y = 1,
+    Z = 2,
+    /// Bench self-test: no axis, runs `SELF_TEST_SEQUENCE` without a host
+    Test = 3,
+}
+
+impl Axis {
+    /// Axis for a 2-bit strap value (`GPIO1 << 1 | GPIO0`, pull-up convention, see `strap::StrapPolarity`)
+    pub fn from_strap(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b11 => Axis::X,  // Both HIGH (floating) → X-axis
+            0b10 => Axis::Y,  // GPIO0=LOW, GPIO1=HIGH → Y-axis
+            0b01 => Axis::Z,  // GPIO0=HIGH, GPIO1=LOW → Z-axis
+            _ => Axis::Test,  // Both LOW → bench self-test
+        }
+    }
+
+    /// USB serial number the host uses to tell the boards apart
+    pub fn serial(self) -> &'static str {
+        match self {
+            Axis::X => "RW-X",
+            Axis::Y => "RW-Y",
+            Axis::Z => "RW-Z",
+            Axis::Test => "RW-TEST",
+        }
+    }
+
+    /// Wheel parameters for this axis (the bench self-test drives an X wheel)
+    pub fn wheel_spec(self) -> WheelSpec {
+        match self {
+            Axis::X | Axis::Test => WHEEL_SPECS[0],
+            Axis::Y => WHEEL_SPECS[1],
+            Axis::Z => WHEEL_SPECS[2],
+        }
+    }
+}
+
+/// Why an axis override was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AxisOverrideError {
+    /// Not a wheel axis (0 = X, 1 = Y, 2 = Z)
+    Axis(u8),
+    /// Force flag other than 0 or 1
+    Force(u8),
+    /// The strap selected a different axis and the force flag was not set
+    Conflict { strapped: Axis, requested: Axis },
+}
+
+/// Axis to run as after an override request
+///
+/// Without the force flag an override only applies when the strap selected
+/// self-test (or already agrees), so a mis-sent report can't relabel a wired board.
+pub fn apply_axis_override(strapped: Axis, report: &RWAxisOverrideReport) -> Result<Axis, AxisOverrideError> {
+    let requested = match report.axis {
+        0 => Axis::X,
+        1 => Axis::Y,
+        2 => Axis::Z,
+        v => return Err(AxisOverrideError::Axis(v)),
+    };
+    let force = match report.force {
+        0 => false,
+        1 => true,
+        v => return Err(AxisOverrideError::Force(v)),
+    };
+
+    if !force && strapped != Axis::Test && strapped != requested {
+        return Err(AxisOverrideError::Conflict { strapped, requested });
+    }
+    Ok(requested)
+}
@@ -0,0 +1,80 @@
+//! Coast test: spin-down measurement for wheel inertia and bearing drag
+//!
+//! Started by the host with the wheel spinning. The drive is cut (both inputs
+//! low, whatever the stop mode) and the status report, which carries the
+//! measured RPM and the uptime, is pushed as the RPM decays until the encoder
+//! reads zero. Any speed command aborts the test.
+
+use crate::deadline::Deadline;
+use crate::protocol::RWCoastTestReport;
+
+/// Give up on a wheel that never reads stopped
+pub const COAST_TEST_TIMEOUT_MS: u64 = 60_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CoastTestCommand {
+    Abort,
+    Start,
+}
+
+impl CoastTestCommand {
+    pub fn from_report(report: &RWCoastTestReport) -> Option<Self> {
+        match report.command {
+            0 => Some(CoastTestCommand::Abort),
+            1 => Some(CoastTestCommand::Start),
+            _ => None,
+        }
+    }
+}
+
+/// How a coast test finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CoastTestEnd {
+    /// The encoder read 0 RPM this long after the drive was cut
+    Stopped { elapsed_ms: u32 },
+    /// Still turning (or never measured) after `COAST_TEST_TIMEOUT_MS`
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CoastTest {
+    // Timer timestamp (us) the drive was cut, None while no test runs
+    started_us: Option<u64>,
+}
+
+impl CoastTest {
+    pub const fn new() -> Self {
+        Self { started_us: None }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started_us.is_some()
+    }
+
+    /// Cut the drive at `now_us` (restarts a running test)
+    pub fn start(&mut self, now_us: u64) {
+        self.started_us = Some(now_us);
+    }
+
+    /// Stop the test; true if one was running
+    pub fn abort(&mut self) -> bool {
+        self.started_us.take().is_some()
+    }
+
+    /// Advance one control tick; `rpm` is the measurement taken this tick, if any
+    pub fn update(&mut self, now_us: u64, rpm: Option<i32>) -> Option<CoastTestEnd> {
+        let started_us = self.started_us?;
+        let elapsed_us = Deadline::at(started_us).elapsed_us(now_us);
+        let end = if rpm == Some(0) {
+            CoastTestEnd::Stopped { elapsed_ms: (elapsed_us / 1000) as u32 }
+        } else if elapsed_us >= COAST_TEST_TIMEOUT_MS * 1000 {
+            CoastTestEnd::TimedOut
+        } else {
+            return None;
+        };
+        self.started_us = None;
+        Some(end)
+    }
+}
@@ -0,0 +1,106 @@
+//! Speed command handling, from the raw output report to the controller target
+//!
+//! Separated from the firmware's main loop so the whole path (report parsing,
+//! clamping, the conditions that drop a command and the duty it maps to) can
+//! be tested on the host with crafted report bytes. Side effects outside the
+//! controller (aborting tests, the command timeout, logging) stay with the
+//! caller.
+
+use crate::config::Config;
+use crate::control::MotorController;
+use crate::motor::MotorSpeed;
+use crate::protocol::{OutputReport, OutputReportError};
+
+/// State that keeps a speed command from reaching the controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandGate {
+    /// Self-test or the demo spin owns the wheel: commands are ignored outright
+    pub host_locked_out: bool,
+    pub estopped: bool,
+    pub driver_awake: bool,
+    pub overcurrent: bool,
+    pub undervoltage: bool,
+    /// A stall fault is latched (a stop command still gets through and re-arms detection)
+    pub stalled: bool,
+}
+
+impl CommandGate {
+    /// Nothing blocking: driver awake, no fault, no e-stop
+    pub const OPEN: Self = Self {
+        host_locked_out: false,
+        estopped: false,
+        driver_awake: true,
+        overcurrent: false,
+        undervoltage: false,
+        stalled: false,
+    };
+
+    fn admits(&self, target: MotorSpeed) -> bool {
+        !self.estopped
+            && self.driver_awake
+            && !self.overcurrent
+            && !self.undervoltage
+            && (!self.stalled || target == MotorSpeed::STOP)
+    }
+}
+
+/// A well-formed speed command and what became of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeedCommand {
+    /// Speed exactly as sent (percent commands converted), before clamping
+    pub raw_speed_normalized: i16,
+    /// Clamped target
+    pub target: MotorSpeed,
+    /// Handed to the controller; false when dropped by the gate
+    pub accepted: bool,
+    /// Duty the target maps to after the speed cap (0 when dropped)
+    pub duty: u8,
+    pub is_forward: bool,
+    /// The controller will kick on its next update
+    pub kickstart: bool,
+}
+
+/// Outcome of one output report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandResult {
+    /// Well-formed, but the host is locked out
+    Ignored,
+    Command(SpeedCommand),
+    Invalid(OutputReportError),
+}
+
+/// Parse an output report (`SPEED_REPORT_ID` or `SPEED_PERCENT_REPORT_ID`) and
+/// set it as the controller target unless `gate` blocks it
+pub fn handle_command(
+    data: &[u8],
+    gate: &CommandGate,
+    controller: &mut MotorController,
+    config: &Config,
+) -> CommandResult {
+    let report = match OutputReport::parse_command(data) {
+        Ok(report) => report,
+        Err(error) => return CommandResult::Invalid(error),
+    };
+    if gate.host_locked_out {
+        return CommandResult::Ignored;
+    }
+
+    let target = MotorSpeed::new(report.speed_normalized());
+    let accepted = gate.admits(target);
+    // Judged from the applied speed before the controller takes the new target
+    let kickstart = accepted && config.kickstart_ms > 0 && controller.will_kickstart(target, config);
+    let (duty, is_forward) = if accepted {
+        controller.set_target(target);
+        controller.limited_target(config).to_duty_and_direction(config)
+    } else {
+        (0, target.speed_normalized >= 0)
+    };
+    CommandResult::Command(SpeedCommand {
+        raw_speed_normalized: report.raw_speed_normalized(),
+        target,
+        accepted,
+        duty,
+        is_forward,
+        kickstart,
+    })
+}
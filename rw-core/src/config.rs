@@ -0,0 +1,619 @@
+//! Runtime configuration
+
+use core::mem::offset_of;
+
+use zerocopy::little_endian::U16;
+
+use crate::current::{CURRENT_LIMITED_TRIP_MS, CURRENT_LIMIT_GAIN, CURRENT_LIMIT_GAIN_MAX, CURRENT_LIMIT_MA, MAX_CURRENT_MA, OVERCURRENT_TRIP_MS};
+use crate::lograte::LOG_INTERVAL_MS;
+use crate::curve::SpeedCurve;
+use crate::driver::IDLE_SLEEP_MS;
+use crate::idle::TELEMETRY_ON_CHANGE;
+use crate::motor::{SCALE_UNITY, SPEED_NORMALIZED_MAX};
+use crate::protocol::{RWConfigCommandReport, RWConfigReport};
+use crate::pwm::{PwmTiming, PWM_FREQ_HZ};
+use crate::feedback::FEEDBACK_TIMEOUT_MS;
+use crate::stall::{STALL_RPM, STALL_WINDOW_MS};
+use crate::supply::MIN_SUPPLY_MV;
+use crate::thermal::{THROTTLE_LIMIT_C, THROTTLE_START_C};
+
+/// Kickstart parameters (defaults, overridable at runtime via `RWConfigReport`)
+pub const KICKSTART_DUTY: u8 = 100;
+pub const KICKSTART_MS: u16 = 150;
+pub const MIN_DUTY: u8 = 40;
+
+/// Measured RPM (in the kick direction) that ends a kickstart early (0 = always timed)
+pub const KICKSTART_RPM: u16 = 200;
+
+/// Motor current that ends a kickstart early once the inrush has decayed below it (mA, 0 = off)
+pub const KICKSTART_CURRENT_MA: u16 = 0;
+
+/// Direction changes with both speeds below this magnitude (normalized units, ~10%) skip the kickstart
+pub const REVERSAL_KICK_THRESHOLD: u16 = 3277;
+
+/// Reversal brake duration in ms before kicking the other way (0 = disabled, needs `StopMode::Brake`)
+pub const REVERSAL_BRAKE_MS: u16 = 0;
+
+/// Measured RPM below which the reversal brake ends early (0 = timed only)
+pub const REVERSAL_BRAKE_RPM: u16 = 0;
+
+/// Low-speed duty dither amplitude in percent (0 = off)
+pub const DITHER_AMPLITUDE: u8 = 0;
+
+/// Dither square wave frequency in Hz
+pub const DITHER_FREQ_HZ: u8 = 10;
+
+/// Dither only below this applied speed magnitude (normalized units, ~10%)
+pub const DITHER_MAX_SPEED: u16 = 3277;
+
+/// Largest accepted dither amplitude in percent
+pub const DITHER_AMPLITUDE_MAX: u8 = 50;
+
+/// Minimum time between starting and stopping the motor in ms (0 = no dwell)
+pub const MIN_DWELL_MS: u16 = 0;
+
+/// Input report push period in ms (`TELEMETRY_ON_CHANGE` = on change and at the idle rate, 0 = never)
+pub const TELEMETRY_PERIOD_MS: u16 = TELEMETRY_ON_CHANGE;
+
+/// Slew limit in normalized units per control tick (0 = no limit)
+pub const SLEW_STEP: u16 = 0;
+
+/// Ramp rate toward stop in normalized units per control tick (0 = use `slew_step`)
+pub const STOP_RAMP_STEP: u16 = 0;
+
+/// Exponential ramp time constant in ms (used with `RampProfile::Exponential`, 0 = no ramp)
+pub const RAMP_TAU_MS: u16 = 100;
+
+/// Commands with a magnitude below this (normalized units) are treated as stop (0 = disabled)
+pub const DEADBAND: u16 = 0;
+
+/// Commands past zero by at most this (normalized units) keep the current direction (0 = disabled)
+pub const DIRECTION_HYSTERESIS: u16 = 0;
+
+/// Closed-loop full-scale RPM override (0 = the axis's `axis::WheelSpec::max_rpm`)
+pub const MAX_RPM_OVERRIDE: u16 = 0;
+
+/// Speed cap in normalized units (full scale = no cap)
+pub const MAX_SPEED_NORMALIZED: u16 = SPEED_NORMALIZED_MAX as u16;
+
+/// Largest accepted per-direction scale (2.0)
+pub const SCALE_MAX: u16 = 2 * SCALE_UNITY;
+
+/// How the motor is stopped when duty is zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StopMode {
+    /// Both inputs low: the wheel spins down freely
+    Coast,
+    /// Both inputs high: the driver shorts the motor windings (DRV8833 slow decay)
+    Brake,
+}
+
+impl StopMode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(StopMode::Coast),
+            1 => Some(StopMode::Brake),
+            _ => None,
+        }
+    }
+}
+
+/// What the drive does while a fault that cuts it is active (`fault::drive_response`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FaultResponse {
+    /// Both inputs low: the wheel spins down freely
+    Coast,
+    /// Both inputs high: the windings are shorted and brake the wheel (more heat in the motor)
+    Brake,
+    /// nSLEEP low: the bridge outputs go high-impedance and the driver stays asleep until the fault
+    /// clears, then goes through the bring-up again
+    DisableDriver,
+}
+
+impl FaultResponse {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(FaultResponse::Coast),
+            1 => Some(FaultResponse::Brake),
+            2 => Some(FaultResponse::DisableDriver),
+            _ => None,
+        }
+    }
+}
+
+/// How the commanded speed is turned into duty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ControlMode {
+    /// Normalized speed maps directly to duty (`MotorSpeed::to_duty_and_direction`)
+    OpenLoop,
+    /// Normalized speed is a target RPM (scaled to `Config::max_rpm`), tracked by PID on encoder feedback
+    ClosedLoop,
+    /// Normalized value is a target torque, i.e. motor current (scaled to `current::TORQUE_MAX_CURRENT_MA`),
+    /// held by a loop on the current sense
+    Torque,
+}
+
+impl ControlMode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(ControlMode::OpenLoop),
+            1 => Some(ControlMode::ClosedLoop),
+            2 => Some(ControlMode::Torque),
+            _ => None,
+        }
+    }
+}
+
+/// How the applied speed approaches a new target on each control tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RampProfile {
+    /// Constant rate of `slew_step` per tick
+    Linear,
+    /// First-order approach with time constant `ramp_tau_ms`, never faster than `slew_step`
+    Exponential,
+}
+
+impl RampProfile {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(RampProfile::Linear),
+            1 => Some(RampProfile::Exponential),
+            _ => None,
+        }
+    }
+}
+
+/// Config storage command from `RWConfigCommandReport`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigCommand {
+    /// Apply `Config::default()` and clear the stored config
+    ResetToDefaults,
+}
+
+impl ConfigCommand {
+    pub fn from_report(report: &RWConfigCommandReport) -> Option<Self> {
+        match report.command {
+            1 => Some(ConfigCommand::ResetToDefaults),
+            _ => None,
+        }
+    }
+}
+
+/// Field of a `RWConfigReport` that failed validation (with the rejected value)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigError {
+    StopMode(u8),
+    MinDuty(u8),
+    KickstartDuty(u8),
+    ControlMode(u8),
+    /// Throttle limit temperature not above the start temperature
+    ThrottleLimit(u8),
+    Curve(u8),
+    FwdScale(u16),
+    RevScale(u16),
+    /// PWM frequency outside `PWM_FREQ_MIN_HZ..=PWM_FREQ_MAX_HZ`
+    PwmFrequency(u16),
+    RampProfile(u8),
+    /// Speed cap outside 1..=`MAX_SPEED_NORMALIZED`
+    MaxSpeed(u16),
+    /// Stall retry flag other than 0 or 1
+    StallRetry(u8),
+    /// Reversal kick threshold above `MAX_SPEED_NORMALIZED`
+    ReversalKickThreshold(u16),
+    /// Reversal brake enabled without `StopMode::Brake`
+    ReversalBrake(u16),
+    /// Dither amplitude above `DITHER_AMPLITUDE_MAX`
+    DitherAmplitude(u8),
+    /// Dither frequency of 0 Hz
+    DitherFrequency(u8),
+    /// Encoder polarity flag other than 0 or 1
+    EncoderReversed(u8),
+    /// Direction hysteresis above `MAX_SPEED_NORMALIZED`
+    DirectionHysteresis(u16),
+    OvercurrentResponse(u8),
+    OverTemperatureResponse(u8),
+    UndervoltageResponse(u8),
+    StallResponse(u8),
+    /// Soft current limit above the overcurrent threshold
+    CurrentLimit(u16),
+    /// Soft current limit gain of 0 with the limit on, or above `CURRENT_LIMIT_GAIN_MAX`
+    CurrentLimitGain(u16),
+}
+
+impl ConfigError {
+    /// Byte offset of the rejected field in `RWConfigReport`
+    pub fn offset(&self) -> usize {
+        match self {
+            ConfigError::StopMode(_) => offset_of!(RWConfigReport, stop_mode),
+            ConfigError::MinDuty(_) => offset_of!(RWConfigReport, min_duty),
+            ConfigError::KickstartDuty(_) => offset_of!(RWConfigReport, kickstart_duty),
+            ConfigError::ControlMode(_) => offset_of!(RWConfigReport, control_mode),
+            ConfigError::ThrottleLimit(_) => offset_of!(RWConfigReport, throttle_limit_c),
+            ConfigError::Curve(_) => offset_of!(RWConfigReport, curve),
+            ConfigError::FwdScale(_) => offset_of!(RWConfigReport, fwd_scale),
+            ConfigError::RevScale(_) => offset_of!(RWConfigReport, rev_scale),
+            ConfigError::PwmFrequency(_) => offset_of!(RWConfigReport, pwm_freq_hz),
+            ConfigError::RampProfile(_) => offset_of!(RWConfigReport, ramp_profile),
+            ConfigError::MaxSpeed(_) => offset_of!(RWConfigReport, max_speed_normalized),
+            ConfigError::StallRetry(_) => offset_of!(RWConfigReport, stall_retry),
+            ConfigError::ReversalKickThreshold(_) => offset_of!(RWConfigReport, reversal_kick_threshold),
+            ConfigError::ReversalBrake(_) => offset_of!(RWConfigReport, reversal_brake_ms),
+            ConfigError::DitherAmplitude(_) => offset_of!(RWConfigReport, dither_amplitude),
+            ConfigError::DitherFrequency(_) => offset_of!(RWConfigReport, dither_freq_hz),
+            ConfigError::EncoderReversed(_) => offset_of!(RWConfigReport, encoder_reversed),
+            ConfigError::DirectionHysteresis(_) => offset_of!(RWConfigReport, direction_hysteresis),
+            ConfigError::OvercurrentResponse(_) => offset_of!(RWConfigReport, overcurrent_response),
+            ConfigError::OverTemperatureResponse(_) => offset_of!(RWConfigReport, over_temperature_response),
+            ConfigError::UndervoltageResponse(_) => offset_of!(RWConfigReport, undervoltage_response),
+            ConfigError::StallResponse(_) => offset_of!(RWConfigReport, stall_response),
+            ConfigError::CurrentLimit(_) => offset_of!(RWConfigReport, current_limit_ma),
+            ConfigError::CurrentLimitGain(_) => offset_of!(RWConfigReport, current_limit_gain),
+        }
+    }
+
+    /// The rejected value
+    pub fn value(&self) -> u16 {
+        match *self {
+            ConfigError::StopMode(v)
+            | ConfigError::MinDuty(v)
+            | ConfigError::KickstartDuty(v)
+            | ConfigError::ControlMode(v)
+            | ConfigError::ThrottleLimit(v)
+            | ConfigError::Curve(v)
+            | ConfigError::RampProfile(v)
+            | ConfigError::StallRetry(v)
+            | ConfigError::DitherAmplitude(v)
+            | ConfigError::DitherFrequency(v)
+            | ConfigError::EncoderReversed(v)
+            | ConfigError::OvercurrentResponse(v)
+            | ConfigError::OverTemperatureResponse(v)
+            | ConfigError::UndervoltageResponse(v)
+            | ConfigError::StallResponse(v) => v as u16,
+            ConfigError::FwdScale(v)
+            | ConfigError::RevScale(v)
+            | ConfigError::PwmFrequency(v)
+            | ConfigError::MaxSpeed(v)
+            | ConfigError::ReversalKickThreshold(v)
+            | ConfigError::ReversalBrake(v)
+            | ConfigError::DirectionHysteresis(v)
+            | ConfigError::CurrentLimit(v)
+            | ConfigError::CurrentLimitGain(v) => v,
+        }
+    }
+}
+
+/// Runtime configuration, updated by the host via `RWConfigReport`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    pub stop_mode: StopMode,
+    pub min_duty: u8,
+    pub kickstart_duty: u8,
+    pub kickstart_ms: u16,
+    pub slew_step: u16,
+    pub control_mode: ControlMode,
+    /// Overcurrent threshold in mA (0 = protection disabled)
+    pub max_current_ma: u16,
+    /// Thermal throttling range (°C)
+    pub throttle_start_c: u8,
+    pub throttle_limit_c: u8,
+    /// Shaping of the normalized speed before duty mapping
+    pub curve: SpeedCurve,
+    /// Forward/reverse magnitude scale in 1/1000 (1000 = 1.0), to match RPM across directions
+    pub fwd_scale: u16,
+    pub rev_scale: u16,
+    /// Normalized magnitude below which the motor stops instead of running at `min_duty`
+    pub deadband: u16,
+    /// Deceleration per control tick when the target is stop (0 = same as `slew_step`)
+    pub stop_ramp_step: u16,
+    /// Motor PWM frequency in Hz (always representable, see `pwm::PwmTiming`)
+    pub pwm_freq_hz: u16,
+    /// Acceleration profile toward a new target
+    pub ramp_profile: RampProfile,
+    /// Time constant of `RampProfile::Exponential` in ms
+    pub ramp_tau_ms: u16,
+    /// Cap on the speed magnitude whatever the host commands (normalized units, or target RPM in closed loop)
+    pub max_speed_normalized: u16,
+    /// Driven without reaching `stall_rpm` for this long counts as a stall (ms, 0 = detection disabled)
+    pub stall_window_ms: u16,
+    pub stall_rpm: u16,
+    /// Retry a stalled start once with a stronger kickstart before giving up
+    pub stall_retry: bool,
+    /// End the kickstart once the encoder reads this RPM in the kick direction (0 = timed pulse only)
+    pub kickstart_rpm: u16,
+    /// A direction change kickstarts only if the old or new speed magnitude reaches this (0 = always)
+    pub reversal_kick_threshold: u16,
+    /// On a kicked reversal, brake for up to this long before driving the other way (ms, 0 = reverse at once)
+    pub reversal_brake_ms: u16,
+    /// End the reversal brake once the encoder reads below this RPM (0 = brake for the full time)
+    pub reversal_brake_rpm: u16,
+    /// Push status and telemetry input reports at this fixed period (ms) instead of on change
+    pub telemetry_period_ms: u16,
+    /// Alternate the duty by this many percent up and down at low speed to break static friction (0 = off)
+    pub dither_amplitude: u8,
+    pub dither_freq_hz: u8,
+    /// Dither only while the applied speed magnitude is below this (normalized units)
+    pub dither_max_speed: u16,
+    /// After the drive starts or stops, hold that state at least this long (ms, 0 = off); e-stop ignores it
+    pub min_dwell_ms: u16,
+    /// Repeats of an unchanged command or motor state are logged at most once per this many ms (0 = all)
+    pub log_interval_ms: u16,
+    /// Decode the encoder with A and B swapped (set by `polarity::PolarityCalibration`)
+    pub encoder_reversed: bool,
+    /// A target on the other side of zero by at most this keeps the current direction (normalized units, 0 = off)
+    pub direction_hysteresis: u16,
+    /// RPM of a full-scale target in closed loop (0 = the wheel's `axis::WheelSpec::max_rpm`)
+    pub max_rpm: u16,
+    /// Undervoltage threshold in mV: the drive is cut while the supply is below it (0 = protection disabled)
+    pub min_supply_mv: u16,
+    /// End the kickstart once the sensed current has risen to this and fallen back below it (mA, 0 = timed pulse only)
+    pub kickstart_current_ma: u16,
+    /// Fall back to open loop after driving in closed loop this long without encoder counts (ms, 0 = never)
+    pub feedback_timeout_ms: u16,
+    /// Drive response per fault while it lasts (over-temperature: once throttling has reached the limit)
+    pub overcurrent_response: FaultResponse,
+    pub over_temperature_response: FaultResponse,
+    pub undervoltage_response: FaultResponse,
+    pub stall_response: FaultResponse,
+    /// Soft current limit in mA: the duty is pulled down to hold the current here (0 = off, hard cutoff only)
+    pub current_limit_ma: u16,
+    /// Soft current limit loop gain, in 1/1000 PWM count of duty ceiling per tick per mA of error
+    pub current_limit_gain: u16,
+    /// Put the driver to sleep after the wheel has been stopped this long without host reports (ms, 0 = never)
+    pub idle_sleep_ms: u16,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            stop_mode: StopMode::Coast,
+            min_duty: MIN_DUTY,
+            kickstart_duty: KICKSTART_DUTY,
+            kickstart_ms: KICKSTART_MS,
+            slew_step: SLEW_STEP,
+            control_mode: ControlMode::OpenLoop,
+            max_current_ma: MAX_CURRENT_MA,
+            throttle_start_c: THROTTLE_START_C,
+            throttle_limit_c: THROTTLE_LIMIT_C,
+            curve: SpeedCurve::Linear,
+            fwd_scale: SCALE_UNITY,
+            rev_scale: SCALE_UNITY,
+            deadband: DEADBAND,
+            stop_ramp_step: STOP_RAMP_STEP,
+            pwm_freq_hz: PWM_FREQ_HZ,
+            ramp_profile: RampProfile::Linear,
+            ramp_tau_ms: RAMP_TAU_MS,
+            max_speed_normalized: MAX_SPEED_NORMALIZED,
+            stall_window_ms: STALL_WINDOW_MS,
+            stall_rpm: STALL_RPM,
+            stall_retry: true,
+            kickstart_rpm: KICKSTART_RPM,
+            reversal_kick_threshold: REVERSAL_KICK_THRESHOLD,
+            reversal_brake_ms: REVERSAL_BRAKE_MS,
+            reversal_brake_rpm: REVERSAL_BRAKE_RPM,
+            telemetry_period_ms: TELEMETRY_PERIOD_MS,
+            dither_amplitude: DITHER_AMPLITUDE,
+            dither_freq_hz: DITHER_FREQ_HZ,
+            dither_max_speed: DITHER_MAX_SPEED,
+            min_dwell_ms: MIN_DWELL_MS,
+            log_interval_ms: LOG_INTERVAL_MS,
+            encoder_reversed: false,
+            direction_hysteresis: DIRECTION_HYSTERESIS,
+            max_rpm: MAX_RPM_OVERRIDE,
+            min_supply_mv: MIN_SUPPLY_MV,
+            kickstart_current_ma: KICKSTART_CURRENT_MA,
+            feedback_timeout_ms: FEEDBACK_TIMEOUT_MS,
+            overcurrent_response: FaultResponse::DisableDriver,
+            over_temperature_response: FaultResponse::DisableDriver,
+            undervoltage_response: FaultResponse::DisableDriver,
+            stall_response: FaultResponse::DisableDriver,
+            current_limit_ma: CURRENT_LIMIT_MA,
+            current_limit_gain: CURRENT_LIMIT_GAIN,
+            idle_sleep_ms: IDLE_SLEEP_MS,
+        }
+    }
+}
+
+impl Config {
+    /// Validate a config report from the host
+    ///
+    /// Every field is checked before anything is built, so a rejected report
+    /// leaves nothing half-applied: the caller keeps its previous config.
+    pub fn from_report(report: &RWConfigReport) -> Result<Self, ConfigError> {
+        let stop_mode = StopMode::from_u8(report.stop_mode)
+            .ok_or(ConfigError::StopMode(report.stop_mode))?;
+        let control_mode = ControlMode::from_u8(report.control_mode)
+            .ok_or(ConfigError::ControlMode(report.control_mode))?;
+        let curve = SpeedCurve::from_u8(report.curve).ok_or(ConfigError::Curve(report.curve))?;
+        let ramp_profile = RampProfile::from_u8(report.ramp_profile)
+            .ok_or(ConfigError::RampProfile(report.ramp_profile))?;
+        let overcurrent_response = FaultResponse::from_u8(report.overcurrent_response)
+            .ok_or(ConfigError::OvercurrentResponse(report.overcurrent_response))?;
+        let over_temperature_response = FaultResponse::from_u8(report.over_temperature_response)
+            .ok_or(ConfigError::OverTemperatureResponse(report.over_temperature_response))?;
+        let undervoltage_response = FaultResponse::from_u8(report.undervoltage_response)
+            .ok_or(ConfigError::UndervoltageResponse(report.undervoltage_response))?;
+        let stall_response = FaultResponse::from_u8(report.stall_response)
+            .ok_or(ConfigError::StallResponse(report.stall_response))?;
+        if report.min_duty > 100 {
+            return Err(ConfigError::MinDuty(report.min_duty));
+        }
+        if report.kickstart_duty > 100 {
+            return Err(ConfigError::KickstartDuty(report.kickstart_duty));
+        }
+        if report.fwd_scale.get() > SCALE_MAX {
+            return Err(ConfigError::FwdScale(report.fwd_scale.get()));
+        }
+        if report.rev_scale.get() > SCALE_MAX {
+            return Err(ConfigError::RevScale(report.rev_scale.get()));
+        }
+        if report.throttle_limit_c <= report.throttle_start_c {
+            return Err(ConfigError::ThrottleLimit(report.throttle_limit_c));
+        }
+        if PwmTiming::for_frequency(report.pwm_freq_hz.get()).is_none() {
+            return Err(ConfigError::PwmFrequency(report.pwm_freq_hz.get()));
+        }
+        if !(1..=MAX_SPEED_NORMALIZED).contains(&report.max_speed_normalized.get()) {
+            return Err(ConfigError::MaxSpeed(report.max_speed_normalized.get()));
+        }
+        if report.stall_retry > 1 {
+            return Err(ConfigError::StallRetry(report.stall_retry));
+        }
+        if report.reversal_kick_threshold.get() > MAX_SPEED_NORMALIZED {
+            return Err(ConfigError::ReversalKickThreshold(report.reversal_kick_threshold.get()));
+        }
+        if report.reversal_brake_ms.get() != 0 && stop_mode != StopMode::Brake {
+            return Err(ConfigError::ReversalBrake(report.reversal_brake_ms.get()));
+        }
+        if report.dither_amplitude > DITHER_AMPLITUDE_MAX {
+            return Err(ConfigError::DitherAmplitude(report.dither_amplitude));
+        }
+        if report.dither_freq_hz == 0 {
+            return Err(ConfigError::DitherFrequency(report.dither_freq_hz));
+        }
+        if report.encoder_reversed > 1 {
+            return Err(ConfigError::EncoderReversed(report.encoder_reversed));
+        }
+        if report.direction_hysteresis.get() > MAX_SPEED_NORMALIZED {
+            return Err(ConfigError::DirectionHysteresis(report.direction_hysteresis.get()));
+        }
+        // A limit above the trip threshold would hold the current where it trips anyway
+        if report.max_current_ma.get() != 0 && report.current_limit_ma.get() > report.max_current_ma.get() {
+            return Err(ConfigError::CurrentLimit(report.current_limit_ma.get()));
+        }
+        // A zero gain never pulls the duty down, so the limit would silently do nothing
+        let gain = report.current_limit_gain.get();
+        if (report.current_limit_ma.get() != 0 && gain == 0) || gain > CURRENT_LIMIT_GAIN_MAX {
+            return Err(ConfigError::CurrentLimitGain(gain));
+        }
+
+        Ok(Self {
+            stop_mode,
+            min_duty: report.min_duty,
+            kickstart_duty: report.kickstart_duty,
+            kickstart_ms: report.kickstart_ms.get(),
+            slew_step: report.slew_step.get(),
+            control_mode,
+            max_current_ma: report.max_current_ma.get(),
+            throttle_start_c: report.throttle_start_c,
+            throttle_limit_c: report.throttle_limit_c,
+            curve,
+            fwd_scale: report.fwd_scale.get(),
+            rev_scale: report.rev_scale.get(),
+            deadband: report.deadband.get(),
+            stop_ramp_step: report.stop_ramp_step.get(),
+            pwm_freq_hz: report.pwm_freq_hz.get(),
+            ramp_profile,
+            ramp_tau_ms: report.ramp_tau_ms.get(),
+            max_speed_normalized: report.max_speed_normalized.get(),
+            stall_window_ms: report.stall_window_ms.get(),
+            stall_rpm: report.stall_rpm.get(),
+            stall_retry: report.stall_retry == 1,
+            kickstart_rpm: report.kickstart_rpm.get(),
+            reversal_kick_threshold: report.reversal_kick_threshold.get(),
+            reversal_brake_ms: report.reversal_brake_ms.get(),
+            reversal_brake_rpm: report.reversal_brake_rpm.get(),
+            telemetry_period_ms: report.telemetry_period_ms.get(),
+            dither_amplitude: report.dither_amplitude,
+            dither_freq_hz: report.dither_freq_hz,
+            dither_max_speed: report.dither_max_speed.get(),
+            min_dwell_ms: report.min_dwell_ms.get(),
+            log_interval_ms: report.log_interval_ms.get(),
+            encoder_reversed: report.encoder_reversed == 1,
+            direction_hysteresis: report.direction_hysteresis.get(),
+            max_rpm: report.max_rpm.get(),
+            min_supply_mv: report.min_supply_mv.get(),
+            kickstart_current_ma: report.kickstart_current_ma.get(),
+            feedback_timeout_ms: report.feedback_timeout_ms.get(),
+            overcurrent_response,
+            over_temperature_response,
+            undervoltage_response,
+            stall_response,
+            current_limit_ma: report.current_limit_ma.get(),
+            current_limit_gain: report.current_limit_gain.get(),
+            idle_sleep_ms: report.idle_sleep_ms.get(),
+        })
+    }
+    /// PWM slice timing for `pwm_freq_hz` (the default timing if it was set out of range directly)
+    pub fn pwm_timing(&self) -> PwmTiming {
+        PwmTiming::for_frequency(self.pwm_freq_hz).unwrap_or(PwmTiming::DEFAULT)
+    }
+
+    /// Duty floor actually applied: `min_duty`, raised to the driver's minimum
+    /// pulse width at `pwm_freq_hz` (`PwmTiming::min_pulse_duty`)
+    ///
+    /// A `min_duty` of 0 (no floor, as in torque mode) stays 0.
+    pub fn effective_min_duty(&self) -> u8 {
+        if self.min_duty == 0 {
+            return 0;
+        }
+        self.min_duty.max(self.pwm_timing().min_pulse_duty())
+    }
+
+    /// How long the current must stay above `max_current_ma` before the overcurrent fault
+    /// trips: `CURRENT_LIMITED_TRIP_MS` with the soft current limit on, so the hard cutoff
+    /// is only the fallback for a current the limit could not bring down
+    pub fn overcurrent_trip_ms(&self) -> u32 {
+        if self.current_limit_ma != 0 {
+            CURRENT_LIMITED_TRIP_MS
+        } else {
+            OVERCURRENT_TRIP_MS
+        }
+    }
+
+    /// Config report carrying this config (inverse of `from_report`)
+    pub fn to_report(&self) -> RWConfigReport {
+        RWConfigReport {
+            stop_mode: self.stop_mode as u8,
+            min_duty: self.min_duty,
+            kickstart_duty: self.kickstart_duty,
+            kickstart_ms: U16::new(self.kickstart_ms),
+            slew_step: U16::new(self.slew_step),
+            control_mode: self.control_mode as u8,
+            max_current_ma: U16::new(self.max_current_ma),
+            throttle_start_c: self.throttle_start_c,
+            throttle_limit_c: self.throttle_limit_c,
+            curve: self.curve as u8,
+            fwd_scale: U16::new(self.fwd_scale),
+            rev_scale: U16::new(self.rev_scale),
+            deadband: U16::new(self.deadband),
+            stop_ramp_step: U16::new(self.stop_ramp_step),
+            pwm_freq_hz: U16::new(self.pwm_freq_hz),
+            ramp_profile: self.ramp_profile as u8,
+            ramp_tau_ms: U16::new(self.ramp_tau_ms),
+            max_speed_normalized: U16::new(self.max_speed_normalized),
+            stall_window_ms: U16::new(self.stall_window_ms),
+            stall_rpm: U16::new(self.stall_rpm),
+            stall_retry: self.stall_retry as u8,
+            kickstart_rpm: U16::new(self.kickstart_rpm),
+            reversal_kick_threshold: U16::new(self.reversal_kick_threshold),
+            reversal_brake_ms: U16::new(self.reversal_brake_ms),
+            reversal_brake_rpm: U16::new(self.reversal_brake_rpm),
+            telemetry_period_ms: U16::new(self.telemetry_period_ms),
+            dither_amplitude: self.dither_amplitude,
+            dither_freq_hz: self.dither_freq_hz,
+            dither_max_speed: U16::new(self.dither_max_speed),
+            min_dwell_ms: U16::new(self.min_dwell_ms),
+            log_interval_ms: U16::new(self.log_interval_ms),
+            encoder_reversed: self.encoder_reversed as u8,
+            direction_hysteresis: U16::new(self.direction_hysteresis),
+            max_rpm: U16::new(self.max_rpm),
+            min_supply_mv: U16::new(self.min_supply_mv),
+            kickstart_current_ma: U16::new(self.kickstart_current_ma),
+            feedback_timeout_ms: U16::new(self.feedback_timeout_ms),
+            overcurrent_response: self.overcurrent_response as u8,
+            over_temperature_response: self.over_temperature_response as u8,
+            undervoltage_response: self.undervoltage_response as u8,
+            stall_response: self.stall_response as u8,
+            current_limit_ma: U16::new(self.current_limit_ma),
+            current_limit_gain: U16::new(self.current_limit_gain),
+            idle_sleep_ms: U16::new(self.idle_sleep_ms),
+        }
+    }
+}
@@ -0,0 +1,559 @@
+//! Per-tick motor control (slew limiting, kickstart state machine, reversal brake, start/stop dwell, closed-loop and torque drive)
+
+use crate::axis::{WheelSpec, DEFAULT_WHEEL_SPEC};
+use crate::config::{Config, ControlMode, RampProfile, StopMode, KICKSTART_RPM};
+use crate::current::{normalized_to_current_ma, TORQUE_KI};
+use crate::deadline::Deadline;
+use crate::motor::{counts_to_percent, needs_kickstart, percent_to_counts, MotorSpeed, PWM_TOP, SPEED_NORMALIZED_MAX};
+use crate::pid::{normalized_to_rpm, Pid};
+
+/// Control loop period. `Config::slew_step` is expressed per tick.
+pub const CONTROL_TICK_US: u64 = 1000;
+
+/// Kickstart duty of a stall retry (`MotorController::retry_kickstart`), run for twice `kickstart_ms`
+pub const RETRY_KICKSTART_DUTY: u8 = 100;
+
+/// Motor driver output for one control tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MotorOutput {
+    /// Both inputs low
+    Coast,
+    /// Both inputs high
+    Brake,
+    /// Both inputs PWM'd together at duty (PWM counts, 0..=PWM_TOP): windings
+    /// shorted for that share of the period and coasting for the rest
+    PartialBrake(u16),
+    /// Forward at duty (PWM counts, 0..=PWM_TOP)
+    Forward(u16),
+    /// Reverse at duty (PWM counts, 0..=PWM_TOP)
+    Reverse(u16),
+}
+
+impl MotorOutput {
+    /// Driven duty in PWM counts, 0 when stopped
+    pub fn counts(self) -> u16 {
+        match self {
+            MotorOutput::Coast | MotorOutput::Brake | MotorOutput::PartialBrake(_) => 0,
+            MotorOutput::Forward(counts) | MotorOutput::Reverse(counts) => counts,
+        }
+    }
+
+    /// Same duty in the opposite direction (stop outputs unchanged)
+    ///
+    /// Used for wheels mounted the other way round; applied after the controller
+    /// so it never affects duty or kickstart decisions.
+    pub fn mirrored(self) -> Self {
+        match self {
+            MotorOutput::Forward(counts) => MotorOutput::Reverse(counts),
+            MotorOutput::Reverse(counts) => MotorOutput::Forward(counts),
+            stop => stop,
+        }
+    }
+
+    /// Driven duty (0-100%, truncated), 0 when stopped
+    pub fn duty(self) -> u8 {
+        counts_to_percent(self.counts())
+    }
+}
+
+/// Move `current` toward `target` by at most `max_step` (0 = no limit)
+pub fn slew_toward(current: i16, target: i16, max_step: u16) -> i16 {
+    if max_step == 0 {
+        return target;
+    }
+    let delta = (target as i32 - current as i32).clamp(-(max_step as i32), max_step as i32);
+    (current as i32 + delta) as i16
+}
+
+/// Move `current` a first-order step toward `target` (time constant `tau_ms`), by at most `max_step` (0 = no limit)
+///
+/// Each tick covers `CONTROL_TICK_US / (tau + CONTROL_TICK_US)` of the remaining
+/// distance, rounded up so the tail still reaches the target. `tau_ms` = 0 jumps.
+pub fn exponential_toward(current: i16, target: i16, tau_ms: u16, max_step: u16) -> i16 {
+    let delta = target as i32 - current as i32;
+    let tau_us = tau_ms as u64 * 1000;
+    let magnitude = (delta.unsigned_abs() as u64 * CONTROL_TICK_US).div_ceil(tau_us + CONTROL_TICK_US) as i32;
+    let step = if delta < 0 { -magnitude } else { magnitude };
+    slew_toward(current, (current as i32 + step) as i16, max_step)
+}
+
+/// `target` held on the side of zero `current` is on while it is past zero by at most `band`
+///
+/// The held target has the same magnitude in the current direction, so a
+/// command wobbling around zero neither flips the direction nor kicks. A
+/// stopped `current`, a stop target or `band` = 0 leaves `target` as it is.
+pub fn hold_direction(current: i16, target: i16, band: u16) -> i16 {
+    if current != 0 && target != 0 && (current > 0) != (target > 0) && target.unsigned_abs() <= band {
+        -target
+    } else {
+        target
+    }
+}
+
+/// `duty` (PWM counts) raised and lowered by `dither_amplitude` on alternate half periods of `dither_freq_hz`
+fn dithered(duty: u16, now_us: u64, config: &Config) -> u16 {
+    let half_period_us = 500_000 / config.dither_freq_hz.max(1) as u64;
+    let amplitude = percent_to_counts(config.dither_amplitude);
+    if (now_us / half_period_us).is_multiple_of(2) {
+        duty.saturating_add(amplitude).min(PWM_TOP)
+    } else {
+        duty.saturating_sub(amplitude)
+    }
+}
+
+/// The driver is powering the motor (as opposed to coasting or braking)
+fn is_driving(output: MotorOutput) -> bool {
+    matches!(output, MotorOutput::Forward(_) | MotorOutput::Reverse(_))
+}
+
+/// Tracks the commanded target, the applied (slew-limited) speed and the kickstart pulse
+#[derive(Debug, Clone, Copy)]
+pub struct MotorController {
+    target: MotorSpeed,
+    applied: MotorSpeed,
+    // End of the running kickstart pulse, if any
+    kickstart_deadline: Option<Deadline>,
+    // End of the brake before a reversal, if any
+    reversal_brake_deadline: Option<Deadline>,
+    // At stop for a reversal brake: the restart kicks whatever the RPM
+    reversing: bool,
+    pid: Pid,
+    // PID output, used instead of `target` in closed-loop mode
+    closed_loop_drive: MotorSpeed,
+    // Current loop output, used instead of `target` in torque mode
+    torque_drive: MotorSpeed,
+    // Full-scale closed-loop RPM of the wheel, unless `Config::max_rpm` overrides it
+    wheel_max_rpm: u16,
+    // Upper bound on the driven duty in PWM counts (thermal throttling)
+    duty_limit: u16,
+    // Set by the last `update`: applied speed still short of its target
+    slewing: bool,
+    // Set by the last `update`: duty cut by `duty_limit`
+    duty_limited: bool,
+    // Duty ceiling of the soft current limit in PWM counts (fractional, as integrated)
+    current_ceiling: f32,
+    // Set by the last `update`: duty cut by `current_ceiling`
+    current_limited: bool,
+    // The next kickstart pulse is a stall retry
+    boosted_kickstart: bool,
+    // The sensed current has reached `kickstart_current_ma` during the running kickstart
+    kickstart_inrush: bool,
+    // Closed loop replaced by open loop for lack of encoder feedback
+    feedback_lost: bool,
+    // Last encoder RPM (0 until the first measurement, and without an encoder)
+    measured_rpm: i32,
+    // Output returned by the last `update`
+    output: MotorOutput,
+    // Timer timestamp (us) the output last switched between driving and stopped, None after `stop_now`
+    switched_us: Option<u64>,
+}
+
+impl Default for MotorController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MotorController {
+    pub const fn new() -> Self {
+        Self {
+            target: MotorSpeed::STOP,
+            applied: MotorSpeed::STOP,
+            kickstart_deadline: None,
+            reversal_brake_deadline: None,
+            reversing: false,
+            pid: Pid::new(),
+            closed_loop_drive: MotorSpeed::STOP,
+            torque_drive: MotorSpeed::STOP,
+            wheel_max_rpm: DEFAULT_WHEEL_SPEC.max_rpm,
+            duty_limit: PWM_TOP,
+            slewing: false,
+            duty_limited: false,
+            current_ceiling: PWM_TOP as f32,
+            current_limited: false,
+            boosted_kickstart: false,
+            kickstart_inrush: false,
+            feedback_lost: false,
+            measured_rpm: 0,
+            output: MotorOutput::Coast,
+            switched_us: None,
+        }
+    }
+
+    /// Set the commanded speed; reached over the following ticks
+    pub fn set_target(&mut self, target: MotorSpeed) {
+        self.target = target;
+    }
+
+    pub fn target(&self) -> MotorSpeed {
+        self.target
+    }
+
+    /// Drive the wheel described by `spec` (defaults to `axis::DEFAULT_WHEEL_SPEC`)
+    pub fn set_wheel_spec(&mut self, spec: WheelSpec) {
+        self.wheel_max_rpm = spec.max_rpm;
+    }
+
+    /// RPM a full-scale target asks for in closed loop: `config.max_rpm`, or the wheel's if that is 0
+    pub fn max_rpm(&self, config: &Config) -> u16 {
+        if config.max_rpm != 0 {
+            config.max_rpm
+        } else {
+            self.wheel_max_rpm
+        }
+    }
+
+    /// Run `ControlMode::ClosedLoop` as open loop (set by `feedback::FeedbackMonitor`)
+    pub fn set_feedback_lost(&mut self, lost: bool) {
+        self.feedback_lost = lost;
+    }
+
+    /// Mode the controller is running in: `config.control_mode`, except open loop after a feedback fallback
+    pub fn control_mode(&self, config: &Config) -> ControlMode {
+        match config.control_mode {
+            ControlMode::ClosedLoop if self.feedback_lost => ControlMode::OpenLoop,
+            mode => mode,
+        }
+    }
+
+    /// Target with its magnitude capped at `config.max_speed_normalized`
+    pub fn limited_target(&self, config: &Config) -> MotorSpeed {
+        let max = config.max_speed_normalized.min(SPEED_NORMALIZED_MAX as u16) as i16;
+        MotorSpeed::new(self.target.speed_normalized.clamp(-max, max))
+    }
+
+    /// Speed currently being driven (after slew limiting)
+    pub fn applied(&self) -> MotorSpeed {
+        self.applied
+    }
+
+    pub fn is_kickstarting(&self) -> bool {
+        self.kickstart_deadline.is_some()
+    }
+
+    /// Whether driving toward `heading` from the applied speed starts a kickstart
+    ///
+    /// As `motor::needs_kickstart`, except that a start from stop is not kicked
+    /// while the last measured RPM shows the wheel still turning that way at
+    /// `kickstart_rpm` (`KICKSTART_RPM` if that is 0): after a firmware reset or
+    /// a cut drive it may still be coasting, and the pulse would only jolt it.
+    /// Without an encoder the RPM reads 0, so every start from stop kicks, and
+    /// so does the restart after a reversal brake.
+    pub fn will_kickstart(&self, heading: MotorSpeed, config: &Config) -> bool {
+        if !needs_kickstart(self.applied, heading, config) {
+            return false;
+        }
+        let (last_duty, _) = self.applied.to_duty_and_direction(config);
+        if last_duty != 0 || self.reversing {
+            return true;
+        }
+        let rpm_forward = if heading.speed_normalized >= 0 { self.measured_rpm } else { -self.measured_rpm };
+        let turning_rpm = if config.kickstart_rpm != 0 { config.kickstart_rpm } else { KICKSTART_RPM };
+        rpm_forward < turning_rpm as i32
+    }
+
+    /// Braking the wheel before driving it the other way
+    pub fn is_reversal_braking(&self) -> bool {
+        self.reversal_brake_deadline.is_some()
+    }
+
+    /// Whether the last tick was slew limited (in closed-loop mode, toward the PID drive)
+    pub fn is_slewing(&self) -> bool {
+        self.slewing
+    }
+
+    /// Whether the last tick's duty was cut by the duty limit
+    pub fn is_duty_limited(&self) -> bool {
+        self.duty_limited
+    }
+
+    /// Whether the last tick's duty was cut by the soft current limit
+    pub fn is_current_limited(&self) -> bool {
+        self.current_limited
+    }
+
+    /// Cap the driven duty (0-100%, including kickstart); 0 holds the motor stopped
+    pub fn set_duty_limit(&mut self, limit: u8) {
+        self.duty_limit = percent_to_counts(limit);
+    }
+
+    /// Cut the drive immediately (no slew, no stop ramp, no kickstart, no dwell) and clear the target
+    pub fn stop_now(&mut self) {
+        self.target = MotorSpeed::STOP;
+        self.applied = MotorSpeed::STOP;
+        self.kickstart_deadline = None;
+        self.reversal_brake_deadline = None;
+        self.reversing = false;
+        self.pid.reset();
+        self.closed_loop_drive = MotorSpeed::STOP;
+        self.torque_drive = MotorSpeed::STOP;
+        self.slewing = false;
+        self.duty_limited = false;
+        self.current_ceiling = PWM_TOP as f32;
+        self.current_limited = false;
+        self.boosted_kickstart = false;
+        self.output = MotorOutput::Coast;
+        self.switched_us = None;
+    }
+
+    /// Cut the drive and start again from stop with a stronger kickstart
+    ///
+    /// The target is kept, so the next tick kicks the wheel at
+    /// `RETRY_KICKSTART_DUTY` for twice `kickstart_ms`.
+    pub fn retry_kickstart(&mut self) {
+        self.applied = MotorSpeed::STOP;
+        self.kickstart_deadline = None;
+        self.reversal_brake_deadline = None;
+        self.reversing = false;
+        self.pid.reset();
+        self.boosted_kickstart = true;
+    }
+
+    /// Feed a new RPM measurement and run one PID step (closed-loop mode only)
+    ///
+    /// The target is read as an RPM scaled to `max_rpm`. The drive never
+    /// reverses against the target direction: overspeed is corrected by
+    /// backing off to stop rather than driving backwards.
+    ///
+    /// In either mode, a running kickstart ends once the wheel turns at
+    /// `kickstart_rpm` in the kick direction. Without an encoder the RPM stays
+    /// 0 and the pulse runs for the full `kickstart_ms`. Likewise a reversal
+    /// brake ends once the wheel is below `reversal_brake_rpm`.
+    pub fn set_measured_rpm(&mut self, rpm: i32, config: &Config) {
+        self.measured_rpm = rpm;
+        if self.is_reversal_braking()
+            && config.reversal_brake_rpm != 0
+            && rpm.unsigned_abs() < config.reversal_brake_rpm as u32
+        {
+            self.reversal_brake_deadline = None;
+        }
+
+        if self.is_kickstarting() && config.kickstart_rpm != 0 {
+            let rpm_forward = if self.applied.speed_normalized >= 0 { rpm } else { -rpm };
+            if rpm_forward >= config.kickstart_rpm as i32 {
+                self.kickstart_deadline = None;
+                self.boosted_kickstart = false;
+            }
+        }
+
+        if self.control_mode(config) != ControlMode::ClosedLoop || self.target == MotorSpeed::STOP {
+            self.pid.reset();
+            self.closed_loop_drive = MotorSpeed::STOP;
+            return;
+        }
+
+        let target = self.limited_target(config).speed_normalized;
+        let drive = self.pid.update(normalized_to_rpm(target, self.max_rpm(config)), rpm);
+        self.closed_loop_drive = if (drive > 0) == (target > 0) {
+            MotorSpeed::new(drive)
+        } else {
+            MotorSpeed::STOP
+        };
+    }
+
+    /// Feed a motor current sample and run one step of the current loop (torque mode only)
+    ///
+    /// The target is read as a current scaled to `current::TORQUE_MAX_CURRENT_MA`.
+    /// The sense reading has no sign, so the loop integrates the magnitude of
+    /// the drive toward the target current and drives in the target's
+    /// direction; like closed loop, it never reverses against the target.
+    ///
+    /// In any mode, a running kickstart ends once the current has reached
+    /// `kickstart_current_ma` and decayed back below it: the inrush of a
+    /// stalled rotor is over and back-EMF has built up. A reading that never
+    /// reaches the threshold (no sense resistor fitted) leaves the pulse timed,
+    /// so `kickstart_ms` stays the upper bound either way.
+    ///
+    /// With `current_limit_ma` set, the sample also runs the soft current
+    /// limit in every mode: a duty ceiling that integrates the current error
+    /// at `current_limit_gain`, starting from the duty being driven once the
+    /// current first exceeds the limit. The wheel keeps spinning as fast as the
+    /// current allows instead of being cut; the ceiling rises back to 100% as
+    /// the load eases and is reset when the target is stop.
+    pub fn set_measured_current(&mut self, current_ma: u16, config: &Config) {
+        if config.current_limit_ma == 0 || self.target == MotorSpeed::STOP {
+            self.current_ceiling = PWM_TOP as f32;
+        } else {
+            let error = config.current_limit_ma as f32 - current_ma as f32;
+            let ceiling = if error < 0.0 && is_driving(self.output) {
+                self.current_ceiling.min(self.output.counts() as f32)
+            } else {
+                self.current_ceiling
+            };
+            let gain = config.current_limit_gain as f32 / 1000.0;
+            self.current_ceiling = (ceiling + gain * error).clamp(0.0, PWM_TOP as f32);
+        }
+
+        if self.is_kickstarting() && config.kickstart_current_ma != 0 {
+            if current_ma >= config.kickstart_current_ma {
+                self.kickstart_inrush = true;
+            } else if self.kickstart_inrush {
+                self.kickstart_deadline = None;
+                self.boosted_kickstart = false;
+            }
+        }
+
+        if config.control_mode != ControlMode::Torque || self.target == MotorSpeed::STOP {
+            self.torque_drive = MotorSpeed::STOP;
+            return;
+        }
+
+        let target = self.limited_target(config).speed_normalized;
+        let drive = self.torque_drive.speed_normalized;
+        // A new direction builds up from zero
+        let magnitude = if drive != 0 && (drive > 0) != (target > 0) { 0 } else { drive.unsigned_abs() };
+        let error = normalized_to_current_ma(target).unsigned_abs() as f32 - current_ma as f32;
+        let magnitude = (magnitude as f32 + TORQUE_KI * error).clamp(0.0, SPEED_NORMALIZED_MAX as f32) as i16;
+        self.torque_drive = MotorSpeed::new(if target > 0 { magnitude } else { -magnitude });
+    }
+
+    /// Advance one control tick and return the output to drive
+    ///
+    /// A kickstart starts whenever the applied speed starts from stop (unless
+    /// the wheel is measured still turning that way, see `will_kickstart`) or
+    /// changes direction. While it runs, speed changes in the same direction only update
+    /// the duty applied once the pulse ends.
+    ///
+    /// Below `dither_max_speed` the duty alternates by `dither_amplitude`
+    /// around its value (never during a kickstart).
+    ///
+    /// With `StopMode::Brake` and `reversal_brake_ms` set, a kicked direction
+    /// change first brakes the wheel from stop, then kicks in the new direction.
+    ///
+    /// A target on the other side of zero by at most `direction_hysteresis`
+    /// keeps the current direction (see `hold_direction`).
+    ///
+    /// Within `min_dwell_ms` of the drive starting or stopping, a tick that
+    /// would switch it the other way is not taken: the previous output is held
+    /// and the target (the latest command) is pursued once the dwell is over.
+    pub fn update(&mut self, now_us: u64, config: &Config) -> MotorOutput {
+        let before = *self;
+        let output = self.step(now_us, config);
+        if is_driving(output) != is_driving(before.output) {
+            let dwell_ms = config.min_dwell_ms as u32;
+            if self.switched_us.is_some_and(|since_us| !Deadline::after_ms(since_us, dwell_ms).is_elapsed(now_us)) {
+                *self = before;
+                return before.output;
+            }
+            self.switched_us = Some(now_us);
+        }
+        self.output = output;
+        output
+    }
+
+    /// One control tick without the dwell
+    fn step(&mut self, now_us: u64, config: &Config) -> MotorOutput {
+        // The current loop finds the duty itself: a duty floor or a kickstart would only add torque nobody asked for
+        let torque_config;
+        let config = if config.control_mode == ControlMode::Torque {
+            torque_config = Config { min_duty: 0, kickstart_ms: 0, ..*config };
+            &torque_config
+        } else {
+            config
+        };
+        let target = match self.control_mode(config) {
+            ControlMode::OpenLoop => self.limited_target(config),
+            ControlMode::ClosedLoop => self.closed_loop_drive,
+            ControlMode::Torque => self.torque_drive,
+        };
+        let target = MotorSpeed::new(hold_direction(
+            self.applied.speed_normalized,
+            target.speed_normalized,
+            config.direction_hysteresis,
+        ));
+        if let Some(deadline) = self.reversal_brake_deadline {
+            if !deadline.is_elapsed(now_us) && target != MotorSpeed::STOP {
+                self.slewing = true;
+                self.duty_limited = false;
+                self.current_limited = false;
+                return MotorOutput::Brake;
+            }
+            self.reversal_brake_deadline = None;
+        }
+
+        // Ramp toward stop at its own rate when one is configured
+        let step = if target == MotorSpeed::STOP && config.stop_ramp_step != 0 {
+            config.stop_ramp_step
+        } else {
+            config.slew_step
+        };
+        let current = self.applied.speed_normalized;
+        let next = MotorSpeed::new(match config.ramp_profile {
+            RampProfile::Linear => slew_toward(current, target.speed_normalized, step),
+            RampProfile::Exponential => exponential_toward(current, target.speed_normalized, config.ramp_tau_ms, step),
+        });
+
+        // Judge a reversal by where the ramp is heading, not by the small step across zero
+        let heading = if (next.speed_normalized > 0) == (target.speed_normalized > 0) { target } else { next };
+        if self.starts_reversal_brake(heading, config) {
+            self.applied = MotorSpeed::STOP;
+            self.kickstart_deadline = None;
+            self.reversal_brake_deadline = Some(Deadline::after_ms(now_us, config.reversal_brake_ms as u32));
+            self.reversing = true;
+            self.slewing = true;
+            self.duty_limited = false;
+            self.current_limited = false;
+            return MotorOutput::Brake;
+        }
+        if self.will_kickstart(heading, config) {
+            let scale = if self.boosted_kickstart { 2 } else { 1 };
+            self.kickstart_deadline = Some(Deadline::after_ms(now_us, config.kickstart_ms as u32 * scale));
+            self.kickstart_inrush = false;
+        }
+        self.reversing = false;
+        self.applied = next;
+        self.slewing = next != target;
+        self.duty_limited = false;
+        self.current_limited = false;
+
+        let stopped = match config.stop_mode {
+            StopMode::Coast => MotorOutput::Coast,
+            StopMode::Brake => MotorOutput::Brake,
+        };
+
+        let (duty, is_forward) = next.to_counts_and_direction(config);
+        if duty == 0 {
+            self.kickstart_deadline = None;
+            return stopped;
+        }
+
+        if let Some(deadline) = self.kickstart_deadline {
+            if deadline.is_elapsed(now_us) {
+                self.kickstart_deadline = None;
+                self.boosted_kickstart = false;
+            }
+        }
+        let kickstart_duty = if self.boosted_kickstart { RETRY_KICKSTART_DUTY } else { config.kickstart_duty };
+        let duty = if self.is_kickstarting() {
+            percent_to_counts(kickstart_duty)
+        } else if config.dither_amplitude != 0 && next.speed_normalized.unsigned_abs() < config.dither_max_speed {
+            dithered(duty, now_us, config)
+        } else {
+            duty
+        };
+        let current_ceiling = self.current_ceiling as u16;
+        self.duty_limited = duty > self.duty_limit;
+        self.current_limited = duty > current_ceiling;
+        let duty = duty.min(self.duty_limit).min(current_ceiling);
+        if duty == 0 {
+            return stopped;
+        }
+
+        if is_forward {
+            MotorOutput::Forward(duty)
+        } else {
+            MotorOutput::Reverse(duty)
+        }
+    }
+
+    /// Whether moving to `heading` is a kicked direction change that should brake first
+    fn starts_reversal_brake(&self, heading: MotorSpeed, config: &Config) -> bool {
+        if config.stop_mode != StopMode::Brake || config.reversal_brake_ms == 0 {
+            return false;
+        }
+        let (last_duty, last_forward) = self.applied.to_duty_and_direction(config);
+        let (_, is_forward) = heading.to_duty_and_direction(config);
+        last_duty > 0 && last_forward != is_forward && needs_kickstart(self.applied, heading, config)
+    }
+}
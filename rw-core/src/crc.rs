@@ -0,0 +1,26 @@
+//! Checksums for wire formats
+
+/// CRC-8/SMBUS (poly 0x07, init 0x00, no reflection, no final XOR)
+pub fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |mut crc, &byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// CRC-32/ISO-HDLC (the zlib/Ethernet CRC: poly 0x04C11DB7 reflected, init and final XOR 0xFFFFFFFF)
+///
+/// For longer data than the reports carry, such as the HID descriptor; host
+/// tools get the same value from `zlib.crc32` or `crc32fast`.
+pub fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |mut crc, &byte| {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+        crc
+    })
+}
@@ -0,0 +1,117 @@
+//! Motor current sensing, overcurrent protection and the torque command scaling
+//!
+//! The sense resistor carries the bridge current whichever way the motor is
+//! driven, so readings have no sign. The ADC takes one sample per control tick
+//! at an arbitrary point of the PWM period; at the default 10kHz the winding
+//! inductance keeps the current close to its average, which is what the
+//! overcurrent check, the soft current limit and the torque loop work on.
+
+use crate::deadline::Deadline;
+use crate::motor::SPEED_NORMALIZED_MAX;
+
+/// Current-sense resistor between the DRV8833 AISEN pin and GND (milliohms)
+pub const SENSE_RESISTOR_MOHM: u32 = 200;
+
+/// RP2040 ADC reference voltage and full-scale reading (12-bit)
+pub const ADC_VREF_MV: u32 = 3300;
+pub const ADC_MAX: u32 = 4095;
+
+/// Default overcurrent threshold (overridable at runtime via `RWConfigReport`, 0 = disabled)
+pub const MAX_CURRENT_MA: u16 = 1500;
+
+/// Current must stay above the threshold this long before the fault trips
+pub const OVERCURRENT_TRIP_MS: u32 = 5;
+
+/// Trip time instead of `OVERCURRENT_TRIP_MS` while the soft current limit is on,
+/// long enough for the limit to pull the duty down after a step in load
+pub const CURRENT_LIMITED_TRIP_MS: u32 = 100;
+
+/// Default soft current limit (overridable at runtime via `RWConfigReport`, 0 = disabled: hard cutoff only)
+pub const CURRENT_LIMIT_MA: u16 = 0;
+
+/// Default soft current limit gain: change of the duty ceiling per control tick per mA
+/// of current error, in 1/1000 PWM count (100 = 0.1 count; 2500 counts are 100%)
+pub const CURRENT_LIMIT_GAIN: u16 = 100;
+
+/// Highest soft current limit gain accepted (1 PWM count per tick per mA); above it
+/// the duty overshoots the limit from one tick to the next and the limit oscillates
+pub const CURRENT_LIMIT_GAIN_MAX: u16 = 1000;
+
+/// The fault clears once current has stayed below `threshold - OVERCURRENT_HYSTERESIS_MA`
+/// for `OVERCURRENT_CLEAR_MS`
+pub const OVERCURRENT_HYSTERESIS_MA: u16 = 200;
+pub const OVERCURRENT_CLEAR_MS: u32 = 1000;
+
+/// Motor current commanded by a full-scale normalized value in `ControlMode::Torque`
+///
+/// Torque is taken as proportional to the winding current (constant torque
+/// constant), so the command is a current. Kept below the default
+/// `MAX_CURRENT_MA` so full torque does not trip the overcurrent fault.
+pub const TORQUE_MAX_CURRENT_MA: u16 = 1000;
+
+/// Torque loop gain: change of the normalized drive per control tick per mA of current error
+pub const TORQUE_KI: f32 = 0.5;
+
+/// Target current in mA for a normalized command (±32767 → ±`TORQUE_MAX_CURRENT_MA`)
+pub fn normalized_to_current_ma(speed_normalized: i16) -> i32 {
+    speed_normalized as i32 * TORQUE_MAX_CURRENT_MA as i32 / SPEED_NORMALIZED_MAX as i32
+}
+
+/// Convert a raw ADC reading of the sense resistor voltage to milliamps
+pub fn adc_to_current_ma(raw: u16) -> u16 {
+    let mv = raw as u32 * ADC_VREF_MV / ADC_MAX;
+    (mv * 1000 / SENSE_RESISTOR_MOHM).min(u16::MAX as u32) as u16
+}
+
+/// Debounced overcurrent fault with hysteresis
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OvercurrentMonitor {
+    tripped: bool,
+    // Timer timestamp (us) since which the current has been past the trip/clear level
+    since_us: Option<u64>,
+}
+
+impl OvercurrentMonitor {
+    pub const fn new() -> Self {
+        Self {
+            tripped: false,
+            since_us: None,
+        }
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped
+    }
+
+    /// Feed a current sample; returns whether the fault is active
+    pub fn update(&mut self, now_us: u64, current_ma: u16, max_current_ma: u16) -> bool {
+        self.update_with_trip(now_us, current_ma, max_current_ma, OVERCURRENT_TRIP_MS)
+    }
+
+    /// As `update`, tripping once the current has stayed above the threshold for `trip_ms`
+    /// (`Config::overcurrent_trip_ms`)
+    pub fn update_with_trip(&mut self, now_us: u64, current_ma: u16, max_current_ma: u16, trip_ms: u32) -> bool {
+        if max_current_ma == 0 {
+            *self = Self::new();
+            return false;
+        }
+
+        let (past_level, hold_ms) = if self.tripped {
+            (current_ma < max_current_ma.saturating_sub(OVERCURRENT_HYSTERESIS_MA), OVERCURRENT_CLEAR_MS)
+        } else {
+            (current_ma > max_current_ma, trip_ms)
+        };
+
+        if !past_level {
+            self.since_us = None;
+            return self.tripped;
+        }
+
+        let since_us = *self.since_us.get_or_insert(now_us);
+        if Deadline::after_ms(since_us, hold_ms).is_elapsed(now_us) {
+            self.tripped = !self.tripped;
+            self.since_us = None;
+        }
+        self.tripped
+    }
+}
@@ -0,0 +1,57 @@
+//! Speed-to-duty shaping curves
+//!
+//! Duty and wheel speed aren't proportional, so a linear mapping feels dead at
+//! low commands. A curve reshapes the normalized magnitude before it is turned
+//! into duty; the sign is applied afterwards, so forward and reverse match.
+
+use crate::motor::SPEED_NORMALIZED_MAX;
+
+/// Shaping applied to the normalized speed magnitude
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpeedCurve {
+    /// Output = input (default)
+    Linear,
+    /// Square-root (gamma 0.5) lookup table, boosts low commands
+    Sqrt,
+}
+
+impl SpeedCurve {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(SpeedCurve::Linear),
+            1 => Some(SpeedCurve::Sqrt),
+            _ => None,
+        }
+    }
+
+    /// Reshape a normalized magnitude (0..=32767)
+    ///
+    /// Tables are sampled at 16 equal intervals of the input range. Values
+    /// between two points are linearly interpolated, so the output is
+    /// continuous and stays monotonic as long as the table is.
+    pub fn apply(self, magnitude: u16) -> u16 {
+        match self {
+            SpeedCurve::Linear => magnitude,
+            SpeedCurve::Sqrt => interpolate(&SQRT_TABLE, magnitude),
+        }
+    }
+}
+
+/// round(32767 * sqrt(i / 16))
+const SQRT_TABLE: [u16; 17] = [
+    0, 8192, 11585, 14189, 16384, 18317, 20066, 21673, 23170, 24575, 25905, 27169, 28377, 29536, 30651,
+    31727, 32767,
+];
+
+fn interpolate(table: &[u16; 17], magnitude: u16) -> u16 {
+    let full = SPEED_NORMALIZED_MAX as u32;
+    let pos = magnitude.min(SPEED_NORMALIZED_MAX as u16) as u32 * 16;
+    let (index, frac) = ((pos / full) as usize, pos % full);
+    if index >= 16 {
+        return table[16];
+    }
+
+    let (lo, hi) = (table[index] as u32, table[index + 1] as u32);
+    (lo + (hi - lo) * frac / full) as u16
+}
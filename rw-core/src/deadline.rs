@@ -0,0 +1,72 @@
+//! Deadlines on the microsecond timer
+//!
+//! A `Deadline` is a point on the 64-bit timer timestamp (`now_us`, as passed
+//! to every `update`). Checking it never blocks: the loop polls `is_elapsed`
+//! each tick instead of waiting. The comparison is on the wrapping difference,
+//! so a deadline set just before the counter wraps still elapses on time (the
+//! RP2040 counter takes far longer than any mission to wrap, but the arithmetic
+//! then no longer depends on that). Spans must stay below 2^63 us.
+
+/// Point on the timer at which something is due
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Deadline {
+    at_us: u64,
+}
+
+impl Deadline {
+    /// Due at timestamp `at_us`
+    pub const fn at(at_us: u64) -> Self {
+        Self { at_us }
+    }
+
+    /// Due `us` after `now_us`
+    pub const fn after_us(now_us: u64, us: u64) -> Self {
+        Self { at_us: now_us.wrapping_add(us) }
+    }
+
+    /// Due `ms` milliseconds after `now_us`
+    pub const fn after_ms(now_us: u64, ms: u32) -> Self {
+        Self::after_us(now_us, ms as u64 * 1000)
+    }
+
+    pub const fn at_us(&self) -> u64 {
+        self.at_us
+    }
+
+    /// `now_us` is at or past the deadline
+    pub const fn is_elapsed(&self, now_us: u64) -> bool {
+        now_us.wrapping_sub(self.at_us) as i64 >= 0
+    }
+
+    /// Time since the deadline, 0 while it is still ahead (a start time used as
+    /// a deadline gives the time since the start)
+    pub const fn elapsed_us(&self, now_us: u64) -> u64 {
+        if self.is_elapsed(now_us) {
+            now_us.wrapping_sub(self.at_us)
+        } else {
+            0
+        }
+    }
+
+    /// Time left until the deadline, 0 once elapsed
+    pub const fn remaining_us(&self, now_us: u64) -> u64 {
+        if self.is_elapsed(now_us) {
+            0
+        } else {
+            self.at_us.wrapping_sub(now_us)
+        }
+    }
+
+    /// Next deadline of a fixed-rate schedule: `period_us` after this one, or
+    /// `now_us` if that has already passed (missed periods are skipped instead
+    /// of run back to back)
+    pub const fn next(&self, period_us: u64, now_us: u64) -> Self {
+        let next = Self::after_us(self.at_us, period_us);
+        if next.is_elapsed(now_us) {
+            Self::at(now_us)
+        } else {
+            next
+        }
+    }
+}
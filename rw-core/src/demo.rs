@@ -0,0 +1,96 @@
+//! Standalone demo spin toggled by a button (no host needed)
+//!
+//! For showing the hardware on its own: a gentle looping pattern that ramps
+//! the wheel up to `DEMO_SPEED`, holds, ramps back down and pauses, then does
+//! the same in reverse, so every direction change starts from a stop. The
+//! ramp is part of the pattern rather than `Config::slew_step`, so the demo
+//! stays gentle whatever the host configured; the controller still applies
+//! kickstart and the protection limits.
+
+use crate::deadline::Deadline;
+use crate::motor::MotorSpeed;
+
+/// Top speed of the pattern (normalized units, 50%)
+pub const DEMO_SPEED: i16 = 16384;
+/// Ramp time between stop and `DEMO_SPEED`, either way (ms)
+pub const DEMO_RAMP_MS: u64 = 3000;
+/// Time held at `DEMO_SPEED` (ms)
+pub const DEMO_HOLD_MS: u64 = 2000;
+/// Time stopped before the direction changes (ms)
+pub const DEMO_PAUSE_MS: u64 = 1000;
+
+/// One direction of the pattern; the full loop is twice this
+pub const DEMO_HALF_CYCLE_MS: u64 = 2 * DEMO_RAMP_MS + DEMO_HOLD_MS + DEMO_PAUSE_MS;
+
+/// The button level has to be stable this long to count as a press or release
+pub const DEMO_BUTTON_DEBOUNCE_MS: u64 = 30;
+
+/// Speed of the pattern `t_ms` after its start
+pub fn demo_speed(t_ms: u64) -> MotorSpeed {
+    let t = t_ms % DEMO_HALF_CYCLE_MS;
+    let ramp = |ms: u64| (DEMO_SPEED as u64 * ms / DEMO_RAMP_MS) as i16;
+    let magnitude = if t < DEMO_RAMP_MS {
+        ramp(t)
+    } else if t < DEMO_RAMP_MS + DEMO_HOLD_MS {
+        DEMO_SPEED
+    } else if t < 2 * DEMO_RAMP_MS + DEMO_HOLD_MS {
+        ramp(2 * DEMO_RAMP_MS + DEMO_HOLD_MS - t)
+    } else {
+        0
+    };
+    let reverse = (t_ms / DEMO_HALF_CYCLE_MS) % 2 == 1;
+    MotorSpeed::new(if reverse { -magnitude } else { magnitude })
+}
+
+/// A running demo spin
+#[derive(Debug, Clone, Copy)]
+pub struct DemoSpin {
+    // Timer timestamp (us) the pattern started
+    started_us: u64,
+}
+
+impl DemoSpin {
+    pub const fn new(now_us: u64) -> Self {
+        Self { started_us: now_us }
+    }
+
+    /// Target for this control tick
+    pub fn update(&self, now_us: u64) -> MotorSpeed {
+        demo_speed(Deadline::at(self.started_us).elapsed_us(now_us) / 1000)
+    }
+}
+
+/// Debounced demo button, reporting presses
+///
+/// Starts out released, so a button already held at boot counts as a press
+/// once it has been stable for `DEMO_BUTTON_DEBOUNCE_MS`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DemoButton {
+    pressed: bool,
+    // Timer timestamp (us) the raw level started to differ from `pressed`, None while it agrees
+    changing_since_us: Option<u64>,
+}
+
+impl DemoButton {
+    pub const fn new() -> Self {
+        Self {
+            pressed: false,
+            changing_since_us: None,
+        }
+    }
+
+    /// Feed the raw button level; true once per debounced press
+    pub fn update(&mut self, now_us: u64, pressed: bool) -> bool {
+        if pressed == self.pressed {
+            self.changing_since_us = None;
+            return false;
+        }
+        let since_us = *self.changing_since_us.get_or_insert(now_us);
+        if !Deadline::after_us(since_us, DEMO_BUTTON_DEBOUNCE_MS * 1000).is_elapsed(now_us) {
+            return false;
+        }
+        self.pressed = pressed;
+        self.changing_since_us = None;
+        pressed
+    }
+}
@@ -0,0 +1,170 @@
+//! DRV8833 sleep/wake sequencing
+//!
+//! The driver comes up asleep (nSLEEP low) with both bridge inputs at zero
+//! duty. After nSLEEP goes high its outputs are not usable until the wake time
+//! has passed, so commands are held off until then. There is no nFAULT pin
+//! wired, so the elapsed wake time is the only confirmation available.
+//!
+//! `IdleSleep` puts the driver back to sleep while the wheel sits stopped
+//! without host activity (`Config::idle_sleep_ms`), to save its standby
+//! current. The next host report wakes it through the same wake time.
+
+use crate::config::Config;
+use crate::deadline::Deadline;
+
+/// nSLEEP high to outputs ready (DRV8833 t_WAKE, 1ms max)
+pub const DRIVER_WAKE_US: u64 = 1000;
+
+/// Default idle sleep delay (overridable at runtime via `RWConfigReport`, 0 = the driver stays awake)
+pub const IDLE_SLEEP_MS: u16 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DriverState {
+    /// nSLEEP low
+    Asleep,
+    /// nSLEEP high, wake time not elapsed until `ready`
+    Waking { ready: Deadline },
+    /// Ready to drive the motor
+    Awake,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DriverEnable {
+    state: DriverState,
+}
+
+impl DriverEnable {
+    pub const fn new() -> Self {
+        Self { state: DriverState::Asleep }
+    }
+
+    pub fn state(&self) -> DriverState {
+        self.state
+    }
+
+    pub fn is_asleep(&self) -> bool {
+        self.state == DriverState::Asleep
+    }
+
+    pub fn is_awake(&self) -> bool {
+        self.state == DriverState::Awake
+    }
+
+    /// nSLEEP was driven high at `now_us`
+    pub fn wake(&mut self, now_us: u64) {
+        if self.is_asleep() {
+            self.state = DriverState::Waking { ready: Deadline::after_us(now_us, DRIVER_WAKE_US) };
+        }
+    }
+
+    /// nSLEEP was driven low
+    pub fn sleep(&mut self) {
+        self.state = DriverState::Asleep;
+    }
+
+    /// Advance the wake timer; true on the call where the driver becomes awake
+    pub fn update(&mut self, now_us: u64) -> bool {
+        match self.state {
+            DriverState::Waking { ready } if ready.is_elapsed(now_us) => {
+                self.state = DriverState::Awake;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Default for DriverEnable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum IdleState {
+    /// Driver in normal use; asleep only for other reasons (bring-up, e-stop, fault)
+    Active,
+    /// Driver put to sleep for idleness
+    Asleep,
+    /// Woken by host activity, wake time not elapsed yet: commands are taken, the drive waits
+    Waking,
+}
+
+/// Idle sleep of the driver
+#[derive(Debug, Clone, Copy)]
+pub struct IdleSleep {
+    state: IdleState,
+    // Sleep once this passes with the wheel still idle, None while it isn't
+    sleep_deadline: Option<Deadline>,
+}
+
+impl IdleSleep {
+    pub const fn new() -> Self {
+        Self { state: IdleState::Active, sleep_deadline: None }
+    }
+
+    pub fn state(&self) -> IdleState {
+        self.state
+    }
+
+    pub fn is_asleep(&self) -> bool {
+        self.state == IdleState::Asleep
+    }
+
+    pub fn is_waking(&self) -> bool {
+        self.state == IdleState::Waking
+    }
+
+    /// Feed whether the wheel is idle (stopped, with nothing running that drives it);
+    /// true on the call where the driver should go to sleep (nSLEEP low)
+    ///
+    /// The idle time restarts whenever the wheel is not idle and on host activity.
+    pub fn update(&mut self, now_us: u64, idle: bool, config: &Config) -> bool {
+        if !idle || config.idle_sleep_ms == 0 || self.state != IdleState::Active {
+            self.sleep_deadline = None;
+            return false;
+        }
+        let deadline = *self.sleep_deadline.get_or_insert(Deadline::after_ms(now_us, config.idle_sleep_ms as u32));
+        if !deadline.is_elapsed(now_us) {
+            return false;
+        }
+        self.state = IdleState::Asleep;
+        self.sleep_deadline = None;
+        true
+    }
+
+    /// A report from the host arrived: restart the idle time; true if the driver was asleep
+    /// for idleness and has to be woken (nSLEEP high, `DriverEnable::wake`)
+    ///
+    /// With `wake_blocked` (a fault response holding the driver disabled) the
+    /// driver stays asleep, so no command is taken for a wake that can't happen.
+    pub fn activity(&mut self, wake_blocked: bool) -> bool {
+        self.sleep_deadline = None;
+        if self.state != IdleState::Asleep || wake_blocked {
+            return false;
+        }
+        self.state = IdleState::Waking;
+        true
+    }
+
+    /// Bring-up is blocked (a fault response disabled the driver): a pending
+    /// wake falls back asleep, to be woken by a host report after the block clears
+    pub fn block_wake(&mut self) {
+        if self.state == IdleState::Waking {
+            self.state = IdleState::Asleep;
+        }
+    }
+
+    /// The driver finished waking (`DriverEnable::update` returned true)
+    pub fn awake(&mut self) {
+        self.state = IdleState::Active;
+    }
+}
+
+impl Default for IdleSleep {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,55 @@
+//! Quadrature encoder decoding and RPM estimation
+
+use crate::deadline::Deadline;
+
+/// Encoder counts per wheel revolution after x4 quadrature decoding
+pub const ENCODER_COUNTS_PER_REV: u32 = 48;
+
+/// Window over which RPM is measured
+pub const RPM_WINDOW_MS: u32 = 100;
+
+/// Count change for a transition between 2-bit quadrature states (`A << 1 | B`)
+///
+/// Forward is A leading B (00 → 10 → 11 → 01). A transition where both channels
+/// changed means an edge was missed and counts as 0.
+pub fn quadrature_step(prev: u8, next: u8) -> i8 {
+    const TABLE: [i8; 16] = [0, -1, 1, 0, 1, 0, 0, -1, -1, 0, 0, 1, 0, 1, -1, 0];
+    TABLE[(((prev & 0b11) << 2) | (next & 0b11)) as usize]
+}
+
+/// Signed RPM from encoder counts sampled over a fixed window
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RpmEstimator {
+    last_count: i32,
+    last_us: u64,
+    rpm: i32,
+}
+
+impl RpmEstimator {
+    pub const fn new() -> Self {
+        Self {
+            last_count: 0,
+            last_us: 0,
+            rpm: 0,
+        }
+    }
+
+    /// Feed the current encoder count; returns the new RPM once `RPM_WINDOW_MS` has elapsed
+    pub fn update(&mut self, now_us: u64, count: i32) -> Option<i32> {
+        let dt_us = Deadline::at(self.last_us).elapsed_us(now_us);
+        if dt_us < RPM_WINDOW_MS as u64 * 1000 {
+            return None;
+        }
+
+        let delta = count.wrapping_sub(self.last_count) as i64;
+        self.rpm = (delta * 60_000_000 / (ENCODER_COUNTS_PER_REV as i64 * dt_us as i64)) as i32;
+        self.last_count = count;
+        self.last_us = now_us;
+        Some(self.rpm)
+    }
+
+    /// Most recent RPM measurement
+    pub fn rpm(&self) -> i32 {
+        self.rpm
+    }
+}
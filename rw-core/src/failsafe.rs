@@ -0,0 +1,135 @@
+//! Command timeout failsafe and emergency stop
+
+use crate::config::FaultResponse;
+use crate::control::MotorOutput;
+use crate::deadline::Deadline;
+use crate::motor::PWM_TOP;
+use crate::protocol::RWEStopReport;
+
+/// Stop the motor if no command has been received for this long
+pub const COMMAND_TIMEOUT_MS: u32 = 500;
+
+/// Time a ramped emergency stop (`EStopCommand::EngageRamped`) takes to reach the full brake
+pub const ESTOP_RAMP_MS: u32 = 40;
+
+/// Longest a ramped emergency stop brakes before the driver is disabled, stopped or not
+pub const ESTOP_BRAKE_TIMEOUT_MS: u32 = 3000;
+
+/// Check whether the host has gone silent for longer than `COMMAND_TIMEOUT_MS`
+pub fn is_command_timed_out(now_us: u64, last_command_us: u64) -> bool {
+    // Longer than: exactly the timeout after the command is still in time
+    Deadline::after_us(last_command_us, COMMAND_TIMEOUT_MS as u64 * 1000 + 1).is_elapsed(now_us)
+}
+
+/// Emergency stop command from `RWEStopReport`
+///
+/// Engaging latches the driver disabled; speed commands are ignored until an
+/// explicit release, so a stale queued speed packet can't restart the wheel.
+/// The ramped variant brakes the wheel through an `EStopRamp` first and
+/// latches the driver disabled once it has stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EStopCommand {
+    Release,
+    /// Driver disabled at once
+    Engage,
+    /// Brake ramped up over `ESTOP_RAMP_MS` and held until stopped, then the driver disabled
+    EngageRamped,
+}
+
+impl EStopCommand {
+    pub fn from_report(report: &RWEStopReport) -> Option<Self> {
+        match report.command {
+            0 => Some(EStopCommand::Release),
+            1 => Some(EStopCommand::Engage),
+            2 => Some(EStopCommand::EngageRamped),
+            _ => None,
+        }
+    }
+}
+
+/// Ramped emergency stop: a brake that builds up over `ESTOP_RAMP_MS`
+///
+/// The drive is cut and the windings are shorted for a growing share of each
+/// PWM period (`MotorOutput::PartialBrake`), from 0 to the full brake at the
+/// end of the ramp, so the braking torque rises over the ramp instead of
+/// stepping to its peak. The full brake is then held until the encoder reads
+/// the wheel stopped, or for at most `ESTOP_BRAKE_TIMEOUT_MS`, and `update`
+/// reports the end: the caller disables the driver then, as for an immediate
+/// e-stop. A 0 RPM reading only counts once the ramp has seen the wheel turn,
+/// so without an encoder fitted the brake is held for the whole timeout.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EStopRamp {
+    // Timer timestamp (us) the ramp started, None while none runs
+    started_us: Option<u64>,
+    // A nonzero RPM was measured since the start, so a 0 RPM is a real stop
+    moved: bool,
+}
+
+impl EStopRamp {
+    pub const fn new() -> Self {
+        Self {
+            started_us: None,
+            moved: false,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started_us.is_some()
+    }
+
+    /// Start braking at `now_us` (a running ramp carries on)
+    pub fn start(&mut self, now_us: u64) {
+        if self.started_us.is_none() {
+            *self = Self {
+                started_us: Some(now_us),
+                moved: false,
+            };
+        }
+    }
+
+    /// Stop the ramp; true if one was running
+    pub fn abort(&mut self) -> bool {
+        let running = self.is_running();
+        *self = Self::new();
+        running
+    }
+
+    /// Output for this tick while the ramp runs
+    pub fn output(&self, now_us: u64) -> Option<MotorOutput> {
+        let started_us = self.started_us?;
+        let ramp_us = ESTOP_RAMP_MS as u64 * 1000;
+        let elapsed_us = Deadline::at(started_us).elapsed_us(now_us).min(ramp_us);
+        if elapsed_us == ramp_us {
+            return Some(MotorOutput::Brake);
+        }
+        Some(MotorOutput::PartialBrake((PWM_TOP as u64 * elapsed_us / ramp_us) as u16))
+    }
+
+    /// `output` with the drive fault `response` applied, unless the ramp runs:
+    /// the e-stop takes priority, so a stall or overcurrent mid-ramp keeps braking
+    pub fn with_fault_response(&self, output: MotorOutput, response: Option<FaultResponse>) -> MotorOutput {
+        match response {
+            _ if self.is_running() => output,
+            Some(FaultResponse::Brake) => MotorOutput::Brake,
+            // Inputs low as well with the driver disabled, so it wakes up coasting
+            Some(FaultResponse::Coast | FaultResponse::DisableDriver) => MotorOutput::Coast,
+            None => output,
+        }
+    }
+
+    /// Advance one control tick; `rpm` is the measurement taken this tick, if any.
+    /// True once, on the tick the stop is done (disable the driver then).
+    pub fn update(&mut self, now_us: u64, rpm: Option<i32>) -> bool {
+        let Some(started_us) = self.started_us else {
+            return false;
+        };
+        self.moved |= rpm.is_some_and(|rpm| rpm != 0);
+        let braked = self.moved && Deadline::after_ms(started_us, ESTOP_RAMP_MS).is_elapsed(now_us) && rpm == Some(0);
+        if !braked && !Deadline::after_ms(started_us, ESTOP_BRAKE_TIMEOUT_MS).is_elapsed(now_us) {
+            return false;
+        }
+        *self = Self::new();
+        true
+    }
+}
@@ -0,0 +1,139 @@
+//! Fault codes reported to the host
+//!
+//! Conditions (overcurrent, over-temperature, command timeout, stall, undervoltage, no feedback) are active while
+//! they last; events (an invalid report, a loop overrun) are never active. Everything seen is
+//! latched until the host clears it explicitly, so a dashboard polling slower
+//! than a fault lasts still sees it. Clearing cannot hide an active condition:
+//! it is latched again on the next tick.
+
+use core::ops::{BitOr, BitOrAssign};
+
+use crate::config::{Config, FaultResponse};
+
+/// Set of faults (bit layout as in `RWStatusReport::faults`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Fault(u16);
+
+impl Fault {
+    pub const NONE: Self = Self(0);
+    /// Motor current above `max_current_ma` (drive cut until it clears)
+    pub const OVERCURRENT: Self = Self(1 << 0);
+    /// Chip temperature in the thermal throttling range
+    pub const OVER_TEMPERATURE: Self = Self(1 << 1);
+    /// No speed command for `COMMAND_TIMEOUT_MS` (motor stopped)
+    pub const COMMAND_TIMEOUT: Self = Self(1 << 2);
+    /// A report was rejected (bad checksum, wrong length, unknown ID or out-of-range field)
+    pub const INVALID_REPORT: Self = Self(1 << 3);
+    /// Wheel driven but not turning (`stall::StallMonitor`), drive cut until a stop command
+    pub const STALL: Self = Self(1 << 5);
+    /// Axis strap unstable at boot. The firmware halts without enumerating USB
+    /// in that case, so the host only sees this bit if that policy changes.
+    pub const AXIS_STRAP: Self = Self(1 << 4);
+    /// A main loop iteration took longer than `looptime::LOOP_OVERRUN_US` (something blocked)
+    pub const LOOP_OVERRUN: Self = Self(1 << 6);
+    /// Supply voltage below `min_supply_mv` (drive cut until it recovers)
+    pub const UNDERVOLTAGE: Self = Self(1 << 7);
+    /// Closed loop driven without encoder counts (`feedback::FeedbackMonitor`), running open loop instead
+    pub const NO_FEEDBACK: Self = Self(1 << 8);
+
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(self) -> u16 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
+
+impl BitOr for Fault {
+    type Output = Self;
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for Fault {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+/// Drive response to the conditions in `cutting` that cut the drive
+///
+/// `cutting` holds the active overcurrent, undervoltage and stall, and
+/// `Fault::OVER_TEMPERATURE` once throttling has reached the limit (short of
+/// that the duty is only derated). None if none of them is set. With several,
+/// the response putting the least into the motor wins: disabling the driver,
+/// then coasting, then braking.
+pub fn drive_response(cutting: Fault, config: &Config) -> Option<FaultResponse> {
+    [
+        (Fault::OVERCURRENT, config.overcurrent_response),
+        (Fault::OVER_TEMPERATURE, config.over_temperature_response),
+        (Fault::UNDERVOLTAGE, config.undervoltage_response),
+        (Fault::STALL, config.stall_response),
+    ]
+    .into_iter()
+    .filter(|&(fault, _)| cutting.contains(fault))
+    .map(|(_, response)| response)
+    .max_by_key(|response| match response {
+        FaultResponse::Brake => 0,
+        FaultResponse::Coast => 1,
+        FaultResponse::DisableDriver => 2,
+    })
+}
+
+/// Active and latched faults
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FaultLog {
+    active: Fault,
+    latched: Fault,
+}
+
+impl FaultLog {
+    pub const fn new() -> Self {
+        Self {
+            active: Fault::NONE,
+            latched: Fault::NONE,
+        }
+    }
+
+    /// Replace the set of active conditions (once per control tick); they are latched too
+    pub fn set_active(&mut self, active: Fault) {
+        self.active = active;
+        self.latched |= active;
+    }
+
+    /// Latch a one-off event
+    pub fn record(&mut self, event: Fault) {
+        self.latched |= event;
+    }
+
+    /// Clear the latched faults in `mask` (active conditions stay set)
+    pub fn clear(&mut self, mask: Fault) {
+        self.latched.remove(mask);
+        self.latched |= self.active;
+    }
+
+    /// Conditions present right now
+    pub fn active(&self) -> Fault {
+        self.active
+    }
+
+    /// Everything seen since the last clear, active conditions included
+    pub fn latched(&self) -> Fault {
+        self.latched
+    }
+}
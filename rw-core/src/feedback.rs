@@ -0,0 +1,66 @@
+//! Missing encoder detection for closed-loop control
+//!
+//! Without encoder counts the measured RPM stays 0 whatever the drive, so the
+//! PID integrates its error up to full duty and slams the wheel. Once the wheel
+//! has been driven in closed loop for `Config::feedback_timeout_ms` with the
+//! RPM still exactly 0, the controller falls back to open loop
+//! (`MotorController::set_feedback_lost`) and `fault::Fault::NO_FEEDBACK` is
+//! raised. The fallback holds until the host writes the config again, so an
+//! unplugged encoder does not make the wheel alternate between the two modes.
+//!
+//! A wheel held still with a working encoder reads 0 as well and falls back the
+//! same way; open loop then drives it the way the host asked without winding
+//! the PID up, and stall detection still applies.
+
+use crate::config::{Config, ControlMode};
+use crate::deadline::Deadline;
+
+/// Default time driven in closed loop without encoder counts before falling back (ms, 0 = disabled)
+pub const FEEDBACK_TIMEOUT_MS: u16 = 300;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FeedbackMonitor {
+    // Timer timestamp (us) since which the wheel has been driven with the RPM at 0
+    since_us: Option<u64>,
+    lost: bool,
+}
+
+impl FeedbackMonitor {
+    pub const fn new() -> Self {
+        Self {
+            since_us: None,
+            lost: false,
+        }
+    }
+
+    /// Running open loop for lack of feedback
+    pub fn is_lost(&self) -> bool {
+        self.lost
+    }
+
+    /// Try closed loop again (after a config write)
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Check one control tick; `duty` is the driven duty in PWM counts.
+    /// Returns true on the tick the feedback is found missing.
+    pub fn update(&mut self, now_us: u64, duty: u16, rpm: i32, config: &Config) -> bool {
+        if config.control_mode != ControlMode::ClosedLoop || config.feedback_timeout_ms == 0 {
+            *self = Self::new();
+            return false;
+        }
+        if self.lost || duty == 0 || rpm != 0 {
+            self.since_us = None;
+            return false;
+        }
+
+        let since_us = *self.since_us.get_or_insert(now_us);
+        if !Deadline::after_ms(since_us, config.feedback_timeout_ms as u32).is_elapsed(now_us) {
+            return false;
+        }
+        self.since_us = None;
+        self.lost = true;
+        true
+    }
+}
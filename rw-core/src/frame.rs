@@ -0,0 +1,121 @@
+//! Framed reports for a plain byte stream (the firmware's UART telemetry)
+//!
+//! A UART has no packet boundaries, so each report travels in a frame:
+//!
+//! | Bytes | Content |
+//! |-------|---------|
+//! | 2 | Sync `FRAME_SYNC` (0xA5 0x5A) |
+//! | 1 | Report ID (as on USB: 1 = status, 7 = telemetry) |
+//! | 1 | Payload length N |
+//! | N | Payload, the same bytes as the HID report after its ID |
+//! | 1 | CRC-8 (`crc::crc8`) over the ID, length and payload |
+//!
+//! The sync bytes can occur inside a payload, so a reader that starts mid-stream
+//! (or loses bytes) may lock onto a false frame; the CRC rejects it and
+//! `FrameDecoder` hunts for the next sync.
+
+use crate::crc::crc8;
+
+pub const FRAME_SYNC: [u8; 2] = [0xA5, 0x5A];
+
+/// Longest payload a frame carries (the largest input report is well below)
+pub const MAX_FRAME_PAYLOAD: usize = 64;
+
+/// Sync, ID, length and CRC around the payload
+pub const FRAME_OVERHEAD: usize = 5;
+
+pub const MAX_FRAME_LEN: usize = MAX_FRAME_PAYLOAD + FRAME_OVERHEAD;
+
+/// One encoded frame
+#[derive(Debug, Clone, Copy)]
+pub struct Frame {
+    bytes: [u8; MAX_FRAME_LEN],
+    len: usize,
+}
+
+impl Frame {
+    /// Frame `payload` as report `report_id`; None if it is longer than `MAX_FRAME_PAYLOAD`
+    pub fn new(report_id: u8, payload: &[u8]) -> Option<Self> {
+        if payload.len() > MAX_FRAME_PAYLOAD {
+            return None;
+        }
+        let mut bytes = [0u8; MAX_FRAME_LEN];
+        bytes[..2].copy_from_slice(&FRAME_SYNC);
+        bytes[2] = report_id;
+        bytes[3] = payload.len() as u8;
+        bytes[4..4 + payload.len()].copy_from_slice(payload);
+        let len = payload.len() + FRAME_OVERHEAD;
+        bytes[len - 1] = crc8(&bytes[2..len - 1]);
+        Some(Self { bytes, len })
+    }
+
+    /// Frame a report as sent over USB (report ID first, as from `to_report()`)
+    pub fn from_report(report: &[u8]) -> Option<Self> {
+        let (&report_id, payload) = report.split_first()?;
+        Self::new(report_id, payload)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameError {
+    /// Length byte above `MAX_FRAME_PAYLOAD`
+    Length(u8),
+    Checksum { expected: u8, actual: u8 },
+}
+
+/// Stream decoder: feed bytes one at a time with `push`
+#[derive(Debug, Clone, Copy)]
+pub struct FrameDecoder {
+    // Frame bytes from the sync on, `len` of them valid
+    buf: [u8; MAX_FRAME_LEN],
+    len: usize,
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameDecoder {
+    pub const fn new() -> Self {
+        Self { buf: [0; MAX_FRAME_LEN], len: 0 }
+    }
+
+    /// Take one byte; returns the report ID and payload once a frame is complete,
+    /// or the error of a rejected one (the decoder then waits for the next sync)
+    pub fn push(&mut self, byte: u8) -> Option<Result<(u8, &[u8]), FrameError>> {
+        match self.len {
+            0 | 1 if byte != FRAME_SYNC[self.len] => {
+                // A repeated first sync byte may still start the frame
+                self.len = usize::from(byte == FRAME_SYNC[0]);
+                self.buf[0] = byte;
+                return None;
+            }
+            3 if byte as usize > MAX_FRAME_PAYLOAD => {
+                self.len = 0;
+                return Some(Err(FrameError::Length(byte)));
+            }
+            _ => {}
+        }
+        self.buf[self.len] = byte;
+        self.len += 1;
+        if self.len < FRAME_OVERHEAD || self.len < self.buf[3] as usize + FRAME_OVERHEAD {
+            return None;
+        }
+
+        let len = self.len;
+        self.len = 0;
+        let expected = crc8(&self.buf[2..len - 1]);
+        let actual = self.buf[len - 1];
+        if expected != actual {
+            return Some(Err(FrameError::Checksum { expected, actual }));
+        }
+        Some(Ok((self.buf[2], &self.buf[4..len - 1])))
+    }
+}
@@ -0,0 +1,103 @@
+//! HID idle rate (SET_IDLE / GET_IDLE)
+//!
+//! The host sets, per input report ID, how often an unchanged report is
+//! repeated. A report goes out when its contents changed or when the idle
+//! period since the last one elapsed; an idle rate of 0 means on change only.
+//!
+//! `Config::telemetry_period_ms` can replace this with a fixed push cadence.
+
+use crate::deadline::Deadline;
+
+/// SET_IDLE duration unit
+pub const IDLE_UNIT_MS: u32 = 4;
+
+/// Idle rate until the host sends SET_IDLE: 100ms, so status liveness keeps advancing
+pub const DEFAULT_IDLE_RATE: IdleRate = IdleRate::from_raw(25);
+
+/// `Config::telemetry_period_ms` value that pushes on change and at the idle rate
+pub const TELEMETRY_ON_CHANGE: u16 = u16::MAX;
+
+/// Idle duration in 4ms units, as carried in the high byte of SET_IDLE's wValue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct IdleRate(u8);
+
+impl IdleRate {
+    /// Report only when the contents change
+    pub const INDEFINITE: Self = Self(0);
+
+    pub const fn from_raw(duration: u8) -> Self {
+        Self(duration)
+    }
+
+    /// Value returned by GET_IDLE
+    pub const fn raw(self) -> u8 {
+        self.0
+    }
+
+    /// Repeat period, None for indefinite
+    pub fn period_ms(self) -> Option<u32> {
+        (self.0 != 0).then(|| self.0 as u32 * IDLE_UNIT_MS)
+    }
+}
+
+/// Decides when one input report is sent
+///
+/// `N` is the report length. Fields that change on every tick (liveness
+/// counters) should be masked out of the bytes passed in, or the report would
+/// never count as unchanged.
+#[derive(Debug, Clone, Copy)]
+pub struct IdleTimer<const N: usize> {
+    /// Contents and timer timestamp (us) of the last report sent
+    last: Option<([u8; N], u64)>,
+}
+
+impl<const N: usize> IdleTimer<N> {
+    pub const fn new() -> Self {
+        Self { last: None }
+    }
+
+    /// Whether `report` should be sent now
+    pub fn is_due(&self, rate: IdleRate, now_us: u64, report: &[u8; N]) -> bool {
+        match &self.last {
+            None => true,
+            Some((last, sent_us)) => {
+                last != report
+                    || rate
+                        .period_ms()
+                        .is_some_and(|period| Deadline::after_ms(*sent_us, period).is_elapsed(now_us))
+            }
+        }
+    }
+
+    /// Whether `report` should be sent now under the telemetry push period
+    ///
+    /// `period_ms` = 0 never sends and `TELEMETRY_ON_CHANGE` defers to
+    /// `is_due`; any other period sends at that cadence, changed or not.
+    pub fn is_due_at(&self, period_ms: u16, rate: IdleRate, now_us: u64, report: &[u8; N]) -> bool {
+        match period_ms {
+            0 => false,
+            TELEMETRY_ON_CHANGE => self.is_due(rate, now_us, report),
+            period => self
+                .last
+                .as_ref()
+                .is_none_or(|(_, sent_us)| Deadline::after_ms(*sent_us, period as u32).is_elapsed(now_us)),
+        }
+    }
+
+    /// Record that `report` was queued at `now_us`
+    pub fn sent(&mut self, report: [u8; N], now_us: u64) {
+        self.last = Some((report, now_us));
+    }
+
+    /// Send the next report regardless of its contents (e.g. after a bus reset)
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+impl<const N: usize> Default for IdleTimer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,41 @@
+//! Hardware-independent reaction wheel logic
+//!
+//! Shared by the `pico-rw-mock` firmware and host-side tools. Everything here is
+//! `no_std` and free of HAL dependencies so it can be unit tested on the host.
+#![no_std]
+
+pub mod axis;
+pub mod coast;
+pub mod command;
+pub mod config;
+pub mod control;
+pub mod crc;
+pub mod current;
+pub mod curve;
+pub mod deadline;
+pub mod demo;
+pub mod driver;
+pub mod encoder;
+pub mod failsafe;
+pub mod fault;
+pub mod feedback;
+pub mod frame;
+pub mod idle;
+pub mod lograte;
+pub mod looptime;
+pub mod min_duty;
+pub mod motor;
+pub mod pid;
+pub mod polarity;
+pub mod protocol;
+pub mod pwm;
+pub mod selftest;
+pub mod sequence;
+pub mod spinup;
+pub mod stall;
+pub mod storage;
+pub mod strap;
+pub mod supply;
+pub mod thermal;
+pub mod wakeup;
+pub mod wheel;
@@ -0,0 +1,80 @@
+//! Rate limiting for log lines on the command and control path
+//!
+//! A host polling every few ms, or a slew ramp changing the duty every tick,
+//! would otherwise print a line per command or tick and flood RTT. A log line
+//! is keyed by the state it reports: a new key is logged at once, while lines
+//! with the same key are coalesced into at most one per interval, carrying the
+//! latest value and how many lines were dropped.
+
+use crate::config::Config;
+use crate::deadline::Deadline;
+
+/// Default minimum time between log lines with an unchanged key in ms (0 = log every line)
+pub const LOG_INTERVAL_MS: u16 = 1000;
+
+/// A line to print now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogLine<V> {
+    pub value: V,
+    /// Lines dropped since the last one printed
+    pub suppressed: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LogLimiter<K, V> {
+    // Key of the last printed line and the timer timestamp (us) it was printed at
+    last: Option<(K, u64)>,
+    // Latest dropped value, printed by `flush` once the interval is over
+    pending: Option<V>,
+    suppressed: u32,
+}
+
+impl<K: Copy + PartialEq, V: Copy> LogLimiter<K, V> {
+    pub const fn new() -> Self {
+        Self {
+            last: None,
+            pending: None,
+            suppressed: 0,
+        }
+    }
+
+    /// Offer a line reporting `value` in state `key`; `Some` if it should be printed now
+    pub fn offer(&mut self, now_us: u64, key: K, value: V, config: &Config) -> Option<LogLine<V>> {
+        let due = match self.last {
+            Some((last_key, last_us)) => last_key != key || Self::interval_over(last_us, now_us, config),
+            None => true,
+        };
+        if !due {
+            self.pending = Some(value);
+            self.suppressed += 1;
+            return None;
+        }
+        self.last = Some((key, now_us));
+        self.pending = None;
+        Some(LogLine { value, suppressed: core::mem::take(&mut self.suppressed) })
+    }
+
+    /// The latest dropped line, once the interval since the last printed one is over
+    ///
+    /// Called every tick where the final value of a burst matters (such as the
+    /// duty a ramp settles at), so it still gets printed after the burst ends.
+    pub fn flush(&mut self, now_us: u64, config: &Config) -> Option<LogLine<V>> {
+        let (key, last_us) = self.last?;
+        if !Self::interval_over(last_us, now_us, config) {
+            return None;
+        }
+        let value = self.pending.take()?;
+        self.last = Some((key, now_us));
+        Some(LogLine { value, suppressed: core::mem::take(&mut self.suppressed) - 1 })
+    }
+
+    fn interval_over(last_us: u64, now_us: u64, config: &Config) -> bool {
+        Deadline::after_ms(last_us, config.log_interval_ms as u32).is_elapsed(now_us)
+    }
+}
+
+impl<K: Copy + PartialEq, V: Copy> Default for LogLimiter<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,42 @@
+//! Main loop timing watchpoint
+//!
+//! The control loop runs every `CONTROL_TICK_US` and nothing in it may block.
+//! Each iteration's busy time (from the tick start to the end of its work) is
+//! measured against the timer; the worst case since the last telemetry report
+//! goes out with the next one, and an iteration longer than `LOOP_OVERRUN_US`
+//! is latched as `Fault::LOOP_OVERRUN`, so a host can check the invariant.
+
+use crate::control::CONTROL_TICK_US;
+use crate::deadline::Deadline;
+
+/// Busy time above which an iteration counts as an overrun: the next tick is already late
+pub const LOOP_OVERRUN_US: u32 = CONTROL_TICK_US as u32;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoopTimer {
+    // Longest busy time (us) since the last `reset`
+    max_us: u32,
+}
+
+impl LoopTimer {
+    pub const fn new() -> Self {
+        Self { max_us: 0 }
+    }
+
+    /// Longest iteration since the last `reset`, in us
+    pub fn max_us(&self) -> u32 {
+        self.max_us
+    }
+
+    /// Start a new reporting window
+    pub fn reset(&mut self) {
+        self.max_us = 0;
+    }
+
+    /// Record one iteration that started at `start_us` and finished at `end_us`; its busy time if it overran
+    pub fn record(&mut self, start_us: u64, end_us: u64) -> Option<u32> {
+        let busy_us = Deadline::at(start_us).elapsed_us(end_us).min(u32::MAX as u64) as u32;
+        self.max_us = self.max_us.max(busy_us);
+        (busy_us > LOOP_OVERRUN_US).then_some(busy_us)
+    }
+}
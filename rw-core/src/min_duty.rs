@@ -0,0 +1,104 @@
+//! Minimum duty calibration: find the duty at which a stopped wheel starts to turn
+//!
+//! Every non-zero command is driven at `Config::min_duty` or more, and the
+//! default (`config::MIN_DUTY`) is only a guess at a typical motor's static
+//! friction. Started by the host with the wheel at rest: the duty is ramped up
+//! from 0 by 1% every `MIN_DUTY_STEP_MS` (no kickstart, slew or PID) until the
+//! encoder registers motion, and the duty driven at that point becomes the
+//! wheel's `min_duty`.
+
+use crate::control::MotorOutput;
+use crate::deadline::Deadline;
+use crate::motor::percent_to_counts;
+use crate::protocol::RWMinDutyCalibrationReport;
+
+/// Time each 1% duty step is held (one `encoder::RPM_WINDOW_MS` window)
+pub const MIN_DUTY_STEP_MS: u64 = 100;
+
+/// Encoder counts (either direction) that count as motion; a single edge can be jitter at rest
+pub const MIN_DUTY_MOTION_COUNTS: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MinDutyCommand {
+    Abort,
+    Start,
+}
+
+impl MinDutyCommand {
+    pub fn from_report(report: &RWMinDutyCalibrationReport) -> Option<Self> {
+        match report.command {
+            0 => Some(MinDutyCommand::Abort),
+            1 => Some(MinDutyCommand::Start),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of a minimum duty calibration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MinDutyResult {
+    /// The wheel started to turn at this duty (0-100%)
+    Found { duty: u8 },
+    /// Held 100% for a step without motion (no encoder, stuck wheel); `min_duty` is left alone
+    NoRotation,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MinDutyCalibration {
+    // Timer timestamp (us) the ramp started, None while no calibration runs
+    started_us: Option<u64>,
+    // Encoder count at the start
+    start_count: i32,
+    // Duty driven this step (0-100%)
+    duty: u8,
+}
+
+impl MinDutyCalibration {
+    pub const fn new() -> Self {
+        Self { started_us: None, start_count: 0, duty: 0 }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started_us.is_some()
+    }
+
+    /// Duty of the current ramp step in percent, None while no calibration runs
+    pub fn duty(&self) -> Option<u8> {
+        self.started_us.map(|_| self.duty)
+    }
+
+    /// Start the ramp from 0% at `now_us` with the encoder at `count` (restarts a running calibration)
+    pub fn start(&mut self, now_us: u64, count: i32) {
+        self.started_us = Some(now_us);
+        self.start_count = count;
+        self.duty = 0;
+    }
+
+    /// Stop the calibration; true if one was running
+    pub fn abort(&mut self) -> bool {
+        self.started_us.take().is_some()
+    }
+
+    /// Output to drive in place of the controller's while the calibration runs
+    pub fn output(&self) -> Option<MotorOutput> {
+        self.duty().map(|duty| MotorOutput::Forward(percent_to_counts(duty)))
+    }
+
+    /// Advance one control tick with the current encoder count
+    pub fn update(&mut self, now_us: u64, count: i32) -> Option<MinDutyResult> {
+        let started_us = self.started_us?;
+        if count.wrapping_sub(self.start_count).unsigned_abs() >= MIN_DUTY_MOTION_COUNTS {
+            self.started_us = None;
+            return Some(MinDutyResult::Found { duty: self.duty });
+        }
+        let step = Deadline::at(started_us).elapsed_us(now_us) / 1000 / MIN_DUTY_STEP_MS;
+        if step > 100 {
+            self.started_us = None;
+            return Some(MinDutyResult::NoRotation);
+        }
+        self.duty = step as u8;
+        None
+    }
+}
@@ -0,0 +1,140 @@
+//! Normalized speed to motor duty mapping
+
+use crate::config::Config;
+
+/// Full-scale normalized speed (100%)
+pub const SPEED_NORMALIZED_MAX: i16 = 32767;
+
+/// Per-direction scale factor of 1.0 (`Config::fwd_scale`/`rev_scale` are in 1/1000)
+pub const SCALE_UNITY: u16 = 1000;
+
+/// Full-scale duty count; duty is driven in counts 0..=PWM_TOP (PWM_TOP = 100%)
+/// and scaled to the hardware TOP of the configured PWM frequency at the driver
+pub const PWM_TOP: u16 = 2500;
+
+/// Convert a duty percentage (0-100%) to PWM counts
+pub fn percent_to_counts(percent: u8) -> u16 {
+    (percent.min(100) as u32 * PWM_TOP as u32 / 100) as u16
+}
+
+/// Convert PWM counts to a duty percentage (truncated)
+pub fn counts_to_percent(counts: u16) -> u8 {
+    (counts.min(PWM_TOP) as u32 * 100 / PWM_TOP as u32) as u8
+}
+
+/// Motor speed state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MotorSpeed {
+    pub speed_normalized: i16,  // Normalized speed (-32767 to +32767)
+}
+
+impl MotorSpeed {
+    pub const STOP: Self = Self { speed_normalized: 0 };
+
+    pub const fn new(speed_normalized: i16) -> Self {
+        Self { speed_normalized }
+    }
+
+    /// Magnitude after the deadband, the speed curve and the per-direction scale,
+    /// before the limit to full scale, and direction
+    fn scaled_magnitude(self, config: &Config) -> (u32, bool) {
+        // unsigned_abs() so that i16::MIN cannot overflow; the curve keeps 0 at 0
+        let is_forward = self.speed_normalized >= 0;
+        let abs_speed = self.speed_normalized.unsigned_abs();
+        // Inside the deadband is a full stop rather than min_duty
+        if abs_speed < config.deadband {
+            return (0, is_forward);
+        }
+        let magnitude = config.curve.apply(abs_speed);
+        let scale = if is_forward { config.fwd_scale } else { config.rev_scale };
+        (magnitude as u32 * scale as u32 / SCALE_UNITY as u32, is_forward)
+    }
+
+    /// `scaled_magnitude` limited to full scale (0..=32767)
+    fn shaped_magnitude(self, config: &Config) -> (u16, bool) {
+        let (magnitude, is_forward) = self.scaled_magnitude(config);
+        (magnitude.min(SPEED_NORMALIZED_MAX as u32) as u16, is_forward)
+    }
+
+    /// The per-direction scale asks for more than 100% duty: the duty from
+    /// `to_duty_and_direction` is stuck at 100% and a larger command changes nothing
+    pub fn is_saturated(self, config: &Config) -> bool {
+        // i16::MIN is clamped like a command, so an unscaled -100% is not saturated
+        let clamped = MotorSpeed::new(self.speed_normalized.max(-SPEED_NORMALIZED_MAX));
+        clamped.scaled_magnitude(config).0 > SPEED_NORMALIZED_MAX as u32
+    }
+
+    pub fn to_duty_and_direction(self, config: &Config) -> (u8, bool) {
+        // Convert normalized speed (-32767 to +32767) to duty cycle (0-100%)
+        // -32767 -> 100% reverse
+        // 0 -> 0% (stop)
+        // +32767 -> 100% forward
+
+        // Beyond 100% the duty saturates (`is_saturated`)
+        let (abs_speed, is_forward) = self.shaped_magnitude(config);
+
+        // Scale: 32767 -> 100% duty
+        // Use min duty (default 40%, raised at high PWM frequencies) when speed > 0
+        let duty = if abs_speed == 0 {
+            0
+        } else {
+            let scaled = (abs_speed as u32 * 100 / SPEED_NORMALIZED_MAX as u32).min(100) as u8;
+            scaled.max(config.effective_min_duty())
+        };
+
+        (duty, is_forward)
+    }
+
+    /// Same mapping as `to_duty_and_direction`, in PWM counts (0..=PWM_TOP)
+    ///
+    /// Keeps the full PWM resolution instead of quantizing to whole percent;
+    /// the duty floor becomes `percent_to_counts(config.effective_min_duty())`.
+    pub fn to_counts_and_direction(self, config: &Config) -> (u16, bool) {
+        let (abs_speed, is_forward) = self.shaped_magnitude(config);
+
+        let counts = if abs_speed == 0 {
+            0
+        } else {
+            let scaled = (abs_speed as u32 * PWM_TOP as u32 / SPEED_NORMALIZED_MAX as u32).min(PWM_TOP as u32) as u16;
+            scaled.max(percent_to_counts(config.effective_min_duty()))
+        };
+
+        (counts, is_forward)
+    }
+}
+
+/// Check if a kickstart is needed when going from `last` to `current`
+/// (start from stop, or direction change while spinning)
+///
+/// A direction change where both magnitudes are below
+/// `config.reversal_kick_threshold` crosses zero without a kick.
+pub fn needs_kickstart(last: MotorSpeed, current: MotorSpeed, config: &Config) -> bool {
+    let (duty, is_forward) = current.to_duty_and_direction(config);
+    let (last_duty, last_forward) = last.to_duty_and_direction(config);
+    let large_reversal = last.speed_normalized.unsigned_abs().max(current.speed_normalized.unsigned_abs())
+        >= config.reversal_kick_threshold;
+
+    duty > 0
+        && (last_duty == 0  // Starting from stop
+            || (last_forward != is_forward && large_reversal))  // Direction change
+}
+
+/// Convert normalized speed to percent (-100 to +100, rounded to nearest)
+///
+/// -32768 saturates to -100 like -32767.
+pub fn normalized_to_percent(speed_normalized: i16) -> i8 {
+    let max = SPEED_NORMALIZED_MAX as i32;
+    let scaled = speed_normalized as i32 * 100;
+    let rounding = if scaled < 0 { -(max / 2) } else { max / 2 };
+    ((scaled + rounding) / max).clamp(-100, 100) as i8
+}
+
+/// Convert percent to normalized speed (rounded to nearest, saturating outside -100..=100)
+///
+/// Inverse of `normalized_to_percent` for every percent in range.
+pub fn percent_to_normalized(percent: i8) -> i16 {
+    let scaled = percent.clamp(-100, 100) as i32 * SPEED_NORMALIZED_MAX as i32;
+    let rounding = if scaled < 0 { -50 } else { 50 };
+    ((scaled + rounding) / 100) as i16
+}
@@ -0,0 +1,63 @@
+//! Closed-loop speed control (PID on measured RPM)
+
+use crate::encoder::RPM_WINDOW_MS;
+use crate::motor::SPEED_NORMALIZED_MAX;
+
+/// Wheel speed commanded by a full-scale normalized speed in closed-loop mode
+/// (default of `axis::WheelSpec::max_rpm`)
+pub const MAX_RPM: i32 = 12000;
+
+/// PID gains: output in normalized speed units per RPM of error
+pub const PID_KP: f32 = 1.0;
+pub const PID_KI: f32 = 4.0;
+pub const PID_KD: f32 = 0.0;
+
+/// PID step period; the controller runs once per RPM measurement
+pub const PID_DT_S: f32 = RPM_WINDOW_MS as f32 / 1000.0;
+
+/// Target RPM for a normalized speed (±32767 → ±`max_rpm`)
+pub fn normalized_to_rpm(speed_normalized: i16, max_rpm: u16) -> i32 {
+    speed_normalized as i32 * max_rpm as i32 / SPEED_NORMALIZED_MAX as i32
+}
+
+/// PID controller producing a normalized drive command
+///
+/// The output is clamped to ±32767. The integral term is clamped to whatever
+/// the P and D terms leave of that range, so it can't wind up while the output
+/// is saturated (e.g. a stalled wheel) and overshoot once the wheel catches up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pid {
+    integral: f32,
+    prev_error: f32,
+}
+
+impl Pid {
+    pub const fn new() -> Self {
+        Self {
+            integral: 0.0,
+            prev_error: 0.0,
+        }
+    }
+
+    /// Clear the integral and derivative state
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Run one step and return the normalized drive command
+    pub fn update(&mut self, target_rpm: i32, measured_rpm: i32) -> i16 {
+        let limit = SPEED_NORMALIZED_MAX as f32;
+        let error = (target_rpm - measured_rpm) as f32;
+
+        let proportional = PID_KP * error;
+        let derivative = PID_KD * (error - self.prev_error) / PID_DT_S;
+        self.prev_error = error;
+
+        // Keep the integral within the headroom left by the P and D terms
+        let high = (limit - proportional - derivative).max(0.0);
+        let low = (-limit - proportional - derivative).min(0.0);
+        self.integral = (self.integral + PID_KI * error * PID_DT_S).clamp(low, high);
+
+        (proportional + self.integral + derivative).clamp(-limit, limit) as i16
+    }
+}
@@ -0,0 +1,104 @@
+//! Encoder polarity calibration: detect swapped A/B channels
+//!
+//! With the channels swapped the measured RPM runs opposite to the commanded
+//! direction, which turns the closed-loop PID and the early kickstart end
+//! against the wheel. Started by the host: the wheel is driven forward at a
+//! fixed duty (no kickstart, slew or PID) for `POLARITY_CHECK_MS`, and the sign
+//! of the last RPM measurement tells whether the decode polarity is right.
+
+use crate::control::MotorOutput;
+use crate::deadline::Deadline;
+use crate::motor::PWM_TOP;
+use crate::protocol::RWEncoderCalibrationReport;
+
+/// Duty driven during the check (60%, above the breakaway duty of a typical wheel)
+pub const POLARITY_CHECK_DUTY: u16 = PWM_TOP / 5 * 3;
+
+/// Spin time before the measured RPM is checked (several `encoder::RPM_WINDOW_MS` windows)
+pub const POLARITY_CHECK_MS: u64 = 1000;
+
+/// Below this measured magnitude the wheel counts as not turning (no encoder, stuck wheel)
+pub const POLARITY_MIN_RPM: i32 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PolarityCommand {
+    Abort,
+    Start,
+}
+
+impl PolarityCommand {
+    pub fn from_report(report: &RWEncoderCalibrationReport) -> Option<Self> {
+        match report.command {
+            0 => Some(PolarityCommand::Abort),
+            1 => Some(PolarityCommand::Start),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of a polarity check, relative to the decode polarity in use during it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PolarityCheck {
+    /// Driven forward and measured forward
+    Matches { rpm: i32 },
+    /// Driven forward but measured in reverse: the decode polarity has to be flipped
+    Reversed { rpm: i32 },
+    /// Never measured `POLARITY_MIN_RPM` either way; the polarity is left alone
+    NoRotation { rpm: i32 },
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PolarityCalibration {
+    // Timer timestamp (us) the drive started, None while no check runs
+    started_us: Option<u64>,
+    // Latest RPM measured since the start
+    rpm: i32,
+}
+
+impl PolarityCalibration {
+    pub const fn new() -> Self {
+        Self { started_us: None, rpm: 0 }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started_us.is_some()
+    }
+
+    /// Start driving forward at `now_us` (restarts a running check)
+    pub fn start(&mut self, now_us: u64) {
+        self.started_us = Some(now_us);
+        self.rpm = 0;
+    }
+
+    /// Stop the check; true if one was running
+    pub fn abort(&mut self) -> bool {
+        self.started_us.take().is_some()
+    }
+
+    /// Output to drive in place of the controller's while the check runs
+    pub fn output(&self) -> Option<MotorOutput> {
+        self.started_us.map(|_| MotorOutput::Forward(POLARITY_CHECK_DUTY))
+    }
+
+    /// Advance one control tick; `rpm` is the measurement taken this tick, if any
+    pub fn update(&mut self, now_us: u64, rpm: Option<i32>) -> Option<PolarityCheck> {
+        let started_us = self.started_us?;
+        if let Some(rpm) = rpm {
+            self.rpm = rpm;
+        }
+        if !Deadline::after_us(started_us, POLARITY_CHECK_MS * 1000).is_elapsed(now_us) {
+            return None;
+        }
+        self.started_us = None;
+        let rpm = self.rpm;
+        Some(if rpm.abs() < POLARITY_MIN_RPM {
+            PolarityCheck::NoRotation { rpm }
+        } else if rpm < 0 {
+            PolarityCheck::Reversed { rpm }
+        } else {
+            PolarityCheck::Matches { rpm }
+        })
+    }
+}
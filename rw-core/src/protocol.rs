@@ -0,0 +1,681 @@
+//! HID report wire formats (all multi-byte fields little-endian)
+//!
+//! Every report on the wire starts with its report ID; the structs here are the
+//! payload that follows it.
+
+use core::mem::size_of;
+
+use zerocopy::little_endian::{I16, U16, U32};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
+use crate::config::{Config, ConfigError};
+use crate::control::{MotorController, MotorOutput};
+use crate::crc::{crc32, crc8};
+use crate::fault::Fault;
+use crate::motor::{percent_to_normalized, MotorSpeed, SPEED_NORMALIZED_MAX};
+use crate::sequence::SEQUENCE_MAX_STEPS;
+use crate::spinup::{SpinUpState, SpinUpTest};
+
+/// Output report (speed) and input report (status)
+pub const SPEED_REPORT_ID: u8 = 1;
+/// Feature report: runtime config
+pub const CONFIG_REPORT_ID: u8 = 2;
+/// Feature report: emergency stop latch
+pub const ESTOP_REPORT_ID: u8 = 3;
+/// Feature report (read-only): axis and firmware version
+pub const DEVICE_INFO_REPORT_ID: u8 = 4;
+/// Feature report: host override of the strap-detected axis
+pub const AXIS_OVERRIDE_REPORT_ID: u8 = 5;
+/// Feature report: config storage commands (reset to defaults)
+pub const CONFIG_COMMAND_REPORT_ID: u8 = 6;
+/// Input report: command path telemetry (received vs acted-upon speed)
+pub const TELEMETRY_REPORT_ID: u8 = 7;
+/// Feature report: clear latched faults
+pub const FAULT_CLEAR_REPORT_ID: u8 = 8;
+/// Output report: speed in whole percent
+pub const SPEED_PERCENT_REPORT_ID: u8 = 9;
+/// Feature report: start or abort a coast (spin-down) test
+pub const COAST_TEST_REPORT_ID: u8 = 10;
+/// Feature report (read-only): config in effect
+pub const EFFECTIVE_CONFIG_REPORT_ID: u8 = 11;
+/// Feature report: load (and read back) a time-stamped speed sequence
+pub const SEQUENCE_REPORT_ID: u8 = 12;
+/// Feature report: start or stop sequence playback
+pub const SEQUENCE_COMMAND_REPORT_ID: u8 = 13;
+/// Feature report: start or abort the encoder polarity calibration
+pub const ENCODER_CALIBRATION_REPORT_ID: u8 = 14;
+/// Feature report: start or abort the minimum duty calibration
+pub const MIN_DUTY_CALIBRATION_REPORT_ID: u8 = 15;
+/// Feature report (read-only): outcome of the last config write
+pub const CONFIG_STATUS_REPORT_ID: u8 = 16;
+/// Feature report (read-only): length and CRC of the HID report descriptor
+pub const DESCRIPTOR_INFO_REPORT_ID: u8 = 17;
+/// Feature report: start or abort a spin-up (time to target) test
+pub const SPIN_UP_TEST_REPORT_ID: u8 = 18;
+
+/// Layout of `RWConfigReport`; bump whenever it changes
+pub const CONFIG_LAYOUT_VERSION: u8 = 21;
+
+/// Output report from host (normalized speed)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct OutputReport {
+    pub speed_normalized: I16,  // Normalized speed: -32767 to +32767 (-100% to +100%)
+    pub checksum: u8,           // CRC-8/SMBUS of the two speed bytes
+}
+
+/// Why an output report was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum OutputReportError {
+    /// Empty, or too short for its report
+    Malformed,
+    /// Another report's ID (e.g. a config report written to the OUT endpoint)
+    /// or a bare payload without one
+    UnknownReportId(u8),
+    /// Checksum mismatch (corrupted in transit)
+    Checksum { expected: u8, received: u8 },
+}
+
+impl OutputReport {
+    /// Report for `speed_normalized` with its checksum filled in
+    pub fn new(speed_normalized: i16) -> Self {
+        let speed_normalized = I16::new(speed_normalized);
+        Self {
+            speed_normalized,
+            checksum: crc8(speed_normalized.as_bytes()),
+        }
+    }
+
+    /// Output report as written by the host (report ID + payload)
+    pub fn to_report(&self) -> [u8; 1 + size_of::<OutputReport>()] {
+        let mut report = [SPEED_REPORT_ID; 1 + size_of::<OutputReport>()];
+        report[1..].copy_from_slice(self.as_bytes());
+        report
+    }
+
+    /// Parse an output report: `SPEED_REPORT_ID` followed by the payload
+    ///
+    /// Trailing bytes (hosts padding to the endpoint size) are ignored.
+    pub fn parse(data: &[u8]) -> Result<Self, OutputReportError> {
+        let report = match data {
+            [SPEED_REPORT_ID, payload @ ..] => Self::read_from_prefix(payload)
+                .map(|(report, _)| report)
+                .map_err(|_| OutputReportError::Malformed)?,
+            [id, ..] => return Err(OutputReportError::UnknownReportId(*id)),
+            [] => return Err(OutputReportError::Malformed),
+        };
+
+        let expected = crc8(report.speed_normalized.as_bytes());
+        if report.checksum != expected {
+            return Err(OutputReportError::Checksum { expected, received: report.checksum });
+        }
+        Ok(report)
+    }
+
+    /// Parse either speed command: `SPEED_REPORT_ID` or `SPEED_PERCENT_REPORT_ID`
+    ///
+    /// Routed on the leading report ID byte; any other ID is rejected before
+    /// the payload is looked at, so no other report can pass for a speed
+    /// command. A percent command is returned converted with
+    /// `percent_to_normalized`, so both drive the same path. Neither has
+    /// priority: each is a complete command and the last one received wins.
+    pub fn parse_command(data: &[u8]) -> Result<Self, OutputReportError> {
+        match data {
+            [SPEED_REPORT_ID, ..] => Self::parse(data),
+            [SPEED_PERCENT_REPORT_ID, ..] => {
+                OutputReportPercent::parse(data).map(|report| Self::new(report.speed_normalized()))
+            }
+            [id, ..] => Err(OutputReportError::UnknownReportId(*id)),
+            [] => Err(OutputReportError::Malformed),
+        }
+    }
+
+    /// Speed exactly as sent, before clamping
+    pub fn raw_speed_normalized(&self) -> i16 {
+        self.speed_normalized.get()
+    }
+
+    /// Normalized speed clamped to the symmetric range -32767..=32767.
+    /// A naive host may map -100% to i16::MIN (-32768), which has no positive counterpart.
+    pub fn speed_normalized(&self) -> i16 {
+        self.speed_normalized.get().clamp(-SPEED_NORMALIZED_MAX, SPEED_NORMALIZED_MAX)
+    }
+}
+
+/// Output report from host (speed in percent, for hosts that do not scale to normalized)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct OutputReportPercent {
+    pub percent: i8,   // Speed: -100 to +100 (%), saturated outside that range
+    pub checksum: u8,  // CRC-8/SMBUS of the percent byte
+}
+
+impl OutputReportPercent {
+    /// Report for `percent` with its checksum filled in
+    pub fn new(percent: i8) -> Self {
+        Self {
+            percent,
+            checksum: crc8(percent.as_bytes()),
+        }
+    }
+
+    /// Output report as written by the host (report ID + payload)
+    pub fn to_report(&self) -> [u8; 1 + size_of::<OutputReportPercent>()] {
+        let mut report = [SPEED_PERCENT_REPORT_ID; 1 + size_of::<OutputReportPercent>()];
+        report[1..].copy_from_slice(self.as_bytes());
+        report
+    }
+
+    /// Parse an output report: `SPEED_PERCENT_REPORT_ID` followed by the payload
+    ///
+    /// Trailing bytes are ignored, as for `OutputReport::parse`.
+    pub fn parse(data: &[u8]) -> Result<Self, OutputReportError> {
+        let report = match data {
+            [SPEED_PERCENT_REPORT_ID, payload @ ..] => Self::read_from_prefix(payload)
+                .map(|(report, _)| report)
+                .map_err(|_| OutputReportError::Malformed)?,
+            [id, ..] => return Err(OutputReportError::UnknownReportId(*id)),
+            [] => return Err(OutputReportError::Malformed),
+        };
+
+        let expected = crc8(report.percent.as_bytes());
+        if report.checksum != expected {
+            return Err(OutputReportError::Checksum { expected, received: report.checksum });
+        }
+        Ok(report)
+    }
+
+    /// Normalized speed (`percent_to_normalized`, saturated to ±100%)
+    pub fn speed_normalized(&self) -> i16 {
+        percent_to_normalized(self.percent)
+    }
+}
+
+/// Input report to host (currently applied state and telemetry)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWStatusReport {
+    pub speed_normalized: I16,  // Applied normalized speed: -32767 to +32767
+    pub duty: u8,               // Driven duty cycle (0-100%), after kickstart and throttling
+    pub flags: u8,              // bit0: forward, bit1: closed loop, bit2: overcurrent fault, bit3: thermal throttling, bit4: e-stop latched, bit5: coast test
+    pub rpm: I16,               // Measured wheel speed (signed RPM, from encoder)
+    pub temperature: I16,       // Chip temperature (0.1°C)
+    pub heartbeat: U32,         // Control tick counter (wraps)
+    pub uptime_ms: U32,         // Milliseconds since boot (wraps after ~49.7 days)
+    pub faults: U16,            // Latched `fault::Fault` bits, until cleared via `RWFaultClearReport`
+}
+
+impl RWStatusReport {
+    pub const FLAG_FORWARD: u8 = 1 << 0;
+    pub const FLAG_CLOSED_LOOP: u8 = 1 << 1;
+    pub const FLAG_OVERCURRENT: u8 = 1 << 2;
+    pub const FLAG_THROTTLING: u8 = 1 << 3;
+    pub const FLAG_ESTOP: u8 = 1 << 4;
+    /// Coast test running: drive cut, `rpm` is the spin-down
+    pub const FLAG_COAST_TEST: u8 = 1 << 5;
+    /// Uploaded speed sequence playing (`sequence::SequencePlayer`)
+    pub const FLAG_SEQUENCE: u8 = 1 << 6;
+    /// Encoder polarity calibration driving the wheel (`polarity::PolarityCalibration`)
+    pub const FLAG_ENCODER_CALIBRATION: u8 = 1 << 7;
+
+    /// `flags` carries the state bits; `FLAG_FORWARD` is derived from `speed`
+    pub fn new(speed: MotorSpeed, output: MotorOutput, rpm: i32, temperature: i16, flags: u8) -> Self {
+        let forward = if speed.speed_normalized >= 0 { Self::FLAG_FORWARD } else { 0 };
+        Self {
+            speed_normalized: I16::new(speed.speed_normalized),
+            duty: output.duty(),
+            flags: (flags & !Self::FLAG_FORWARD) | forward,
+            rpm: I16::new(rpm.clamp(i16::MIN as i32, i16::MAX as i32) as i16),
+            temperature: I16::new(temperature),
+            heartbeat: U32::new(0),
+            uptime_ms: U32::new(0),
+            faults: U16::new(0),
+        }
+    }
+
+    /// Set the latched faults
+    pub fn with_faults(mut self, faults: Fault) -> Self {
+        self.faults = U16::new(faults.bits());
+        self
+    }
+
+    /// Set the liveness counters
+    ///
+    /// Both wrap; the host should compare them with wrapping arithmetic. The
+    /// uptime going backwards (other than at the wrap) means the device reset,
+    /// a heartbeat that stops advancing means the main loop or polling stalled.
+    pub fn with_liveness(mut self, heartbeat: u32, uptime_ms: u32) -> Self {
+        self.heartbeat = U32::new(heartbeat);
+        self.uptime_ms = U32::new(uptime_ms);
+        self
+    }
+
+    /// Input report as pushed on the interrupt IN endpoint (report ID + payload)
+    pub fn to_report(&self) -> [u8; 1 + size_of::<RWStatusReport>()] {
+        let mut report = [SPEED_REPORT_ID; 1 + size_of::<RWStatusReport>()];
+        report[1..].copy_from_slice(self.as_bytes());
+        report
+    }
+}
+
+/// Input report to host (command path telemetry)
+///
+/// Shows where a speed command was modified between the wire and the driver:
+/// `received` -> clamping and speed cap -> `target` -> slew limiting and deadband -> `effective`.
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWTelemetryReport {
+    pub received: I16,   // Last host speed_normalized exactly as received (-32768..=32767)
+    pub target: I16,     // Controller target after clamping and the speed cap (self-test steps and failsafe stops included)
+    pub effective: I16,  // Speed acted upon: after slew limiting, 0 inside the deadband
+    pub flags: u8,       // bit0: clamped, bit1: deadbanded, bit2: kickstarting, bit3: throttled, bit4: slewing, bit5: speed capped, bit6: reversal braking, bit7: driver enabled
+    pub pwm_errors: U16, // PWM duty-set calls that failed since boot (saturates)
+    pub encoder_reversed: u8, // 1 = encoder decoded with A and B swapped (`Config::encoder_reversed`)
+    pub min_duty: u8,         // `Config::min_duty` in effect (0-100%), updated by the minimum duty calibration
+    pub min_duty_ramp: u8,    // Ramp duty (0-100%) while the minimum duty calibration runs, `MIN_DUTY_RAMP_IDLE` otherwise
+    pub loop_max_us: U16,     // Longest main loop iteration since the previous telemetry report (us, saturates)
+    pub supply_mv: U16,       // Motor supply (VSYS) voltage of the latest sample (mV)
+    pub control_mode: u8,     // Mode in effect (as `RWConfigReport::control_mode`): open loop after a missing-encoder fallback
+    pub spin_up_state: u8,    // `spinup::SpinUpState` of the last spin-up test
+    pub spin_up_ms: U16,      // Time to target of the last spin-up test (ms), `SPIN_UP_NONE` unless it got there
+    pub pwm_top: U16,         // Hardware TOP of the PWM slice for `Config::pwm_freq_hz` (`pwm::PwmTiming`)
+    pub pwm_div_int: u8,      // Integer clock divider of the PWM slice; the period is `div_int * (top + 1)` system clocks
+    pub min_duty_effective: u8, // Duty floor applied (0-100%): `min_duty` raised to the driver's minimum pulse (`Config::effective_min_duty`)
+    pub duty_saturated: u8,   // 1 = the scaled `effective` speed asks for more than 100% duty (`MotorSpeed::is_saturated`)
+    pub current_limited: u8,  // 1 = duty cut by the soft current limit (`Config::current_limit_ma`)
+    pub idle_asleep: u8,      // 1 = driver asleep for idleness (`Config::idle_sleep_ms`) until the next host report
+}
+
+impl RWTelemetryReport {
+    /// `received` was outside -32767..=32767
+    pub const FLAG_CLAMPED: u8 = 1 << 0;
+    /// The slewed speed is non-zero but inside the deadband, so the motor is stopped
+    pub const FLAG_DEADBANDED: u8 = 1 << 1;
+    /// Kickstart pulse running (duty is `kickstart_duty`)
+    pub const FLAG_KICKSTARTING: u8 = 1 << 2;
+    /// Duty cut by the thermal limit
+    pub const FLAG_THROTTLED: u8 = 1 << 3;
+    /// `effective` still ramping (in closed-loop mode, toward the PID drive)
+    pub const FLAG_SLEWING: u8 = 1 << 4;
+    /// Target magnitude cut to `max_speed_normalized`
+    pub const FLAG_SPEED_LIMITED: u8 = 1 << 5;
+    /// Braking before a direction change (see `Config::reversal_brake_ms`)
+    pub const FLAG_REVERSAL_BRAKING: u8 = 1 << 6;
+    /// Driver nSLEEP high; clear means the driver is disabled, not just commanded to stop
+    pub const FLAG_DRIVER_ENABLED: u8 = 1 << 7;
+
+    /// `min_duty_ramp` while no minimum duty calibration runs
+    pub const MIN_DUTY_RAMP_IDLE: u8 = 0xFF;
+
+    /// `spin_up_ms` without a time to report
+    pub const SPIN_UP_NONE: u16 = 0xFFFF;
+
+    /// Telemetry after a control tick, for the last host command `received`
+    pub fn new(received: i16, controller: &MotorController, config: &Config) -> Self {
+        let applied = controller.applied().speed_normalized;
+        let deadbanded = applied != 0 && applied.unsigned_abs() < config.deadband;
+        let effective = if deadbanded { 0 } else { applied };
+
+        let mut flags = 0;
+        if received.unsigned_abs() > SPEED_NORMALIZED_MAX as u16 {
+            flags |= Self::FLAG_CLAMPED;
+        }
+        if deadbanded {
+            flags |= Self::FLAG_DEADBANDED;
+        }
+        if controller.is_kickstarting() {
+            flags |= Self::FLAG_KICKSTARTING;
+        }
+        if controller.is_duty_limited() {
+            flags |= Self::FLAG_THROTTLED;
+        }
+        if controller.is_slewing() {
+            flags |= Self::FLAG_SLEWING;
+        }
+        if controller.is_reversal_braking() {
+            flags |= Self::FLAG_REVERSAL_BRAKING;
+        }
+        let target = controller.limited_target(config);
+        if target != controller.target() {
+            flags |= Self::FLAG_SPEED_LIMITED;
+        }
+
+        Self {
+            received: I16::new(received),
+            target: I16::new(target.speed_normalized),
+            effective: I16::new(effective),
+            flags,
+            pwm_errors: U16::new(0),
+            encoder_reversed: config.encoder_reversed as u8,
+            min_duty: config.min_duty,
+            min_duty_ramp: Self::MIN_DUTY_RAMP_IDLE,
+            loop_max_us: U16::new(0),
+            supply_mv: U16::new(0),
+            control_mode: controller.control_mode(config) as u8,
+            spin_up_state: SpinUpState::Idle as u8,
+            spin_up_ms: U16::new(Self::SPIN_UP_NONE),
+            pwm_top: U16::new(config.pwm_timing().top),
+            pwm_div_int: config.pwm_timing().div_int,
+            min_duty_effective: config.effective_min_duty(),
+            duty_saturated: MotorSpeed::new(effective).is_saturated(config) as u8,
+            current_limited: controller.is_current_limited() as u8,
+            idle_asleep: 0,
+        }
+    }
+
+    /// Set the state and result of the spin-up test
+    pub fn with_spin_up(mut self, test: &SpinUpTest) -> Self {
+        self.spin_up_state = test.state() as u8;
+        let elapsed_ms = test.elapsed_ms().map_or(Self::SPIN_UP_NONE, |ms| ms.min(Self::SPIN_UP_NONE as u32 - 1) as u16);
+        self.spin_up_ms = U16::new(elapsed_ms);
+        self
+    }
+
+    /// Set the measured supply voltage
+    pub fn with_supply_mv(mut self, supply_mv: u16) -> Self {
+        self.supply_mv = U16::new(supply_mv);
+        self
+    }
+
+    /// Set the longest main loop iteration of the reporting window
+    pub fn with_loop_max_us(mut self, max_us: u32) -> Self {
+        self.loop_max_us = U16::new(max_us.min(u16::MAX as u32) as u16);
+        self
+    }
+
+    /// Set the ramp duty of a running minimum duty calibration (None while none runs)
+    pub fn with_min_duty_ramp(mut self, duty: Option<u8>) -> Self {
+        self.min_duty_ramp = duty.unwrap_or(Self::MIN_DUTY_RAMP_IDLE);
+        self
+    }
+
+    /// Set the PWM duty-set failure count
+    pub fn with_pwm_errors(mut self, errors: u16) -> Self {
+        self.pwm_errors = U16::new(errors);
+        self
+    }
+
+    /// Set the driver enable (nSLEEP) level
+    pub fn with_driver_enabled(mut self, enabled: bool) -> Self {
+        if enabled {
+            self.flags |= Self::FLAG_DRIVER_ENABLED;
+        } else {
+            self.flags &= !Self::FLAG_DRIVER_ENABLED;
+        }
+        self
+    }
+
+    /// Set whether the driver is asleep for idleness
+    pub fn with_idle_asleep(mut self, asleep: bool) -> Self {
+        self.idle_asleep = asleep as u8;
+        self
+    }
+
+    /// Input report as pushed on the interrupt IN endpoint (report ID + payload)
+    pub fn to_report(&self) -> [u8; 1 + size_of::<RWTelemetryReport>()] {
+        let mut report = [TELEMETRY_REPORT_ID; 1 + size_of::<RWTelemetryReport>()];
+        report[1..].copy_from_slice(self.as_bytes());
+        report
+    }
+}
+
+/// Feature report from host (runtime config)
+/// All fields are written at once; the host must send the complete report.
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWConfigReport {
+    pub stop_mode: u8,       // 0 = coast, 1 = brake
+    pub min_duty: u8,        // Minimum duty when spinning (0-100%)
+    pub kickstart_duty: u8,  // Kickstart duty (0-100%)
+    pub kickstart_ms: U16,   // Kickstart duration (ms, little-endian)
+    pub slew_step: U16,      // Max speed change per control tick (normalized units, 0 = no limit)
+    pub control_mode: u8,    // 0 = open loop, 1 = closed loop (PID on encoder RPM)
+    pub max_current_ma: U16, // Overcurrent threshold (mA, 0 = disabled)
+    pub throttle_start_c: u8, // Thermal throttling starts above this temperature (°C)
+    pub throttle_limit_c: u8, // Duty reaches zero at this temperature (°C, must be above start)
+    pub curve: u8,           // Speed curve: 0 = linear, 1 = sqrt
+    pub fwd_scale: U16,      // Forward magnitude scale (1/1000, 0-2000)
+    pub rev_scale: U16,      // Reverse magnitude scale (1/1000, 0-2000)
+    pub deadband: U16,       // Stop below this normalized magnitude (0 = disabled)
+    pub stop_ramp_step: U16, // Ramp rate toward stop per control tick (normalized units, 0 = use slew_step)
+    pub pwm_freq_hz: U16,    // PWM frequency (Hz, 1000-40000)
+    pub ramp_profile: u8,    // 0 = linear (slew_step), 1 = exponential (ramp_tau_ms, capped at slew_step)
+    pub ramp_tau_ms: U16,    // Exponential ramp time constant (ms, 0 = no ramp)
+    pub max_speed_normalized: U16, // Speed magnitude cap (normalized units, 1-32767, 32767 = no cap)
+    pub stall_window_ms: U16, // Stall detection window (ms, 0 = disabled)
+    pub stall_rpm: U16,      // A driven wheel below this RPM counts as stalled
+    pub stall_retry: u8,     // 1 = retry once with a stronger kickstart, 0 = cut the drive at once
+    pub kickstart_rpm: U16,  // End the kickstart early at this RPM (0 = timed pulse only)
+    pub reversal_kick_threshold: U16, // Reversals below this magnitude on both sides skip the kickstart (0 = always kick)
+    pub reversal_brake_ms: U16, // Brake before a kicked reversal (ms, 0 = disabled, needs stop_mode = brake)
+    pub reversal_brake_rpm: U16, // End the reversal brake early below this RPM (0 = timed only)
+    pub telemetry_period_ms: U16, // Input report push period (ms, 0 = never, 0xFFFF = on change)
+    pub dither_amplitude: u8, // Low-speed duty dither (±%, 0-50, 0 = off)
+    pub dither_freq_hz: u8,   // Dither square wave frequency (Hz, 1-255)
+    pub dither_max_speed: U16, // Dither only below this applied magnitude (normalized units)
+    pub min_dwell_ms: U16,     // Minimum time between drive start and stop (ms, 0 = off)
+    pub log_interval_ms: U16,  // Minimum time between repeated hot-path log lines (ms, 0 = log all)
+    pub encoder_reversed: u8,  // 1 = decode the encoder with A and B swapped, 0 = as wired
+    pub direction_hysteresis: U16, // Targets past zero by at most this keep the direction (normalized units, 0 = off)
+    pub max_rpm: U16,              // Closed-loop RPM of a full-scale target (0 = the axis's wheel default)
+    pub min_supply_mv: U16,        // Undervoltage threshold: stop the motor below this supply (mV, 0 = disabled)
+    pub kickstart_current_ma: U16, // End the kickstart once the inrush current has decayed below this (mA, 0 = timed pulse only)
+    pub feedback_timeout_ms: U16,  // Closed loop falls back to open loop after this long driven without encoder counts (ms, 0 = never)
+    pub overcurrent_response: u8,  // Drive response while the fault lasts: 0 = coast, 1 = brake, 2 = disable the driver
+    pub over_temperature_response: u8, // Same, once thermal throttling has reached the limit
+    pub undervoltage_response: u8, // Same, for undervoltage
+    pub stall_response: u8,        // Same, for a latched stall
+    pub current_limit_ma: U16,     // Soft current limit: duty pulled down to hold the current here (mA, 0 = hard cutoff only)
+    pub current_limit_gain: U16,   // Soft current limit gain (1/1000 PWM count of duty per tick per mA of error)
+    pub idle_sleep_ms: U16,        // Driver to sleep (nSLEEP low) after this long stopped without host reports (ms, 0 = never)
+}
+
+/// Feature report to host (read-only, GET_REPORT): config in effect
+///
+/// What the firmware applied after validation, for confirming a write or
+/// rebuilding host state after reconnecting. A host should not interpret
+/// `config` when `layout_version` is not the `CONFIG_LAYOUT_VERSION` it knows.
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWEffectiveConfigReport {
+    pub layout_version: u8,      // `CONFIG_LAYOUT_VERSION` of the firmware
+    pub inverted: u8,            // 1 = positive commands spin this axis backwards (mounting), 0 = normal
+    pub config: RWConfigReport,  // Same layout as the config feature report
+}
+
+impl RWEffectiveConfigReport {
+    pub fn new(config: &Config, inverted: bool) -> Self {
+        Self {
+            layout_version: CONFIG_LAYOUT_VERSION,
+            inverted: inverted as u8,
+            config: config.to_report(),
+        }
+    }
+}
+
+/// Feature report to host (read-only, GET_REPORT): outcome of the last config write (ID 2)
+///
+/// A config report is applied whole or not at all; after a rejection the
+/// previous config stays in effect and this says which field was refused.
+/// `writes` changes with every config report, so a host can tell the outcome
+/// of its own write from an older one.
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWConfigStatusReport {
+    pub writes: u8, // Config reports received since boot (wraps)
+    pub result: u8, // `RESULT_*`
+    pub field: u8,  // Rejected field: byte offset in `RWConfigReport`, `FIELD_NONE` otherwise
+    pub value: U16, // Rejected value, or the payload length of a wrong-length report; 0 otherwise
+}
+
+impl RWConfigStatusReport {
+    /// No config report received since boot
+    pub const RESULT_NONE: u8 = 0;
+    pub const RESULT_ACCEPTED: u8 = 1;
+    /// A field failed validation (`field`, `value`)
+    pub const RESULT_INVALID_VALUE: u8 = 2;
+    /// Payload length is not that of `RWConfigReport`
+    pub const RESULT_INVALID_LENGTH: u8 = 3;
+
+    /// `field` when no field was rejected
+    pub const FIELD_NONE: u8 = 0xFF;
+
+    /// Status before the first config write
+    pub fn new() -> Self {
+        Self::with_result(0, Self::RESULT_NONE, Self::FIELD_NONE, 0)
+    }
+
+    pub fn accepted(writes: u8) -> Self {
+        Self::with_result(writes, Self::RESULT_ACCEPTED, Self::FIELD_NONE, 0)
+    }
+
+    pub fn rejected(writes: u8, error: &ConfigError) -> Self {
+        Self::with_result(writes, Self::RESULT_INVALID_VALUE, error.offset() as u8, error.value())
+    }
+
+    /// Rejected for a payload of `len` bytes
+    pub fn invalid_length(writes: u8, len: usize) -> Self {
+        let len = len.min(u16::MAX as usize) as u16;
+        Self::with_result(writes, Self::RESULT_INVALID_LENGTH, Self::FIELD_NONE, len)
+    }
+
+    fn with_result(writes: u8, result: u8, field: u8, value: u16) -> Self {
+        Self { writes, result, field, value: U16::new(value) }
+    }
+}
+
+impl Default for RWConfigStatusReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Feature report from host (emergency stop)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWEStopReport {
+    pub command: u8,  // 1 = engage (latch driver disabled), 2 = engage ramped (brake ramped up first), 0 = release
+}
+
+/// Feature report to host (read-only, GET_REPORT): device identification
+///
+/// Lets the host map devices to axes and check compatibility without parsing
+/// the USB serial string.
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWDeviceInfoReport {
+    pub axis: u8,           // 0 = X, 1 = Y, 2 = Z, 3 = self-test (strap or host override)
+    pub version_major: u8,  // Firmware version
+    pub version_minor: u8,
+    pub version_patch: u8,
+    pub strap_levels: u8,   // Axis strap pin levels at boot (GPIO1 << 1 | GPIO0, 1 = HIGH), STRAP_NOT_READ on fixed-axis builds
+    pub strap_axis: u8,     // Axis the strap selected (or the build fixed), before any host override; as `axis`
+}
+
+impl RWDeviceInfoReport {
+    /// `strap_levels` when the firmware was built for a fixed axis and never read the pins
+    pub const STRAP_NOT_READ: u8 = 0xFF;
+}
+
+/// Feature report to host (read-only, GET_REPORT): fingerprint of the report descriptor
+///
+/// Lets a host tool check that the firmware describes its reports the way the
+/// tool expects (or spot stale firmware) without parsing the descriptor. The
+/// CRC is `crc::crc32` over the descriptor bytes as sent in the HID class
+/// descriptor of interface 0.
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWDescriptorInfoReport {
+    pub length: U16,  // Descriptor length in bytes
+    pub crc32: U32,   // CRC-32/ISO-HDLC of the descriptor
+}
+
+impl RWDescriptorInfoReport {
+    pub fn new(descriptor: &[u8]) -> Self {
+        Self {
+            length: U16::new(descriptor.len().min(u16::MAX as usize) as u16),
+            crc32: U32::new(crc32(descriptor)),
+        }
+    }
+}
+
+/// Feature report from host (axis override, kept until reset)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWAxisOverrideReport {
+    pub axis: u8,   // 0 = X, 1 = Y, 2 = Z
+    pub force: u8,  // 1 = also override a strap-detected axis, 0 = only when the strap selected self-test
+}
+
+/// Feature report from host (clear latched faults)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWFaultClearReport {
+    pub mask: U16,  // `fault::Fault` bits to clear (0xFFFF = all); active conditions stay set
+}
+
+/// Feature report from host (coast test)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWCoastTestReport {
+    pub command: u8,  // 1 = cut the drive and stream the spin-down, 0 = abort
+}
+
+/// Feature report from host (spin-up test)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWSpinUpTestReport {
+    pub command: u8,             // 1 = step to `speed_normalized` and time it, 0 = abort
+    pub speed_normalized: I16,   // Step target (-32767 to +32767, clamped like a speed command)
+}
+
+/// One setpoint of `RWSequenceReport`
+#[derive(Clone, Copy, FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWSequenceStepReport {
+    pub t_ms: U32,             // Time after the start of playback (ms, not before the previous step)
+    pub speed_normalized: I16, // Speed to command at that time, as in the output report
+}
+
+impl RWSequenceStepReport {
+    pub fn new(t_ms: u32, speed_normalized: i16) -> Self {
+        Self { t_ms: U32::new(t_ms), speed_normalized: I16::new(speed_normalized) }
+    }
+}
+
+/// Feature report from host (speed sequence), read back as loaded
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWSequenceReport {
+    pub count: u8, // Steps used (0-8); the rest of `steps` is ignored
+    pub steps: [RWSequenceStepReport; SEQUENCE_MAX_STEPS],
+}
+
+/// Feature report from host (sequence playback)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWSequenceCommandReport {
+    pub command: u8, // 1 = play from the first step, 0 = stop
+}
+
+/// Feature report from host (encoder polarity calibration)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWEncoderCalibrationReport {
+    pub command: u8, // 1 = spin forward and check the encoder direction, 0 = abort
+}
+
+/// Feature report from host (minimum duty calibration)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWMinDutyCalibrationReport {
+    pub command: u8, // 1 = ramp the duty up until the wheel turns and store it as min_duty, 0 = abort
+}
+
+/// Feature report from host (config storage command)
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct RWConfigCommandReport {
+    pub command: u8,  // 1 = reset to defaults (also clears the stored config)
+}
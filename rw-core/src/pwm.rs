@@ -0,0 +1,60 @@
+//! PWM frequency to slice divider/TOP
+
+/// System clock feeding the PWM slices
+pub const SYS_CLOCK_HZ: u32 = 125_000_000;
+
+/// Default PWM frequency
+pub const PWM_FREQ_HZ: u16 = 10_000;
+
+/// Accepted PWM frequency range (below is audible and rough, above wastes power in the driver)
+pub const PWM_FREQ_MIN_HZ: u16 = 1_000;
+pub const PWM_FREQ_MAX_HZ: u16 = 40_000;
+
+/// Shortest input pulse the DRV8833 turns into an output pulse: its propagation
+/// delay plus the output rise and fall times, with margin. At high PWM
+/// frequencies a small duty is shorter than this and the bridge barely switches.
+pub const DRIVER_MIN_PULSE_NS: u32 = 2_000;
+
+/// Largest usable TOP (0xFFFF would leave no room for a 100% compare value)
+const TOP_MAX: u32 = 0xFFFE;
+
+/// Integer clock divider and TOP for a PWM slice; the period is `div_int * (top + 1)` cycles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PwmTiming {
+    pub div_int: u8,
+    pub top: u16,
+}
+
+impl PwmTiming {
+    /// Timing for `PWM_FREQ_HZ`
+    pub const DEFAULT: Self = Self { div_int: 1, top: 12499 };
+
+    /// Timing for `freq_hz`, using the smallest divider (finest duty resolution)
+    ///
+    /// None outside `PWM_FREQ_MIN_HZ..=PWM_FREQ_MAX_HZ`.
+    pub fn for_frequency(freq_hz: u16) -> Option<Self> {
+        if !(PWM_FREQ_MIN_HZ..=PWM_FREQ_MAX_HZ).contains(&freq_hz) {
+            return None;
+        }
+        let cycles = SYS_CLOCK_HZ / freq_hz as u32;
+        let div_int = cycles.div_ceil(TOP_MAX + 1).max(1);
+        let top = cycles / div_int - 1;
+        Some(Self {
+            div_int: u8::try_from(div_int).ok()?,
+            top: top as u16,
+        })
+    }
+
+    /// Smallest duty (0-100%, rounded up) whose on-time is at least `DRIVER_MIN_PULSE_NS`
+    pub fn min_pulse_duty(&self) -> u8 {
+        let period_cycles = self.div_int as u64 * (self.top as u64 + 1);
+        let pulse_cycles = DRIVER_MIN_PULSE_NS as u64 * SYS_CLOCK_HZ as u64 / 1_000_000_000;
+        (pulse_cycles * 100).div_ceil(period_cycles).min(100) as u8
+    }
+
+    /// Frequency actually produced (integer division rounds it slightly)
+    pub fn frequency_hz(&self) -> u32 {
+        SYS_CLOCK_HZ / (self.div_int as u32 * (self.top as u32 + 1))
+    }
+}
@@ -0,0 +1,60 @@
+//! Standalone bench self-test sequence (no host needed)
+
+use crate::deadline::Deadline;
+use crate::motor::MotorSpeed;
+
+/// One step of the self-test: hold `speed` for `duration_ms`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfTestStep {
+    pub speed: MotorSpeed,
+    pub duration_ms: u32,
+}
+
+const fn step(speed_normalized: i16, duration_ms: u32) -> SelfTestStep {
+    SelfTestStep {
+        speed: MotorSpeed::new(speed_normalized),
+        duration_ms,
+    }
+}
+
+/// Forward and reverse at half and full speed, stopping in between so every
+/// direction change goes through the kickstart. Repeats from the start.
+pub const SELF_TEST_SEQUENCE: [SelfTestStep; 8] = [
+    step(16384, 2000),
+    step(0, 1000),
+    step(-16384, 2000),
+    step(0, 1000),
+    step(32767, 2000),
+    step(0, 1000),
+    step(-32767, 2000),
+    step(0, 2000),
+];
+
+/// Walks through `SELF_TEST_SEQUENCE` on the timer
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SelfTest {
+    index: usize,
+    step_deadline: Option<Deadline>,
+}
+
+impl SelfTest {
+    pub const fn new() -> Self {
+        Self {
+            index: 0,
+            step_deadline: None,
+        }
+    }
+
+    /// Returns the step index and step when a new step begins (the first call starts step 0)
+    pub fn update(&mut self, now_us: u64) -> Option<(usize, SelfTestStep)> {
+        match self.step_deadline {
+            Some(deadline) if !deadline.is_elapsed(now_us) => return None,
+            Some(_) => self.index = (self.index + 1) % SELF_TEST_SEQUENCE.len(),
+            None => {}
+        }
+
+        let step = SELF_TEST_SEQUENCE[self.index];
+        self.step_deadline = Some(Deadline::after_ms(now_us, step.duration_ms));
+        Some((self.index, step))
+    }
+}
@@ -0,0 +1,167 @@
+//! Time-stamped speed sequence played back against the firmware timer
+//!
+//! The host uploads a short list of (t_ms, speed) setpoints and starts
+//! playback; each setpoint is applied `t_ms` after the start as if the host
+//! had commanded it then, so recorded maneuvers don't pick up host timing
+//! jitter. A live speed command stops playback.
+
+use zerocopy::FromZeros;
+
+use crate::deadline::Deadline;
+use crate::motor::SPEED_NORMALIZED_MAX;
+use crate::protocol::{RWSequenceCommandReport, RWSequenceReport, RWSequenceStepReport};
+
+/// Setpoints one sequence can hold
+pub const SEQUENCE_MAX_STEPS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SequenceStep {
+    /// Time after the start of playback (ms)
+    pub t_ms: u32,
+    pub speed_normalized: i16,
+}
+
+/// Why a sequence report was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SequenceError {
+    /// Step count above `SEQUENCE_MAX_STEPS`
+    TooManySteps(u8),
+    /// Step at this index is timed before the previous one
+    OutOfOrder(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Sequence {
+    steps: [SequenceStep; SEQUENCE_MAX_STEPS],
+    len: u8,
+}
+
+impl Sequence {
+    pub const fn new() -> Self {
+        Self {
+            steps: [SequenceStep { t_ms: 0, speed_normalized: 0 }; SEQUENCE_MAX_STEPS],
+            len: 0,
+        }
+    }
+
+    pub fn steps(&self) -> &[SequenceStep] {
+        &self.steps[..self.len as usize]
+    }
+
+    /// Validate a sequence report (speeds clamped to ±32767 like live commands)
+    pub fn from_report(report: &RWSequenceReport) -> Result<Self, SequenceError> {
+        if report.count as usize > SEQUENCE_MAX_STEPS {
+            return Err(SequenceError::TooManySteps(report.count));
+        }
+        let mut sequence = Self::new();
+        for (index, step) in report.steps[..report.count as usize].iter().enumerate() {
+            let step = SequenceStep {
+                t_ms: step.t_ms.get(),
+                speed_normalized: step.speed_normalized.get().max(-SPEED_NORMALIZED_MAX),
+            };
+            if index > 0 && step.t_ms < sequence.steps[index - 1].t_ms {
+                return Err(SequenceError::OutOfOrder(index as u8));
+            }
+            sequence.steps[index] = step;
+        }
+        sequence.len = report.count;
+        Ok(sequence)
+    }
+
+    /// Sequence report carrying this sequence (inverse of `from_report`)
+    pub fn to_report(&self) -> RWSequenceReport {
+        let mut report = RWSequenceReport::new_zeroed();
+        report.count = self.len;
+        for (out, step) in report.steps.iter_mut().zip(self.steps()) {
+            *out = RWSequenceStepReport::new(step.t_ms, step.speed_normalized);
+        }
+        report
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SequenceCommand {
+    Stop,
+    Start,
+}
+
+impl SequenceCommand {
+    pub fn from_report(report: &RWSequenceCommandReport) -> Option<Self> {
+        match report.command {
+            0 => Some(SequenceCommand::Stop),
+            1 => Some(SequenceCommand::Start),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SequencePlayer {
+    sequence: Sequence,
+    // Timer timestamp (us) playback started, None while stopped
+    started_us: Option<u64>,
+    // Index of the next step to apply
+    next: usize,
+}
+
+impl SequencePlayer {
+    pub const fn new() -> Self {
+        Self {
+            sequence: Sequence::new(),
+            started_us: None,
+            next: 0,
+        }
+    }
+
+    pub fn sequence(&self) -> &Sequence {
+        &self.sequence
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.started_us.is_some()
+    }
+
+    /// Replace the sequence, stopping playback; true if it was playing
+    pub fn load(&mut self, sequence: Sequence) -> bool {
+        let was_playing = self.stop();
+        self.sequence = sequence;
+        was_playing
+    }
+
+    /// Play from the first step at `now_us` (restarts a running playback); false if the sequence is empty
+    pub fn start(&mut self, now_us: u64) -> bool {
+        if self.sequence.steps().is_empty() {
+            return false;
+        }
+        self.started_us = Some(now_us);
+        self.next = 0;
+        true
+    }
+
+    /// Stop playback; true if it was playing
+    pub fn stop(&mut self) -> bool {
+        self.started_us.take().is_some()
+    }
+
+    /// Advance one control tick; the speed to command now, if a step came due
+    ///
+    /// When several steps are due at once only the last one is returned.
+    /// Playback ends after the last step.
+    pub fn update(&mut self, now_us: u64) -> Option<i16> {
+        let started_us = self.started_us?;
+        let elapsed_ms = Deadline::at(started_us).elapsed_us(now_us) / 1000;
+        let steps = self.sequence.steps();
+        let mut speed = None;
+        while let Some(step) = steps.get(self.next).filter(|step| step.t_ms as u64 <= elapsed_ms) {
+            speed = Some(step.speed_normalized);
+            self.next += 1;
+        }
+        if self.next == steps.len() {
+            self.started_us = None;
+        }
+        speed
+    }
+}
@@ -0,0 +1,144 @@
+//! Spin-up test: time for a speed step to show at the wheel
+//!
+//! For tuning the ramp, kickstart and PID settings. Started by the host with
+//! a target speed, which is commanded as a step through the controller like a
+//! speed command. The test then times how long the encoder takes to cover
+//! `SPIN_UP_FRACTION_PERCENT` of the way from the RPM at the start to the RPM
+//! the target asks for (the closed-loop scaling, `MotorController::max_rpm`).
+//! A step down (or to a stop) is timed the same way.
+//!
+//! Only new RPM measurements are checked, so the time has the resolution of
+//! `encoder::RPM_WINDOW_MS` and includes the lag of the measurement window.
+
+use crate::deadline::Deadline;
+use crate::motor::{MotorSpeed, SPEED_NORMALIZED_MAX};
+use crate::protocol::RWSpinUpTestReport;
+
+/// Part of the step the wheel has to cover
+pub const SPIN_UP_FRACTION_PERCENT: i32 = 90;
+
+/// Give up on a wheel that never gets there
+pub const SPIN_UP_TIMEOUT_MS: u64 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpinUpCommand {
+    Abort,
+    Start(MotorSpeed),
+}
+
+impl SpinUpCommand {
+    pub fn from_report(report: &RWSpinUpTestReport) -> Option<Self> {
+        match report.command {
+            0 => Some(SpinUpCommand::Abort),
+            // i16::MIN is clamped like a speed command
+            1 => Some(SpinUpCommand::Start(MotorSpeed::new(report.speed_normalized.get().max(-SPEED_NORMALIZED_MAX)))),
+            _ => None,
+        }
+    }
+}
+
+/// How the last spin-up test finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SpinUpEnd {
+    /// The RPM covered the step this long after the start
+    Reached { elapsed_ms: u32 },
+    /// Not there after `SPIN_UP_TIMEOUT_MS`
+    TimedOut,
+}
+
+/// State reported in telemetry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum SpinUpState {
+    /// No result: never run, or aborted (also by another command) before it finished
+    Idle = 0,
+    Running = 1,
+    Reached = 2,
+    TimedOut = 3,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpinUpTest {
+    // Timer timestamp (us) of the step, None while no test runs
+    started_us: Option<u64>,
+    // RPM at the step and the RPM that ends the test
+    start_rpm: i32,
+    end_rpm: i32,
+    // Result of the last finished test, kept until the next start
+    end: Option<SpinUpEnd>,
+}
+
+impl SpinUpTest {
+    pub const fn new() -> Self {
+        Self {
+            started_us: None,
+            start_rpm: 0,
+            end_rpm: 0,
+            end: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started_us.is_some()
+    }
+
+    /// Time a step to `target` made at `now_us` with the wheel at `rpm`;
+    /// `max_rpm` is the RPM of a full-scale target. Restarts a running test
+    /// and drops the previous result.
+    pub fn start(&mut self, now_us: u64, target: MotorSpeed, rpm: i32, max_rpm: u16) {
+        let target_rpm = target.speed_normalized as i32 * max_rpm as i32 / SPEED_NORMALIZED_MAX as i32;
+        self.started_us = Some(now_us);
+        self.start_rpm = rpm;
+        self.end_rpm = rpm + (target_rpm - rpm) * SPIN_UP_FRACTION_PERCENT / 100;
+        self.end = None;
+    }
+
+    /// Stop a running test (leaving no result); true if one was running.
+    /// The result of a finished test is kept.
+    pub fn abort(&mut self) -> bool {
+        self.started_us.take().is_some()
+    }
+
+    /// Advance one control tick; `rpm` is the measurement taken this tick, if any
+    pub fn update(&mut self, now_us: u64, rpm: Option<i32>) -> Option<SpinUpEnd> {
+        let started_us = self.started_us?;
+        let elapsed_us = Deadline::at(started_us).elapsed_us(now_us);
+        let reached = rpm.is_some_and(|rpm| {
+            if self.end_rpm >= self.start_rpm {
+                rpm >= self.end_rpm
+            } else {
+                rpm <= self.end_rpm
+            }
+        });
+        let end = if reached {
+            SpinUpEnd::Reached { elapsed_ms: (elapsed_us / 1000) as u32 }
+        } else if elapsed_us >= SPIN_UP_TIMEOUT_MS * 1000 {
+            SpinUpEnd::TimedOut
+        } else {
+            return None;
+        };
+        self.started_us = None;
+        self.end = Some(end);
+        Some(end)
+    }
+
+    pub fn state(&self) -> SpinUpState {
+        match (self.started_us, self.end) {
+            (Some(_), _) => SpinUpState::Running,
+            (None, Some(SpinUpEnd::Reached { .. })) => SpinUpState::Reached,
+            (None, Some(SpinUpEnd::TimedOut)) => SpinUpState::TimedOut,
+            (None, None) => SpinUpState::Idle,
+        }
+    }
+
+    /// Time to target of the last test, None unless it got there
+    pub fn elapsed_ms(&self) -> Option<u32> {
+        match (self.started_us, self.end) {
+            (None, Some(SpinUpEnd::Reached { elapsed_ms })) => Some(elapsed_ms),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! Stalled wheel detection on encoder RPM
+//!
+//! A wheel is stalled when it is being driven but the measured speed stays
+//! below `Config::stall_rpm` for `Config::stall_window_ms`. The first stall of
+//! a command can be retried with a stronger kickstart; after that the drive is
+//! cut and the stall latches until the host commands stop.
+//!
+//! Without an encoder the measured RPM is always 0, so detection is disabled
+//! by default (`stall_window_ms` = 0).
+
+use crate::config::Config;
+use crate::deadline::Deadline;
+
+/// Default detection window in ms (0 = disabled)
+pub const STALL_WINDOW_MS: u16 = 0;
+
+/// Default RPM below which a driven wheel counts as not turning
+pub const STALL_RPM: u16 = 100;
+
+/// Outcome of a stall check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StallEvent {
+    /// Stalled for the first time since the last stop: cut the drive and kick again harder
+    Retry,
+    /// Still stalled (or retry disabled): drive cut until the host commands stop
+    Stalled,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StallMonitor {
+    // Timer timestamp (us) since which the wheel has been driven without turning
+    since_us: Option<u64>,
+    retried: bool,
+    stalled: bool,
+}
+
+impl StallMonitor {
+    pub const fn new() -> Self {
+        Self {
+            since_us: None,
+            retried: false,
+            stalled: false,
+        }
+    }
+
+    pub fn is_stalled(&self) -> bool {
+        self.stalled
+    }
+
+    /// Re-arm after a stop command, including the one retry
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Check one control tick; `duty` is the driven duty in PWM counts
+    pub fn update(&mut self, now_us: u64, duty: u16, rpm: i32, config: &Config) -> Option<StallEvent> {
+        if config.stall_window_ms == 0 {
+            *self = Self::new();
+            return None;
+        }
+        if self.stalled || duty == 0 || rpm.unsigned_abs() >= config.stall_rpm as u32 {
+            self.since_us = None;
+            return None;
+        }
+
+        let since_us = *self.since_us.get_or_insert(now_us);
+        if !Deadline::after_ms(since_us, config.stall_window_ms as u32).is_elapsed(now_us) {
+            return None;
+        }
+        self.since_us = None;
+        if config.stall_retry && !self.retried {
+            self.retried = true;
+            Some(StallEvent::Retry)
+        } else {
+            self.stalled = true;
+            Some(StallEvent::Stalled)
+        }
+    }
+}
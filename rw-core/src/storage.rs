@@ -0,0 +1,74 @@
+//! Flash record for the persisted runtime config
+
+use zerocopy::little_endian::U32;
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
+use crate::config::{Config, ConfigError};
+use crate::crc::crc8;
+use crate::protocol::{RWConfigReport, CONFIG_LAYOUT_VERSION};
+
+/// "RWCF" in flash byte order
+pub const STORED_CONFIG_MAGIC: u32 = u32::from_le_bytes(*b"RWCF");
+
+/// Follows `RWConfigReport` layout changes, so old records fall back to defaults
+pub const STORED_CONFIG_VERSION: u8 = CONFIG_LAYOUT_VERSION;
+
+/// Config as written to flash: header, the config report payload and a CRC-8 over both
+#[derive(FromBytes, IntoBytes, KnownLayout, Immutable, Unaligned)]
+#[repr(C)]
+pub struct StoredConfig {
+    pub magic: U32,
+    pub version: u8,
+    pub config: RWConfigReport,
+    pub crc: u8,  // CRC-8/SMBUS of all preceding bytes
+}
+
+/// Why a stored record was not used (the caller falls back to `Config::default()`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StoredConfigError {
+    /// Shorter than a record
+    Truncated,
+    /// No record (erased flash reads 0xFF)
+    Magic(u32),
+    /// Written by firmware with a different config layout
+    Version(u8),
+    /// Corrupted record
+    Checksum { expected: u8, received: u8 },
+    /// Record intact but the config fails validation
+    Config(ConfigError),
+}
+
+impl StoredConfig {
+    pub fn new(config: &Config) -> Self {
+        let mut record = Self {
+            magic: U32::new(STORED_CONFIG_MAGIC),
+            version: STORED_CONFIG_VERSION,
+            config: config.to_report(),
+            crc: 0,
+        };
+        record.crc = record.checksum();
+        record
+    }
+
+    /// Validate a record read from flash (trailing bytes are ignored)
+    pub fn load(data: &[u8]) -> Result<Config, StoredConfigError> {
+        let (record, _) = Self::read_from_prefix(data).map_err(|_| StoredConfigError::Truncated)?;
+        if record.magic.get() != STORED_CONFIG_MAGIC {
+            return Err(StoredConfigError::Magic(record.magic.get()));
+        }
+        if record.version != STORED_CONFIG_VERSION {
+            return Err(StoredConfigError::Version(record.version));
+        }
+        let expected = record.checksum();
+        if record.crc != expected {
+            return Err(StoredConfigError::Checksum { expected, received: record.crc });
+        }
+        Config::from_report(&record.config).map_err(StoredConfigError::Config)
+    }
+
+    fn checksum(&self) -> u8 {
+        let bytes = self.as_bytes();
+        crc8(&bytes[..bytes.len() - 1])
+    }
+}
@@ -0,0 +1,56 @@
+//! Axis strap pin sampling
+//!
+//! Strap values are in the pull-up convention of `axis::Axis::from_strap`: a
+//! bit is 1 for a pin left at its idle level (no strap fitted) and 0 for a pin
+//! strapped to the other rail. `StrapPolarity` maps the pin levels of either
+//! board wiring onto it, so an unstrapped board is X and both straps fitted
+//! select the self-test whichever way the pins are pulled.
+
+/// Wait after enabling the pulls before the first sample (long strap wires charge slowly)
+pub const STRAP_SETTLE_MS: u32 = 10;
+
+/// Number of strap readings taken at boot
+pub const STRAP_SAMPLES: usize = 8;
+
+/// Interval between strap readings
+pub const STRAP_SAMPLE_INTERVAL_MS: u32 = 1;
+
+/// Readings that must agree before a strap value is trusted (3/4 of `STRAP_SAMPLES`)
+pub const STRAP_MAJORITY: usize = STRAP_SAMPLES * 3 / 4;
+
+/// Most common 2-bit strap value in `samples`, or None if it has fewer than `STRAP_MAJORITY` votes
+pub fn strap_majority(samples: &[u8]) -> Option<u8> {
+    let mut votes = [0usize; 4];
+    for &sample in samples {
+        votes[(sample & 0b11) as usize] += 1;
+    }
+
+    let (value, &count) = votes.iter().enumerate().max_by_key(|&(_, count)| *count)?;
+    (count >= STRAP_MAJORITY).then_some(value as u8)
+}
+
+/// How the board biases the strap pins
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StrapPolarity {
+    /// Pulled up, strapped to GND (the original boards)
+    PullUp,
+    /// Pulled down, strapped to 3V3
+    PullDown,
+}
+
+impl StrapPolarity {
+    /// 2-bit strap value (`GPIO1 << 1 | GPIO0`) for the sampled pin levels
+    pub fn strap_bits(self, gpio0_high: bool, gpio1_high: bool) -> u8 {
+        let idle = |high: bool| (high == (self == StrapPolarity::PullUp)) as u8;
+        (idle(gpio1_high) << 1) | idle(gpio0_high)
+    }
+
+    /// Pin levels (`GPIO1 << 1 | GPIO0`, 1 = HIGH) that read as strap value `bits`
+    pub fn pin_levels(self, bits: u8) -> u8 {
+        match self {
+            StrapPolarity::PullUp => bits & 0b11,
+            StrapPolarity::PullDown => !bits & 0b11,
+        }
+    }
+}
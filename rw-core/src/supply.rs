@@ -0,0 +1,75 @@
+//! Supply voltage sensing and undervoltage protection
+//!
+//! The DRV8833 motor supply is the Pico's VSYS, which the board brings to
+//! GPIO29 (ADC3) through a 200k/100k divider. A bench supply sagging under
+//! load browns out the driver before the RP2040 notices, so the wheel is
+//! stopped below a configurable level instead of running erratically.
+
+use crate::current::{ADC_MAX, ADC_VREF_MV};
+use crate::deadline::Deadline;
+
+/// VSYS is divided by this before the ADC (R1 = 200k, R2 = 100k on the Pico)
+pub const SUPPLY_DIVIDER: u32 = 3;
+
+/// Default undervoltage threshold (overridable at runtime via `RWConfigReport`, 0 = disabled)
+pub const MIN_SUPPLY_MV: u16 = 4000;
+
+/// Supply must stay below the threshold this long before the fault trips
+pub const UNDERVOLTAGE_TRIP_MS: u32 = 10;
+
+/// The fault clears once the supply has stayed above `threshold + UNDERVOLTAGE_HYSTERESIS_MV`
+/// for `UNDERVOLTAGE_CLEAR_MS`
+pub const UNDERVOLTAGE_HYSTERESIS_MV: u16 = 200;
+pub const UNDERVOLTAGE_CLEAR_MS: u32 = 500;
+
+/// Convert a raw ADC reading of the divided supply to millivolts
+pub fn adc_to_supply_mv(raw: u16) -> u16 {
+    (raw as u32 * ADC_VREF_MV * SUPPLY_DIVIDER / ADC_MAX).min(u16::MAX as u32) as u16
+}
+
+/// Debounced undervoltage fault with hysteresis
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UndervoltageMonitor {
+    tripped: bool,
+    // Timer timestamp (us) since which the supply has been past the trip/clear level
+    since_us: Option<u64>,
+}
+
+impl UndervoltageMonitor {
+    pub const fn new() -> Self {
+        Self {
+            tripped: false,
+            since_us: None,
+        }
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped
+    }
+
+    /// Feed a supply sample; returns whether the fault is active
+    pub fn update(&mut self, now_us: u64, supply_mv: u16, min_supply_mv: u16) -> bool {
+        if min_supply_mv == 0 {
+            *self = Self::new();
+            return false;
+        }
+
+        let (past_level, hold_ms) = if self.tripped {
+            (supply_mv > min_supply_mv.saturating_add(UNDERVOLTAGE_HYSTERESIS_MV), UNDERVOLTAGE_CLEAR_MS)
+        } else {
+            (supply_mv < min_supply_mv, UNDERVOLTAGE_TRIP_MS)
+        };
+
+        if !past_level {
+            self.since_us = None;
+            return self.tripped;
+        }
+
+        let since_us = *self.since_us.get_or_insert(now_us);
+        if Deadline::after_ms(since_us, hold_ms).is_elapsed(now_us) {
+            self.tripped = !self.tripped;
+            self.since_us = None;
+        }
+        self.tripped
+    }
+}
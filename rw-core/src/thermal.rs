@@ -0,0 +1,62 @@
+//! Thermal throttling from the RP2040 internal temperature sensor (ADC4)
+
+use crate::config::Config;
+use crate::current::{ADC_MAX, ADC_VREF_MV};
+
+/// Temperature sampling period
+pub const TEMP_SAMPLE_MS: u32 = 100;
+
+/// Default throttling range (overridable at runtime via `RWConfigReport`):
+/// full duty up to `THROTTLE_START_C`, scaled linearly down to zero at `THROTTLE_LIMIT_C`
+pub const THROTTLE_START_C: u8 = 60;
+pub const THROTTLE_LIMIT_C: u8 = 80;
+
+/// Convert a raw temperature sensor reading to 0.1°C
+///
+/// RP2040 datasheet: T = 27 - (V - 0.706) / 0.001721
+pub fn adc_to_decicelsius(raw: u16) -> i16 {
+    let uv = raw as i64 * ADC_VREF_MV as i64 * 1000 / ADC_MAX as i64;
+    (270 - (uv - 706_000) * 10 / 1721) as i16
+}
+
+/// Maximum allowed duty (0-100%) at the given temperature (0.1°C)
+pub fn max_duty_for_temperature(decicelsius: i16, config: &Config) -> u8 {
+    let start = config.throttle_start_c as i32 * 10;
+    let limit = config.throttle_limit_c as i32 * 10;
+    let t = decicelsius as i32;
+    if t <= start {
+        100
+    } else if t >= limit {
+        0
+    } else {
+        (100 * (limit - t) / (limit - start)) as u8
+    }
+}
+
+/// Smoothed chip temperature (first-order IIR, 1/8 weight per sample)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TemperatureFilter {
+    decicelsius: Option<i16>,
+}
+
+impl TemperatureFilter {
+    pub const fn new() -> Self {
+        Self { decicelsius: None }
+    }
+
+    /// Feed a raw sensor reading; returns the filtered temperature (0.1°C)
+    pub fn update(&mut self, raw: u16) -> i16 {
+        let sample = adc_to_decicelsius(raw);
+        let filtered = match self.decicelsius {
+            None => sample,
+            Some(prev) => (prev as i32 + (sample as i32 - prev as i32) / 8) as i16,
+        };
+        self.decicelsius = Some(filtered);
+        filtered
+    }
+
+    /// Filtered temperature (0.1°C), None before the first sample
+    pub fn decicelsius(&self) -> Option<i16> {
+        self.decicelsius
+    }
+}
@@ -0,0 +1,57 @@
+//! USB remote wakeup pacing
+//!
+//! While the bus is suspended, a pending condition (an active fault, the wake
+//! button) asks the host to resume it. The device has to stay suspended for a
+//! while before signaling, and a host that ignored the request is asked again
+//! only after a retry interval.
+
+use crate::deadline::Deadline;
+
+/// Minimum bus idle time before remote wakeup signaling (USB 2.0, 7.1.7.7)
+pub const WAKEUP_MIN_SUSPEND_MS: u64 = 5;
+
+/// Interval between repeated wakeup requests while the condition stays pending
+pub const WAKEUP_RETRY_MS: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RemoteWakeup {
+    /// Timer timestamp (us) the bus was suspended, None while it is not
+    suspended_us: Option<u64>,
+    last_request_us: Option<u64>,
+}
+
+impl RemoteWakeup {
+    pub const fn new() -> Self {
+        Self {
+            suspended_us: None,
+            last_request_us: None,
+        }
+    }
+
+    pub fn suspended(&mut self, now_us: u64) {
+        self.suspended_us = Some(now_us);
+        self.last_request_us = None;
+    }
+
+    pub fn resumed(&mut self) {
+        self.suspended_us = None;
+    }
+
+    /// Whether to signal remote wakeup now; a true result counts as a request
+    pub fn should_request(&mut self, now_us: u64, pending: bool) -> bool {
+        let Some(suspended_us) = self.suspended_us else {
+            return false;
+        };
+        if !pending || !Deadline::after_us(suspended_us, WAKEUP_MIN_SUSPEND_MS * 1000).is_elapsed(now_us) {
+            return false;
+        }
+        if self
+            .last_request_us
+            .is_some_and(|last_us| !Deadline::after_us(last_us, WAKEUP_RETRY_MS * 1000).is_elapsed(now_us))
+        {
+            return false;
+        }
+        self.last_request_us = Some(now_us);
+        true
+    }
+}
@@ -0,0 +1,106 @@
+//! Software wheel model for exercising the control logic without hardware
+//!
+//! A DC motor driven at a duty fraction settles at that fraction of its
+//! no-load speed with a first-order time constant (rotor inertia against
+//! back-EMF). Bearing friction decelerates the wheel at a constant rate, and a
+//! stopped wheel only breaks loose once the duty exceeds its static friction.
+//! Brake shorts the windings and pulls the wheel toward 0 with the electrical
+//! time constant (a partial brake in proportion to its duty); coast leaves
+//! only friction.
+
+use crate::control::MotorOutput;
+use crate::encoder::ENCODER_COUNTS_PER_REV;
+use crate::motor::PWM_TOP;
+use crate::pid::MAX_RPM;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WheelParams {
+    /// Speed at 100% duty with no friction
+    pub no_load_rpm: f32,
+    /// Time constant of the speed response to a duty step (inertia), in ms
+    pub time_constant_ms: f32,
+    /// Constant deceleration of a turning wheel by bearing friction, in RPM/s
+    pub friction_rpm_per_s: f32,
+    /// Duty (0.0-1.0) a stopped wheel needs before it starts turning
+    pub breakaway_duty: f32,
+}
+
+impl Default for WheelParams {
+    /// A small brushed wheel matching `pid::MAX_RPM`: needs about 45% duty to start
+    fn default() -> Self {
+        Self {
+            no_load_rpm: MAX_RPM as f32,
+            time_constant_ms: 200.0,
+            friction_rpm_per_s: 300.0,
+            breakaway_duty: 0.45,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WheelModel {
+    params: WheelParams,
+    rpm: f32,
+    // Encoder position in (fractional) counts
+    position: f32,
+}
+
+impl WheelModel {
+    pub const fn new(params: WheelParams) -> Self {
+        Self {
+            params,
+            rpm: 0.0,
+            position: 0.0,
+        }
+    }
+
+    pub fn rpm(&self) -> f32 {
+        self.rpm
+    }
+
+    /// Quadrature count an encoder on the wheel would read (for `encoder::RpmEstimator`)
+    pub fn encoder_count(&self) -> i32 {
+        self.position as i32
+    }
+
+    /// Speed the wheel settles at when driven at `duty` (-1.0..=1.0, negative = reverse)
+    pub fn steady_state_rpm(&self, duty: f32) -> f32 {
+        duty * self.params.no_load_rpm
+    }
+
+    /// Advance by `dt_us` with the driver at `output`
+    pub fn step(&mut self, output: MotorOutput, dt_us: u64) {
+        let dt_s = dt_us as f32 / 1_000_000.0;
+        let duty = match output {
+            MotorOutput::Forward(counts) => counts as f32 / PWM_TOP as f32,
+            MotorOutput::Reverse(counts) => -(counts as f32) / PWM_TOP as f32,
+            MotorOutput::Coast | MotorOutput::Brake | MotorOutput::PartialBrake(_) => 0.0,
+        };
+        let magnitude = if duty < 0.0 { -duty } else { duty };
+        if self.rpm == 0.0 && magnitude <= self.params.breakaway_duty {
+            return;
+        }
+
+        // Back-EMF pulls toward the driven speed; coasting windings carry no current
+        let electrical = (self.steady_state_rpm(duty) - self.rpm) * dt_s * 1000.0 / self.params.time_constant_ms;
+        let electrical = match output {
+            MotorOutput::Coast => 0.0,
+            // Shorted for part of the period only
+            MotorOutput::PartialBrake(counts) => electrical * counts as f32 / PWM_TOP as f32,
+            _ => electrical,
+        };
+        let friction = self.params.friction_rpm_per_s * dt_s;
+        let next = self.rpm + electrical;
+        self.rpm = if next > friction {
+            next - friction
+        } else if next < -friction {
+            next + friction
+        } else if magnitude > self.params.breakaway_duty {
+            // Driven hard enough to keep turning through zero
+            next
+        } else {
+            0.0
+        };
+        self.position += self.rpm / 60.0 * ENCODER_COUNTS_PER_REV as f32 * dt_s;
+    }
+}
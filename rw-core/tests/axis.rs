@@ -0,0 +1,49 @@
+use rw_core::axis::{apply_axis_override, Axis, AxisOverrideError, WHEEL_SPECS};
+use rw_core::protocol::RWAxisOverrideReport;
+
+fn request(axis: u8, force: u8) -> RWAxisOverrideReport {
+    RWAxisOverrideReport { axis, force }
+}
+
+#[test]
+fn strap_values_map_to_axes() {
+    assert_eq!(Axis::from_strap(0b11), Axis::X);
+    assert_eq!(Axis::from_strap(0b10), Axis::Y);
+    assert_eq!(Axis::from_strap(0b01), Axis::Z);
+    assert_eq!(Axis::from_strap(0b00), Axis::Test);
+    assert_eq!(Axis::Test.serial(), "RW-TEST");
+}
+
+#[test]
+fn wheel_specs_per_axis() {
+    assert_eq!(Axis::X.wheel_spec(), WHEEL_SPECS[0]);
+    assert_eq!(Axis::Y.wheel_spec(), WHEEL_SPECS[1]);
+    assert_eq!(Axis::Z.wheel_spec(), WHEEL_SPECS[2]);
+    assert_eq!(Axis::Test.wheel_spec(), Axis::X.wheel_spec());
+    assert_eq!(WHEEL_SPECS[0].max_rpm, 12000);
+}
+
+#[test]
+fn override_applies_to_self_test_strap() {
+    assert_eq!(apply_axis_override(Axis::Test, &request(1, 0)), Ok(Axis::Y));
+}
+
+#[test]
+fn override_matching_strap_is_accepted() {
+    assert_eq!(apply_axis_override(Axis::Z, &request(2, 0)), Ok(Axis::Z));
+}
+
+#[test]
+fn conflicting_override_needs_force() {
+    assert_eq!(
+        apply_axis_override(Axis::X, &request(2, 0)),
+        Err(AxisOverrideError::Conflict { strapped: Axis::X, requested: Axis::Z })
+    );
+    assert_eq!(apply_axis_override(Axis::X, &request(2, 1)), Ok(Axis::Z));
+}
+
+#[test]
+fn invalid_override_is_rejected() {
+    assert_eq!(apply_axis_override(Axis::Test, &request(3, 1)), Err(AxisOverrideError::Axis(3)));
+    assert_eq!(apply_axis_override(Axis::Test, &request(0, 2)), Err(AxisOverrideError::Force(2)));
+}
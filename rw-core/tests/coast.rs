@@ -0,0 +1,39 @@
+use rw_core::coast::{CoastTest, CoastTestCommand, CoastTestEnd, COAST_TEST_TIMEOUT_MS};
+use rw_core::protocol::RWCoastTestReport;
+
+#[test]
+fn coast_test_ends_when_wheel_reads_stopped() {
+    let mut test = CoastTest::new();
+    test.start(1_000_000);
+    assert!(test.is_running());
+    assert_eq!(test.update(1_100_000, Some(2500)), None);
+    assert_eq!(test.update(1_150_000, None), None);
+    assert_eq!(test.update(4_200_000, Some(0)), Some(CoastTestEnd::Stopped { elapsed_ms: 3200 }));
+    assert!(!test.is_running());
+    assert_eq!(test.update(4_300_000, Some(0)), None);
+}
+
+#[test]
+fn coast_test_times_out() {
+    let mut test = CoastTest::new();
+    test.start(0);
+    let timeout_us = COAST_TEST_TIMEOUT_MS * 1000;
+    assert_eq!(test.update(timeout_us - 1, Some(10)), None);
+    assert_eq!(test.update(timeout_us, None), Some(CoastTestEnd::TimedOut));
+}
+
+#[test]
+fn coast_test_abort() {
+    let mut test = CoastTest::new();
+    assert!(!test.abort());
+    test.start(0);
+    assert!(test.abort());
+    assert_eq!(test.update(1000, Some(0)), None);
+}
+
+#[test]
+fn coast_test_command_from_report() {
+    assert_eq!(CoastTestCommand::from_report(&RWCoastTestReport { command: 1 }), Some(CoastTestCommand::Start));
+    assert_eq!(CoastTestCommand::from_report(&RWCoastTestReport { command: 0 }), Some(CoastTestCommand::Abort));
+    assert_eq!(CoastTestCommand::from_report(&RWCoastTestReport { command: 2 }), None);
+}
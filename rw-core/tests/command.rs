@@ -0,0 +1,203 @@
+use rw_core::command::{handle_command, CommandGate, CommandResult, SpeedCommand};
+use rw_core::config::Config;
+use rw_core::control::MotorController;
+use rw_core::motor::MotorSpeed;
+use rw_core::protocol::{
+    OutputReport, OutputReportError, OutputReportPercent, RWConfigReport, AXIS_OVERRIDE_REPORT_ID, COAST_TEST_REPORT_ID,
+    CONFIG_COMMAND_REPORT_ID, CONFIG_REPORT_ID, CONFIG_STATUS_REPORT_ID, DESCRIPTOR_INFO_REPORT_ID, DEVICE_INFO_REPORT_ID,
+    EFFECTIVE_CONFIG_REPORT_ID, ENCODER_CALIBRATION_REPORT_ID, ESTOP_REPORT_ID, FAULT_CLEAR_REPORT_ID,
+    MIN_DUTY_CALIBRATION_REPORT_ID, SEQUENCE_COMMAND_REPORT_ID, SEQUENCE_REPORT_ID, SPEED_PERCENT_REPORT_ID, SPEED_REPORT_ID,
+    SPIN_UP_TEST_REPORT_ID, TELEMETRY_REPORT_ID,
+};
+use zerocopy::IntoBytes;
+
+fn send(data: &[u8], gate: &CommandGate) -> (CommandResult, MotorController) {
+    let mut controller = MotorController::new();
+    let result = handle_command(data, gate, &mut controller, &Config::default());
+    (result, controller)
+}
+
+fn command(result: CommandResult) -> SpeedCommand {
+    match result {
+        CommandResult::Command(command) => command,
+        other => panic!("expected a command, got {other:?}"),
+    }
+}
+
+#[test]
+fn speed_report_sets_target_and_duty() {
+    let (result, controller) = send(&OutputReport::new(16384).to_report(), &CommandGate::OPEN);
+    let command = command(result);
+    assert_eq!(command.target, MotorSpeed::new(16384));
+    assert!(command.accepted);
+    assert_eq!(command.duty, 50);
+    assert!(command.is_forward);
+    assert!(command.kickstart);
+    assert_eq!(controller.target(), MotorSpeed::new(16384));
+}
+
+#[test]
+fn percent_report_takes_the_same_path() {
+    let (result, controller) = send(&OutputReportPercent::new(-50).to_report(), &CommandGate::OPEN);
+    let command = command(result);
+    assert_eq!(command.duty, 50);
+    assert!(!command.is_forward);
+    assert_eq!(controller.target(), command.target);
+}
+
+#[test]
+fn small_command_is_raised_to_min_duty() {
+    let config = Config::default();
+    let command = command(send(&OutputReport::new(100).to_report(), &CommandGate::OPEN).0);
+    assert_eq!(command.duty, config.min_duty);
+}
+
+#[test]
+fn boundary_speeds() {
+    let command_for = |speed: i16| command(send(&OutputReport::new(speed).to_report(), &CommandGate::OPEN).0);
+
+    let full = command_for(i16::MAX);
+    assert_eq!((full.duty, full.is_forward), (100, true));
+
+    // i16::MIN is clamped to -32767, but reported as sent
+    let min = command_for(i16::MIN);
+    assert_eq!(min.raw_speed_normalized, i16::MIN);
+    assert_eq!(min.target, MotorSpeed::new(-32767));
+    assert_eq!((min.duty, min.is_forward), (100, false));
+
+    let stop = command_for(0);
+    assert_eq!(stop.duty, 0);
+    assert!(!stop.kickstart);
+}
+
+#[test]
+fn speed_cap_limits_the_duty() {
+    let config = Config { max_speed_normalized: 16384, ..Config::default() };
+    let mut controller = MotorController::new();
+    let result = handle_command(&OutputReport::new(32767).to_report(), &CommandGate::OPEN, &mut controller, &config);
+    assert_eq!(command(result).duty, 50);
+}
+
+#[test]
+fn trailing_padding_is_ignored() {
+    let mut data = [0u8; 64];
+    data[..4].copy_from_slice(&OutputReport::new(-8192).to_report());
+    assert_eq!(command(send(&data, &CommandGate::OPEN).0).target, MotorSpeed::new(-8192));
+}
+
+#[test]
+fn malformed_reports_leave_the_target() {
+    for data in [&[][..], &[SPEED_REPORT_ID, 0x00][..], &[SPEED_PERCENT_REPORT_ID][..]] {
+        let (result, controller) = send(data, &CommandGate::OPEN);
+        assert_eq!(result, CommandResult::Invalid(OutputReportError::Malformed), "{data:?}");
+        assert_eq!(controller.target(), MotorSpeed::STOP);
+    }
+}
+
+#[test]
+fn other_report_ids_are_not_speed_commands() {
+    // Every other report, with a valid speed payload behind its ID
+    let ids = [
+        0,
+        CONFIG_REPORT_ID,
+        ESTOP_REPORT_ID,
+        DEVICE_INFO_REPORT_ID,
+        AXIS_OVERRIDE_REPORT_ID,
+        CONFIG_COMMAND_REPORT_ID,
+        TELEMETRY_REPORT_ID,
+        FAULT_CLEAR_REPORT_ID,
+        COAST_TEST_REPORT_ID,
+        EFFECTIVE_CONFIG_REPORT_ID,
+        SEQUENCE_REPORT_ID,
+        SEQUENCE_COMMAND_REPORT_ID,
+        ENCODER_CALIBRATION_REPORT_ID,
+        MIN_DUTY_CALIBRATION_REPORT_ID,
+        CONFIG_STATUS_REPORT_ID,
+        DESCRIPTOR_INFO_REPORT_ID,
+        SPIN_UP_TEST_REPORT_ID,
+        0x42,
+    ];
+    for id in ids {
+        let mut data = OutputReport::new(16384).to_report();
+        data[0] = id;
+        let (result, controller) = send(&data, &CommandGate::OPEN);
+        assert_eq!(result, CommandResult::Invalid(OutputReportError::UnknownReportId(id)));
+        assert_eq!(controller.target(), MotorSpeed::STOP);
+    }
+}
+
+#[test]
+fn config_write_on_the_out_endpoint_is_rejected() {
+    let config = Config { min_duty: 100, ..Config::default() };
+    let mut data = [CONFIG_REPORT_ID; 1 + size_of::<RWConfigReport>()];
+    data[1..].copy_from_slice(config.to_report().as_bytes());
+    let (result, controller) = send(&data, &CommandGate::OPEN);
+    assert_eq!(result, CommandResult::Invalid(OutputReportError::UnknownReportId(CONFIG_REPORT_ID)));
+    assert_eq!(controller.target(), MotorSpeed::STOP);
+}
+
+#[test]
+fn corrupted_checksum_is_rejected() {
+    let mut data = OutputReport::new(16384).to_report();
+    data[1] ^= 0x01;
+    let (result, controller) = send(&data, &CommandGate::OPEN);
+    assert!(matches!(result, CommandResult::Invalid(OutputReportError::Checksum { .. })));
+    assert_eq!(controller.target(), MotorSpeed::STOP);
+}
+
+#[test]
+fn locked_out_host_is_ignored() {
+    let gate = CommandGate { host_locked_out: true, ..CommandGate::OPEN };
+    let (result, controller) = send(&OutputReport::new(16384).to_report(), &gate);
+    assert_eq!(result, CommandResult::Ignored);
+    assert_eq!(controller.target(), MotorSpeed::STOP);
+
+    // Garbled reports are still reported as such
+    assert_eq!(send(&[0x42], &gate).0, CommandResult::Invalid(OutputReportError::UnknownReportId(0x42)));
+}
+
+#[test]
+fn blocked_commands_are_dropped() {
+    let gates = [
+        CommandGate { estopped: true, ..CommandGate::OPEN },
+        CommandGate { driver_awake: false, ..CommandGate::OPEN },
+        CommandGate { overcurrent: true, ..CommandGate::OPEN },
+        CommandGate { undervoltage: true, ..CommandGate::OPEN },
+        CommandGate { stalled: true, ..CommandGate::OPEN },
+    ];
+    for gate in gates {
+        let (result, controller) = send(&OutputReport::new(16384).to_report(), &gate);
+        let command = command(result);
+        assert!(!command.accepted, "{gate:?}");
+        assert_eq!((command.duty, command.kickstart), (0, false));
+        assert_eq!(controller.target(), MotorSpeed::STOP);
+    }
+}
+
+#[test]
+fn stop_gets_through_a_stall() {
+    let gate = CommandGate { stalled: true, ..CommandGate::OPEN };
+    let config = Config::default();
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    let result = handle_command(&OutputReport::new(0).to_report(), &gate, &mut controller, &config);
+    assert!(command(result).accepted);
+    assert_eq!(controller.target(), MotorSpeed::STOP);
+}
+
+#[test]
+fn no_kickstart_while_already_running() {
+    let config = Config::default();
+    let mut controller = MotorController::new();
+    handle_command(&OutputReport::new(16384).to_report(), &CommandGate::OPEN, &mut controller, &config);
+    for tick in 0..1000 {
+        controller.update(tick * 1000, &config);
+    }
+    let result = handle_command(&OutputReport::new(20000).to_report(), &CommandGate::OPEN, &mut controller, &config);
+    assert!(!command(result).kickstart);
+
+    let config = Config { kickstart_ms: 0, ..config };
+    let mut controller = MotorController::new();
+    let result = handle_command(&OutputReport::new(16384).to_report(), &CommandGate::OPEN, &mut controller, &config);
+    assert!(!command(result).kickstart);
+}
@@ -0,0 +1,795 @@
+use rw_core::axis::{WheelSpec, DEFAULT_WHEEL_SPEC};
+use rw_core::config::{Config, ControlMode, RampProfile, StopMode};
+use rw_core::control::{exponential_toward, hold_direction, slew_toward, MotorController, MotorOutput, CONTROL_TICK_US};
+use rw_core::current::TORQUE_MAX_CURRENT_MA;
+use rw_core::motor::{percent_to_counts, MotorSpeed, PWM_TOP, SPEED_NORMALIZED_MAX};
+use rw_core::pid::MAX_RPM;
+
+/// Run `ticks` control ticks starting at `start_us`, returning the outputs
+fn run(controller: &mut MotorController, config: &Config, start_us: u64, ticks: u64) -> Vec<MotorOutput> {
+    (0..ticks)
+        .map(|i| controller.update(start_us + i * CONTROL_TICK_US, config))
+        .collect()
+}
+
+/// Count kickstart pulses (transitions into the kickstarting state)
+fn count_kickstarts(controller: &mut MotorController, config: &Config, ticks: u64) -> usize {
+    let mut count = 0;
+    let mut was_kickstarting = controller.is_kickstarting();
+    for i in 0..ticks {
+        controller.update(i * CONTROL_TICK_US, config);
+        if controller.is_kickstarting() && !was_kickstarting {
+            count += 1;
+        }
+        was_kickstarting = controller.is_kickstarting();
+    }
+    count
+}
+
+#[test]
+fn slew_limits_step() {
+    assert_eq!(slew_toward(0, 1000, 100), 100);
+    assert_eq!(slew_toward(0, -1000, 100), -100);
+    assert_eq!(slew_toward(950, 1000, 100), 1000);
+    assert_eq!(slew_toward(32767, -32767, 1000), 31767);
+}
+
+#[test]
+fn slew_disabled_jumps_to_target() {
+    assert_eq!(slew_toward(6553, -29490, 0), -29490);
+}
+
+#[test]
+fn kickstart_pulse_then_target_duty() {
+    let config = Config::default();
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+
+    assert_eq!(controller.update(0, &config), MotorOutput::Forward(percent_to_counts(config.kickstart_duty)));
+    assert!(controller.is_kickstarting());
+
+    let end_us = config.kickstart_ms as u64 * 1000;
+    assert_eq!(controller.update(end_us - 1, &config), MotorOutput::Forward(percent_to_counts(config.kickstart_duty)));
+    assert_eq!(controller.update(end_us, &config), MotorOutput::Forward(1250));
+    assert!(!controller.is_kickstarting());
+}
+
+#[test]
+fn same_direction_change_keeps_kickstart_running() {
+    let config = Config::default();
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    controller.update(0, &config);
+
+    controller.set_target(MotorSpeed::new(32767));
+    assert_eq!(controller.update(1000, &config), MotorOutput::Forward(percent_to_counts(config.kickstart_duty)));
+    assert!(controller.is_kickstarting());
+}
+
+#[test]
+fn stop_cancels_kickstart() {
+    let config = Config { stop_mode: StopMode::Brake, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    controller.update(0, &config);
+
+    controller.set_target(MotorSpeed::STOP);
+    assert_eq!(controller.update(1000, &config), MotorOutput::Brake);
+    assert!(!controller.is_kickstarting());
+}
+
+#[test]
+fn ramp_reaches_target() {
+    let config = Config { slew_step: 1000, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(10000));
+
+    run(&mut controller, &config, 0, 9);
+    assert_eq!(controller.applied().speed_normalized, 9000);
+    run(&mut controller, &config, 9 * CONTROL_TICK_US, 5);
+    assert_eq!(controller.applied().speed_normalized, 10000);
+}
+
+#[test]
+fn ramp_across_zero_kickstarts_once() {
+    // Jump from +20% to -90% with a ramp that does not land exactly on zero
+    let config = Config { slew_step: 700, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(6553));
+    run(&mut controller, &config, 0, 200);
+    assert!(!controller.is_kickstarting());
+
+    controller.set_target(MotorSpeed::new(-29490));
+    assert_eq!(count_kickstarts(&mut controller, &config, 500), 1);
+    assert_eq!(controller.applied().speed_normalized, -29490);
+}
+
+#[test]
+fn ramp_across_zero_small_reversal_skips_kickstart() {
+    // +5% to -5% stays below the reversal threshold at both ends
+    let config = Config { slew_step: 700, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(1638));
+    run(&mut controller, &config, 0, 200);
+
+    controller.set_target(MotorSpeed::new(-1638));
+    assert_eq!(count_kickstarts(&mut controller, &config, 500), 0);
+    assert_eq!(controller.applied().speed_normalized, -1638);
+}
+
+#[test]
+fn ramp_through_exact_zero_kickstarts_once() {
+    let config = Config { slew_step: 1000, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(5000));
+    run(&mut controller, &config, 0, 200);
+
+    controller.set_target(MotorSpeed::new(-5000));
+    assert_eq!(count_kickstarts(&mut controller, &config, 500), 1);
+}
+
+#[test]
+fn closed_loop_waits_for_feedback() {
+    let config = Config { control_mode: ControlMode::ClosedLoop, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    assert_eq!(controller.update(0, &config), MotorOutput::Coast);
+
+    controller.set_measured_rpm(0, &config);
+    assert!(matches!(controller.update(CONTROL_TICK_US, &config), MotorOutput::Forward(_)));
+}
+
+/// Stalled-rotor stand-in for the current sense: 2A at 100% duty, no back-EMF
+fn stalled_current_ma(output: MotorOutput) -> u16 {
+    (output.counts() as u32 * 2000 / PWM_TOP as u32) as u16
+}
+
+/// Run the torque loop for `ticks`, returning the last output and whether any tick kickstarted
+fn run_torque(controller: &mut MotorController, config: &Config, ticks: u64) -> (MotorOutput, bool) {
+    let mut output = MotorOutput::Coast;
+    let mut kicked = false;
+    for i in 0..ticks {
+        output = controller.update(i * CONTROL_TICK_US, config);
+        kicked |= controller.is_kickstarting();
+        controller.set_measured_current(stalled_current_ma(output), config);
+    }
+    (output, kicked)
+}
+
+#[test]
+fn torque_mode_holds_the_target_current() {
+    let config = Config { control_mode: ControlMode::Torque, ..Config::default() };
+    let mut controller = MotorController::new();
+    // Half scale: half of TORQUE_MAX_CURRENT_MA
+    controller.set_target(MotorSpeed::new(16384));
+    let (output, kicked) = run_torque(&mut controller, &config, 1000);
+    let current_ma = stalled_current_ma(output);
+    assert!(current_ma.abs_diff(TORQUE_MAX_CURRENT_MA / 2) <= 10, "{current_ma}mA");
+    // 25% duty: below min_duty, and no kickstart pulse on the way
+    assert!(matches!(output, MotorOutput::Forward(duty) if duty < percent_to_counts(config.min_duty)), "{output:?}");
+    assert!(!kicked);
+}
+
+#[test]
+fn current_limit_holds_a_stalled_rotor_at_the_limit() {
+    let config = Config { current_limit_ma: 1000, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(SPEED_NORMALIZED_MAX));
+    let (output, _) = run_torque(&mut controller, &config, 1000);
+    let current_ma = stalled_current_ma(output);
+    // Still driven at the limit instead of cut
+    assert!(current_ma.abs_diff(1000) <= 10, "{current_ma}mA");
+    assert!(matches!(output, MotorOutput::Forward(_)), "{output:?}");
+    assert!(controller.is_current_limited());
+}
+
+#[test]
+fn current_limit_lifts_as_the_load_eases() {
+    let config = Config { current_limit_ma: 1000, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(SPEED_NORMALIZED_MAX));
+    run_torque(&mut controller, &config, 1000);
+
+    // Back-EMF up: the current drops well below the limit and the full duty returns
+    let mut output = MotorOutput::Coast;
+    for i in 1000..1100 {
+        output = controller.update(i * CONTROL_TICK_US, &config);
+        controller.set_measured_current(300, &config);
+    }
+    assert_eq!(output, MotorOutput::Forward(PWM_TOP));
+    assert!(!controller.is_current_limited());
+
+    // Off by default: the stalled rotor gets the full duty
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(SPEED_NORMALIZED_MAX));
+    let (output, _) = run_torque(&mut controller, &Config::default(), 1000);
+    assert_eq!(output, MotorOutput::Forward(PWM_TOP));
+}
+
+#[test]
+fn torque_mode_reverses_with_the_target() {
+    let config = Config { control_mode: ControlMode::Torque, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    run_torque(&mut controller, &config, 500);
+
+    controller.set_target(MotorSpeed::new(-32767));
+    let (output, _) = run_torque(&mut controller, &config, 1000);
+    assert!(matches!(output, MotorOutput::Reverse(_)), "{output:?}");
+    assert!(stalled_current_ma(output).abs_diff(TORQUE_MAX_CURRENT_MA) <= 10);
+}
+
+#[test]
+fn torque_mode_waits_for_current_feedback() {
+    let config = Config { control_mode: ControlMode::Torque, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    assert_eq!(controller.update(0, &config), MotorOutput::Coast);
+
+    controller.set_measured_current(0, &config);
+    assert!(matches!(controller.update(CONTROL_TICK_US, &config), MotorOutput::Forward(_)));
+
+    // Already past the target current: back off to stop rather than reverse
+    controller.set_measured_current(5000, &config);
+    assert_eq!(controller.update(2 * CONTROL_TICK_US, &config), MotorOutput::Coast);
+}
+
+#[test]
+fn current_feedback_ignored_outside_torque_mode() {
+    let config = Config { slew_step: 0, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    controller.set_measured_current(5000, &config);
+    assert!(matches!(controller.update(0, &config), MotorOutput::Forward(_)));
+}
+
+#[test]
+fn closed_loop_overspeed_does_not_reverse() {
+    let config = Config { control_mode: ControlMode::ClosedLoop, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(8000));
+    controller.set_measured_rpm(10000, &config);
+    assert_eq!(controller.update(0, &config), MotorOutput::Coast);
+}
+
+#[test]
+fn closed_loop_scales_to_the_wheel_max_rpm() {
+    // 8000 normalized is ~2930 RPM on the default wheel, ~488 RPM on a 2000 RPM one
+    let config = Config { control_mode: ControlMode::ClosedLoop, ..Config::default() };
+    let mut controller = MotorController::new();
+    assert_eq!(controller.max_rpm(&config), MAX_RPM as u16);
+    controller.set_target(MotorSpeed::new(8000));
+    controller.set_measured_rpm(2500, &config);
+    assert!(matches!(controller.update(0, &config), MotorOutput::Forward(_)));
+
+    let mut slow = MotorController::new();
+    slow.set_wheel_spec(WheelSpec { max_rpm: 2000, ..DEFAULT_WHEEL_SPEC });
+    slow.set_target(MotorSpeed::new(8000));
+    slow.set_measured_rpm(2500, &config);
+    assert_eq!(slow.update(0, &config), MotorOutput::Coast);
+
+    // A config override wins over the wheel
+    let config = Config { max_rpm: 12000, ..config };
+    let mut overridden = MotorController::new();
+    overridden.set_wheel_spec(WheelSpec { max_rpm: 2000, ..DEFAULT_WHEEL_SPEC });
+    assert_eq!(overridden.max_rpm(&config), 12000);
+    overridden.set_target(MotorSpeed::new(8000));
+    overridden.set_measured_rpm(2500, &config);
+    assert!(matches!(overridden.update(0, &config), MotorOutput::Forward(_)));
+}
+
+#[test]
+fn open_loop_ignores_feedback() {
+    let config = Config::default();
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    controller.set_measured_rpm(0, &config);
+    run(&mut controller, &config, 0, 200);
+    controller.set_measured_rpm(12000, &config);
+    assert_eq!(controller.update(200 * CONTROL_TICK_US, &config), MotorOutput::Forward(1250));
+}
+
+#[test]
+fn lost_feedback_falls_back_to_open_loop() {
+    let config = Config { control_mode: ControlMode::ClosedLoop, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_feedback_lost(true);
+    assert_eq!(controller.control_mode(&config), ControlMode::OpenLoop);
+    controller.set_target(MotorSpeed::new(16384));
+    // No RPM reading needed, and the PID stays out of it
+    run(&mut controller, &config, 0, 200);
+    controller.set_measured_rpm(0, &config);
+    assert_eq!(controller.update(200 * CONTROL_TICK_US, &config), MotorOutput::Forward(1250));
+
+    controller.set_feedback_lost(false);
+    assert_eq!(controller.control_mode(&config), ControlMode::ClosedLoop);
+}
+
+#[test]
+fn stop_now_skips_slew() {
+    let config = Config { slew_step: 100, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(3000));
+    run(&mut controller, &config, 0, 100);
+
+    controller.stop_now();
+    assert_eq!(controller.target(), MotorSpeed::STOP);
+    assert_eq!(controller.update(100 * CONTROL_TICK_US, &config), MotorOutput::Coast);
+}
+
+#[test]
+fn duty_limit_caps_kickstart_and_duty() {
+    let config = Config::default();
+    let mut controller = MotorController::new();
+    controller.set_duty_limit(60);
+    controller.set_target(MotorSpeed::new(32767));
+    assert_eq!(controller.update(0, &config), MotorOutput::Forward(1500));
+
+    controller.set_duty_limit(0);
+    assert_eq!(controller.update(CONTROL_TICK_US, &config), MotorOutput::Coast);
+}
+
+#[test]
+fn mirrored_flips_direction_only() {
+    assert_eq!(MotorOutput::Forward(1200).mirrored(), MotorOutput::Reverse(1200));
+    assert_eq!(MotorOutput::Reverse(2500).mirrored(), MotorOutput::Forward(2500));
+    assert_eq!(MotorOutput::Coast.mirrored(), MotorOutput::Coast);
+    assert_eq!(MotorOutput::Brake.mirrored(), MotorOutput::Brake);
+    assert_eq!(MotorOutput::Forward(1200).mirrored().duty(), MotorOutput::Forward(1200).duty());
+}
+
+#[test]
+fn stop_ramps_down_at_stop_rate() {
+    let config = Config { stop_ramp_step: 1000, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(32767));
+    run(&mut controller, &config, 0, 200);
+    assert_eq!(controller.applied().speed_normalized, 32767);
+
+    controller.set_target(MotorSpeed::STOP);
+    controller.update(200 * CONTROL_TICK_US, &config);
+    assert_eq!(controller.applied().speed_normalized, 31767);
+
+    // 33 ticks in total to reach zero, and the driver is only released at the end
+    let outputs = run(&mut controller, &config, 201 * CONTROL_TICK_US, 32);
+    assert!(outputs[..31].iter().all(|o| matches!(o, MotorOutput::Forward(_))));
+    assert_eq!(outputs[31], MotorOutput::Coast);
+}
+
+#[test]
+fn stop_ramp_does_not_limit_acceleration() {
+    let config = Config { stop_ramp_step: 1000, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(20000));
+    controller.update(0, &config);
+    assert_eq!(controller.applied().speed_normalized, 20000);
+}
+
+#[test]
+fn stop_now_skips_stop_ramp() {
+    let config = Config { stop_ramp_step: 10, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(20000));
+    run(&mut controller, &config, 0, 200);
+
+    controller.stop_now();
+    assert_eq!(controller.update(200 * CONTROL_TICK_US, &config), MotorOutput::Coast);
+}
+
+#[test]
+fn exponential_step_covers_a_fraction_of_the_distance() {
+    // tau = 9ms: 1/10 of the remaining distance per 1ms tick, rounded up
+    assert_eq!(exponential_toward(0, 10000, 9, 0), 1000);
+    assert_eq!(exponential_toward(0, -10000, 9, 0), -1000);
+    assert_eq!(exponential_toward(9995, 10000, 9, 0), 9996);
+    assert_eq!(exponential_toward(0, 10000, 9, 300), 300);
+    assert_eq!(exponential_toward(123, -20000, 0, 0), -20000);
+}
+
+#[test]
+fn exponential_ramp_reaches_63_percent_after_tau() {
+    let config = Config { ramp_profile: RampProfile::Exponential, ramp_tau_ms: 100, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(30000));
+
+    run(&mut controller, &config, 0, 100);
+    let applied = controller.applied().speed_normalized;
+    assert!((18800..=19200).contains(&applied), "{applied}");
+
+    // Rounding up lands exactly on the target instead of creeping forever
+    run(&mut controller, &config, 100 * CONTROL_TICK_US, 2000);
+    assert_eq!(controller.applied().speed_normalized, 30000);
+    assert!(!controller.is_slewing());
+}
+
+#[test]
+fn speed_cap_limits_both_directions() {
+    let config = Config { max_speed_normalized: 16000, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(30000));
+    run(&mut controller, &config, 0, 1);
+    assert_eq!(controller.applied().speed_normalized, 16000);
+    assert_eq!(controller.target().speed_normalized, 30000);
+
+    controller.set_target(MotorSpeed::new(-30000));
+    run(&mut controller, &config, CONTROL_TICK_US, 1);
+    assert_eq!(controller.applied().speed_normalized, -16000);
+
+    // Commands inside the cap pass unchanged
+    controller.set_target(MotorSpeed::new(-12000));
+    run(&mut controller, &config, 2 * CONTROL_TICK_US, 1);
+    assert_eq!(controller.applied().speed_normalized, -12000);
+}
+
+#[test]
+fn speed_cap_limits_closed_loop_target() {
+    let config = Config { control_mode: ControlMode::ClosedLoop, max_speed_normalized: 1, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(32767));
+    assert_eq!(controller.limited_target(&config).speed_normalized, 1);
+}
+
+#[test]
+fn stall_retry_kicks_harder_and_longer() {
+    let config = Config { kickstart_duty: 60, kickstart_ms: 100, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(20000));
+    let outputs = run(&mut controller, &config, 0, 300);
+    assert_eq!(outputs[0], MotorOutput::Forward(percent_to_counts(60)));
+
+    controller.retry_kickstart();
+    let outputs = run(&mut controller, &config, 1_000_000, 250);
+    let boosted = MotorOutput::Forward(percent_to_counts(100));
+    assert_eq!(outputs[0], boosted);
+    assert_eq!(outputs[199], boosted);
+    assert_ne!(outputs[200], boosted);
+
+    // Later kickstarts are back to normal
+    controller.set_target(MotorSpeed::new(-20000));
+    let outputs = run(&mut controller, &config, 2_000_000, 1);
+    assert_eq!(outputs[0], MotorOutput::Reverse(percent_to_counts(60)));
+}
+
+#[test]
+fn kickstart_ends_once_wheel_turns() {
+    let config = Config { kickstart_duty: 100, kickstart_ms: 150, kickstart_rpm: 200, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(-16000));
+    run(&mut controller, &config, 0, 50);
+    assert!(controller.is_kickstarting());
+
+    // Turning the wrong way (still coasting from before) does not count
+    controller.set_measured_rpm(500, &config);
+    assert!(controller.is_kickstarting());
+    controller.set_measured_rpm(-199, &config);
+    assert!(controller.is_kickstarting());
+
+    controller.set_measured_rpm(-250, &config);
+    assert!(!controller.is_kickstarting());
+    let outputs = run(&mut controller, &config, 50 * CONTROL_TICK_US, 1);
+    assert_ne!(outputs[0], MotorOutput::Reverse(percent_to_counts(100)));
+}
+
+#[test]
+fn coasting_wheel_starts_without_kickstart() {
+    // Still spinning forward from before a reset: the first command picks it up without a pulse
+    let config = Config { kickstart_rpm: 200, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_measured_rpm(1500, &config);
+    controller.set_target(MotorSpeed::new(8000));
+    assert!(!controller.will_kickstart(MotorSpeed::new(8000), &config));
+    assert_eq!(count_kickstarts(&mut controller, &config, 200), 0);
+    assert!(matches!(controller.update(200 * CONTROL_TICK_US, &config), MotorOutput::Forward(_)));
+
+    // Turning too slowly, or the other way, is a start from stop
+    for rpm in [199, -1500] {
+        let mut controller = MotorController::new();
+        controller.set_measured_rpm(rpm, &config);
+        controller.set_target(MotorSpeed::new(8000));
+        assert_eq!(count_kickstarts(&mut controller, &config, 200), 1, "{rpm}rpm");
+    }
+}
+
+#[test]
+fn coasting_check_needs_the_encoder() {
+    // No measurement (no encoder, or none yet after boot) is taken as stopped
+    let config = Config::default();
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(-8000));
+    assert_eq!(count_kickstarts(&mut controller, &config, 200), 1);
+
+    // With the RPM end disabled the default threshold still judges a turning wheel
+    let config = Config { kickstart_rpm: 0, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_measured_rpm(-300, &config);
+    controller.set_target(MotorSpeed::new(-8000));
+    assert_eq!(count_kickstarts(&mut controller, &config, 200), 0);
+}
+
+#[test]
+fn kickstart_stays_timed_without_rpm_threshold() {
+    let config = Config { kickstart_ms: 150, kickstart_rpm: 0, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16000));
+    run(&mut controller, &config, 0, 50);
+    controller.set_measured_rpm(5000, &config);
+    assert!(controller.is_kickstarting());
+}
+
+#[test]
+fn kickstart_ends_once_inrush_decays() {
+    let config = Config { kickstart_ms: 150, kickstart_rpm: 0, kickstart_current_ma: 600, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16000));
+    run(&mut controller, &config, 0, 10);
+    assert!(controller.is_kickstarting());
+
+    // Below the threshold before the inrush was seen: still kicking
+    controller.set_measured_current(100, &config);
+    assert!(controller.is_kickstarting());
+    controller.set_measured_current(1500, &config);
+    controller.set_measured_current(700, &config);
+    assert!(controller.is_kickstarting());
+
+    controller.set_measured_current(550, &config);
+    assert!(!controller.is_kickstarting());
+    let outputs = run(&mut controller, &config, 10 * CONTROL_TICK_US, 1);
+    assert_ne!(outputs[0], MotorOutput::Forward(percent_to_counts(config.kickstart_duty)));
+}
+
+#[test]
+fn kickstart_stays_timed_without_current_reading() {
+    let config = Config { kickstart_ms: 150, kickstart_rpm: 0, kickstart_current_ma: 600, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16000));
+    run(&mut controller, &config, 0, 1);
+    for tick in 1..150 {
+        controller.set_measured_current(0, &config);
+        run(&mut controller, &config, tick * CONTROL_TICK_US, 1);
+        assert!(controller.is_kickstarting(), "ended at tick {tick}");
+    }
+    run(&mut controller, &config, 150 * CONTROL_TICK_US, 1);
+    assert!(!controller.is_kickstarting());
+
+    // The next kick starts over: the inrush of the previous one does not count
+    let config = Config { kickstart_ms: 150, kickstart_rpm: 0, kickstart_current_ma: 600, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16000));
+    run(&mut controller, &config, 0, 1);
+    controller.set_measured_current(1500, &config);
+    controller.set_target(MotorSpeed::STOP);
+    run(&mut controller, &config, CONTROL_TICK_US, 1);
+    controller.set_target(MotorSpeed::new(16000));
+    run(&mut controller, &config, 2 * CONTROL_TICK_US, 1);
+    controller.set_measured_current(300, &config);
+    assert!(controller.is_kickstarting());
+}
+
+#[test]
+fn reversal_brakes_before_kicking_the_other_way() {
+    let config = Config { stop_mode: StopMode::Brake, reversal_brake_ms: 100, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(29490));
+    run(&mut controller, &config, 0, 200);
+
+    controller.set_target(MotorSpeed::new(-29490));
+    let outputs = run(&mut controller, &config, 200 * CONTROL_TICK_US, 100);
+    assert!(outputs.iter().all(|&output| output == MotorOutput::Brake));
+    assert!(controller.is_reversal_braking());
+
+    let outputs = run(&mut controller, &config, 300 * CONTROL_TICK_US, 1);
+    assert_eq!(outputs[0], MotorOutput::Reverse(percent_to_counts(100)));
+    assert!(!controller.is_reversal_braking());
+    assert!(controller.is_kickstarting());
+}
+
+#[test]
+fn reversal_brake_ends_once_wheel_slows() {
+    let config = Config {
+        stop_mode: StopMode::Brake,
+        reversal_brake_ms: 500,
+        reversal_brake_rpm: 300,
+        ..Config::default()
+    };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(29490));
+    run(&mut controller, &config, 0, 200);
+    controller.set_target(MotorSpeed::new(-29490));
+    run(&mut controller, &config, 200 * CONTROL_TICK_US, 10);
+
+    controller.set_measured_rpm(1200, &config);
+    assert!(controller.is_reversal_braking());
+    controller.set_measured_rpm(-250, &config);
+    assert!(!controller.is_reversal_braking());
+    let outputs = run(&mut controller, &config, 210 * CONTROL_TICK_US, 1);
+    assert_eq!(outputs[0], MotorOutput::Reverse(percent_to_counts(100)));
+}
+
+#[test]
+fn reversal_brake_needs_brake_stop_mode() {
+    let config = Config { stop_mode: StopMode::Coast, reversal_brake_ms: 100, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(29490));
+    run(&mut controller, &config, 0, 200);
+
+    controller.set_target(MotorSpeed::new(-29490));
+    let outputs = run(&mut controller, &config, 200 * CONTROL_TICK_US, 1);
+    assert_eq!(outputs[0], MotorOutput::Reverse(percent_to_counts(100)));
+    assert!(!controller.is_reversal_braking());
+}
+
+#[test]
+fn small_reversal_skips_reversal_brake() {
+    let config = Config { stop_mode: StopMode::Brake, reversal_brake_ms: 100, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(1638));
+    run(&mut controller, &config, 0, 200);
+
+    controller.set_target(MotorSpeed::new(-1638));
+    let outputs = run(&mut controller, &config, 200 * CONTROL_TICK_US, 1);
+    assert!(matches!(outputs[0], MotorOutput::Reverse(_)));
+}
+
+#[test]
+fn dither_alternates_around_low_speed_duty() {
+    // 5% commanded maps to min_duty (40%); 10Hz dither flips every 50ms
+    let config = Config { kickstart_ms: 0, dither_amplitude: 5, dither_freq_hz: 10, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(1638));
+    let outputs = run(&mut controller, &config, 0, 100);
+    assert_eq!(outputs[10], MotorOutput::Forward(percent_to_counts(45)));
+    assert_eq!(outputs[60], MotorOutput::Forward(percent_to_counts(35)));
+}
+
+#[test]
+fn dither_only_below_max_speed() {
+    let config = Config { kickstart_ms: 0, dither_amplitude: 5, dither_max_speed: 3277, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    let outputs = run(&mut controller, &config, 0, 100);
+    assert_eq!(outputs[10], outputs[60]);
+}
+
+#[test]
+fn dither_off_by_default() {
+    let config = Config { kickstart_ms: 0, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(1638));
+    let outputs = run(&mut controller, &config, 0, 100);
+    assert_eq!(outputs[10], outputs[60]);
+}
+
+#[test]
+fn dwell_holds_drive_after_start() {
+    let config = Config { kickstart_ms: 0, min_dwell_ms: 100, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    let driving = run(&mut controller, &config, 0, 10)[9];
+
+    controller.set_target(MotorSpeed::STOP);
+    let outputs = run(&mut controller, &config, 10 * CONTROL_TICK_US, 100);
+    assert!(outputs[..90].iter().all(|&output| output == driving));
+    assert_eq!(outputs[90], MotorOutput::Coast);
+}
+
+#[test]
+fn dwell_applies_latest_command() {
+    let config = Config { kickstart_ms: 0, min_dwell_ms: 100, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    run(&mut controller, &config, 0, 10);
+    controller.set_target(MotorSpeed::STOP);
+    run(&mut controller, &config, 10 * CONTROL_TICK_US, 10);
+
+    // Back to driving before the dwell ran out: never stopped, now at the new speed
+    controller.set_target(MotorSpeed::new(8192));
+    let outputs = run(&mut controller, &config, 20 * CONTROL_TICK_US, 100);
+    assert!(outputs.iter().all(|output| matches!(output, MotorOutput::Forward(_))));
+    assert_eq!(outputs[99], MotorOutput::Forward(MotorSpeed::new(8192).to_counts_and_direction(&config).0));
+}
+
+#[test]
+fn stop_now_bypasses_dwell() {
+    let config = Config { kickstart_ms: 0, min_dwell_ms: 100, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    run(&mut controller, &config, 0, 10);
+
+    controller.stop_now();
+    assert_eq!(run(&mut controller, &config, 10 * CONTROL_TICK_US, 1)[0], MotorOutput::Coast);
+    controller.set_target(MotorSpeed::new(16384));
+    assert!(matches!(run(&mut controller, &config, 11 * CONTROL_TICK_US, 1)[0], MotorOutput::Forward(_)));
+}
+
+/// Alternate the target between `a` and `b` every tick, returning the outputs and kickstarts started
+fn oscillate(controller: &mut MotorController, config: &Config, a: i16, b: i16, ticks: u64) -> (Vec<MotorOutput>, usize) {
+    let mut kickstarts = 0;
+    let outputs = (0..ticks)
+        .map(|i| {
+            controller.set_target(MotorSpeed::new(if i % 2 == 0 { a } else { b }));
+            let was_kickstarting = controller.is_kickstarting();
+            let output = controller.update(i * CONTROL_TICK_US, config);
+            if controller.is_kickstarting() && !was_kickstarting {
+                kickstarts += 1;
+            }
+            output
+        })
+        .collect();
+    (outputs, kickstarts)
+}
+
+#[test]
+fn hold_direction_within_band() {
+    assert_eq!(hold_direction(1000, -200, 300), 200);
+    assert_eq!(hold_direction(-1000, 300, 300), -300);
+    assert_eq!(hold_direction(1000, -301, 300), -301);
+    assert_eq!(hold_direction(1000, 0, 300), 0);
+    assert_eq!(hold_direction(0, -200, 300), -200);
+    assert_eq!(hold_direction(1000, -200, 0), -200);
+    assert_eq!(hold_direction(1000, i16::MIN, 32767), i16::MIN);
+}
+
+#[test]
+fn oscillation_around_zero_flips_without_hysteresis() {
+    let config = Config { reversal_kick_threshold: 0, ..Config::default() };
+    let mut controller = MotorController::new();
+    let (outputs, _) = oscillate(&mut controller, &config, 200, -200, 300);
+    assert!(matches!(outputs[298], MotorOutput::Forward(_)));
+    assert!(matches!(outputs[299], MotorOutput::Reverse(_)));
+    assert!(controller.is_kickstarting(), "every flip restarts the kickstart");
+}
+
+#[test]
+fn hysteresis_holds_direction_while_oscillating() {
+    let config = Config { reversal_kick_threshold: 0, direction_hysteresis: 328, ..Config::default() };
+    let mut controller = MotorController::new();
+    let (outputs, kickstarts) = oscillate(&mut controller, &config, 200, -200, 300);
+    assert!(outputs.iter().all(|output| matches!(output, MotorOutput::Forward(_))));
+    assert_eq!(kickstarts, 1);
+    assert!(!controller.is_kickstarting());
+
+    // Starting the other way round holds reverse instead
+    let mut controller = MotorController::new();
+    let (outputs, _) = oscillate(&mut controller, &config, -200, 200, 100);
+    assert!(outputs.iter().all(|output| matches!(output, MotorOutput::Reverse(_))));
+}
+
+#[test]
+fn hysteresis_switches_once_committed() {
+    let config = Config { kickstart_ms: 0, direction_hysteresis: 328, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(200));
+    run(&mut controller, &config, 0, 10);
+    controller.set_target(MotorSpeed::new(-328));
+    assert!(matches!(run(&mut controller, &config, 10 * CONTROL_TICK_US, 1)[0], MotorOutput::Forward(_)));
+    controller.set_target(MotorSpeed::new(-329));
+    assert!(matches!(run(&mut controller, &config, 11 * CONTROL_TICK_US, 1)[0], MotorOutput::Reverse(_)));
+}
+
+#[test]
+fn hysteresis_with_deadband_stays_stopped() {
+    // Inside the deadband the wheel stops, and the held direction keeps it from kicking either way
+    let config = Config { deadband: 300, direction_hysteresis: 600, ..Config::default() };
+    let mut controller = MotorController::new();
+    let (outputs, kickstarts) = oscillate(&mut controller, &config, 250, -250, 100);
+    assert!(outputs.iter().all(|&output| output == MotorOutput::Coast));
+    assert_eq!(kickstarts, 0);
+
+    // Above the deadband it drives one way only
+    let mut controller = MotorController::new();
+    let (outputs, kickstarts) = oscillate(&mut controller, &config, 500, -500, 100);
+    assert!(outputs.iter().all(|output| matches!(output, MotorOutput::Forward(_))));
+    assert_eq!(kickstarts, 1);
+}
+
+#[test]
+fn hysteresis_does_not_hold_from_stop() {
+    let config = Config { kickstart_ms: 0, direction_hysteresis: 328, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(200));
+    run(&mut controller, &config, 0, 10);
+    controller.set_target(MotorSpeed::STOP);
+    run(&mut controller, &config, 10 * CONTROL_TICK_US, 10);
+    controller.set_target(MotorSpeed::new(-200));
+    assert!(matches!(run(&mut controller, &config, 20 * CONTROL_TICK_US, 1)[0], MotorOutput::Reverse(_)));
+}
@@ -0,0 +1,73 @@
+use rw_core::config::Config;
+use rw_core::current::{
+    adc_to_current_ma, OvercurrentMonitor, CURRENT_LIMITED_TRIP_MS, OVERCURRENT_CLEAR_MS, OVERCURRENT_HYSTERESIS_MA,
+    OVERCURRENT_TRIP_MS,
+};
+
+const MAX_MA: u16 = 1500;
+const TRIP_US: u64 = OVERCURRENT_TRIP_MS as u64 * 1000;
+const CLEAR_US: u64 = OVERCURRENT_CLEAR_MS as u64 * 1000;
+
+#[test]
+fn adc_conversion() {
+    assert_eq!(adc_to_current_ma(0), 0);
+    // 3.3V across 0.2 ohm
+    assert_eq!(adc_to_current_ma(4095), 16500);
+    // ~200mV -> ~1A
+    assert_eq!(adc_to_current_ma(249), 1000);
+}
+
+#[test]
+fn short_spike_does_not_trip() {
+    let mut monitor = OvercurrentMonitor::new();
+    assert!(!monitor.update(0, 3000, MAX_MA));
+    assert!(!monitor.update(TRIP_US - 1, 3000, MAX_MA));
+    assert!(!monitor.update(TRIP_US, 100, MAX_MA));
+    assert!(!monitor.update(TRIP_US + 1, 3000, MAX_MA));
+}
+
+#[test]
+fn sustained_overcurrent_trips() {
+    let mut monitor = OvercurrentMonitor::new();
+    monitor.update(0, 3000, MAX_MA);
+    assert!(monitor.update(TRIP_US, 3000, MAX_MA));
+    assert!(monitor.is_tripped());
+}
+
+#[test]
+fn clears_only_below_hysteresis_band() {
+    let mut monitor = OvercurrentMonitor::new();
+    monitor.update(0, 3000, MAX_MA);
+    monitor.update(TRIP_US, 3000, MAX_MA);
+
+    // Just under the threshold but inside the hysteresis band: stays tripped
+    let inside = MAX_MA - OVERCURRENT_HYSTERESIS_MA / 2;
+    monitor.update(TRIP_US, inside, MAX_MA);
+    assert!(monitor.update(TRIP_US + 2 * CLEAR_US, inside, MAX_MA));
+
+    let below = MAX_MA - OVERCURRENT_HYSTERESIS_MA - 1;
+    let start = TRIP_US + 2 * CLEAR_US;
+    assert!(monitor.update(start, below, MAX_MA));
+    assert!(monitor.update(start + CLEAR_US - 1, below, MAX_MA));
+    assert!(!monitor.update(start + CLEAR_US, below, MAX_MA));
+}
+
+#[test]
+fn zero_threshold_disables() {
+    let mut monitor = OvercurrentMonitor::new();
+    monitor.update(0, 3000, 0);
+    assert!(!monitor.update(TRIP_US * 10, u16::MAX, 0));
+}
+
+#[test]
+fn soft_limit_delays_the_trip() {
+    assert_eq!(Config::default().overcurrent_trip_ms(), OVERCURRENT_TRIP_MS);
+    let config = Config { current_limit_ma: 1000, ..Config::default() };
+    let trip_ms = config.overcurrent_trip_ms();
+    assert_eq!(trip_ms, CURRENT_LIMITED_TRIP_MS);
+
+    let mut monitor = OvercurrentMonitor::new();
+    assert!(!monitor.update_with_trip(0, 3000, MAX_MA, trip_ms));
+    assert!(!monitor.update_with_trip(TRIP_US, 3000, MAX_MA, trip_ms));
+    assert!(monitor.update_with_trip(trip_ms as u64 * 1000, 3000, MAX_MA, trip_ms));
+}
@@ -0,0 +1,48 @@
+use rw_core::config::Config;
+use rw_core::curve::SpeedCurve;
+use rw_core::motor::MotorSpeed;
+
+#[test]
+fn linear_is_identity() {
+    for magnitude in [0, 1, 100, 16384, 32767] {
+        assert_eq!(SpeedCurve::Linear.apply(magnitude), magnitude);
+    }
+}
+
+#[test]
+fn sqrt_table_points() {
+    assert_eq!(SpeedCurve::Sqrt.apply(0), 0);
+    assert_eq!(SpeedCurve::Sqrt.apply(32767), 32767);
+    // Input 1/4 of full scale is exactly table point 4 -> sqrt(1/4) = 1/2
+    assert_eq!(SpeedCurve::Sqrt.apply(8192), 16384);
+}
+
+#[test]
+fn sqrt_interpolates_linearly_between_points() {
+    // Halfway between table points 4 and 5 (8192 and 10240 of input)
+    let mid = SpeedCurve::Sqrt.apply(9216);
+    assert!((mid as i32 - (16384 + 18317) / 2).abs() <= 1);
+}
+
+#[test]
+fn sqrt_monotonic_and_nonzero() {
+    let mut prev = 0;
+    for magnitude in 1..=32767u16 {
+        let out = SpeedCurve::Sqrt.apply(magnitude);
+        assert!(out > 0 && out >= prev, "at {}", magnitude);
+        prev = out;
+    }
+}
+
+#[test]
+fn curve_applies_to_both_directions() {
+    let config = Config { curve: SpeedCurve::Sqrt, min_duty: 0, ..Config::default() };
+    assert_eq!(MotorSpeed::new(8192).to_duty_and_direction(&config), (50, true));
+    assert_eq!(MotorSpeed::new(-8192).to_duty_and_direction(&config), (50, false));
+    assert_eq!(MotorSpeed::new(-8192).to_counts_and_direction(&config), (1250, false));
+}
+
+#[test]
+fn large_magnitude_clamped() {
+    assert_eq!(SpeedCurve::Sqrt.apply(32768), 32767);
+}
@@ -0,0 +1,52 @@
+use rw_core::deadline::Deadline;
+
+#[test]
+fn elapses_at_the_deadline() {
+    let deadline = Deadline::after_ms(1_000_000, 150);
+    assert_eq!(deadline.at_us(), 1_150_000);
+    assert!(!deadline.is_elapsed(1_149_999));
+    assert!(deadline.is_elapsed(1_150_000));
+    assert!(deadline.is_elapsed(2_000_000));
+    assert_eq!(deadline.remaining_us(1_100_000), 50_000);
+    assert_eq!(deadline.remaining_us(1_200_000), 0);
+}
+
+#[test]
+fn earlier_timestamp_is_not_elapsed() {
+    // A deadline set from a later timestamp than the one checked (stale `now_us`)
+    let deadline = Deadline::after_us(1_000_000, 0);
+    assert!(!deadline.is_elapsed(0));
+    assert_eq!(deadline.remaining_us(0), 1_000_000);
+}
+
+#[test]
+fn handles_wraparound() {
+    let now_us = u64::MAX - 499;
+    let deadline = Deadline::after_us(now_us, 1000);
+    assert_eq!(deadline.at_us(), 500);
+    assert!(!deadline.is_elapsed(now_us));
+    assert!(!deadline.is_elapsed(u64::MAX));
+    assert!(!deadline.is_elapsed(499));
+    assert!(deadline.is_elapsed(500));
+    assert_eq!(deadline.remaining_us(u64::MAX), 501);
+}
+
+#[test]
+fn fixed_rate_schedule_skips_missed_periods() {
+    let tick = Deadline::at(10_000);
+    // On time: exactly one period on, whenever in the period it was handled
+    assert_eq!(tick.next(1000, 10_000), Deadline::at(11_000));
+    assert_eq!(tick.next(1000, 10_400), Deadline::at(11_000));
+
+    // Late by several periods: due again at once, not once per missed period
+    assert_eq!(tick.next(1000, 15_300), Deadline::at(15_300));
+    assert_eq!(Deadline::at(u64::MAX - 100).next(1000, u64::MAX), Deadline::at(899));
+}
+
+#[test]
+fn time_since_a_deadline() {
+    let start = Deadline::at(u64::MAX - 99);
+    assert_eq!(start.elapsed_us(u64::MAX - 100), 0);
+    assert_eq!(start.elapsed_us(u64::MAX - 99), 0);
+    assert_eq!(start.elapsed_us(400), 500);
+}
@@ -0,0 +1,61 @@
+use rw_core::demo::{
+    demo_speed, DemoButton, DemoSpin, DEMO_BUTTON_DEBOUNCE_MS, DEMO_HALF_CYCLE_MS, DEMO_HOLD_MS, DEMO_RAMP_MS,
+    DEMO_SPEED,
+};
+use rw_core::motor::MotorSpeed;
+
+#[test]
+fn pattern_ramps_holds_and_pauses() {
+    assert_eq!(demo_speed(0), MotorSpeed::STOP);
+    assert_eq!(demo_speed(DEMO_RAMP_MS / 2), MotorSpeed::new(DEMO_SPEED / 2));
+    assert_eq!(demo_speed(DEMO_RAMP_MS), MotorSpeed::new(DEMO_SPEED));
+    assert_eq!(demo_speed(DEMO_RAMP_MS + DEMO_HOLD_MS - 1), MotorSpeed::new(DEMO_SPEED));
+    assert_eq!(demo_speed(DEMO_RAMP_MS + DEMO_HOLD_MS + DEMO_RAMP_MS / 2), MotorSpeed::new(DEMO_SPEED / 2));
+    assert_eq!(demo_speed(2 * DEMO_RAMP_MS + DEMO_HOLD_MS), MotorSpeed::STOP);
+    assert_eq!(demo_speed(DEMO_HALF_CYCLE_MS - 1), MotorSpeed::STOP);
+}
+
+#[test]
+fn pattern_alternates_direction_and_loops() {
+    assert_eq!(demo_speed(DEMO_HALF_CYCLE_MS + DEMO_RAMP_MS), MotorSpeed::new(-DEMO_SPEED));
+    assert_eq!(demo_speed(2 * DEMO_HALF_CYCLE_MS + DEMO_RAMP_MS), MotorSpeed::new(DEMO_SPEED));
+
+    // Small steps only: every tick changes the target by at most one ramp increment
+    let max_step = (DEMO_SPEED as u64).div_ceil(DEMO_RAMP_MS) as u16;
+    for t_ms in 1..2 * DEMO_HALF_CYCLE_MS {
+        let step = demo_speed(t_ms).speed_normalized.abs_diff(demo_speed(t_ms - 1).speed_normalized);
+        assert!(step <= max_step, "jump of {step} at {t_ms}ms");
+    }
+}
+
+#[test]
+fn spin_runs_from_its_start() {
+    let spin = DemoSpin::new(5_000_000);
+    assert_eq!(spin.update(5_000_000), MotorSpeed::STOP);
+    assert_eq!(spin.update(5_000_000 + DEMO_RAMP_MS * 1000), MotorSpeed::new(DEMO_SPEED));
+}
+
+#[test]
+fn button_press_is_debounced() {
+    let mut button = DemoButton::new();
+    // Contact bounce
+    assert!(!button.update(0, true));
+    assert!(!button.update(5000, false));
+    assert!(!button.update(10_000, true));
+    assert!(!button.update((10 + DEMO_BUTTON_DEBOUNCE_MS) * 1000 - 1, true));
+    assert!(button.update((10 + DEMO_BUTTON_DEBOUNCE_MS) * 1000, true));
+    // Held: one press only
+    assert!(!button.update(1_000_000, true));
+    // Release, then the next press
+    assert!(!button.update(2_000_000, false));
+    assert!(!button.update((2000 + DEMO_BUTTON_DEBOUNCE_MS) * 1000, false));
+    assert!(!button.update(3_000_000, true));
+    assert!(button.update((3000 + DEMO_BUTTON_DEBOUNCE_MS) * 1000, true));
+}
+
+#[test]
+fn button_held_at_boot_counts_as_a_press() {
+    let mut button = DemoButton::new();
+    assert!(!button.update(0, true));
+    assert!(button.update(DEMO_BUTTON_DEBOUNCE_MS * 1000, true));
+}
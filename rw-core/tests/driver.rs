@@ -0,0 +1,117 @@
+use rw_core::config::Config;
+use rw_core::deadline::Deadline;
+use rw_core::driver::{DriverEnable, DriverState, IdleSleep, IdleState, DRIVER_WAKE_US};
+
+#[test]
+fn starts_asleep() {
+    let driver = DriverEnable::new();
+    assert!(driver.is_asleep());
+    assert!(!driver.is_awake());
+}
+
+#[test]
+fn awake_after_wake_time() {
+    let mut driver = DriverEnable::new();
+    driver.wake(1000);
+    assert_eq!(driver.state(), DriverState::Waking { ready: Deadline::at(1000 + DRIVER_WAKE_US) });
+    assert!(!driver.update(1000 + DRIVER_WAKE_US - 1));
+    assert!(driver.update(1000 + DRIVER_WAKE_US));
+    assert!(driver.is_awake());
+    // Reported once
+    assert!(!driver.update(1000 + 2 * DRIVER_WAKE_US));
+}
+
+#[test]
+fn wake_while_waking_keeps_start_time() {
+    let mut driver = DriverEnable::new();
+    driver.wake(0);
+    driver.wake(500);
+    assert!(driver.update(DRIVER_WAKE_US));
+}
+
+#[test]
+fn sleep_requires_a_new_wake_time() {
+    let mut driver = DriverEnable::new();
+    driver.wake(0);
+    driver.update(DRIVER_WAKE_US);
+    driver.sleep();
+    assert!(driver.is_asleep());
+    assert!(!driver.update(10 * DRIVER_WAKE_US));
+
+    driver.wake(10 * DRIVER_WAKE_US);
+    assert!(!driver.is_awake());
+}
+
+fn idle_config(idle_sleep_ms: u16) -> Config {
+    Config { idle_sleep_ms, ..Config::default() }
+}
+
+#[test]
+fn idle_sleep_after_the_delay() {
+    let config = idle_config(100);
+    let mut idle = IdleSleep::new();
+    assert!(!idle.update(0, true, &config));
+    assert!(!idle.update(99_999, true, &config));
+    assert!(idle.update(100_000, true, &config));
+    assert!(idle.is_asleep());
+    // Reported once
+    assert!(!idle.update(200_000, true, &config));
+}
+
+#[test]
+fn idle_time_restarts_when_busy_or_on_host_reports() {
+    let config = idle_config(100);
+    let mut idle = IdleSleep::new();
+    idle.update(0, true, &config);
+    idle.update(50_000, false, &config);
+    assert!(!idle.update(60_000, true, &config));
+    assert!(!idle.update(150_000, true, &config));
+    assert!(!idle.activity(false));
+    assert!(!idle.update(200_000, true, &config));
+    assert!(idle.update(300_000, true, &config));
+}
+
+#[test]
+fn host_report_wakes_from_idle_sleep() {
+    let config = idle_config(10);
+    let mut idle = IdleSleep::new();
+    idle.update(0, true, &config);
+    idle.update(10_000, true, &config);
+    assert!(idle.activity(false));
+    assert_eq!(idle.state(), IdleState::Waking);
+    // No new sleep until the driver is awake again and a full delay passes
+    assert!(!idle.update(100_000, true, &config));
+    idle.awake();
+    assert!(!idle.update(100_000, true, &config));
+    assert!(idle.update(110_000, true, &config));
+}
+
+#[test]
+fn idle_sleep_off_by_default() {
+    let config = Config::default();
+    let mut idle = IdleSleep::new();
+    idle.update(0, true, &config);
+    assert!(!idle.update(u32::MAX as u64, true, &config));
+    assert!(!idle.is_asleep());
+}
+
+#[test]
+fn no_wake_from_idle_sleep_while_faulted() {
+    let config = idle_config(10);
+    let mut idle = IdleSleep::new();
+    idle.update(0, true, &config);
+    idle.update(10_000, true, &config);
+    // The fault holds the driver disabled: still asleep, nothing to take commands for
+    assert!(!idle.activity(true));
+    assert!(idle.is_asleep());
+    assert!(!idle.is_waking());
+
+    // A fault that starts while waking sends it back to sleep
+    assert!(idle.activity(false));
+    idle.block_wake();
+    assert!(idle.is_asleep());
+
+    // The next report after the fault clears wakes it
+    assert!(idle.activity(false));
+    assert!(idle.is_waking());
+}
@@ -0,0 +1,39 @@
+use rw_core::encoder::{quadrature_step, RpmEstimator, ENCODER_COUNTS_PER_REV, RPM_WINDOW_MS};
+
+const FORWARD: [u8; 4] = [0b00, 0b10, 0b11, 0b01];
+
+#[test]
+fn forward_sequence_counts_up() {
+    for i in 0..4 {
+        assert_eq!(quadrature_step(FORWARD[i], FORWARD[(i + 1) % 4]), 1);
+    }
+}
+
+#[test]
+fn reverse_sequence_counts_down() {
+    for i in 0..4 {
+        assert_eq!(quadrature_step(FORWARD[(i + 1) % 4], FORWARD[i]), -1);
+    }
+}
+
+#[test]
+fn no_change_or_missed_edge_counts_zero() {
+    assert_eq!(quadrature_step(0b00, 0b00), 0);
+    assert_eq!(quadrature_step(0b00, 0b11), 0);
+    assert_eq!(quadrature_step(0b10, 0b01), 0);
+}
+
+#[test]
+fn rpm_over_window() {
+    let window_us = RPM_WINDOW_MS as u64 * 1000;
+    let mut rpm = RpmEstimator::new();
+
+    // Not enough time elapsed yet
+    assert_eq!(rpm.update(window_us - 1, 10), None);
+
+    // One revolution per window = 600 RPM for a 100ms window
+    let counts = ENCODER_COUNTS_PER_REV as i32;
+    assert_eq!(rpm.update(window_us, counts), Some(600));
+    assert_eq!(rpm.update(2 * window_us, 0), Some(-600));
+    assert_eq!(rpm.rpm(), -600);
+}
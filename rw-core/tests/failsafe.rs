@@ -0,0 +1,138 @@
+use rw_core::config::FaultResponse;
+use rw_core::control::{MotorOutput, CONTROL_TICK_US};
+use rw_core::failsafe::{is_command_timed_out, EStopRamp, COMMAND_TIMEOUT_MS, ESTOP_BRAKE_TIMEOUT_MS, ESTOP_RAMP_MS};
+use rw_core::motor::PWM_TOP;
+use rw_core::wheel::{WheelModel, WheelParams};
+
+const TIMEOUT_US: u64 = COMMAND_TIMEOUT_MS as u64 * 1000;
+const RAMP_US: u64 = ESTOP_RAMP_MS as u64 * 1000;
+
+#[test]
+fn not_timed_out_within_window() {
+    assert!(!is_command_timed_out(1_000_000, 1_000_000));
+    assert!(!is_command_timed_out(1_000_000 + TIMEOUT_US, 1_000_000));
+}
+
+#[test]
+fn timed_out_after_window() {
+    assert!(is_command_timed_out(1_000_000 + TIMEOUT_US + 1, 1_000_000));
+}
+
+#[test]
+fn command_newer_than_now_is_not_timed_out() {
+    assert!(!is_command_timed_out(0, 1_000_000));
+}
+
+#[test]
+fn ramp_builds_up_to_the_full_brake() {
+    let mut ramp = EStopRamp::new();
+    ramp.start(1000);
+    assert_eq!(ramp.output(1000), Some(MotorOutput::PartialBrake(0)));
+    assert_eq!(ramp.output(1000 + RAMP_US / 2), Some(MotorOutput::PartialBrake(PWM_TOP / 2)));
+    assert_eq!(ramp.output(1000 + RAMP_US), Some(MotorOutput::Brake));
+    // Starting again keeps the ramp going from where it is
+    ramp.start(1000 + RAMP_US / 2);
+    assert_eq!(ramp.output(1000 + RAMP_US), Some(MotorOutput::Brake));
+}
+
+#[test]
+fn ramped_stop_ends_once_the_wheel_is_stopped() {
+    let mut ramp = EStopRamp::new();
+    ramp.start(0);
+    // Not before the brake is fully on, nor while still turning
+    assert!(!ramp.update(RAMP_US / 2, Some(300)));
+    assert!(!ramp.update(RAMP_US / 2 + 1000, Some(0)));
+    assert!(!ramp.update(RAMP_US, Some(120)));
+    assert!(!ramp.update(RAMP_US + 1000, None));
+    assert!(ramp.update(RAMP_US + 2000, Some(0)));
+    assert!(!ramp.is_running());
+    assert_eq!(ramp.output(RAMP_US + 2000), None);
+    assert!(!ramp.update(RAMP_US + 3000, Some(0)));
+}
+
+#[test]
+fn ramped_stop_gives_up_on_a_wheel_that_keeps_turning() {
+    let mut ramp = EStopRamp::new();
+    ramp.start(0);
+    assert!(!ramp.update(ESTOP_BRAKE_TIMEOUT_MS as u64 * 1000 - 1, Some(50)));
+    assert!(ramp.update(ESTOP_BRAKE_TIMEOUT_MS as u64 * 1000, Some(50)));
+}
+
+#[test]
+fn ramped_stop_without_an_encoder_brakes_until_the_timeout() {
+    let brake_timeout_us = ESTOP_BRAKE_TIMEOUT_MS as u64 * 1000;
+    let mut ramp = EStopRamp::new();
+    ramp.start(0);
+    // No counts ever seen: 0 RPM proves nothing, the full brake stays on
+    let mut now_us = 0;
+    while now_us < brake_timeout_us {
+        assert!(!ramp.update(now_us, Some(0)), "ended at {now_us}us");
+        now_us += 10_000;
+    }
+    assert_eq!(ramp.output(brake_timeout_us - 1), Some(MotorOutput::Brake));
+    assert!(ramp.update(brake_timeout_us, Some(0)));
+}
+
+#[test]
+fn restarted_ramp_needs_the_wheel_to_turn_again() {
+    let mut ramp = EStopRamp::new();
+    ramp.start(0);
+    assert!(!ramp.update(RAMP_US / 2, Some(300)));
+    assert!(ramp.abort());
+    ramp.start(RAMP_US);
+    assert!(!ramp.update(2 * RAMP_US, Some(0)));
+}
+
+#[test]
+fn aborted_ramp_never_ends() {
+    let mut ramp = EStopRamp::new();
+    ramp.start(0);
+    assert!(ramp.abort());
+    assert!(!ramp.update(RAMP_US, Some(0)));
+    assert!(!ramp.abort());
+}
+
+#[test]
+fn fault_response_does_not_override_a_running_ramp() {
+    let mut ramp = EStopRamp::new();
+    let driven = MotorOutput::Forward(PWM_TOP / 2);
+    assert_eq!(ramp.with_fault_response(driven, None), driven);
+    assert_eq!(ramp.with_fault_response(driven, Some(FaultResponse::Brake)), MotorOutput::Brake);
+    assert_eq!(ramp.with_fault_response(driven, Some(FaultResponse::Coast)), MotorOutput::Coast);
+    assert_eq!(ramp.with_fault_response(driven, Some(FaultResponse::DisableDriver)), MotorOutput::Coast);
+
+    // A stall or overcurrent tripping mid-ramp leaves the brake on
+    ramp.start(0);
+    let braking = ramp.output(RAMP_US / 2).unwrap();
+    for response in [FaultResponse::Brake, FaultResponse::Coast, FaultResponse::DisableDriver] {
+        assert_eq!(ramp.with_fault_response(braking, Some(response)), braking);
+    }
+}
+
+#[test]
+fn ramped_stop_slows_the_wheel_faster_than_coasting() {
+    let mut spinning = WheelModel::new(WheelParams::default());
+    for _ in 0..2000 {
+        spinning.step(MotorOutput::Forward(PWM_TOP), CONTROL_TICK_US);
+    }
+    let (mut ramped, mut coasting, mut braked) = (spinning, spinning, spinning);
+    let mut ramp = EStopRamp::new();
+    ramp.start(0);
+    let mut now_us = 0;
+    while now_us < RAMP_US {
+        ramped.step(ramp.output(now_us).unwrap(), CONTROL_TICK_US);
+        coasting.step(MotorOutput::Coast, CONTROL_TICK_US);
+        braked.step(MotorOutput::Brake, CONTROL_TICK_US);
+        now_us += CONTROL_TICK_US;
+    }
+    // Braking, but gentler than the full brake from the start
+    assert!(ramped.rpm() < coasting.rpm(), "{} vs {}", ramped.rpm(), coasting.rpm());
+    assert!(ramped.rpm() > braked.rpm(), "{} vs {}", ramped.rpm(), braked.rpm());
+
+    while now_us < 200_000 {
+        ramped.step(ramp.output(now_us).unwrap(), CONTROL_TICK_US);
+        coasting.step(MotorOutput::Coast, CONTROL_TICK_US);
+        now_us += CONTROL_TICK_US;
+    }
+    assert!(ramped.rpm() < coasting.rpm() * 0.5, "{} vs {}", ramped.rpm(), coasting.rpm());
+}
@@ -0,0 +1,66 @@
+use rw_core::config::{Config, FaultResponse};
+use rw_core::fault::{drive_response, Fault, FaultLog};
+
+#[test]
+fn events_latch_until_cleared() {
+    let mut log = FaultLog::new();
+    log.record(Fault::INVALID_REPORT);
+    assert!(log.active().is_empty());
+    assert_eq!(log.latched(), Fault::INVALID_REPORT);
+
+    log.clear(Fault::INVALID_REPORT);
+    assert!(log.latched().is_empty());
+}
+
+#[test]
+fn condition_stays_latched_after_it_ends() {
+    let mut log = FaultLog::new();
+    log.set_active(Fault::OVERCURRENT);
+    log.set_active(Fault::NONE);
+    assert!(log.active().is_empty());
+    assert_eq!(log.latched(), Fault::OVERCURRENT);
+}
+
+#[test]
+fn clearing_keeps_active_conditions() {
+    let mut log = FaultLog::new();
+    log.set_active(Fault::OVER_TEMPERATURE);
+    log.record(Fault::INVALID_REPORT);
+
+    log.clear(Fault::from_bits(0xFF));
+    assert_eq!(log.latched(), Fault::OVER_TEMPERATURE);
+}
+
+#[test]
+fn clear_only_touches_masked_bits() {
+    let mut log = FaultLog::new();
+    log.record(Fault::INVALID_REPORT | Fault::COMMAND_TIMEOUT);
+    log.clear(Fault::COMMAND_TIMEOUT);
+    assert_eq!(log.latched(), Fault::INVALID_REPORT);
+    assert!(!log.latched().contains(Fault::COMMAND_TIMEOUT));
+}
+
+#[test]
+fn drive_response_defaults_to_disabling_the_driver() {
+    let config = Config::default();
+    assert_eq!(drive_response(Fault::NONE, &config), None);
+    for fault in [Fault::OVERCURRENT, Fault::OVER_TEMPERATURE, Fault::UNDERVOLTAGE, Fault::STALL] {
+        assert_eq!(drive_response(fault, &config), Some(FaultResponse::DisableDriver));
+    }
+    // Faults that don't cut the drive have no response
+    assert_eq!(drive_response(Fault::COMMAND_TIMEOUT | Fault::NO_FEEDBACK, &config), None);
+}
+
+#[test]
+fn least_drive_wins() {
+    let config = Config {
+        overcurrent_response: FaultResponse::Coast,
+        over_temperature_response: FaultResponse::Brake,
+        undervoltage_response: FaultResponse::DisableDriver,
+        stall_response: FaultResponse::Brake,
+        ..Config::default()
+    };
+    assert_eq!(drive_response(Fault::STALL, &config), Some(FaultResponse::Brake));
+    assert_eq!(drive_response(Fault::STALL | Fault::OVERCURRENT, &config), Some(FaultResponse::Coast));
+    assert_eq!(drive_response(Fault::OVERCURRENT | Fault::UNDERVOLTAGE, &config), Some(FaultResponse::DisableDriver));
+}
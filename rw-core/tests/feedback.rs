@@ -0,0 +1,49 @@
+use rw_core::config::{Config, ControlMode};
+use rw_core::feedback::{FeedbackMonitor, FEEDBACK_TIMEOUT_MS};
+
+const TIMEOUT_US: u64 = FEEDBACK_TIMEOUT_MS as u64 * 1000;
+
+fn closed_loop() -> Config {
+    Config { control_mode: ControlMode::ClosedLoop, ..Config::default() }
+}
+
+#[test]
+fn trips_after_the_timeout_without_counts() {
+    let config = closed_loop();
+    let mut monitor = FeedbackMonitor::new();
+    assert!(!monitor.update(0, 1000, 0, &config));
+    assert!(!monitor.update(TIMEOUT_US - 1, 1000, 0, &config));
+    assert!(monitor.update(TIMEOUT_US, 1000, 0, &config));
+    assert!(monitor.is_lost());
+    // Reported once, then latched
+    assert!(!monitor.update(TIMEOUT_US + 1000, 1000, 0, &config));
+    assert!(!monitor.update(TIMEOUT_US + 2000, 1000, 1500, &config));
+    assert!(monitor.is_lost());
+
+    monitor.clear();
+    assert!(!monitor.is_lost());
+}
+
+#[test]
+fn counts_or_no_drive_restart_the_timer() {
+    let config = closed_loop();
+    let mut monitor = FeedbackMonitor::new();
+    monitor.update(0, 1000, 0, &config);
+    monitor.update(TIMEOUT_US / 2, 1000, 12, &config);
+    assert!(!monitor.update(TIMEOUT_US, 1000, 0, &config));
+
+    monitor.update(2 * TIMEOUT_US - 1, 0, 0, &config);
+    assert!(!monitor.update(2 * TIMEOUT_US, 1000, 0, &config));
+    assert!(monitor.update(3 * TIMEOUT_US, 1000, 0, &config));
+}
+
+#[test]
+fn only_watched_in_closed_loop() {
+    for config in [Config { feedback_timeout_ms: 0, ..closed_loop() }, Config { control_mode: ControlMode::OpenLoop, ..closed_loop() }] {
+        let mut monitor = FeedbackMonitor::new();
+        for tick in 0..=2 * FEEDBACK_TIMEOUT_MS as u64 {
+            assert!(!monitor.update(tick * 1000, 1000, 0, &config));
+        }
+        assert!(!monitor.is_lost());
+    }
+}
@@ -0,0 +1,59 @@
+use rw_core::control::MotorOutput;
+use rw_core::frame::{Frame, FrameDecoder, FrameError, FRAME_OVERHEAD, FRAME_SYNC, MAX_FRAME_PAYLOAD};
+use rw_core::motor::MotorSpeed;
+use rw_core::protocol::{RWStatusReport, SPEED_REPORT_ID};
+
+fn decode_all(decoder: &mut FrameDecoder, bytes: &[u8]) -> Vec<Result<(u8, Vec<u8>), FrameError>> {
+    bytes
+        .iter()
+        .filter_map(|&byte| decoder.push(byte).map(|result| result.map(|(id, payload)| (id, payload.to_vec()))))
+        .collect()
+}
+
+#[test]
+fn frame_layout() {
+    let frame = Frame::new(7, &[1, 2, 3]).unwrap();
+    let bytes = frame.as_bytes();
+    assert_eq!(bytes.len(), 3 + FRAME_OVERHEAD);
+    assert_eq!(&bytes[..6], &[0xA5, 0x5A, 7, 3, 1, 2]);
+    assert_eq!(bytes[7], rw_core::crc::crc8(&[7, 3, 1, 2, 3]));
+
+    assert!(Frame::new(7, &[0; MAX_FRAME_PAYLOAD]).is_some());
+    assert!(Frame::new(7, &[0; MAX_FRAME_PAYLOAD + 1]).is_none());
+    assert!(Frame::from_report(&[]).is_none());
+}
+
+#[test]
+fn status_report_round_trips() {
+    let report = RWStatusReport::new(MotorSpeed::new(-12000), MotorOutput::Reverse(900), -1500, 312, 0).to_report();
+    let frame = Frame::from_report(&report).unwrap();
+    let mut decoder = FrameDecoder::new();
+    let frames = decode_all(&mut decoder, frame.as_bytes());
+    assert_eq!(frames, [Ok((SPEED_REPORT_ID, report[1..].to_vec()))]);
+}
+
+#[test]
+fn decoder_resyncs_after_noise_and_bad_frames() {
+    let good = Frame::new(1, &[0xA5, 0x5A, 9]).unwrap();
+    let mut corrupted = Frame::new(7, &[4, 5]).unwrap().as_bytes().to_vec();
+    corrupted[4] ^= 0x10;
+
+    let mut stream = vec![0x00, 0xA5, 0xA5];
+    stream.extend_from_slice(&FRAME_SYNC);
+    stream.extend_from_slice(&[1, (MAX_FRAME_PAYLOAD + 1) as u8]);
+    stream.extend_from_slice(&corrupted);
+    stream.extend_from_slice(good.as_bytes());
+
+    let mut decoder = FrameDecoder::new();
+    let frames = decode_all(&mut decoder, &stream);
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0], Err(FrameError::Length(MAX_FRAME_PAYLOAD as u8 + 1)));
+    assert!(matches!(frames[1], Err(FrameError::Checksum { .. })));
+    assert_eq!(frames[2], Ok((1, vec![0xA5, 0x5A, 9])));
+}
+
+#[test]
+fn empty_payload() {
+    let mut decoder = FrameDecoder::new();
+    assert_eq!(decode_all(&mut decoder, Frame::new(3, &[]).unwrap().as_bytes()), [Ok((3, vec![]))]);
+}
@@ -0,0 +1,78 @@
+use rw_core::idle::{IdleRate, IdleTimer, DEFAULT_IDLE_RATE, TELEMETRY_ON_CHANGE};
+
+#[test]
+fn idle_rate_units() {
+    assert_eq!(IdleRate::INDEFINITE.period_ms(), None);
+    assert_eq!(IdleRate::from_raw(1).period_ms(), Some(4));
+    assert_eq!(DEFAULT_IDLE_RATE.period_ms(), Some(100));
+}
+
+#[test]
+fn first_report_is_always_due() {
+    let timer = IdleTimer::<2>::new();
+    assert!(timer.is_due(IdleRate::INDEFINITE, 0, &[0, 0]));
+}
+
+#[test]
+fn unchanged_report_waits_for_idle_period() {
+    let rate = IdleRate::from_raw(25);
+    let mut timer = IdleTimer::new();
+    timer.sent([1, 2], 1_000_000);
+    assert!(!timer.is_due(rate, 1_099_000, &[1, 2]));
+    assert!(timer.is_due(rate, 1_100_000, &[1, 2]));
+}
+
+#[test]
+fn changed_report_is_due_at_once() {
+    let mut timer = IdleTimer::new();
+    timer.sent([1, 2], 1_000_000);
+    assert!(timer.is_due(IdleRate::from_raw(25), 1_001_000, &[1, 3]));
+}
+
+#[test]
+fn indefinite_rate_sends_only_on_change() {
+    let mut timer = IdleTimer::new();
+    timer.sent([1, 2], 0);
+    assert!(!timer.is_due(IdleRate::INDEFINITE, 1_000_000_000, &[1, 2]));
+    assert!(timer.is_due(IdleRate::INDEFINITE, 1_000_000_000, &[0, 2]));
+}
+
+#[test]
+fn reset_forces_next_report() {
+    let mut timer = IdleTimer::new();
+    timer.sent([1, 2], 0);
+    timer.reset();
+    assert!(timer.is_due(IdleRate::INDEFINITE, 1000, &[1, 2]));
+}
+
+#[test]
+fn telemetry_period_pushes_at_fixed_cadence() {
+    let mut timer = IdleTimer::new();
+    timer.sent([1, 2], 1_000_000);
+    // A change alone does not send, an unchanged report still goes out on time
+    assert!(!timer.is_due_at(20, DEFAULT_IDLE_RATE, 1_019_000, &[1, 3]));
+    assert!(timer.is_due_at(20, DEFAULT_IDLE_RATE, 1_020_000, &[1, 2]));
+}
+
+#[test]
+fn telemetry_period_zero_never_pushes() {
+    let timer = IdleTimer::<2>::new();
+    assert!(!timer.is_due_at(0, DEFAULT_IDLE_RATE, 0, &[0, 0]));
+}
+
+#[test]
+fn telemetry_on_change_follows_idle_rate() {
+    let mut timer = IdleTimer::new();
+    timer.sent([1, 2], 1_000_000);
+    assert!(timer.is_due_at(TELEMETRY_ON_CHANGE, IdleRate::INDEFINITE, 1_001_000, &[1, 3]));
+    assert!(!timer.is_due_at(TELEMETRY_ON_CHANGE, IdleRate::INDEFINITE, 5_000_000, &[1, 2]));
+}
+
+#[test]
+fn idle_period_runs_across_timer_wraparound() {
+    let rate = IdleRate::from_raw(25);
+    let mut timer = IdleTimer::new();
+    timer.sent([1, 2], u64::MAX - 49_999);
+    assert!(!timer.is_due(rate, 49_999, &[1, 2]));
+    assert!(timer.is_due(rate, 50_000, &[1, 2]));
+}
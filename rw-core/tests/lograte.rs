@@ -0,0 +1,41 @@
+use rw_core::config::Config;
+use rw_core::lograte::{LogLimiter, LogLine};
+
+#[test]
+fn first_line_and_key_changes_log_at_once() {
+    let config = Config::default();
+    let mut limiter = LogLimiter::new();
+    assert_eq!(limiter.offer(0, 1, 'a', &config), Some(LogLine { value: 'a', suppressed: 0 }));
+    assert_eq!(limiter.offer(10_000, 2, 'b', &config), Some(LogLine { value: 'b', suppressed: 0 }));
+}
+
+#[test]
+fn repeats_log_once_per_interval_with_count() {
+    let config = Config { log_interval_ms: 100, ..Config::default() };
+    let mut limiter = LogLimiter::new();
+    limiter.offer(0, 1, 'a', &config);
+    for now_us in (10_000..100_000).step_by(10_000) {
+        assert_eq!(limiter.offer(now_us, 1, 'a', &config), None);
+    }
+    assert_eq!(limiter.offer(100_000, 1, 'a', &config), Some(LogLine { value: 'a', suppressed: 9 }));
+}
+
+#[test]
+fn flush_prints_the_last_dropped_value_after_the_interval() {
+    let config = Config { log_interval_ms: 100, ..Config::default() };
+    let mut limiter = LogLimiter::new();
+    limiter.offer(0, 1, 10, &config);
+    limiter.offer(1000, 1, 11, &config);
+    limiter.offer(2000, 1, 12, &config);
+    assert_eq!(limiter.flush(50_000, &config), None);
+    assert_eq!(limiter.flush(100_000, &config), Some(LogLine { value: 12, suppressed: 1 }));
+    assert_eq!(limiter.flush(300_000, &config), None);
+}
+
+#[test]
+fn zero_interval_logs_every_line() {
+    let config = Config { log_interval_ms: 0, ..Config::default() };
+    let mut limiter = LogLimiter::new();
+    limiter.offer(0, 1, 'a', &config);
+    assert_eq!(limiter.offer(0, 1, 'a', &config), Some(LogLine { value: 'a', suppressed: 0 }));
+}
@@ -0,0 +1,45 @@
+use rw_core::config::Config;
+use rw_core::control::MotorController;
+use rw_core::looptime::{LoopTimer, LOOP_OVERRUN_US};
+use rw_core::protocol::RWTelemetryReport;
+use zerocopy::IntoBytes;
+
+#[test]
+fn keeps_the_worst_case_of_the_window() {
+    let mut timer = LoopTimer::new();
+    assert_eq!(timer.record(1_000, 1_120), None);
+    assert_eq!(timer.record(2_000, 2_480), None);
+    assert_eq!(timer.record(3_000, 3_050), None);
+    assert_eq!(timer.max_us(), 480);
+
+    timer.reset();
+    assert_eq!(timer.max_us(), 0);
+    assert_eq!(timer.record(4_000, 4_090), None);
+    assert_eq!(timer.max_us(), 90);
+}
+
+#[test]
+fn longer_than_a_tick_is_an_overrun() {
+    let mut timer = LoopTimer::new();
+    assert_eq!(timer.record(0, LOOP_OVERRUN_US as u64), None);
+    assert_eq!(timer.record(0, LOOP_OVERRUN_US as u64 + 1), Some(LOOP_OVERRUN_US + 1));
+    // A flash write blocks for tens of ms
+    assert_eq!(timer.record(5_000, 45_000), Some(40_000));
+    assert_eq!(timer.max_us(), 40_000);
+}
+
+#[test]
+fn clock_going_backwards_reads_as_zero() {
+    let mut timer = LoopTimer::new();
+    assert_eq!(timer.record(5_000, 4_000), None);
+    assert_eq!(timer.max_us(), 0);
+}
+
+#[test]
+fn telemetry_loop_time_saturates() {
+    let telemetry = RWTelemetryReport::new(0, &MotorController::new(), &Config::default());
+    assert_eq!(telemetry.loop_max_us.get(), 0);
+    let telemetry = telemetry.with_loop_max_us(431);
+    assert_eq!(&telemetry.as_bytes()[12..14], &431u16.to_le_bytes());
+    assert_eq!(telemetry.with_loop_max_us(100_000).loop_max_us.get(), u16::MAX);
+}
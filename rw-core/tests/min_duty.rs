@@ -0,0 +1,90 @@
+use rw_core::control::{MotorOutput, CONTROL_TICK_US};
+use rw_core::min_duty::{MinDutyCalibration, MinDutyCommand, MinDutyResult, MIN_DUTY_STEP_MS};
+use rw_core::motor::percent_to_counts;
+use rw_core::protocol::RWMinDutyCalibrationReport;
+use rw_core::wheel::{WheelModel, WheelParams};
+
+/// Run a calibration against the wheel model, with the encoder channels swapped or not
+fn run_calibration(params: WheelParams, swapped: bool) -> MinDutyResult {
+    let mut calibration = MinDutyCalibration::new();
+    let mut wheel = WheelModel::new(params);
+    calibration.start(0, 0);
+    let mut now_us = 0;
+    loop {
+        let output = calibration.output().expect("calibration ended without a result");
+        wheel.step(output, CONTROL_TICK_US);
+        now_us += CONTROL_TICK_US;
+        let count = if swapped { -wheel.encoder_count() } else { wheel.encoder_count() };
+        if let Some(result) = calibration.update(now_us, count) {
+            assert!(!calibration.is_running());
+            return result;
+        }
+    }
+}
+
+#[test]
+fn finds_the_breakaway_duty() {
+    // The default model breaks loose above 45%
+    let result = run_calibration(WheelParams::default(), false);
+    assert!(matches!(result, MinDutyResult::Found { duty } if (46..=48).contains(&duty)), "{result:?}");
+}
+
+#[test]
+fn motion_counts_in_either_direction() {
+    let params = WheelParams { breakaway_duty: 0.2, ..WheelParams::default() };
+    let result = run_calibration(params, true);
+    assert!(matches!(result, MinDutyResult::Found { duty } if (21..=23).contains(&duty)), "{result:?}");
+}
+
+#[test]
+fn stuck_wheel_finds_nothing() {
+    let params = WheelParams { breakaway_duty: 1.5, ..WheelParams::default() };
+    assert_eq!(run_calibration(params, false), MinDutyResult::NoRotation);
+}
+
+#[test]
+fn duty_ramps_one_percent_per_step() {
+    let mut calibration = MinDutyCalibration::new();
+    calibration.start(1_000_000, 500);
+    assert_eq!(calibration.update(1_000_000, 500), None);
+    assert_eq!(calibration.duty(), Some(0));
+    assert_eq!(calibration.output(), Some(MotorOutput::Forward(0)));
+
+    let step_us = MIN_DUTY_STEP_MS * 1000;
+    assert_eq!(calibration.update(1_000_000 + 30 * step_us - 1, 501), None);
+    assert_eq!(calibration.duty(), Some(29));
+    assert_eq!(calibration.update(1_000_000 + 30 * step_us, 501), None);
+    assert_eq!(calibration.output(), Some(MotorOutput::Forward(percent_to_counts(30))));
+
+    assert_eq!(calibration.update(1_000_000 + 31 * step_us, 498), Some(MinDutyResult::Found { duty: 30 }));
+    assert_eq!(calibration.duty(), None);
+}
+
+#[test]
+fn gives_up_after_holding_full_duty() {
+    let mut calibration = MinDutyCalibration::new();
+    calibration.start(0, 0);
+    let step_us = MIN_DUTY_STEP_MS * 1000;
+    assert_eq!(calibration.update(101 * step_us - 1, 0), None);
+    assert_eq!(calibration.duty(), Some(100));
+    assert_eq!(calibration.update(101 * step_us, 0), Some(MinDutyResult::NoRotation));
+    assert_eq!(calibration.output(), None);
+}
+
+#[test]
+fn abort_stops_driving() {
+    let mut calibration = MinDutyCalibration::new();
+    assert!(!calibration.abort());
+    calibration.start(0, 0);
+    assert!(calibration.output().is_some());
+    assert!(calibration.abort());
+    assert_eq!(calibration.output(), None);
+    assert_eq!(calibration.update(1000, 100), None);
+}
+
+#[test]
+fn min_duty_command_from_report() {
+    assert_eq!(MinDutyCommand::from_report(&RWMinDutyCalibrationReport { command: 1 }), Some(MinDutyCommand::Start));
+    assert_eq!(MinDutyCommand::from_report(&RWMinDutyCalibrationReport { command: 0 }), Some(MinDutyCommand::Abort));
+    assert_eq!(MinDutyCommand::from_report(&RWMinDutyCalibrationReport { command: 2 }), None);
+}
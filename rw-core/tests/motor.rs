@@ -0,0 +1,237 @@
+use rw_core::config::{Config, MIN_DUTY};
+use rw_core::motor::{
+    counts_to_percent, needs_kickstart, normalized_to_percent, percent_to_counts, percent_to_normalized, MotorSpeed,
+    PWM_TOP, SPEED_NORMALIZED_MAX,
+};
+
+fn duty_and_direction(speed_normalized: i16) -> (u8, bool) {
+    MotorSpeed::new(speed_normalized).to_duty_and_direction(&Config::default())
+}
+
+#[test]
+fn boundary_speeds() {
+    assert_eq!(duty_and_direction(0), (0, true));
+    assert_eq!(duty_and_direction(32767), (100, true));
+    assert_eq!(duty_and_direction(-32767), (100, false));
+    // i16::MIN must not overflow
+    assert_eq!(duty_and_direction(-32768), (100, false));
+}
+
+#[test]
+fn small_speeds_use_min_duty() {
+    assert_eq!(duty_and_direction(1), (MIN_DUTY, true));
+    assert_eq!(duty_and_direction(-1), (MIN_DUTY, false));
+    assert_eq!(duty_and_direction(16384), (50, true));
+}
+
+#[test]
+fn min_duty_follows_config() {
+    let config = Config { min_duty: 10, ..Config::default() };
+    assert_eq!(MotorSpeed::new(1).to_duty_and_direction(&config), (10, true));
+}
+
+#[test]
+fn min_duty_raised_for_short_pulses() {
+    // 5% at 40kHz is a 1.25us pulse, below the driver's 2us
+    let config = Config { min_duty: 5, pwm_freq_hz: 40_000, ..Config::default() };
+    assert_eq!(config.effective_min_duty(), 8);
+    assert_eq!(MotorSpeed::new(1).to_duty_and_direction(&config), (8, true));
+    assert_eq!(MotorSpeed::new(1).to_counts_and_direction(&config), (percent_to_counts(8), true));
+
+    // Above the pulse floor, or no floor at all, min_duty applies as set
+    assert_eq!(Config { min_duty: 10, ..config }.effective_min_duty(), 10);
+    assert_eq!(Config { min_duty: 0, ..config }.effective_min_duty(), 0);
+}
+
+#[test]
+fn saturation_boundary() {
+    // 1.25x forward: 26214 * 1.25 = 32767.5 is still full scale, one step more is beyond it
+    let config = Config { fwd_scale: 1250, ..Config::default() };
+    assert!(!MotorSpeed::new(26214).is_saturated(&config));
+    assert!(MotorSpeed::new(26215).is_saturated(&config));
+    assert_eq!(MotorSpeed::new(26215).to_duty_and_direction(&config), (100, true));
+    assert_eq!(MotorSpeed::new(32767).to_duty_and_direction(&config), (100, true));
+
+    // The reverse scale is unity, so only the bound of the command itself counts
+    assert!(!MotorSpeed::new(-32767).is_saturated(&config));
+    assert!(!MotorSpeed::new(i16::MIN).is_saturated(&config));
+    assert!(MotorSpeed::new(i16::MIN).is_saturated(&Config { rev_scale: 1001, ..config }));
+}
+
+#[test]
+fn no_saturation_at_unity_scale() {
+    let config = Config::default();
+    assert!(!MotorSpeed::new(SPEED_NORMALIZED_MAX).is_saturated(&config));
+    // Scaled down, full scale never reaches 100%
+    let config = Config { fwd_scale: 800, ..config };
+    assert!(!MotorSpeed::new(SPEED_NORMALIZED_MAX).is_saturated(&config));
+    assert_eq!(MotorSpeed::new(SPEED_NORMALIZED_MAX).to_duty_and_direction(&config), (79, true));
+}
+
+#[test]
+fn kickstart_on_start_from_stop() {
+    let config = Config::default();
+    assert!(needs_kickstart(MotorSpeed::STOP, MotorSpeed::new(10000), &config));
+    assert!(needs_kickstart(MotorSpeed::STOP, MotorSpeed::new(-10000), &config));
+}
+
+#[test]
+fn kickstart_on_direction_change() {
+    let config = Config::default();
+    assert!(needs_kickstart(MotorSpeed::new(10000), MotorSpeed::new(-10000), &config));
+    assert!(needs_kickstart(MotorSpeed::new(-10000), MotorSpeed::new(10000), &config));
+}
+
+#[test]
+fn small_reversal_skips_kickstart() {
+    // -5% to +5%: both sides below the ~10% default threshold
+    let config = Config::default();
+    assert!(!needs_kickstart(MotorSpeed::new(-1638), MotorSpeed::new(1638), &config));
+    assert!(!needs_kickstart(MotorSpeed::new(3000), MotorSpeed::new(-100), &config));
+}
+
+#[test]
+fn large_reversal_kickstarts() {
+    let config = Config::default();
+    // Large new target
+    assert!(needs_kickstart(MotorSpeed::new(-1638), MotorSpeed::new(16384), &config));
+    // Reversing a fast wheel to a small target still kicks against the momentum
+    assert!(needs_kickstart(MotorSpeed::new(-16384), MotorSpeed::new(1638), &config));
+    assert!(needs_kickstart(MotorSpeed::new(-3277), MotorSpeed::new(100), &config));
+}
+
+#[test]
+fn zero_reversal_threshold_always_kicks() {
+    let config = Config { reversal_kick_threshold: 0, ..Config::default() };
+    assert!(needs_kickstart(MotorSpeed::new(-1), MotorSpeed::new(1), &config));
+}
+
+#[test]
+fn small_start_from_stop_still_kickstarts() {
+    let config = Config::default();
+    assert!(needs_kickstart(MotorSpeed::STOP, MotorSpeed::new(1638), &config));
+}
+
+#[test]
+fn no_kickstart_in_same_direction_or_on_stop() {
+    let config = Config::default();
+    assert!(!needs_kickstart(MotorSpeed::new(10000), MotorSpeed::new(20000), &config));
+    assert!(!needs_kickstart(MotorSpeed::new(-10000), MotorSpeed::new(-20000), &config));
+    assert!(!needs_kickstart(MotorSpeed::new(10000), MotorSpeed::STOP, &config));
+    assert!(!needs_kickstart(MotorSpeed::STOP, MotorSpeed::STOP, &config));
+}
+
+#[test]
+fn percent_conversion() {
+    assert_eq!(normalized_to_percent(0), 0);
+    assert_eq!(normalized_to_percent(32767), 100);
+    assert_eq!(normalized_to_percent(-32767), -100);
+    assert_eq!(normalized_to_percent(16384), 50);
+    assert_eq!(percent_to_normalized(100), 32767);
+    assert_eq!(percent_to_normalized(-100), -32767);
+    assert_eq!(percent_to_normalized(50), 16384);
+}
+
+#[test]
+fn percent_conversion_saturates() {
+    assert_eq!(normalized_to_percent(i16::MIN), -100);
+    assert_eq!(percent_to_normalized(127), 32767);
+    assert_eq!(percent_to_normalized(-128), -32767);
+}
+
+#[test]
+fn percent_conversion_rounds_at_one_percent() {
+    // 1% is 327.67 normalized units
+    assert_eq!(percent_to_normalized(1), 328);
+    assert_eq!(percent_to_normalized(-1), -328);
+    assert_eq!(normalized_to_percent(163), 0);
+    assert_eq!(normalized_to_percent(164), 1);
+    assert_eq!(normalized_to_percent(-163), 0);
+    assert_eq!(normalized_to_percent(-164), -1);
+}
+
+#[test]
+fn percent_round_trips() {
+    for percent in -100..=100 {
+        assert_eq!(normalized_to_percent(percent_to_normalized(percent)), percent);
+    }
+}
+
+fn counts_and_direction(speed_normalized: i16, config: &Config) -> (u16, bool) {
+    MotorSpeed::new(speed_normalized).to_counts_and_direction(config)
+}
+
+#[test]
+fn counts_full_scale() {
+    let config = Config::default();
+    assert_eq!(counts_and_direction(0, &config), (0, true));
+    assert_eq!(counts_and_direction(32767, &config), (PWM_TOP, true));
+    assert_eq!(counts_and_direction(-32767, &config), (PWM_TOP, false));
+    assert_eq!(counts_and_direction(i16::MIN, &config), (PWM_TOP, false));
+}
+
+#[test]
+fn counts_min_duty_floor() {
+    let config = Config::default();
+    assert_eq!(counts_and_direction(1, &config), (percent_to_counts(MIN_DUTY), true));
+}
+
+#[test]
+fn counts_monotonic_and_near_linear() {
+    let config = Config { min_duty: 0, ..Config::default() };
+    let mut prev = 0;
+    for speed in (0..=32767i32).step_by(7) {
+        let (counts, _) = counts_and_direction(speed as i16, &config);
+        assert!(counts >= prev, "not monotonic at {}", speed);
+        let ideal = speed as f64 * PWM_TOP as f64 / 32767.0;
+        assert!((counts as f64 - ideal).abs() < 1.0, "{} counts for {} (ideal {})", counts, speed, ideal);
+        prev = counts;
+    }
+}
+
+#[test]
+fn counts_agree_with_percent() {
+    let config = Config::default();
+    for speed in (-32767..=32767i32).step_by(101) {
+        let speed = MotorSpeed::new(speed as i16);
+        let (duty, is_forward) = speed.to_duty_and_direction(&config);
+        let (counts, counts_forward) = speed.to_counts_and_direction(&config);
+        assert_eq!((counts_to_percent(counts), counts_forward), (duty, is_forward));
+    }
+}
+
+#[test]
+fn direction_scales_are_independent() {
+    let config = Config { min_duty: 0, fwd_scale: 800, rev_scale: 1000, ..Config::default() };
+    assert_eq!(MotorSpeed::new(16384).to_duty_and_direction(&config), (40, true));
+    assert_eq!(MotorSpeed::new(-16384).to_duty_and_direction(&config), (50, false));
+}
+
+#[test]
+fn direction_scale_clamps_to_full_duty() {
+    let config = Config { rev_scale: 2000, ..Config::default() };
+    assert_eq!(MotorSpeed::new(-20000).to_duty_and_direction(&config), (100, false));
+    assert_eq!(MotorSpeed::new(-32767).to_counts_and_direction(&config), (PWM_TOP, false));
+}
+
+#[test]
+fn deadband_stops_small_commands() {
+    let config = Config { deadband: 500, ..Config::default() };
+    assert_eq!(MotorSpeed::new(499).to_duty_and_direction(&config), (0, true));
+    assert_eq!(MotorSpeed::new(-499).to_duty_and_direction(&config), (0, false));
+    assert_eq!(MotorSpeed::new(499).to_counts_and_direction(&config), (0, true));
+}
+
+#[test]
+fn outside_deadband_uses_min_duty() {
+    let config = Config { deadband: 500, ..Config::default() };
+    assert_eq!(MotorSpeed::new(500).to_duty_and_direction(&config), (MIN_DUTY, true));
+    assert_eq!(MotorSpeed::new(-500).to_duty_and_direction(&config), (MIN_DUTY, false));
+}
+
+#[test]
+fn deadband_does_not_kickstart() {
+    let config = Config { deadband: 500, ..Config::default() };
+    assert!(!needs_kickstart(MotorSpeed::STOP, MotorSpeed::new(100), &config));
+    assert!(needs_kickstart(MotorSpeed::new(100), MotorSpeed::new(600), &config));
+}
@@ -0,0 +1,49 @@
+use rw_core::pid::{normalized_to_rpm, Pid, MAX_RPM};
+
+#[test]
+fn target_rpm_scaling() {
+    let max_rpm = MAX_RPM as u16;
+    assert_eq!(normalized_to_rpm(0, max_rpm), 0);
+    assert_eq!(normalized_to_rpm(32767, max_rpm), MAX_RPM);
+    assert_eq!(normalized_to_rpm(-32767, max_rpm), -MAX_RPM);
+    assert_eq!(normalized_to_rpm(16384, max_rpm), MAX_RPM / 2);
+    assert_eq!(normalized_to_rpm(-32767, u16::MAX), -(u16::MAX as i32));
+}
+
+#[test]
+fn output_saturates() {
+    let mut pid = Pid::new();
+    assert_eq!(pid.update(MAX_RPM * 10, 0), 32767);
+    assert_eq!(pid.update(-MAX_RPM * 10, 0), -32767);
+}
+
+#[test]
+fn integral_removes_steady_error() {
+    let mut pid = Pid::new();
+    let first = pid.update(1000, 900);
+    let later = (0..10).map(|_| pid.update(1000, 900)).last().unwrap();
+    assert!(later > first);
+}
+
+#[test]
+fn anti_windup_recovers_quickly() {
+    let mut pid = Pid::new();
+    // Stalled wheel: saturated for a long time
+    for _ in 0..1000 {
+        pid.update(MAX_RPM, 0);
+    }
+    assert_eq!(pid.update(MAX_RPM, 0), 32767);
+    // Once the wheel reaches the target, the output must come off the rail immediately
+    assert!(pid.update(MAX_RPM, MAX_RPM + 100) < 32767);
+}
+
+#[test]
+fn reset_clears_state() {
+    let mut pid = Pid::new();
+    for _ in 0..10 {
+        pid.update(1000, 0);
+    }
+    pid.reset();
+    let mut fresh = Pid::new();
+    assert_eq!(pid.update(1000, 900), fresh.update(1000, 900));
+}
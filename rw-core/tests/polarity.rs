@@ -0,0 +1,70 @@
+use rw_core::control::CONTROL_TICK_US;
+use rw_core::encoder::RpmEstimator;
+use rw_core::polarity::{PolarityCalibration, PolarityCheck, PolarityCommand, POLARITY_CHECK_MS};
+use rw_core::protocol::RWEncoderCalibrationReport;
+use rw_core::wheel::{WheelModel, WheelParams};
+
+/// Run a check against the wheel model, with the encoder channels swapped or not
+fn run_check(params: WheelParams, swapped: bool) -> PolarityCheck {
+    let mut calibration = PolarityCalibration::new();
+    let mut wheel = WheelModel::new(params);
+    let mut estimator = RpmEstimator::new();
+    calibration.start(0);
+    let mut now_us = 0;
+    loop {
+        let output = calibration.output().expect("check ended without a result");
+        wheel.step(output, CONTROL_TICK_US);
+        now_us += CONTROL_TICK_US;
+        let count = if swapped { -wheel.encoder_count() } else { wheel.encoder_count() };
+        if let Some(check) = calibration.update(now_us, estimator.update(now_us, count)) {
+            assert!(!calibration.is_running());
+            return check;
+        }
+    }
+}
+
+#[test]
+fn correct_wiring_matches() {
+    let check = run_check(WheelParams::default(), false);
+    assert!(matches!(check, PolarityCheck::Matches { rpm } if rpm > 5000), "{check:?}");
+}
+
+#[test]
+fn swapped_channels_are_detected() {
+    let check = run_check(WheelParams::default(), true);
+    assert!(matches!(check, PolarityCheck::Reversed { rpm } if rpm < -5000), "{check:?}");
+}
+
+#[test]
+fn stuck_wheel_is_inconclusive() {
+    let params = WheelParams { breakaway_duty: 0.9, ..WheelParams::default() };
+    assert_eq!(run_check(params, true), PolarityCheck::NoRotation { rpm: 0 });
+}
+
+#[test]
+fn check_ends_after_the_spin_time() {
+    let mut calibration = PolarityCalibration::new();
+    calibration.start(1_000_000);
+    let end_us = 1_000_000 + POLARITY_CHECK_MS * 1000;
+    assert_eq!(calibration.update(end_us - 1, Some(3000)), None);
+    assert_eq!(calibration.update(end_us, None), Some(PolarityCheck::Matches { rpm: 3000 }));
+    assert_eq!(calibration.output(), None);
+    assert_eq!(calibration.update(end_us + 1000, Some(3000)), None);
+}
+
+#[test]
+fn abort_stops_driving() {
+    let mut calibration = PolarityCalibration::new();
+    assert!(!calibration.abort());
+    calibration.start(0);
+    assert!(calibration.output().is_some());
+    assert!(calibration.abort());
+    assert_eq!(calibration.output(), None);
+}
+
+#[test]
+fn polarity_command_from_report() {
+    assert_eq!(PolarityCommand::from_report(&RWEncoderCalibrationReport { command: 1 }), Some(PolarityCommand::Start));
+    assert_eq!(PolarityCommand::from_report(&RWEncoderCalibrationReport { command: 0 }), Some(PolarityCommand::Abort));
+    assert_eq!(PolarityCommand::from_report(&RWEncoderCalibrationReport { command: 2 }), None);
+}
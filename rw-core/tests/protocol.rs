@@ -0,0 +1,707 @@
+use rw_core::config::{Config, ConfigCommand, ConfigError, ControlMode, FaultResponse, RampProfile, StopMode};
+use rw_core::curve::SpeedCurve;
+use rw_core::control::{MotorController, MotorOutput};
+use rw_core::crc::{crc32, crc8};
+use rw_core::current::CURRENT_LIMIT_GAIN_MAX;
+use rw_core::failsafe::EStopCommand;
+use rw_core::fault::Fault;
+use rw_core::motor::MotorSpeed;
+use rw_core::spinup::{SpinUpState, SpinUpTest};
+use rw_core::protocol::{
+    OutputReport, OutputReportError, OutputReportPercent, RWConfigCommandReport, RWConfigReport, RWDeviceInfoReport, RWEStopReport, RWStatusReport,
+    RWConfigStatusReport, RWDescriptorInfoReport, RWEffectiveConfigReport, RWTelemetryReport, CONFIG_LAYOUT_VERSION,
+    CONFIG_REPORT_ID,
+    SPEED_PERCENT_REPORT_ID,
+    SPEED_REPORT_ID,
+    TELEMETRY_REPORT_ID,
+};
+use zerocopy::{FromBytes, IntoBytes};
+
+#[test]
+fn output_report_clamps_i16_min() {
+    let report = OutputReport::new(i16::MIN);
+    assert_eq!(report.speed_normalized(), -32767);
+
+    let report = OutputReport::new(-32767);
+    assert_eq!(report.speed_normalized(), -32767);
+
+    let report = OutputReport::new(32767);
+    assert_eq!(report.speed_normalized(), 32767);
+}
+
+#[test]
+fn config_report_accepted() {
+    let mut report = Config::default().to_report();
+    report.stop_mode = 1;
+    report.min_duty = 30;
+    report.kickstart_duty = 90;
+    report.kickstart_ms.set(200);
+    report.slew_step.set(100);
+    report.control_mode = 1;
+    report.max_current_ma.set(1200);
+    report.throttle_start_c = 50;
+    report.throttle_limit_c = 90;
+    report.curve = 1;
+    report.rev_scale.set(900);
+    report.deadband.set(300);
+    report.stop_ramp_step.set(500);
+    report.pwm_freq_hz.set(20000);
+    report.ramp_profile = 1;
+    report.ramp_tau_ms.set(50);
+    report.max_speed_normalized.set(10000);
+    report.stall_window_ms.set(500);
+    report.stall_rpm.set(100);
+    report.stall_retry = 0;
+    report.kickstart_rpm.set(300);
+    report.reversal_kick_threshold.set(1000);
+    report.reversal_brake_ms.set(200);
+    report.reversal_brake_rpm.set(300);
+    report.telemetry_period_ms.set(50);
+    report.dither_amplitude = 5;
+    report.dither_freq_hz = 20;
+    report.dither_max_speed.set(10000);
+    report.min_dwell_ms.set(500);
+    report.log_interval_ms.set(100);
+    report.encoder_reversed = 1;
+    report.direction_hysteresis.set(328);
+    report.max_rpm.set(6000);
+    report.min_supply_mv.set(900);
+    report.overcurrent_response = 0;
+    report.over_temperature_response = 1;
+    report.undervoltage_response = 2;
+    report.stall_response = 0;
+    report.current_limit_ma.set(1000);
+    report.current_limit_gain.set(200);
+    let config = Config::from_report(&report).unwrap();
+    assert_eq!(config.stop_mode, StopMode::Brake);
+    assert_eq!(config.min_duty, 30);
+    assert_eq!(config.kickstart_duty, 90);
+    assert_eq!(config.kickstart_ms, 200);
+    assert_eq!(config.slew_step, 100);
+    assert_eq!(config.control_mode, ControlMode::ClosedLoop);
+    assert_eq!(config.max_current_ma, 1200);
+    assert_eq!(config.throttle_start_c, 50);
+    assert_eq!(config.throttle_limit_c, 90);
+    assert_eq!(config.curve, SpeedCurve::Sqrt);
+    assert_eq!(config.fwd_scale, 1000);
+    assert_eq!(config.rev_scale, 900);
+    assert_eq!(config.deadband, 300);
+    assert_eq!(config.stop_ramp_step, 500);
+    assert_eq!(config.pwm_freq_hz, 20000);
+    assert_eq!(config.ramp_profile, RampProfile::Exponential);
+    assert_eq!(config.ramp_tau_ms, 50);
+    assert_eq!(config.max_speed_normalized, 10000);
+    assert_eq!(config.stall_window_ms, 500);
+    assert_eq!(config.stall_rpm, 100);
+    assert!(!config.stall_retry);
+    assert_eq!(config.kickstart_rpm, 300);
+    assert_eq!(config.reversal_kick_threshold, 1000);
+    assert_eq!(config.reversal_brake_ms, 200);
+    assert_eq!(config.reversal_brake_rpm, 300);
+    assert_eq!(config.telemetry_period_ms, 50);
+    assert_eq!(config.dither_amplitude, 5);
+    assert_eq!(config.dither_freq_hz, 20);
+    assert_eq!(config.dither_max_speed, 10000);
+    assert_eq!(config.min_dwell_ms, 500);
+    assert_eq!(config.log_interval_ms, 100);
+    assert!(config.encoder_reversed);
+    assert_eq!(config.direction_hysteresis, 328);
+    assert_eq!(config.max_rpm, 6000);
+    assert_eq!(config.min_supply_mv, 900);
+    assert_eq!(config.overcurrent_response, FaultResponse::Coast);
+    assert_eq!(config.over_temperature_response, FaultResponse::Brake);
+    assert_eq!(config.undervoltage_response, FaultResponse::DisableDriver);
+    assert_eq!(config.stall_response, FaultResponse::Coast);
+    assert_eq!(config.current_limit_ma, 1000);
+    assert_eq!(config.current_limit_gain, 200);
+}
+
+#[test]
+fn config_to_report_round_trips() {
+    let bytes = [1, 30, 90, 200, 0, 100, 0, 1, 0xB0, 0x04, 50, 90, 1, 0xE8, 0x03, 0x84, 0x03, 0x2C, 0x01, 0xF4, 0x01, 0x10, 0x27, 1, 0x32, 0x00, 0x10, 0x27, 0xF4, 0x01, 0x64, 0x00, 1, 0xC8, 0x00, 0xCD, 0x0C, 0x64, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0, 0x0A, 0xCD, 0x0C, 0x64, 0x00, 0xE8, 0x03, 1, 0x48, 0x01, 0x10, 0x27, 0xA0, 0x0F, 0x00, 0x00, 0x2C, 0x01, 2, 2, 2, 2, 0x00, 0x00, 0x64, 0x00, 0x00, 0x00];
+    let config = Config::from_report(&RWConfigReport::read_from_bytes(&bytes).unwrap()).unwrap();
+    assert_eq!(config.to_report().as_bytes(), &bytes);
+}
+
+#[test]
+fn config_report_rejected() {
+    let mut report = Config::default().to_report();
+    report.stop_mode = 2;
+    assert_eq!(Config::from_report(&report), Err(ConfigError::StopMode(2)));
+
+    let mut report = Config::default().to_report();
+    report.min_duty = 101;
+    assert_eq!(Config::from_report(&report), Err(ConfigError::MinDuty(101)));
+
+    let mut report = Config::default().to_report();
+    report.kickstart_duty = 101;
+    assert_eq!(Config::from_report(&report), Err(ConfigError::KickstartDuty(101)));
+
+    let mut report = Config::default().to_report();
+    report.control_mode = 3;
+    assert_eq!(Config::from_report(&report), Err(ConfigError::ControlMode(3)));
+
+    let mut report = Config::default().to_report();
+    report.throttle_start_c = report.throttle_limit_c;
+    assert_eq!(Config::from_report(&report), Err(ConfigError::ThrottleLimit(80)));
+
+    let mut report = Config::default().to_report();
+    report.curve = 2;
+    assert_eq!(Config::from_report(&report), Err(ConfigError::Curve(2)));
+
+    let mut report = Config::default().to_report();
+    report.fwd_scale.set(2001);
+    assert_eq!(Config::from_report(&report), Err(ConfigError::FwdScale(2001)));
+
+    let mut report = Config::default().to_report();
+    report.rev_scale.set(2001);
+    assert_eq!(Config::from_report(&report), Err(ConfigError::RevScale(2001)));
+
+    let mut report = Config::default().to_report();
+    report.pwm_freq_hz.set(999);
+    assert_eq!(Config::from_report(&report), Err(ConfigError::PwmFrequency(999)));
+
+    let mut report = Config::default().to_report();
+    report.ramp_profile = 2;
+    assert_eq!(Config::from_report(&report), Err(ConfigError::RampProfile(2)));
+
+    let mut report = Config::default().to_report();
+    report.max_speed_normalized.set(0);
+    assert_eq!(Config::from_report(&report), Err(ConfigError::MaxSpeed(0)));
+    report.max_speed_normalized.set(32768);
+    assert_eq!(Config::from_report(&report), Err(ConfigError::MaxSpeed(32768)));
+
+    let mut report = Config::default().to_report();
+    report.stall_retry = 2;
+    assert_eq!(Config::from_report(&report), Err(ConfigError::StallRetry(2)));
+
+    let mut report = Config::default().to_report();
+    report.reversal_kick_threshold.set(32768);
+    assert_eq!(Config::from_report(&report), Err(ConfigError::ReversalKickThreshold(32768)));
+
+    // Coast by default, and the reversal brake needs the brake stop mode
+    let mut report = Config::default().to_report();
+    report.reversal_brake_ms.set(100);
+    assert_eq!(Config::from_report(&report), Err(ConfigError::ReversalBrake(100)));
+
+    let mut report = Config::default().to_report();
+    report.dither_amplitude = 51;
+    assert_eq!(Config::from_report(&report), Err(ConfigError::DitherAmplitude(51)));
+
+    let mut report = Config::default().to_report();
+    report.dither_freq_hz = 0;
+    assert_eq!(Config::from_report(&report), Err(ConfigError::DitherFrequency(0)));
+
+    let mut report = Config::default().to_report();
+    report.encoder_reversed = 2;
+    assert_eq!(Config::from_report(&report), Err(ConfigError::EncoderReversed(2)));
+
+    let mut report = Config::default().to_report();
+    report.direction_hysteresis.set(32768);
+    assert_eq!(Config::from_report(&report), Err(ConfigError::DirectionHysteresis(32768)));
+
+    let mut report = Config::default().to_report();
+    report.stall_response = 3;
+    assert_eq!(Config::from_report(&report), Err(ConfigError::StallResponse(3)));
+
+    let mut report = Config::default().to_report();
+    report.current_limit_ma.set(report.max_current_ma.get() + 1);
+    assert_eq!(Config::from_report(&report), Err(ConfigError::CurrentLimit(1501)));
+    // Any limit goes with the overcurrent cutoff disabled
+    report.max_current_ma.set(0);
+    assert!(Config::from_report(&report).is_ok());
+
+    let mut report = Config::default().to_report();
+    report.current_limit_ma.set(1000);
+    report.current_limit_gain.set(0);
+    let error = Config::from_report(&report).unwrap_err();
+    assert_eq!(error, ConfigError::CurrentLimitGain(0));
+    assert_eq!(error.offset(), 68);
+    // A zero gain is harmless with the limit off
+    report.current_limit_ma.set(0);
+    assert!(Config::from_report(&report).is_ok());
+
+    let mut report = Config::default().to_report();
+    report.current_limit_gain.set(CURRENT_LIMIT_GAIN_MAX + 1);
+    assert_eq!(Config::from_report(&report), Err(ConfigError::CurrentLimitGain(1001)));
+    report.current_limit_gain.set(CURRENT_LIMIT_GAIN_MAX);
+    assert!(Config::from_report(&report).is_ok());
+}
+
+#[test]
+fn config_report_rejected_whole() {
+    // Valid new min_duty and scales, but an unsupported PWM frequency: nothing of it may apply
+    let mut report = Config::default().to_report();
+    report.min_duty = 25;
+    report.fwd_scale.set(900);
+    report.pwm_freq_hz.set(50_000);
+    let error = Config::from_report(&report).unwrap_err();
+    assert_eq!(error, ConfigError::PwmFrequency(50_000));
+    assert_eq!(error.offset(), 21);
+    assert_eq!(error.value(), 50_000);
+}
+
+#[test]
+fn config_report_selects_torque_mode() {
+    let mut report = Config::default().to_report();
+    report.control_mode = 2;
+    assert_eq!(Config::from_report(&report).unwrap().control_mode, ControlMode::Torque);
+}
+
+#[test]
+fn rejected_field_offsets() {
+    let mut report = Config::default().to_report();
+    report.min_duty = 101;
+    let error = Config::from_report(&report).unwrap_err();
+    assert_eq!((error.offset(), error.value()), (1, 101));
+    assert_eq!(report.as_bytes()[error.offset()], 101);
+
+    let mut report = Config::default().to_report();
+    report.direction_hysteresis.set(40_000);
+    let error = Config::from_report(&report).unwrap_err();
+    assert_eq!(error.offset(), 52);
+    assert_eq!(&report.as_bytes()[52..54], &40_000u16.to_le_bytes());
+
+    // Cross-field checks name the field that has to change
+    let mut report = Config::default().to_report();
+    report.throttle_limit_c = report.throttle_start_c;
+    assert_eq!(Config::from_report(&report).unwrap_err().offset(), 11);
+
+    let mut report = Config::default().to_report();
+    report.over_temperature_response = 7;
+    let error = Config::from_report(&report).unwrap_err();
+    assert_eq!((error.offset(), error.value()), (63, 7));
+
+    let mut report = Config::default().to_report();
+    report.current_limit_ma.set(2000);
+    let error = Config::from_report(&report).unwrap_err();
+    assert_eq!((error.offset(), error.value()), (66, 2000));
+}
+
+#[test]
+fn config_status_report_layout() {
+    let status = RWConfigStatusReport::new();
+    assert_eq!(status.as_bytes(), &[0, RWConfigStatusReport::RESULT_NONE, RWConfigStatusReport::FIELD_NONE, 0, 0]);
+
+    let status = RWConfigStatusReport::accepted(3);
+    assert_eq!(status.as_bytes(), &[3, RWConfigStatusReport::RESULT_ACCEPTED, RWConfigStatusReport::FIELD_NONE, 0, 0]);
+
+    let status = RWConfigStatusReport::rejected(4, &ConfigError::PwmFrequency(50_000));
+    assert_eq!(status.as_bytes(), &[4, RWConfigStatusReport::RESULT_INVALID_VALUE, 21, 0x50, 0xC3]);
+
+    let status = RWConfigStatusReport::invalid_length(5, 53);
+    assert_eq!(status.as_bytes(), &[5, RWConfigStatusReport::RESULT_INVALID_LENGTH, RWConfigStatusReport::FIELD_NONE, 53, 0]);
+}
+
+/// Output report bytes with the ID prefix and a valid checksum
+fn output_report_bytes(speed_normalized: i16) -> [u8; 4] {
+    let [lo, hi] = speed_normalized.to_le_bytes();
+    [SPEED_REPORT_ID, lo, hi, crc8(&[lo, hi])]
+}
+
+#[test]
+fn output_report_with_report_id() {
+    let report = OutputReport::parse(&output_report_bytes(-1000)).unwrap();
+    assert_eq!(report.speed_normalized(), -1000);
+}
+
+#[test]
+fn output_report_wrong_or_missing_id() {
+    let [lo, hi] = 1000i16.to_le_bytes();
+    let checksum = crc8(&[lo, hi]);
+    // Bare payload or report ID 0 (hosts that predate report IDs)
+    assert_eq!(OutputReport::parse(&[lo, hi, checksum]).err(), Some(OutputReportError::UnknownReportId(lo)));
+    assert_eq!(OutputReport::parse(&[0, lo, hi, checksum]).err(), Some(OutputReportError::UnknownReportId(0)));
+    assert_eq!(
+        OutputReport::parse(&[CONFIG_REPORT_ID, lo, hi, checksum]).err(),
+        Some(OutputReportError::UnknownReportId(CONFIG_REPORT_ID))
+    );
+    assert_eq!(OutputReport::parse(&[]).err(), Some(OutputReportError::Malformed));
+    assert_eq!(OutputReport::parse(&[SPEED_REPORT_ID, lo]).err(), Some(OutputReportError::Malformed));
+}
+
+#[test]
+fn output_report_padded() {
+    let mut buf = [0u8; 64];
+    buf[..4].copy_from_slice(&output_report_bytes(-32767));
+    let report = OutputReport::parse(&buf).unwrap();
+    assert_eq!(report.speed_normalized(), -32767);
+}
+
+#[test]
+fn output_report_too_short() {
+    assert!(OutputReport::parse(&[]).is_err());
+    assert!(OutputReport::parse(&[SPEED_REPORT_ID]).is_err());
+    assert!(OutputReport::parse(&[SPEED_REPORT_ID, 0x12]).is_err());
+    // Missing checksum byte
+    assert!(OutputReport::parse(&output_report_bytes(1000)[..3]).is_err());
+}
+
+#[test]
+fn output_report_checksum_mismatch() {
+    let mut bytes = output_report_bytes(1000);
+    let expected = bytes[3];
+    bytes[2] ^= 0x40;
+    assert_eq!(
+        OutputReport::parse(&bytes).err(),
+        Some(OutputReportError::Checksum { expected: crc8(&bytes[1..3]), received: expected })
+    );
+
+    let mut bytes = output_report_bytes(1000);
+    bytes[3] ^= 0x01;
+    assert!(matches!(OutputReport::parse(&bytes), Err(OutputReportError::Checksum { .. })));
+}
+
+#[test]
+fn output_report_new_round_trips() {
+    let bytes = OutputReport::new(-12345).to_report();
+    assert_eq!(bytes, output_report_bytes(-12345));
+    assert_eq!(OutputReport::parse(&bytes).unwrap().speed_normalized(), -12345);
+}
+
+#[test]
+fn percent_report_parses() {
+    let [percent] = (-50i8).to_le_bytes();
+    let bytes = [SPEED_PERCENT_REPORT_ID, percent, crc8(&[percent])];
+    assert_eq!(OutputReportPercent::new(-50).to_report(), bytes);
+    assert_eq!(OutputReportPercent::parse(&bytes).unwrap().speed_normalized(), -16384);
+}
+
+#[test]
+fn percent_report_saturates() {
+    assert_eq!(OutputReportPercent::new(120).speed_normalized(), 32767);
+    assert_eq!(OutputReportPercent::new(i8::MIN).speed_normalized(), -32767);
+}
+
+#[test]
+fn percent_report_checksum_mismatch() {
+    let mut bytes = OutputReportPercent::new(25).to_report();
+    bytes[2] ^= 0x01;
+    assert!(matches!(OutputReportPercent::parse(&bytes), Err(OutputReportError::Checksum { .. })));
+    assert!(matches!(OutputReport::parse_command(&bytes), Err(OutputReportError::Checksum { .. })));
+}
+
+#[test]
+fn speed_command_accepts_either_report() {
+    let normalized = OutputReport::parse_command(&output_report_bytes(-1000)).unwrap();
+    assert_eq!(normalized.speed_normalized(), -1000);
+    let percent = OutputReport::parse_command(&OutputReportPercent::new(1).to_report()).unwrap();
+    assert_eq!(percent.speed_normalized(), 328);
+    assert_eq!(
+        OutputReport::parse_command(&[CONFIG_REPORT_ID, 0, 0, 0]).err(),
+        Some(OutputReportError::UnknownReportId(CONFIG_REPORT_ID))
+    );
+}
+
+#[test]
+fn speed_command_routes_on_the_report_id() {
+    // The same payload means different things under the two speed IDs
+    let payload = OutputReportPercent::new(50).to_report();
+    let mut normalized = [SPEED_REPORT_ID; 4];
+    normalized[1..].copy_from_slice(OutputReport::new(-1000).as_bytes());
+    assert_eq!(OutputReport::parse_command(&payload).unwrap().speed_normalized(), 16384);
+    assert_eq!(OutputReport::parse_command(&normalized).unwrap().speed_normalized(), -1000);
+
+    // A payload under the other speed ID is rejected (too short, checksum) instead of passing as the other
+    let mut swapped = payload;
+    swapped[0] = SPEED_REPORT_ID;
+    assert_eq!(OutputReport::parse_command(&swapped).err(), Some(OutputReportError::Malformed));
+    normalized[0] = SPEED_PERCENT_REPORT_ID;
+    assert!(matches!(OutputReport::parse_command(&normalized), Err(OutputReportError::Checksum { .. })));
+
+    assert_eq!(OutputReport::parse_command(&[]).err(), Some(OutputReportError::Malformed));
+}
+
+#[test]
+fn effective_config_report() {
+    let config = Config { min_duty: 35, max_speed_normalized: 20000, ..Config::default() };
+    let report = RWEffectiveConfigReport::new(&config, true);
+    assert_eq!(report.layout_version, CONFIG_LAYOUT_VERSION);
+    assert_eq!(report.inverted, 1);
+    assert_eq!(report.as_bytes().len(), 2 + core::mem::size_of::<RWConfigReport>());
+    assert_eq!(Config::from_report(&report.config), Ok(config));
+    assert_eq!(RWEffectiveConfigReport::new(&config, false).inverted, 0);
+}
+
+#[test]
+fn crc8_check_value() {
+    // CRC-8/SMBUS check value
+    assert_eq!(crc8(b"123456789"), 0xF4);
+    assert_eq!(crc8(&[]), 0);
+}
+
+#[test]
+fn crc32_check_value() {
+    // CRC-32/ISO-HDLC check value (same as zlib.crc32)
+    assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    assert_eq!(crc32(&[]), 0);
+}
+
+#[test]
+fn descriptor_info_fingerprints_the_descriptor() {
+    let descriptor = [0x06, 0x00, 0xFF, 0x09, 0x01, 0xA1, 0x01, 0xC0];
+    let report = RWDescriptorInfoReport::new(&descriptor);
+    assert_eq!(report.as_bytes(), &[8, 0, 0x90, 0xBC, 0xE0, 0xA8]);
+    assert_eq!(report.crc32.get(), crc32(&descriptor));
+
+    let mut changed = descriptor;
+    changed[4] = 0x02;
+    assert_ne!(RWDescriptorInfoReport::new(&changed).crc32, report.crc32);
+}
+
+#[test]
+fn status_report_has_report_id() {
+    let status = RWStatusReport::new(MotorSpeed::new(-1000), MotorOutput::Reverse(100), 1200, 250, 0);
+    let report = status.to_report();
+    assert_eq!(report[0], SPEED_REPORT_ID);
+    assert_eq!(&report[1..], status.as_bytes());
+    assert_eq!(report[4] & RWStatusReport::FLAG_FORWARD, 0);
+}
+
+#[test]
+fn config_command_report() {
+    let report = RWConfigCommandReport::read_from_bytes(&[1]).unwrap();
+    assert_eq!(ConfigCommand::from_report(&report), Some(ConfigCommand::ResetToDefaults));
+    let report = RWConfigCommandReport::read_from_bytes(&[0]).unwrap();
+    assert_eq!(ConfigCommand::from_report(&report), None);
+}
+
+#[test]
+fn estop_report() {
+    let engage = RWEStopReport::read_from_bytes(&[1]).unwrap();
+    assert_eq!(EStopCommand::from_report(&engage), Some(EStopCommand::Engage));
+    let release = RWEStopReport::read_from_bytes(&[0]).unwrap();
+    assert_eq!(EStopCommand::from_report(&release), Some(EStopCommand::Release));
+    let ramped = RWEStopReport::read_from_bytes(&[2]).unwrap();
+    assert_eq!(EStopCommand::from_report(&ramped), Some(EStopCommand::EngageRamped));
+    let invalid = RWEStopReport::read_from_bytes(&[3]).unwrap();
+    assert_eq!(EStopCommand::from_report(&invalid), None);
+}
+
+#[test]
+fn device_info_layout() {
+    let info = RWDeviceInfoReport {
+        axis: 2,
+        version_major: 0,
+        version_minor: 1,
+        version_patch: 3,
+        strap_levels: 0b01,
+        strap_axis: 2,
+    };
+    assert_eq!(info.as_bytes(), &[2, 0, 1, 3, 0b01, 2]);
+}
+
+#[test]
+fn status_report_liveness() {
+    let status = RWStatusReport::new(MotorSpeed::STOP, MotorOutput::Coast, 0, 0, 0).with_liveness(u32::MAX, 1234);
+    let report = status.to_report();
+    assert_eq!(report.len(), 1 + 18);
+    assert_eq!(&report[9..13], &u32::MAX.to_le_bytes());
+    assert_eq!(&report[13..17], &1234u32.to_le_bytes());
+    assert_eq!(&report[17..19], &[0, 0]);
+}
+
+#[test]
+fn status_report_faults() {
+    let faults = Fault::OVERCURRENT | Fault::INVALID_REPORT;
+    let status = RWStatusReport::new(MotorSpeed::STOP, MotorOutput::Coast, 0, 0, 0).with_faults(faults);
+    assert_eq!(&status.to_report()[17..19], &[0b1001, 0]);
+
+    // Faults past bit 7 go in the high byte
+    let status = RWStatusReport::new(MotorSpeed::STOP, MotorOutput::Coast, 0, 0, 0).with_faults(Fault::NO_FEEDBACK);
+    assert_eq!(&status.to_report()[17..19], &[0, 0b1]);
+}
+
+#[test]
+fn telemetry_shows_clamping_and_slewing() {
+    let config = Config { slew_step: 1000, ..Config::default() };
+    let mut controller = MotorController::new();
+    let command = OutputReport::new(i16::MIN);
+    controller.set_target(MotorSpeed::new(command.speed_normalized()));
+    controller.update(0, &config);
+
+    let telemetry = RWTelemetryReport::new(command.raw_speed_normalized(), &controller, &config);
+    assert_eq!(telemetry.received.get(), i16::MIN);
+    assert_eq!(telemetry.target.get(), -32767);
+    assert_eq!(telemetry.effective.get(), -1000);
+    let flags = RWTelemetryReport::FLAG_CLAMPED | RWTelemetryReport::FLAG_SLEWING | RWTelemetryReport::FLAG_KICKSTARTING;
+    assert_eq!(telemetry.flags, flags);
+
+    let report = telemetry.to_report();
+    assert_eq!(report.len(), 28);
+    assert_eq!(report[0], TELEMETRY_REPORT_ID);
+    assert_eq!(&report[1..], telemetry.as_bytes());
+}
+
+#[test]
+fn telemetry_shows_driver_enable() {
+    let config = Config::default();
+    let controller = MotorController::new();
+    let telemetry = RWTelemetryReport::new(0, &controller, &config);
+    assert_eq!(telemetry.flags & RWTelemetryReport::FLAG_DRIVER_ENABLED, 0);
+
+    let telemetry = telemetry.with_driver_enabled(true);
+    assert_eq!(telemetry.flags, RWTelemetryReport::FLAG_DRIVER_ENABLED);
+    assert_eq!(telemetry.with_driver_enabled(false).flags, 0);
+}
+
+#[test]
+fn telemetry_carries_pwm_errors() {
+    let config = Config::default();
+    let controller = MotorController::new();
+    let telemetry = RWTelemetryReport::new(0, &controller, &config);
+    assert_eq!(telemetry.pwm_errors.get(), 0);
+
+    let telemetry = telemetry.with_pwm_errors(3);
+    assert_eq!(telemetry.pwm_errors.get(), 3);
+    assert_eq!(&telemetry.as_bytes()[7..9], &[3, 0]);
+}
+
+#[test]
+fn telemetry_shows_encoder_polarity() {
+    let controller = MotorController::new();
+    let telemetry = RWTelemetryReport::new(0, &controller, &Config::default());
+    assert_eq!(telemetry.encoder_reversed, 0);
+
+    let config = Config { encoder_reversed: true, ..Config::default() };
+    let telemetry = RWTelemetryReport::new(0, &controller, &config);
+    assert_eq!(telemetry.as_bytes()[9], 1);
+}
+
+#[test]
+fn telemetry_shows_min_duty_calibration() {
+    let controller = MotorController::new();
+    let config = Config { min_duty: 27, ..Config::default() };
+    let telemetry = RWTelemetryReport::new(0, &controller, &config);
+    assert_eq!(&telemetry.as_bytes()[10..12], &[27, RWTelemetryReport::MIN_DUTY_RAMP_IDLE]);
+
+    let telemetry = telemetry.with_min_duty_ramp(Some(12));
+    assert_eq!(telemetry.min_duty_ramp, 12);
+    assert_eq!(telemetry.with_min_duty_ramp(None).min_duty_ramp, RWTelemetryReport::MIN_DUTY_RAMP_IDLE);
+}
+
+#[test]
+fn telemetry_shows_pwm_timing() {
+    let controller = MotorController::new();
+    let telemetry = RWTelemetryReport::new(0, &controller, &Config::default());
+    assert_eq!(&telemetry.as_bytes()[20..23], &[0xD3, 0x30, 1]);
+
+    // 1kHz needs a divider; 125MHz / (2 * 62500) is exact
+    let config = Config { pwm_freq_hz: 1000, ..Config::default() };
+    let telemetry = RWTelemetryReport::new(0, &controller, &config);
+    assert_eq!((telemetry.pwm_top.get(), telemetry.pwm_div_int), (62499, 2));
+
+    // 30kHz is not representable: 125MHz / 4166 = 30004Hz
+    let config = Config { pwm_freq_hz: 30000, ..Config::default() };
+    let telemetry = RWTelemetryReport::new(0, &controller, &config);
+    assert_eq!((telemetry.pwm_top.get(), telemetry.pwm_div_int), (4165, 1));
+    assert_eq!(config.pwm_timing().frequency_hz(), 30004);
+}
+
+#[test]
+fn telemetry_shows_effective_min_duty() {
+    let controller = MotorController::new();
+    let config = Config { min_duty: 5, ..Config::default() };
+    let telemetry = RWTelemetryReport::new(0, &controller, &config);
+    assert_eq!((telemetry.min_duty, telemetry.min_duty_effective), (5, 5));
+
+    let config = Config { pwm_freq_hz: 40_000, ..config };
+    let telemetry = RWTelemetryReport::new(0, &controller, &config);
+    assert_eq!((telemetry.min_duty, telemetry.as_bytes()[23]), (5, 8));
+}
+
+#[test]
+fn telemetry_flags_duty_saturation() {
+    let config = Config { fwd_scale: 1500, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    for tick in 0..1000 {
+        controller.update(tick * 1000, &config);
+    }
+    assert_eq!(RWTelemetryReport::new(16384, &controller, &config).duty_saturated, 0);
+
+    controller.set_target(MotorSpeed::new(30000));
+    for tick in 1000..2000 {
+        controller.update(tick * 1000, &config);
+    }
+    let telemetry = RWTelemetryReport::new(30000, &controller, &config);
+    assert_eq!(telemetry.as_bytes()[24], 1);
+}
+
+#[test]
+fn telemetry_shows_spin_up_result() {
+    let controller = MotorController::new();
+    let config = Config::default();
+    let telemetry = RWTelemetryReport::new(0, &controller, &config);
+    assert_eq!(&telemetry.as_bytes()[17..20], &[SpinUpState::Idle as u8, 0xFF, 0xFF]);
+
+    let mut test = SpinUpTest::new();
+    test.start(0, MotorSpeed::new(32767), 0, 6000);
+    let telemetry = RWTelemetryReport::new(0, &controller, &config).with_spin_up(&test);
+    assert_eq!(&telemetry.as_bytes()[17..20], &[SpinUpState::Running as u8, 0xFF, 0xFF]);
+
+    test.update(300_000, Some(5400));
+    let telemetry = RWTelemetryReport::new(0, &controller, &config).with_spin_up(&test);
+    assert_eq!(telemetry.spin_up_state, SpinUpState::Reached as u8);
+    assert_eq!(telemetry.spin_up_ms.get(), 300);
+}
+
+#[test]
+fn telemetry_shows_deadband_and_throttling() {
+    let config = Config { deadband: 500, slew_step: 0, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(300));
+    controller.update(0, &config);
+    let telemetry = RWTelemetryReport::new(300, &controller, &config);
+    assert_eq!(telemetry.effective.get(), 0);
+    assert_eq!(telemetry.flags, RWTelemetryReport::FLAG_DEADBANDED);
+
+    controller.set_duty_limit(50);
+    controller.set_target(MotorSpeed::new(32767));
+    controller.update(1000, &config);
+    let telemetry = RWTelemetryReport::new(32767, &controller, &config);
+    assert_eq!(telemetry.effective.get(), 32767);
+    assert_ne!(telemetry.flags & RWTelemetryReport::FLAG_THROTTLED, 0);
+    assert_eq!(telemetry.flags & RWTelemetryReport::FLAG_DEADBANDED, 0);
+}
+
+#[test]
+fn telemetry_shows_speed_cap() {
+    let config = Config { max_speed_normalized: 10000, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(-20000));
+    controller.update(0, &config);
+    let telemetry = RWTelemetryReport::new(-20000, &controller, &config);
+    assert_eq!(telemetry.target.get(), -10000);
+    assert_eq!(telemetry.effective.get(), -10000);
+    assert_ne!(telemetry.flags & RWTelemetryReport::FLAG_SPEED_LIMITED, 0);
+
+    controller.set_target(MotorSpeed::new(5000));
+    controller.update(1000, &config);
+    let telemetry = RWTelemetryReport::new(5000, &controller, &config);
+    assert_eq!(telemetry.flags & RWTelemetryReport::FLAG_SPEED_LIMITED, 0);
+}
+
+#[test]
+fn telemetry_flags_current_limit() {
+    let config = Config { current_limit_ma: 1000, ..Config::default() };
+    let mut controller = MotorController::new();
+    controller.set_target(MotorSpeed::new(16384));
+    controller.update(0, &config);
+    assert_eq!(RWTelemetryReport::new(16384, &controller, &config).current_limited, 0);
+
+    // Well over the limit: the next tick's duty is pulled down
+    controller.set_measured_current(3000, &config);
+    controller.update(1000, &config);
+    let telemetry = RWTelemetryReport::new(16384, &controller, &config);
+    assert_eq!(telemetry.as_bytes()[25], 1);
+}
+
+#[test]
+fn telemetry_reports_idle_sleep() {
+    let config = Config::default();
+    let controller = MotorController::new();
+    let telemetry = RWTelemetryReport::new(0, &controller, &config);
+    assert_eq!(telemetry.idle_asleep, 0);
+    assert_eq!(telemetry.with_idle_asleep(true).as_bytes()[26], 1);
+}
@@ -0,0 +1,40 @@
+use rw_core::pwm::{PwmTiming, PWM_FREQ_HZ, PWM_FREQ_MAX_HZ, PWM_FREQ_MIN_HZ};
+
+#[test]
+fn default_frequency_is_exact() {
+    let timing = PwmTiming::for_frequency(PWM_FREQ_HZ).unwrap();
+    assert_eq!(timing, PwmTiming::DEFAULT);
+    assert_eq!(timing, PwmTiming { div_int: 1, top: 12499 });
+    assert_eq!(timing.frequency_hz(), 10_000);
+}
+
+#[test]
+fn low_frequency_uses_divider() {
+    let timing = PwmTiming::for_frequency(PWM_FREQ_MIN_HZ).unwrap();
+    assert_eq!(timing.div_int, 2);
+    assert_eq!(timing.frequency_hz(), 1_000);
+}
+
+#[test]
+fn range_limits() {
+    assert!(PwmTiming::for_frequency(PWM_FREQ_MAX_HZ).is_some());
+    assert_eq!(PwmTiming::for_frequency(PWM_FREQ_MIN_HZ - 1), None);
+    assert_eq!(PwmTiming::for_frequency(PWM_FREQ_MAX_HZ + 1), None);
+    assert_eq!(PwmTiming::for_frequency(0), None);
+}
+
+#[test]
+fn frequency_within_one_percent_across_range() {
+    for freq in (PWM_FREQ_MIN_HZ..=PWM_FREQ_MAX_HZ).step_by(97) {
+        let actual = PwmTiming::for_frequency(freq).unwrap().frequency_hz() as i64;
+        assert!((actual - freq as i64).abs() * 100 <= freq as i64, "{freq} Hz -> {actual} Hz");
+    }
+}
+
+#[test]
+fn min_pulse_duty_grows_with_frequency() {
+    // 2us in a 100us period; 1kHz rounds 0.2% up
+    assert_eq!(PwmTiming::DEFAULT.min_pulse_duty(), 2);
+    assert_eq!(PwmTiming::for_frequency(PWM_FREQ_MIN_HZ).unwrap().min_pulse_duty(), 1);
+    assert_eq!(PwmTiming::for_frequency(PWM_FREQ_MAX_HZ).unwrap().min_pulse_duty(), 8);
+}
@@ -0,0 +1,31 @@
+use rw_core::motor::MotorSpeed;
+use rw_core::selftest::{SelfTest, SELF_TEST_SEQUENCE};
+
+#[test]
+fn first_update_starts_step_zero() {
+    let mut test = SelfTest::new();
+    assert_eq!(test.update(5_000_000), Some((0, SELF_TEST_SEQUENCE[0])));
+    assert_eq!(test.update(5_000_001), None);
+}
+
+#[test]
+fn advances_after_step_duration_and_wraps() {
+    let mut test = SelfTest::new();
+    let mut now_us = 0;
+    test.update(now_us);
+    for i in 1..=SELF_TEST_SEQUENCE.len() {
+        now_us += SELF_TEST_SEQUENCE[i - 1].duration_ms as u64 * 1000;
+        assert_eq!(test.update(now_us - 1), None);
+        let expected = i % SELF_TEST_SEQUENCE.len();
+        assert_eq!(test.update(now_us), Some((expected, SELF_TEST_SEQUENCE[expected])));
+    }
+}
+
+#[test]
+fn sequence_stops_between_direction_changes() {
+    for pair in SELF_TEST_SEQUENCE.windows(2) {
+        let (a, b) = (pair[0].speed.speed_normalized, pair[1].speed.speed_normalized);
+        assert!(a == 0 || b == 0 || (a > 0) == (b > 0));
+    }
+    assert_eq!(SELF_TEST_SEQUENCE.last().unwrap().speed, MotorSpeed::STOP);
+}
@@ -0,0 +1,105 @@
+use rw_core::protocol::{RWSequenceCommandReport, RWSequenceReport, RWSequenceStepReport};
+use rw_core::sequence::{Sequence, SequenceCommand, SequenceError, SequencePlayer, SequenceStep, SEQUENCE_MAX_STEPS};
+use zerocopy::{FromBytes, FromZeros, IntoBytes};
+
+fn report(steps: &[(u32, i16)]) -> RWSequenceReport {
+    let mut report = RWSequenceReport::new_zeroed();
+    report.count = steps.len() as u8;
+    for (out, &(t_ms, speed)) in report.steps.iter_mut().zip(steps) {
+        *out = RWSequenceStepReport::new(t_ms, speed);
+    }
+    report
+}
+
+fn player(steps: &[(u32, i16)]) -> SequencePlayer {
+    let mut player = SequencePlayer::new();
+    player.load(Sequence::from_report(&report(steps)).unwrap());
+    player
+}
+
+#[test]
+fn sequence_report_layout() {
+    let report = report(&[(1000, -2)]);
+    let bytes = report.as_bytes();
+    assert_eq!(bytes.len(), 1 + 6 * SEQUENCE_MAX_STEPS);
+    assert_eq!(&bytes[..7], &[1, 0xE8, 0x03, 0, 0, 0xFE, 0xFF]);
+}
+
+#[test]
+fn sequence_from_report_round_trips() {
+    let sequence = Sequence::from_report(&report(&[(0, 16384), (500, 16384), (1500, i16::MIN)])).unwrap();
+    assert_eq!(
+        sequence.steps(),
+        &[
+            SequenceStep { t_ms: 0, speed_normalized: 16384 },
+            SequenceStep { t_ms: 500, speed_normalized: 16384 },
+            SequenceStep { t_ms: 1500, speed_normalized: -32767 },
+        ]
+    );
+    let bytes = sequence.to_report();
+    assert_eq!(Sequence::from_report(&RWSequenceReport::read_from_bytes(bytes.as_bytes()).unwrap()), Ok(sequence));
+}
+
+#[test]
+fn sequence_report_rejected() {
+    let mut too_many = report(&[]);
+    too_many.count = SEQUENCE_MAX_STEPS as u8 + 1;
+    assert_eq!(Sequence::from_report(&too_many), Err(SequenceError::TooManySteps(9)));
+    assert_eq!(
+        Sequence::from_report(&report(&[(0, 100), (200, 0), (100, 0)])),
+        Err(SequenceError::OutOfOrder(2))
+    );
+}
+
+#[test]
+fn steps_apply_at_their_time() {
+    let mut player = player(&[(0, 8192), (250, -8192), (1000, 0)]);
+    assert!(player.start(5_000_000));
+    assert!(player.is_playing());
+    assert_eq!(player.update(5_000_000), Some(8192));
+    assert_eq!(player.update(5_001_000), None);
+    assert_eq!(player.update(5_249_999), None);
+    assert_eq!(player.update(5_250_000), Some(-8192));
+    assert_eq!(player.update(5_999_000), None);
+    assert_eq!(player.update(6_000_000), Some(0));
+    assert!(!player.is_playing());
+    assert_eq!(player.update(7_000_000), None);
+}
+
+#[test]
+fn overdue_steps_apply_the_latest() {
+    let mut player = player(&[(0, 100), (10, 200), (20, 300), (500, 0)]);
+    player.start(0);
+    assert_eq!(player.update(30_000), Some(300));
+    assert!(player.is_playing());
+}
+
+#[test]
+fn restart_plays_from_the_first_step() {
+    let mut player = player(&[(0, 100), (100, 0)]);
+    player.start(0);
+    assert_eq!(player.update(150_000), Some(0));
+    assert!(player.start(200_000));
+    assert_eq!(player.update(200_000), Some(100));
+}
+
+#[test]
+fn stop_and_load_end_playback() {
+    let mut player = player(&[(0, 100), (100, 0)]);
+    assert!(!player.stop());
+    player.start(0);
+    assert!(player.stop());
+    assert_eq!(player.update(200_000), None);
+
+    player.start(0);
+    assert!(player.load(Sequence::new()));
+    assert!(!player.is_playing());
+    assert!(!player.start(0), "an empty sequence does not play");
+}
+
+#[test]
+fn sequence_command_from_report() {
+    assert_eq!(SequenceCommand::from_report(&RWSequenceCommandReport { command: 1 }), Some(SequenceCommand::Start));
+    assert_eq!(SequenceCommand::from_report(&RWSequenceCommandReport { command: 0 }), Some(SequenceCommand::Stop));
+    assert_eq!(SequenceCommand::from_report(&RWSequenceCommandReport { command: 2 }), None);
+}
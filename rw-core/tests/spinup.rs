@@ -0,0 +1,93 @@
+use rw_core::motor::MotorSpeed;
+use rw_core::protocol::RWSpinUpTestReport;
+use rw_core::spinup::{SpinUpCommand, SpinUpEnd, SpinUpState, SpinUpTest, SPIN_UP_TIMEOUT_MS};
+use zerocopy::FromBytes;
+
+const MAX_RPM: u16 = 6000;
+
+#[test]
+fn command_from_report() {
+    let parse = |bytes: &[u8]| SpinUpCommand::from_report(&RWSpinUpTestReport::read_from_bytes(bytes).unwrap());
+    assert_eq!(parse(&[0, 0, 0]), Some(SpinUpCommand::Abort));
+    assert_eq!(parse(&[1, 0x00, 0x40]), Some(SpinUpCommand::Start(MotorSpeed::new(16384))));
+    assert_eq!(parse(&[1, 0x00, 0x80]), Some(SpinUpCommand::Start(MotorSpeed::new(-32767))));
+    assert_eq!(parse(&[2, 0, 0]), None);
+}
+
+#[test]
+fn times_the_step_to_90_percent() {
+    let mut test = SpinUpTest::new();
+    assert_eq!(test.state(), SpinUpState::Idle);
+
+    // Half scale of a 6000rpm wheel: done at 2700rpm
+    test.start(1_000_000, MotorSpeed::new(16384), 0, MAX_RPM);
+    assert_eq!(test.state(), SpinUpState::Running);
+    assert_eq!(test.update(1_050_000, None), None);
+    assert_eq!(test.update(1_100_000, Some(1500)), None);
+    assert_eq!(test.update(1_200_000, Some(2699)), None);
+    assert_eq!(test.update(1_300_000, Some(2750)), Some(SpinUpEnd::Reached { elapsed_ms: 300 }));
+    assert_eq!(test.state(), SpinUpState::Reached);
+    assert_eq!(test.elapsed_ms(), Some(300));
+    // Done: later measurements change nothing
+    assert_eq!(test.update(1_400_000, Some(0)), None);
+    assert_eq!(test.elapsed_ms(), Some(300));
+}
+
+#[test]
+fn steps_down_and_in_reverse() {
+    // From 3000rpm to a stop: done at 300rpm
+    let mut test = SpinUpTest::new();
+    test.start(0, MotorSpeed::STOP, 3000, MAX_RPM);
+    assert_eq!(test.update(100_000, Some(301)), None);
+    assert_eq!(test.update(200_000, Some(300)), Some(SpinUpEnd::Reached { elapsed_ms: 200 }));
+
+    // Full reverse from rest: done at -5400rpm
+    test.start(0, MotorSpeed::new(-32767), 0, MAX_RPM);
+    assert_eq!(test.update(100_000, Some(5500)), None);
+    assert_eq!(test.update(150_000, Some(-5399)), None);
+    assert_eq!(test.update(200_000, Some(-5400)), Some(SpinUpEnd::Reached { elapsed_ms: 200 }));
+}
+
+#[test]
+fn times_out_if_never_reached() {
+    let mut test = SpinUpTest::new();
+    test.start(0, MotorSpeed::new(32767), 0, MAX_RPM);
+    assert_eq!(test.update(SPIN_UP_TIMEOUT_MS * 1000 - 1, Some(100)), None);
+    assert_eq!(test.update(SPIN_UP_TIMEOUT_MS * 1000, None), Some(SpinUpEnd::TimedOut));
+    assert_eq!(test.state(), SpinUpState::TimedOut);
+    assert_eq!(test.elapsed_ms(), None);
+}
+
+#[test]
+fn new_step_resets_the_measurement() {
+    let mut test = SpinUpTest::new();
+    test.start(0, MotorSpeed::new(16384), 0, MAX_RPM);
+    test.update(200_000, Some(3000));
+    assert_eq!(test.elapsed_ms(), Some(200));
+
+    // Restarted from where the wheel is now, with the previous result dropped: done at 5700rpm
+    test.start(1_000_000, MotorSpeed::new(32767), 3000, MAX_RPM);
+    assert_eq!(test.state(), SpinUpState::Running);
+    assert_eq!(test.elapsed_ms(), None);
+    assert_eq!(test.update(1_100_000, Some(5400)), None);
+    assert_eq!(test.update(1_500_000, Some(5700)), Some(SpinUpEnd::Reached { elapsed_ms: 500 }));
+
+    // A running test restarts its timer too
+    test.start(2_000_000, MotorSpeed::STOP, 5700, MAX_RPM);
+    test.start(3_000_000, MotorSpeed::new(32767), 0, MAX_RPM);
+    assert_eq!(test.update(3_100_000, Some(5400)), Some(SpinUpEnd::Reached { elapsed_ms: 100 }));
+}
+
+#[test]
+fn abort_leaves_no_result_but_keeps_a_finished_one() {
+    let mut test = SpinUpTest::new();
+    test.start(0, MotorSpeed::new(16384), 0, MAX_RPM);
+    assert!(test.abort());
+    assert_eq!(test.state(), SpinUpState::Idle);
+    assert_eq!(test.update(100_000, Some(3000)), None);
+
+    test.start(0, MotorSpeed::new(16384), 0, MAX_RPM);
+    test.update(100_000, Some(3000));
+    assert!(!test.abort());
+    assert_eq!(test.state(), SpinUpState::Reached);
+}
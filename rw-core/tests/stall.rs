@@ -0,0 +1,69 @@
+use rw_core::config::Config;
+use rw_core::stall::{StallEvent, StallMonitor};
+
+const DUTY: u16 = 1500;
+
+fn config(retry: bool) -> Config {
+    Config { stall_window_ms: 300, stall_rpm: 100, stall_retry: retry, ..Config::default() }
+}
+
+#[test]
+fn disabled_by_default() {
+    let mut stall = StallMonitor::new();
+    let config = Config::default();
+    assert_eq!(stall.update(0, DUTY, 0, &config), None);
+    assert_eq!(stall.update(10_000_000, DUTY, 0, &config), None);
+}
+
+#[test]
+fn retries_once_then_latches() {
+    let config = config(true);
+    let mut stall = StallMonitor::new();
+    assert_eq!(stall.update(0, DUTY, 0, &config), None);
+    assert_eq!(stall.update(299_999, DUTY, 0, &config), None);
+    assert_eq!(stall.update(300_000, DUTY, 0, &config), Some(StallEvent::Retry));
+    assert!(!stall.is_stalled());
+
+    // The window restarts for the retry
+    assert_eq!(stall.update(300_001, DUTY, 0, &config), None);
+    assert_eq!(stall.update(600_001, DUTY, 0, &config), Some(StallEvent::Stalled));
+    assert!(stall.is_stalled());
+    assert_eq!(stall.update(900_001, DUTY, 0, &config), None);
+}
+
+#[test]
+fn no_retry_when_disabled() {
+    let config = config(false);
+    let mut stall = StallMonitor::new();
+    stall.update(0, DUTY, 0, &config);
+    assert_eq!(stall.update(300_000, DUTY, 0, &config), Some(StallEvent::Stalled));
+}
+
+#[test]
+fn turning_or_undriven_wheel_is_not_stalled() {
+    let config = config(false);
+    let mut stall = StallMonitor::new();
+    stall.update(0, DUTY, 0, &config);
+    // Speed picks up in time: the window restarts
+    assert_eq!(stall.update(200_000, DUTY, -150, &config), None);
+    assert_eq!(stall.update(400_000, DUTY, 0, &config), None);
+    assert_eq!(stall.update(600_000, 0, 0, &config), None);
+    assert_eq!(stall.update(800_000, DUTY, 0, &config), None);
+    assert_eq!(stall.update(1_100_000, DUTY, 0, &config), Some(StallEvent::Stalled));
+}
+
+#[test]
+fn clear_rearms_the_retry() {
+    let config = config(true);
+    let mut stall = StallMonitor::new();
+    stall.update(0, DUTY, 0, &config);
+    stall.update(300_000, DUTY, 0, &config);
+    stall.update(300_001, DUTY, 0, &config);
+    stall.update(600_001, DUTY, 0, &config);
+    assert!(stall.is_stalled());
+
+    stall.clear();
+    assert!(!stall.is_stalled());
+    stall.update(700_000, DUTY, 0, &config);
+    assert_eq!(stall.update(1_000_000, DUTY, 0, &config), Some(StallEvent::Retry));
+}
@@ -0,0 +1,51 @@
+use rw_core::config::{Config, StopMode};
+use rw_core::storage::{StoredConfig, StoredConfigError, STORED_CONFIG_VERSION};
+use zerocopy::IntoBytes;
+
+fn tuned_config() -> Config {
+    Config {
+        stop_mode: StopMode::Brake,
+        min_duty: 25,
+        kickstart_ms: 300,
+        fwd_scale: 950,
+        ..Config::default()
+    }
+}
+
+#[test]
+fn record_round_trips() {
+    let record = StoredConfig::new(&tuned_config());
+    assert_eq!(StoredConfig::load(record.as_bytes()), Ok(tuned_config()));
+}
+
+#[test]
+fn erased_flash_is_not_a_record() {
+    assert_eq!(StoredConfig::load(&[0xFF; 256]), Err(StoredConfigError::Magic(0xFFFF_FFFF)));
+}
+
+#[test]
+fn short_data_is_truncated() {
+    let record = StoredConfig::new(&Config::default());
+    let bytes = record.as_bytes();
+    assert_eq!(StoredConfig::load(&bytes[..bytes.len() - 1]), Err(StoredConfigError::Truncated));
+}
+
+#[test]
+fn corrupted_record_is_rejected() {
+    let record = StoredConfig::new(&tuned_config());
+    let mut bytes = [0u8; 256];
+    let len = record.as_bytes().len();
+    bytes[..len].copy_from_slice(record.as_bytes());
+    bytes[6] ^= 0x01;
+    assert!(matches!(StoredConfig::load(&bytes), Err(StoredConfigError::Checksum { .. })));
+}
+
+#[test]
+fn other_version_is_rejected() {
+    let mut record = StoredConfig::new(&tuned_config());
+    record.version = STORED_CONFIG_VERSION + 1;
+    assert_eq!(
+        StoredConfig::load(record.as_bytes()),
+        Err(StoredConfigError::Version(STORED_CONFIG_VERSION + 1))
+    );
+}
@@ -0,0 +1,70 @@
+use rw_core::axis::Axis;
+use rw_core::strap::{strap_majority, StrapPolarity, STRAP_MAJORITY, STRAP_SAMPLES};
+
+#[test]
+fn stable_reading_is_accepted() {
+    assert_eq!(strap_majority(&[0b10; STRAP_SAMPLES]), Some(0b10));
+}
+
+#[test]
+fn occasional_glitch_is_outvoted() {
+    let mut samples = [0b11; STRAP_SAMPLES];
+    for sample in samples.iter_mut().take(STRAP_SAMPLES - STRAP_MAJORITY) {
+        *sample = 0b01;
+    }
+    assert_eq!(strap_majority(&samples), Some(0b11));
+}
+
+#[test]
+fn unstable_reading_is_rejected() {
+    let mut samples = [0b11; STRAP_SAMPLES];
+    for sample in samples.iter_mut().take(STRAP_SAMPLES - STRAP_MAJORITY + 1) {
+        *sample = 0b01;
+    }
+    assert_eq!(strap_majority(&samples), None);
+}
+
+#[test]
+fn no_samples_is_rejected() {
+    assert_eq!(strap_majority(&[]), None);
+}
+
+#[test]
+fn pull_up_reads_levels_as_is() {
+    let bits = |gpio0, gpio1| StrapPolarity::PullUp.strap_bits(gpio0, gpio1);
+    assert_eq!(bits(true, true), 0b11);
+    assert_eq!(bits(false, true), 0b10);
+    assert_eq!(bits(true, false), 0b01);
+    assert_eq!(bits(false, false), 0b00);
+}
+
+#[test]
+fn pull_down_maps_to_the_same_axes() {
+    let axis = |gpio0, gpio1| Axis::from_strap(StrapPolarity::PullDown.strap_bits(gpio0, gpio1));
+    // Nothing fitted reads LOW and is still X; both fitted is still the self-test
+    assert_eq!(axis(false, false), Axis::X);
+    assert_eq!(axis(true, false), Axis::Y);
+    assert_eq!(axis(false, true), Axis::Z);
+    assert_eq!(axis(true, true), Axis::Test);
+}
+
+#[test]
+fn pull_down_glitches_are_outvoted_the_same_way() {
+    let mut samples = [StrapPolarity::PullDown.strap_bits(false, false); STRAP_SAMPLES];
+    samples[0] = StrapPolarity::PullDown.strap_bits(true, false);
+    assert_eq!(strap_majority(&samples).map(Axis::from_strap), Some(Axis::X));
+    for sample in samples.iter_mut().take(STRAP_SAMPLES - STRAP_MAJORITY + 1) {
+        *sample = StrapPolarity::PullDown.strap_bits(true, true);
+    }
+    assert_eq!(strap_majority(&samples), None);
+}
+
+#[test]
+fn pin_levels_invert_the_polarity_mapping() {
+    for polarity in [StrapPolarity::PullUp, StrapPolarity::PullDown] {
+        for (gpio0, gpio1) in [(false, false), (true, false), (false, true), (true, true)] {
+            let levels = ((gpio1 as u8) << 1) | gpio0 as u8;
+            assert_eq!(polarity.pin_levels(polarity.strap_bits(gpio0, gpio1)), levels, "{polarity:?}");
+        }
+    }
+}
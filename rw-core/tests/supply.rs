@@ -0,0 +1,72 @@
+use rw_core::config::Config;
+use rw_core::control::MotorController;
+use rw_core::protocol::RWTelemetryReport;
+use rw_core::supply::{
+    adc_to_supply_mv, UndervoltageMonitor, UNDERVOLTAGE_CLEAR_MS, UNDERVOLTAGE_HYSTERESIS_MV,
+    UNDERVOLTAGE_TRIP_MS,
+};
+use zerocopy::IntoBytes;
+
+const MIN_MV: u16 = 4000;
+const TRIP_US: u64 = UNDERVOLTAGE_TRIP_MS as u64 * 1000;
+const CLEAR_US: u64 = UNDERVOLTAGE_CLEAR_MS as u64 * 1000;
+
+#[test]
+fn adc_conversion() {
+    assert_eq!(adc_to_supply_mv(0), 0);
+    // 3.3V at the pin is 9.9V before the divider
+    assert_eq!(adc_to_supply_mv(4095), 9900);
+    // 1V at the pin
+    assert_eq!(adc_to_supply_mv(1241), 3000);
+}
+
+#[test]
+fn short_dip_does_not_trip() {
+    let mut monitor = UndervoltageMonitor::new();
+    assert!(!monitor.update(0, 3000, MIN_MV));
+    assert!(!monitor.update(TRIP_US - 1, 3000, MIN_MV));
+    assert!(!monitor.update(TRIP_US, 4800, MIN_MV));
+    assert!(!monitor.update(TRIP_US + 1, 3000, MIN_MV));
+}
+
+#[test]
+fn sustained_sag_trips() {
+    let mut monitor = UndervoltageMonitor::new();
+    monitor.update(0, 3500, MIN_MV);
+    assert!(monitor.update(TRIP_US, 3500, MIN_MV));
+    assert!(monitor.is_tripped());
+}
+
+#[test]
+fn clears_only_above_hysteresis_band() {
+    let mut monitor = UndervoltageMonitor::new();
+    monitor.update(0, 3500, MIN_MV);
+    monitor.update(TRIP_US, 3500, MIN_MV);
+
+    // Back over the threshold but inside the hysteresis band: stays tripped
+    let inside = MIN_MV + UNDERVOLTAGE_HYSTERESIS_MV / 2;
+    monitor.update(TRIP_US, inside, MIN_MV);
+    assert!(monitor.update(TRIP_US + 2 * CLEAR_US, inside, MIN_MV));
+
+    let above = MIN_MV + UNDERVOLTAGE_HYSTERESIS_MV + 1;
+    let start = TRIP_US + 2 * CLEAR_US;
+    assert!(monitor.update(start, above, MIN_MV));
+    assert!(monitor.update(start + CLEAR_US - 1, above, MIN_MV));
+    assert!(!monitor.update(start + CLEAR_US, above, MIN_MV));
+}
+
+#[test]
+fn zero_threshold_disables() {
+    let mut monitor = UndervoltageMonitor::new();
+    monitor.update(0, 0, MIN_MV);
+    monitor.update(TRIP_US, 0, MIN_MV);
+    assert!(monitor.is_tripped());
+    assert!(!monitor.update(TRIP_US + 1, 0, 0));
+    assert!(!monitor.is_tripped());
+}
+
+#[test]
+fn telemetry_carries_supply_voltage() {
+    let telemetry = RWTelemetryReport::new(0, &MotorController::new(), &Config::default()).with_supply_mv(4870);
+    assert_eq!(&telemetry.as_bytes()[14..16], &4870u16.to_le_bytes());
+}
@@ -0,0 +1,36 @@
+use rw_core::config::Config;
+use rw_core::thermal::{adc_to_decicelsius, max_duty_for_temperature, TemperatureFilter};
+
+#[test]
+fn sensor_conversion() {
+    // 0.706V at 27°C
+    assert_eq!(adc_to_decicelsius(876), 270);
+    // Lower voltage is hotter
+    assert!(adc_to_decicelsius(800) > 270);
+}
+
+#[test]
+fn throttle_curve() {
+    let config = Config { throttle_start_c: 60, throttle_limit_c: 80, ..Config::default() };
+    assert_eq!(max_duty_for_temperature(250, &config), 100);
+    assert_eq!(max_duty_for_temperature(600, &config), 100);
+    assert_eq!(max_duty_for_temperature(700, &config), 50);
+    assert_eq!(max_duty_for_temperature(750, &config), 25);
+    assert_eq!(max_duty_for_temperature(800, &config), 0);
+    assert_eq!(max_duty_for_temperature(1000, &config), 0);
+}
+
+#[test]
+fn filter_tracks_slowly() {
+    let mut filter = TemperatureFilter::new();
+    assert_eq!(filter.decicelsius(), None);
+    assert_eq!(filter.update(876), 270);
+
+    let hot = adc_to_decicelsius(700);
+    let first = filter.update(700);
+    assert!(first > 270 && first < hot);
+    for _ in 0..100 {
+        filter.update(700);
+    }
+    assert!((filter.decicelsius().unwrap() - hot).abs() <= 8);
+}
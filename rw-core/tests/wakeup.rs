@@ -0,0 +1,39 @@
+use rw_core::wakeup::{RemoteWakeup, WAKEUP_MIN_SUSPEND_MS, WAKEUP_RETRY_MS};
+
+#[test]
+fn no_request_while_awake() {
+    let mut wakeup = RemoteWakeup::new();
+    assert!(!wakeup.should_request(1_000_000, true));
+}
+
+#[test]
+fn waits_for_minimum_suspend_time() {
+    let mut wakeup = RemoteWakeup::new();
+    wakeup.suspended(1_000_000);
+    assert!(!wakeup.should_request((1000 + WAKEUP_MIN_SUSPEND_MS) * 1000 - 1, true));
+    assert!(wakeup.should_request((1000 + WAKEUP_MIN_SUSPEND_MS) * 1000, true));
+}
+
+#[test]
+fn nothing_pending_no_request() {
+    let mut wakeup = RemoteWakeup::new();
+    wakeup.suspended(0);
+    assert!(!wakeup.should_request(100_000, false));
+}
+
+#[test]
+fn retries_after_interval() {
+    let mut wakeup = RemoteWakeup::new();
+    wakeup.suspended(0);
+    assert!(wakeup.should_request(100_000, true));
+    assert!(!wakeup.should_request((100 + WAKEUP_RETRY_MS) * 1000 - 1, true));
+    assert!(wakeup.should_request((100 + WAKEUP_RETRY_MS) * 1000, true));
+}
+
+#[test]
+fn resume_stops_requests() {
+    let mut wakeup = RemoteWakeup::new();
+    wakeup.suspended(0);
+    wakeup.resumed();
+    assert!(!wakeup.should_request(100_000, true));
+}
@@ -0,0 +1,135 @@
+use rw_core::config::{Config, ControlMode, StopMode};
+use rw_core::control::{MotorController, CONTROL_TICK_US};
+use rw_core::encoder::RpmEstimator;
+use rw_core::motor::MotorSpeed;
+use rw_core::pid::{normalized_to_rpm, MAX_RPM};
+use rw_core::wheel::{WheelModel, WheelParams};
+
+/// Controller driving the wheel model the way the firmware loop does:
+/// one update per control tick, encoder RPM fed back every `RPM_WINDOW_MS`
+struct Bench {
+    controller: MotorController,
+    wheel: WheelModel,
+    estimator: RpmEstimator,
+    config: Config,
+    now_us: u64,
+    kickstarts: usize,
+}
+
+impl Bench {
+    fn new(config: Config, params: WheelParams) -> Self {
+        Self {
+            controller: MotorController::new(),
+            wheel: WheelModel::new(params),
+            estimator: RpmEstimator::new(),
+            config,
+            now_us: 0,
+            kickstarts: 0,
+        }
+    }
+
+    fn command(&mut self, speed_normalized: i16) {
+        self.controller.set_target(MotorSpeed::new(speed_normalized));
+    }
+
+    fn run(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            let was_kickstarting = self.controller.is_kickstarting();
+            let output = self.controller.update(self.now_us, &self.config);
+            if self.controller.is_kickstarting() && !was_kickstarting {
+                self.kickstarts += 1;
+            }
+            self.wheel.step(output, CONTROL_TICK_US);
+            self.now_us += CONTROL_TICK_US;
+            if let Some(rpm) = self.estimator.update(self.now_us, self.wheel.encoder_count()) {
+                self.controller.set_measured_rpm(rpm, &self.config);
+            }
+        }
+    }
+
+    /// Ticks until the wheel speed is within `tolerance` RPM of `rpm` (None if not within `max_ticks`)
+    fn ticks_to_reach(&mut self, rpm: f32, tolerance: f32, max_ticks: u64) -> Option<u64> {
+        (1..=max_ticks).find(|_| {
+            self.run(1);
+            (self.wheel.rpm() - rpm).abs() <= tolerance
+        })
+    }
+}
+
+#[test]
+fn open_loop_settles_at_driven_speed() {
+    let mut bench = Bench::new(Config::default(), WheelParams::default());
+    bench.command(16384);
+    // 50% duty, minus the friction offset of 300 RPM/s * 0.2 s
+    let expected = bench.wheel.steady_state_rpm(0.5) - 60.0;
+    let ticks = bench.ticks_to_reach(expected, 0.02 * expected, 2000).unwrap();
+    assert!(ticks > 500, "settled after {ticks} ticks, faster than the inertia allows");
+    bench.run(1000);
+    assert!((bench.wheel.rpm() - expected).abs() <= 0.02 * expected);
+    assert_eq!(bench.kickstarts, 1);
+}
+
+#[test]
+fn kickstart_breaks_static_friction() {
+    // 5% maps to min_duty (40%), below the wheel's 45% breakaway duty
+    let config = Config { kickstart_ms: 0, ..Config::default() };
+    let mut bench = Bench::new(config, WheelParams::default());
+    bench.command(1638);
+    bench.run(1000);
+    assert_eq!(bench.wheel.rpm(), 0.0);
+
+    let mut bench = Bench::new(Config::default(), WheelParams::default());
+    bench.command(1638);
+    bench.run(1000);
+    assert!(bench.wheel.rpm() > 1000.0);
+    assert_eq!(bench.kickstarts, 1);
+}
+
+#[test]
+fn low_speed_zero_crossing_does_not_kick() {
+    let params = WheelParams { breakaway_duty: 0.3, ..WheelParams::default() };
+    let config = Config { slew_step: 100, ..Config::default() };
+    let mut bench = Bench::new(config, params);
+    bench.command(1638);
+    bench.run(1000);
+    assert_eq!(bench.kickstarts, 1);
+
+    bench.command(-1638);
+    bench.ticks_to_reach(bench.wheel.steady_state_rpm(-0.4), 200.0, 3000).unwrap();
+    assert_eq!(bench.kickstarts, 1);
+}
+
+#[test]
+fn large_reversal_kicks_once() {
+    let mut bench = Bench::new(Config { slew_step: 100, ..Config::default() }, WheelParams::default());
+    bench.command(16384);
+    bench.run(1000);
+    bench.command(-16384);
+    bench.ticks_to_reach(bench.wheel.steady_state_rpm(-0.5), 200.0, 3000).unwrap();
+    assert_eq!(bench.kickstarts, 2);
+}
+
+#[test]
+fn closed_loop_reaches_target_rpm() {
+    let config = Config { control_mode: ControlMode::ClosedLoop, ..Config::default() };
+    let mut bench = Bench::new(config, WheelParams::default());
+    bench.command(16384);
+    let target = normalized_to_rpm(16384, MAX_RPM as u16) as f32;
+    bench.ticks_to_reach(target, 0.03 * target, 5000).unwrap();
+    bench.run(3000);
+    assert!((bench.wheel.rpm() - target).abs() <= 0.03 * target, "at {} RPM", bench.wheel.rpm());
+}
+
+#[test]
+fn brake_stops_faster_than_coast() {
+    let ticks_to_stop = |stop_mode| {
+        let mut bench = Bench::new(Config { stop_mode, ..Config::default() }, WheelParams::default());
+        bench.command(32767);
+        bench.run(2000);
+        bench.command(0);
+        bench.ticks_to_reach(0.0, 0.0, 60_000).unwrap()
+    };
+    let coast = ticks_to_stop(StopMode::Coast);
+    let brake = ticks_to_stop(StopMode::Brake);
+    assert!(brake * 10 < coast, "brake {brake} ticks, coast {coast} ticks");
+}
@@ -0,0 +1,40 @@
+//! Hold one wheel at a speed from async code and print its status reports
+//!
+//! Usage: cargo run -p rw-host --example async_monitor [SERIAL] [PERCENT]   (default RW-X, 30%)
+
+use std::future::poll_fn;
+use std::time::Duration;
+
+use futures_core::Stream;
+use rw_host::AsyncReactionWheel;
+
+const RESEND_INTERVAL: Duration = Duration::from_millis(100);
+const RUN_TIME: Duration = Duration::from_secs(5);
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), rw_host::Error> {
+    let mut args = std::env::args().skip(1);
+    let serial = args.next().unwrap_or_else(|| "RW-X".to_owned());
+    let percent: f32 = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(30.0);
+
+    let mut wheel = AsyncReactionWheel::open(&serial).await?;
+    let mut telemetry = wheel.telemetry().expect("first call");
+    let mut resend = tokio::time::interval(RESEND_INTERVAL);
+    let deadline = tokio::time::sleep(RUN_TIME);
+    tokio::pin!(deadline);
+    loop {
+        tokio::select! {
+            // Commands must keep coming faster than the firmware's 500ms command timeout
+            _ = resend.tick() => {
+                if let Err(e) = wheel.set_speed_percent(percent).await {
+                    eprintln!("{e}");
+                }
+            }
+            Some(status) = poll_fn(|cx| std::pin::Pin::new(&mut telemetry).poll_next(cx)) => {
+                println!("{:>6} rpm  duty {:>3}%  faults {:#06x}", status.rpm, status.duty, status.faults.bits());
+            }
+            _ = &mut deadline => break,
+        }
+    }
+    wheel.set_speed_percent(0.0).await
+}
@@ -0,0 +1,28 @@
+//! Sweep one wheel from -100% to +100% and stop
+//!
+//! Usage: cargo run -p rw-host --example sweep [SERIAL]   (default RW-X)
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use rw_host::ReactionWheel;
+
+const STEP_PERCENT: usize = 5;
+const STEP_INTERVAL: Duration = Duration::from_millis(200);
+
+fn main() -> Result<(), rw_host::Error> {
+    let serial = std::env::args().nth(1).unwrap_or_else(|| "RW-X".to_owned());
+    let mut wheel = ReactionWheel::open(&serial)?;
+    println!("Sweeping {}", wheel.serial());
+
+    // Commands must keep coming faster than the firmware's 500ms command timeout
+    for percent in (-100..=100).step_by(STEP_PERCENT) {
+        println!("{percent:+4}%");
+        if let Err(e) = wheel.set_speed_percent(percent as f32) {
+            eprintln!("{e}");
+        }
+        sleep(STEP_INTERVAL);
+    }
+
+    wheel.set_speed_percent(0.0)
+}
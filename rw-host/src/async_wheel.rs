@@ -0,0 +1,144 @@
+//! Async driver: a `ReactionWheel` on a background thread
+//!
+//! hidapi only has blocking calls, so each `AsyncReactionWheel` owns a thread
+//! that holds the device, writes the commands sent to it and reads status
+//! reports in between. Commands are answered through a oneshot channel and
+//! status reports are forwarded to a `TelemetryStream`, so the wheel fits into
+//! a tokio (or any other) executor without blocking it.
+
+use std::pin::Pin;
+use std::sync::mpsc as std_mpsc;
+use std::task::{Context, Poll};
+use std::thread;
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{percent_to_normalized, Error, ReactionWheel, Telemetry, Transport};
+
+/// Longest the background thread blocks in a read before it picks up new commands
+pub const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Status reports buffered for a slow stream consumer; newer ones are dropped once it is full
+pub const TELEMETRY_BUFFER: usize = 64;
+
+enum Command {
+    SetSpeed { speed_normalized: i16, reply: oneshot::Sender<Result<(), Error>> },
+}
+
+/// One reaction wheel driven from async code
+///
+/// Dropping it ends the background thread (within `POLL_INTERVAL`) and the
+/// telemetry stream. Like the blocking driver, the wheel stops on its own if
+/// commands stop coming for the firmware's 500ms command timeout.
+pub struct AsyncReactionWheel {
+    serial: String,
+    commands: std_mpsc::Sender<Command>,
+    telemetry: Option<mpsc::Receiver<Telemetry>>,
+}
+
+impl AsyncReactionWheel {
+    /// Open the wheel with USB serial `serial` (e.g. `"RW-X"`) on a new background thread
+    pub async fn open(serial: &str) -> Result<Self, Error> {
+        Self::spawn(serial.to_owned(), ReactionWheel::open).await
+    }
+
+    /// Open HID interface `interface` of the device with USB serial `serial` (see `ReactionWheel::open_interface`)
+    pub async fn open_interface(serial: &str, interface: i32) -> Result<Self, Error> {
+        Self::spawn(serial.to_owned(), move |serial| ReactionWheel::open_interface(serial, interface)).await
+    }
+
+    /// Drive an already opened wheel (on any transport, e.g. `ReactionWheel::mock`) from a new background thread
+    pub async fn from_wheel<T: Transport + Send + 'static>(wheel: ReactionWheel<T>) -> Result<Self, Error> {
+        Self::spawn(wheel.serial().to_owned(), move |_| Ok(wheel)).await
+    }
+
+    async fn spawn<T, F>(serial: String, open: F) -> Result<Self, Error>
+    where
+        T: Transport,
+        F: FnOnce(&str) -> Result<ReactionWheel<T>, Error> + Send + 'static,
+    {
+        let (opened_tx, opened_rx) = oneshot::channel();
+        let (commands, commands_rx) = std_mpsc::channel();
+        let (telemetry_tx, telemetry) = mpsc::channel(TELEMETRY_BUFFER);
+        let name = serial.clone();
+        thread::Builder::new()
+            .name(format!("rw-host {serial}"))
+            .spawn(move || match open(&name) {
+                Ok(wheel) => {
+                    let _ = opened_tx.send(Ok(()));
+                    run(wheel, commands_rx, telemetry_tx);
+                }
+                Err(e) => {
+                    let _ = opened_tx.send(Err(e));
+                }
+            })
+            .map_err(|_| Error::Disconnected(serial.clone()))?;
+        opened_rx.await.map_err(|_| Error::Disconnected(serial.clone()))??;
+        Ok(Self { serial, commands, telemetry: Some(telemetry) })
+    }
+
+    pub fn serial(&self) -> &str {
+        &self.serial
+    }
+
+    /// Command a speed in percent (-100.0..=100.0, clamped); resolves once the report is written
+    pub async fn set_speed_percent(&self, percent: f32) -> Result<(), Error> {
+        self.set_speed_normalized(percent_to_normalized(percent)).await
+    }
+
+    /// Command a normalized speed (-32767..=32767); resolves once the report is written
+    pub async fn set_speed_normalized(&self, speed_normalized: i16) -> Result<(), Error> {
+        let (reply, written) = oneshot::channel();
+        self.commands
+            .send(Command::SetSpeed { speed_normalized, reply })
+            .map_err(|_| Error::Disconnected(self.serial.clone()))?;
+        written.await.map_err(|_| Error::Disconnected(self.serial.clone()))?
+    }
+
+    /// Stream of status reports; only the first call gets it, later calls return None
+    pub fn telemetry(&mut self) -> Option<TelemetryStream> {
+        self.telemetry.take().map(|receiver| TelemetryStream { receiver })
+    }
+}
+
+/// Status reports of one wheel, ending when its `AsyncReactionWheel` is dropped
+///
+/// Reports arriving while the buffer (`TELEMETRY_BUFFER`) is full are dropped.
+/// While the wheel is disconnected nothing arrives; the stream continues after
+/// a reconnect.
+pub struct TelemetryStream {
+    receiver: mpsc::Receiver<Telemetry>,
+}
+
+impl Stream for TelemetryStream {
+    type Item = Telemetry;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Telemetry>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Background thread: write pending commands, then read status until the next poll
+fn run<T: Transport>(mut wheel: ReactionWheel<T>, commands: std_mpsc::Receiver<Command>, telemetry: mpsc::Sender<Telemetry>) {
+    loop {
+        loop {
+            match commands.try_recv() {
+                Ok(Command::SetSpeed { speed_normalized, reply }) => {
+                    let _ = reply.send(wheel.set_speed_normalized(speed_normalized));
+                }
+                Err(std_mpsc::TryRecvError::Empty) => break,
+                Err(std_mpsc::TryRecvError::Disconnected) => return,
+            }
+        }
+        match wheel.read_telemetry(POLL_INTERVAL) {
+            Ok(Some(report)) => {
+                let _ = telemetry.try_send(report);
+            }
+            Ok(None) => {}
+            // Retried by `ReactionWheel` at most once per `RECONNECT_INTERVAL`
+            Err(_) => thread::sleep(POLL_INTERVAL),
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Record the status reports of one or more wheels to CSV
+//!
+//! Usage: rw-log [-o FILE] [SERIAL...]   (default rw-log.csv, all connected wheels)
+//!
+//! Each wheel is read on its own thread; rows are stamped with the time since
+//! the logger started, shared by all wheels, and written (one line at a time,
+//! so stopping with Ctrl-C loses nothing) in arrival order. A wheel that
+//! disconnects gets one gap row and is reopened when it comes back.
+
+use std::fs::File;
+use std::io::{LineWriter, Write};
+use std::process::ExitCode;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rw_host::csv::{csv_row, LogEvent, CSV_HEADER};
+use rw_host::ReactionWheel;
+
+const DEFAULT_OUTPUT: &str = "rw-log.csv";
+
+/// Longest a read blocks; the firmware sends status at least every 100ms by default
+const READ_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Wait between failed reads of a disconnected wheel (reconnects are tried once per second anyway)
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+fn main() -> ExitCode {
+    let mut output = DEFAULT_OUTPUT.to_owned();
+    let mut serials = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => match args.next() {
+                Some(path) => output = path,
+                None => return usage(),
+            },
+            "-h" | "--help" => {
+                usage();
+                return ExitCode::SUCCESS;
+            }
+            _ if arg.starts_with('-') => return usage(),
+            _ => serials.push(arg),
+        }
+    }
+
+    if serials.is_empty() {
+        serials = match rw_host::list_serials() {
+            Ok(serials) if serials.is_empty() => {
+                eprintln!("error: no reaction wheels are connected");
+                return ExitCode::FAILURE;
+            }
+            Ok(serials) => serials,
+            Err(e) => {
+                eprintln!("error: {e}");
+                return ExitCode::FAILURE;
+            }
+        };
+    }
+
+    let mut wheels = Vec::new();
+    for serial in &serials {
+        match ReactionWheel::open(serial) {
+            Ok(wheel) => wheels.push(wheel),
+            Err(e) => {
+                eprintln!("error: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let mut file = match File::create(&output) {
+        Ok(file) => LineWriter::new(file),
+        Err(e) => {
+            eprintln!("error: {output}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = writeln!(file, "{CSV_HEADER}") {
+        eprintln!("error: {output}: {e}");
+        return ExitCode::FAILURE;
+    }
+    eprintln!("logging {} to {output}, Ctrl-C to stop", serials.join(", "));
+
+    let start = Instant::now();
+    let (rows, rows_rx) = mpsc::channel();
+    for wheel in wheels {
+        let rows = rows.clone();
+        thread::spawn(move || record(wheel, start, rows));
+    }
+    drop(rows);
+
+    for row in rows_rx {
+        if let Err(e) = writeln!(file, "{row}") {
+            eprintln!("error: {output}: {e}");
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: rw-log [-o FILE] [SERIAL...]   (default {DEFAULT_OUTPUT}, all connected wheels)");
+    ExitCode::FAILURE
+}
+
+/// Read one wheel until the writer goes away, sending formatted rows
+fn record(mut wheel: ReactionWheel, start: Instant, rows: mpsc::Sender<String>) {
+    let mut connected = true;
+    loop {
+        let row = match wheel.read_telemetry(READ_TIMEOUT) {
+            Ok(Some(telemetry)) => {
+                let event = if connected { LogEvent::Report } else { LogEvent::Reconnected };
+                if !connected {
+                    eprintln!("{}: reconnected", wheel.serial());
+                    connected = true;
+                }
+                csv_row(start.elapsed().as_secs_f64(), wheel.serial(), event, Some(&telemetry))
+            }
+            Ok(None) => continue,
+            Err(_) if !connected => {
+                thread::sleep(RETRY_INTERVAL);
+                continue;
+            }
+            Err(e) => {
+                eprintln!("{e}");
+                connected = false;
+                csv_row(start.elapsed().as_secs_f64(), wheel.serial(), LogEvent::Disconnected, None)
+            }
+        };
+        if rows.send(row).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Spin direction calibration: which way a positive command turns each wheel
+//!
+//! The firmware's per-axis inversion and the way a wheel is mounted both flip
+//! the physical direction, so a positive command does not turn every wheel the
+//! same way about its body axis. The mapping is measured once per wheel: spin
+//! it slowly forward (`probe_rpm`), check which way it turned about the body
+//! axis (right-hand rule), and record a `Direction` for its serial. A
+//! `ReactionWheel` with that direction set (`ReactionWheel::set_direction`)
+//! flips its commands so positive means the positive body axis.
+//!
+//! The encoder RPM from the probe is only evidence for the direction when the
+//! encoder's positive sense is known to match the body axis; the firmware's
+//! encoder polarity calibration (ID 14) aligns it with the drive instead, after
+//! which a forward spin always reads positive. `rw-cli --calibrate-direction`
+//! therefore shows the RPM and asks the person at the bench.
+//!
+//! Calibrations are kept in a text file, one `SERIAL = normal|reversed` line
+//! per wheel (`#` starts a comment), at `default_path()` unless given.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::{percent_to_normalized, Error, ReactionWheel, Transport};
+
+/// Speed of the probe spin, slow enough to be safe on a loose wheel
+pub const PROBE_PERCENT: f32 = 15.0;
+
+/// How long the probe spins; the encoder RPM is measured over 100ms windows
+pub const PROBE_DURATION: Duration = Duration::from_millis(1500);
+
+/// Probe RPM below which the encoder is taken to show no rotation
+pub const PROBE_MIN_RPM: i16 = 50;
+
+/// Interval between repeated probe commands (well inside the firmware's command timeout)
+const PROBE_RESEND_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Which way a positive command turns a wheel about its body axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    /// Positive commands spin the wheel positive about the body axis
+    #[default]
+    Normal,
+    /// Positive commands spin it negative; commands are negated
+    Reversed,
+}
+
+impl Direction {
+    /// Direction shown by an encoder whose positive sense matches the body axis,
+    /// for a forward probe that measured `rpm`; None if it barely moved
+    pub fn from_probe_rpm(rpm: i16) -> Option<Self> {
+        if rpm >= PROBE_MIN_RPM {
+            Some(Direction::Normal)
+        } else if rpm <= -PROBE_MIN_RPM {
+            Some(Direction::Reversed)
+        } else {
+            None
+        }
+    }
+
+    /// Normalized command for a body-frame speed (i16::MIN saturates to the full forward speed)
+    pub fn apply(self, speed_normalized: i16) -> i16 {
+        match self {
+            Direction::Normal => speed_normalized,
+            Direction::Reversed => speed_normalized.saturating_neg(),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Direction::Normal => "normal",
+            Direction::Reversed => "reversed",
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CalibrationError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("line {line}: expected `SERIAL = normal|reversed`, got {text:?}")]
+    Parse { line: usize, text: String },
+}
+
+/// Recorded directions, by USB serial number
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirectionCalibration {
+    directions: BTreeMap<String, Direction>,
+}
+
+impl DirectionCalibration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Direction recorded for `serial`, Normal if it was never calibrated
+    pub fn direction(&self, serial: &str) -> Direction {
+        self.directions.get(serial).copied().unwrap_or_default()
+    }
+
+    pub fn is_calibrated(&self, serial: &str) -> bool {
+        self.directions.contains_key(serial)
+    }
+
+    pub fn set(&mut self, serial: &str, direction: Direction) {
+        self.directions.insert(serial.to_owned(), direction);
+    }
+
+    pub fn parse(text: &str) -> Result<Self, CalibrationError> {
+        let mut calibration = Self::new();
+        for (index, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parsed = line.split_once('=').and_then(|(serial, direction)| {
+                let direction = match direction.trim() {
+                    "normal" => Direction::Normal,
+                    "reversed" => Direction::Reversed,
+                    _ => return None,
+                };
+                let serial = serial.trim();
+                (!serial.is_empty()).then_some((serial, direction))
+            });
+            let Some((serial, direction)) = parsed else {
+                return Err(CalibrationError::Parse { line: index + 1, text: raw.to_owned() });
+            };
+            calibration.set(serial, direction);
+        }
+        Ok(calibration)
+    }
+
+    /// File contents, one line per wheel in serial order
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("# Spin direction per wheel (rw-cli --calibrate-direction)\n");
+        for (serial, direction) in &self.directions {
+            let _ = writeln!(text, "{serial} = {}", direction.as_str());
+        }
+        text
+    }
+
+    /// Read `path`; a missing file is an empty calibration
+    pub fn load(path: &Path) -> Result<Self, CalibrationError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write `path`, creating its directory if needed
+    pub fn save(&self, path: &Path) -> Result<(), CalibrationError> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_text())?;
+        Ok(())
+    }
+}
+
+/// `$XDG_CONFIG_HOME/rw-host/directions.conf` (`~/.config` without it), or the
+/// working directory when neither is set
+pub fn default_path() -> PathBuf {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+    match config_dir {
+        Some(dir) => dir.join("rw-host").join("directions.conf"),
+        None => PathBuf::from("rw-directions.conf"),
+    }
+}
+
+/// Spin `wheel` at `percent` as the firmware sees it (ignoring its direction)
+/// for `duration`, then stop it; returns the last RPM measured while spinning
+///
+/// None if no status report arrived.
+pub fn probe_rpm<T: Transport>(
+    wheel: &mut ReactionWheel<T>,
+    percent: f32,
+    duration: Duration,
+) -> Result<Option<i16>, Error> {
+    let spun = spin(wheel, percent_to_normalized(percent), duration);
+    // Stop after a failure too; the firmware timeout covers a wheel that is gone
+    let stopped = wheel.write_speed(0);
+    let rpm = spun?;
+    stopped?;
+    Ok(rpm)
+}
+
+fn spin<T: Transport>(wheel: &mut ReactionWheel<T>, speed_normalized: i16, duration: Duration) -> Result<Option<i16>, Error> {
+    let start = Instant::now();
+    let mut rpm = None;
+    loop {
+        wheel.write_speed(speed_normalized)?;
+        if start.elapsed() >= duration {
+            return Ok(rpm);
+        }
+        // Reading paces the resends; a status report arrives at least every 100ms by default
+        let resend_at = Instant::now() + PROBE_RESEND_INTERVAL;
+        while let Some(timeout) = resend_at.checked_duration_since(Instant::now()) {
+            if let Some(telemetry) = wheel.read_telemetry(timeout)? {
+                rpm = Some(telemetry.rpm);
+            }
+        }
+    }
+}
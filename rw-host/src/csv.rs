@@ -0,0 +1,56 @@
+//! CSV rows for telemetry logs (`rw-log`)
+//!
+//! One row per status report, timestamped against a clock shared by all logged
+//! wheels so the axes line up. A gap row (event `disconnected`, data columns
+//! empty) marks where a wheel dropped out; empty cells load as NaN in
+//! pandas/numpy, so plots break the line there instead of interpolating.
+
+use std::fmt::Write;
+
+use crate::Telemetry;
+
+pub const CSV_HEADER: &str = "time_s,serial,event,speed_normalized,duty,rpm,temperature_c,faults,flags,heartbeat,uptime_ms";
+
+/// What a log row records besides the telemetry itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogEvent {
+    /// Plain status report
+    Report,
+    /// First status report after a gap
+    Reconnected,
+    /// The wheel stopped answering; no telemetry
+    Disconnected,
+}
+
+impl LogEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogEvent::Report => "",
+            LogEvent::Reconnected => "reconnected",
+            LogEvent::Disconnected => "disconnected",
+        }
+    }
+}
+
+/// Format one row (without the line ending); `telemetry` is None for gap rows
+pub fn csv_row(time_s: f64, serial: &str, event: LogEvent, telemetry: Option<&Telemetry>) -> String {
+    let mut row = format!("{time_s:.3},{serial},{}", event.as_str());
+    match telemetry {
+        Some(t) => {
+            let _ = write!(
+                row,
+                ",{},{},{},{:.1},{},{},{},{}",
+                t.speed_normalized,
+                t.duty,
+                t.rpm,
+                t.temperature_c,
+                t.faults.bits(),
+                t.flags,
+                t.heartbeat,
+                t.uptime_ms
+            );
+        }
+        None => row.push_str(",,,,,,,,"),
+    }
+    row
+}
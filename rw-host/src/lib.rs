@@ -0,0 +1,361 @@
+//! Host-side driver for the pico-rw-mock reaction wheels over USB HID
+//!
+//! Each wheel is a separate Pico that enumerates with VID/PID `0x2E8A`/`0x0B33`
+//! and a serial number naming its axis (`RW-X`, `RW-Y`, `RW-Z`). Reports are
+//! built with the `rw-core` wire formats, so host and firmware cannot drift apart.
+//!
+//! `ReactionWheel` is blocking; `AsyncReactionWheel` runs one on a background
+//! thread for async control loops. `calibration` maps each wheel's spin
+//! direction onto its body axis. `ReactionWheel` is generic over its
+//! `Transport`, USB HID by default; `mock::MockTransport` simulates a wheel for
+//! testing without hardware.
+
+use std::time::{Duration, Instant};
+
+use hidapi::{HidApi, HidDevice, HidError};
+use rw_core::axis::Axis;
+use rw_core::fault::Fault;
+use rw_core::motor::SPEED_NORMALIZED_MAX;
+use rw_core::protocol::{OutputReport, RWStatusReport, SPEED_REPORT_ID};
+use zerocopy::FromBytes;
+
+use crate::calibration::{Direction, DirectionCalibration};
+use crate::mock::MockTransport;
+use crate::transport::HidTransport;
+
+mod async_wheel;
+pub mod calibration;
+pub mod csv;
+pub mod mock;
+pub mod transport;
+pub use async_wheel::{AsyncReactionWheel, TelemetryStream};
+pub use transport::Transport;
+
+/// USB vendor ID (Raspberry Pi)
+pub const VID: u16 = 0x2E8A;
+/// USB product ID of the reaction wheel firmware
+pub const PID: u16 = 0x0B33;
+
+/// HID interface of motor B on firmware built with `dual_motor`
+pub const MOTOR_B_INTERFACE: i32 = 1;
+
+/// Minimum time between reconnection attempts of a disconnected wheel
+pub const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("HID error: {0}")]
+    Hid(#[from] HidError),
+    #[error("no reaction wheel with serial {0:?} is connected")]
+    NotFound(String),
+    #[error("reaction wheel {0:?} is disconnected")]
+    Disconnected(String),
+}
+
+/// Map a speed in percent (-100.0..=100.0, clamped) to the normalized wire value
+///
+/// NaN maps to 0 (stop), as float-to-int casts saturate it to zero. Whole
+/// percents give the same value as `rw_core::motor::percent_to_normalized`.
+pub fn percent_to_normalized(percent: f32) -> i16 {
+    let max = SPEED_NORMALIZED_MAX as f32;
+    (percent.clamp(-100.0, 100.0) / 100.0 * max).round() as i16
+}
+
+/// Wheel state from a status input report (ID 1)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Telemetry {
+    /// Applied normalized speed (-32767..=32767)
+    pub speed_normalized: i16,
+    /// Driven duty (0-100%)
+    pub duty: u8,
+    /// `RWStatusReport::FLAG_*` bits
+    pub flags: u8,
+    /// Measured wheel speed from the encoder
+    pub rpm: i16,
+    /// Chip temperature in °C
+    pub temperature_c: f32,
+    /// Control tick counter (wraps)
+    pub heartbeat: u32,
+    /// Device uptime in ms (wraps)
+    pub uptime_ms: u32,
+    /// Latched faults
+    pub faults: Fault,
+}
+
+impl Telemetry {
+    /// Parse an input report as read from the device (report ID first); None for other reports
+    pub fn from_report(report: &[u8]) -> Option<Self> {
+        let (&id, payload) = report.split_first()?;
+        if id != SPEED_REPORT_ID {
+            return None;
+        }
+        let (status, _) = RWStatusReport::read_from_prefix(payload).ok()?;
+        Some(Self {
+            speed_normalized: status.speed_normalized.get(),
+            duty: status.duty,
+            flags: status.flags,
+            rpm: status.rpm.get(),
+            temperature_c: status.temperature.get() as f32 / 10.0,
+            heartbeat: status.heartbeat.get(),
+            uptime_ms: status.uptime_ms.get(),
+            faults: Fault::from_bits(status.faults.get()),
+        })
+    }
+}
+
+/// Serial numbers of all connected reaction wheels (once per device, even with several interfaces)
+pub fn connected_serials(api: &HidApi) -> Vec<String> {
+    let mut serials: Vec<String> = api
+        .device_list()
+        .filter(|info| info.vendor_id() == VID && info.product_id() == PID)
+        .filter_map(|info| info.serial_number().map(str::to_owned))
+        .collect();
+    serials.sort();
+    serials.dedup();
+    serials
+}
+
+/// Serial numbers of all connected reaction wheels, enumerating the bus afresh
+pub fn list_serials() -> Result<Vec<String>, Error> {
+    Ok(connected_serials(&HidApi::new()?))
+}
+
+/// One reaction wheel, identified by its USB serial number
+///
+/// Over HID (the default transport), a write failure marks the wheel
+/// disconnected; later commands try to reopen it by serial at most once per
+/// `RECONNECT_INTERVAL`.
+pub struct ReactionWheel<T = HidTransport> {
+    serial: String,
+    transport: T,
+    direction: Direction,
+}
+
+impl ReactionWheel {
+    /// Open the wheel with USB serial `serial` (e.g. `"RW-X"`)
+    pub fn open(serial: &str) -> Result<Self, Error> {
+        Self::open_with(&HidApi::new()?, serial)
+    }
+
+    /// Open HID interface `interface` of the device with USB serial `serial`
+    ///
+    /// Dual-motor firmware drives its second wheel through `MOTOR_B_INTERFACE`.
+    pub fn open_interface(serial: &str, interface: i32) -> Result<Self, Error> {
+        Self::open_on(&HidApi::new()?, serial, Some(interface))
+    }
+
+    fn open_with(api: &HidApi, serial: &str) -> Result<Self, Error> {
+        Self::open_on(api, serial, None)
+    }
+
+    fn open_on(api: &HidApi, serial: &str, interface: Option<i32>) -> Result<Self, Error> {
+        Ok(Self::with_transport(serial, HidTransport::open(api, serial, interface)?))
+    }
+}
+
+impl ReactionWheel<MockTransport> {
+    /// A simulated wheel with USB serial `serial` (see `mock`), in real time; `transport()`
+    /// gives the handle to inspect it
+    pub fn mock(serial: &str) -> Self {
+        Self::with_transport(serial, MockTransport::new(serial))
+    }
+}
+
+impl<T: Transport> ReactionWheel<T> {
+    /// Wheel `serial` reached through `transport`
+    pub fn with_transport(serial: &str, transport: T) -> Self {
+        Self {
+            serial: serial.to_owned(),
+            transport,
+            direction: Direction::Normal,
+        }
+    }
+
+    pub fn serial(&self) -> &str {
+        &self.serial
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.transport.is_connected()
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Spin direction of this wheel (see `calibration`); speed commands are
+    /// negated for `Direction::Reversed`, telemetry is left as the wheel reports it
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    /// Command a speed in percent (-100.0..=100.0, clamped)
+    pub fn set_speed_percent(&mut self, percent: f32) -> Result<(), Error> {
+        self.set_speed_normalized(percent_to_normalized(percent))
+    }
+
+    /// Command a normalized speed (-32767..=32767)
+    pub fn set_speed_normalized(&mut self, speed_normalized: i16) -> Result<(), Error> {
+        self.write_speed(self.direction.apply(speed_normalized))
+    }
+
+    // Speed as the firmware sees it, without the direction mapping
+    pub(crate) fn write_speed(&mut self, speed_normalized: i16) -> Result<(), Error> {
+        self.transport.write(&OutputReport::new(speed_normalized).to_report())
+    }
+
+    /// Wait up to `timeout` for the next status report; None on timeout or for other input reports
+    ///
+    /// The firmware sends status on change and at the idle rate (100ms by default).
+    pub fn read_telemetry(&mut self, timeout: Duration) -> Result<Option<Telemetry>, Error> {
+        let mut buf = [0u8; 64];
+        let len = self.transport.read(&mut buf, timeout)?;
+        Ok(Telemetry::from_report(&buf[..len]))
+    }
+
+    fn ensure_connected(&mut self) -> Result<(), Error> {
+        self.transport.ensure_connected()
+    }
+}
+
+pub(crate) fn open_device(api: &HidApi, serial: &str, interface: Option<i32>) -> Option<HidDevice> {
+    let Some(interface) = interface else {
+        return api.open_serial(VID, PID, serial).ok();
+    };
+    api.device_list()
+        .find(|info| {
+            info.vendor_id() == VID
+                && info.product_id() == PID
+                && info.serial_number() == Some(serial)
+                && info.interface_number() == interface
+        })
+        .and_then(|info| info.open_device(api).ok())
+}
+
+/// Wheels found on the bus, by axis
+pub struct AxisSet<T = HidTransport> {
+    pub x: Option<ReactionWheel<T>>,
+    pub y: Option<ReactionWheel<T>>,
+    pub z: Option<ReactionWheel<T>>,
+}
+
+// Only for HID, so `AxisSet::default()` needs no type annotation
+impl Default for AxisSet {
+    fn default() -> Self {
+        Self { x: None, y: None, z: None }
+    }
+}
+
+impl AxisSet {
+    /// Enumerate connected wheels and open each axis that is present
+    pub fn connect() -> Result<Self, Error> {
+        let api = HidApi::new()?;
+        let serials = connected_serials(&api);
+        let open = |axis: Axis| {
+            let serial = axis.serial();
+            serials
+                .iter()
+                .any(|s| s == serial)
+                .then(|| ReactionWheel::open_with(&api, serial).ok())
+                .flatten()
+        };
+        Ok(Self {
+            x: open(Axis::X),
+            y: open(Axis::Y),
+            z: open(Axis::Z),
+        })
+    }
+}
+
+impl AxisSet<MockTransport> {
+    /// Simulated wheels on all three axes (see `mock`)
+    pub fn mock() -> Self {
+        Self {
+            x: Some(ReactionWheel::mock(Axis::X.serial())),
+            y: Some(ReactionWheel::mock(Axis::Y.serial())),
+            z: Some(ReactionWheel::mock(Axis::Z.serial())),
+        }
+    }
+}
+
+impl<T: Transport + Send> AxisSet<T> {
+    /// Set the recorded spin direction of every present wheel
+    pub fn apply_calibration(&mut self, calibration: &DirectionCalibration) {
+        for wheel in [&mut self.x, &mut self.y, &mut self.z].into_iter().flatten() {
+            wheel.set_direction(calibration.direction(wheel.serial()));
+        }
+    }
+
+    /// Wheel for `axis` (None for `Axis::Test` or when it is not connected)
+    pub fn get_mut(&mut self, axis: Axis) -> Option<&mut ReactionWheel<T>> {
+        match axis {
+            Axis::X => self.x.as_mut(),
+            Axis::Y => self.y.as_mut(),
+            Axis::Z => self.z.as_mut(),
+            Axis::Test => None,
+        }
+    }
+
+    /// Command all three axes in percent; missing wheels are skipped
+    ///
+    /// Every present wheel is written even if an earlier one fails; the first error is returned.
+    pub fn set_speed_percent(&mut self, percent: [f32; 3]) -> Result<(), Error> {
+        let mut result = Ok(());
+        for (wheel, percent) in [&mut self.x, &mut self.y, &mut self.z].into_iter().zip(percent) {
+            if let Some(wheel) = wheel {
+                let written = wheel.set_speed_percent(percent);
+                if result.is_ok() {
+                    result = written;
+                }
+            }
+        }
+        result
+    }
+
+    /// Command all three axes at once, in percent; returns when the last write has completed
+    ///
+    /// Unlike `set_speed_percent` this is all or nothing up front: every axis
+    /// must be present and connected (a disconnected wheel gets one reconnect
+    /// attempt), otherwise nothing is written. The three reports are then written
+    /// concurrently, one thread per wheel, because each write waits for its own
+    /// device's next interrupt OUT poll (10ms interval); written one after the
+    /// other, the waits would add up. A write returns once the device has
+    /// acknowledged the transfer, and the firmware applies it on its next 1ms
+    /// tick, so the wheels start within about 11ms of each other.
+    ///
+    /// The returned duration, from the call until the last acknowledgment, is an
+    /// upper bound on that skew (plus the 1ms tick). A write that fails once the
+    /// others are under way cannot be undone; that error is returned and the
+    /// other wheels keep their new speed.
+    pub fn set_all(&mut self, x: f32, y: f32, z: f32) -> Result<Duration, Error> {
+        let wheels = [
+            connected(Axis::X, &mut self.x)?,
+            connected(Axis::Y, &mut self.y)?,
+            connected(Axis::Z, &mut self.z)?,
+        ];
+
+        let start = Instant::now();
+        let results = std::thread::scope(|scope| {
+            let writes = wheels
+                .into_iter()
+                .zip([x, y, z])
+                .map(|(wheel, percent)| scope.spawn(move || wheel.set_speed_percent(percent)))
+                .collect::<Vec<_>>();
+            writes.into_iter().map(|write| write.join().expect("HID write thread panicked")).collect::<Vec<_>>()
+        });
+        let elapsed = start.elapsed();
+        results.into_iter().collect::<Result<(), _>>()?;
+        Ok(elapsed)
+    }
+}
+
+/// The wheel of `axis`, reconnected if needed
+fn connected<T: Transport>(axis: Axis, wheel: &mut Option<ReactionWheel<T>>) -> Result<&mut ReactionWheel<T>, Error> {
+    let wheel = wheel.as_mut().ok_or_else(|| Error::NotFound(axis.serial().to_owned()))?;
+    wheel.ensure_connected()?;
+    Ok(wheel)
+}
@@ -0,0 +1,253 @@
+//! Simulated wheel, for tests and host development without hardware
+//!
+//! `MockTransport` answers like the firmware does: speed reports go through
+//! `rw_core::command::handle_command` into a `MotorController` that drives a
+//! `WheelModel` on the 1ms control tick, with the encoder RPM fed back and
+//! the 500ms command timeout applied, and a status report comes back every
+//! `MOCK_STATUS_INTERVAL` (the firmware's default idle rate). The model runs in
+//! real time, catching up whenever the transport is used, so host code sees
+//! the pacing it would see on the bus. With `with_manual_clock` time only moves
+//! on `advance` and on reads waiting for a report, so tests run at once and
+//! the same every time.
+//!
+//! Clones share one simulated wheel: keep a clone to check the commands
+//! received and the wheel state after handing the transport to a
+//! `ReactionWheel` (`ReactionWheel::mock`, a `MockReactionWheel`).
+
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use rw_core::command::{handle_command, CommandGate, CommandResult};
+use rw_core::config::Config;
+use rw_core::control::{MotorController, MotorOutput, CONTROL_TICK_US};
+use rw_core::deadline::Deadline;
+use rw_core::encoder::RpmEstimator;
+use rw_core::failsafe::is_command_timed_out;
+use rw_core::motor::MotorSpeed;
+use rw_core::protocol::RWStatusReport;
+use rw_core::wheel::{WheelModel, WheelParams};
+
+use crate::transport::Transport;
+use crate::{Error, ReactionWheel};
+
+/// Interval between status reports
+pub const MOCK_STATUS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Chip temperature reported by the mock, in 0.1°C
+const MOCK_TEMPERATURE: i16 = 250;
+
+/// Wheel driver on a simulated wheel
+pub type MockReactionWheel = ReactionWheel<MockTransport>;
+
+/// Transport to a simulated wheel
+#[derive(Clone)]
+pub struct MockTransport {
+    serial: String,
+    wheel: Arc<Mutex<MockWheel>>,
+}
+
+// Where the model time comes from
+enum Clock {
+    // Wall time since the mock was created
+    Real(Instant),
+    // Time in us, moved by `MockTransport::advance` and by reads
+    Manual(u64),
+}
+
+impl Clock {
+    fn now_us(&self) -> u64 {
+        match *self {
+            Clock::Real(start) => start.elapsed().as_micros() as u64,
+            Clock::Manual(now_us) => now_us,
+        }
+    }
+}
+
+struct MockWheel {
+    clock: Clock,
+    // Model time; trails the clock by less than a tick
+    now_us: u64,
+    config: Config,
+    controller: MotorController,
+    output: MotorOutput,
+    model: WheelModel,
+    estimator: RpmEstimator,
+    last_command_us: Option<u64>,
+    next_status: Deadline,
+    // Speeds of the valid speed commands received, as sent
+    commands: Vec<i16>,
+    connected: bool,
+}
+
+impl MockWheel {
+    /// Run control ticks up to the clock
+    fn catch_up(&mut self) {
+        let now_us = self.clock.now_us();
+        while self.now_us + CONTROL_TICK_US <= now_us {
+            if self.last_command_us.is_some_and(|last_us| is_command_timed_out(self.now_us, last_us)) {
+                self.controller.set_target(MotorSpeed::STOP);
+                self.last_command_us = None;
+            }
+            self.output = self.controller.update(self.now_us, &self.config);
+            self.model.step(self.output, CONTROL_TICK_US);
+            self.now_us += CONTROL_TICK_US;
+            if let Some(rpm) = self.estimator.update(self.now_us, self.model.encoder_count()) {
+                self.controller.set_measured_rpm(rpm, &self.config);
+            }
+        }
+    }
+
+    fn status_report(&self) -> [u8; 1 + size_of::<RWStatusReport>()] {
+        let heartbeat = (self.now_us / CONTROL_TICK_US) as u32;
+        RWStatusReport::new(self.controller.applied(), self.output, self.estimator.rpm(), MOCK_TEMPERATURE, 0)
+            .with_liveness(heartbeat, (self.now_us / 1000) as u32)
+            .to_report()
+    }
+}
+
+impl MockTransport {
+    /// Simulated wheel with USB serial `serial`, at rest, with the default config and wheel
+    pub fn new(serial: &str) -> Self {
+        Self::with_params(serial, WheelParams::default())
+    }
+
+    pub fn with_params(serial: &str, params: WheelParams) -> Self {
+        let wheel = MockWheel {
+            clock: Clock::Real(Instant::now()),
+            now_us: 0,
+            config: Config::default(),
+            controller: MotorController::new(),
+            output: MotorOutput::Coast,
+            model: WheelModel::new(params),
+            estimator: RpmEstimator::new(),
+            last_command_us: None,
+            next_status: Deadline::at(0),
+            commands: Vec::new(),
+            connected: true,
+        };
+        Self { serial: serial.to_owned(), wheel: Arc::new(Mutex::new(wheel)) }
+    }
+
+    /// Stop following real time: the model time then only moves on `advance`, and a
+    /// read waiting for the next report moves it on to the report instead of sleeping
+    pub fn with_manual_clock(self) -> Self {
+        {
+            let mut wheel = self.lock();
+            wheel.clock = Clock::Manual(wheel.clock.now_us());
+        }
+        self
+    }
+
+    /// Move a manual clock on by `duration`, running the model up to it
+    ///
+    /// Panics on a mock following real time.
+    pub fn advance(&self, duration: Duration) {
+        let mut wheel = self.lock();
+        let Clock::Manual(now_us) = &mut wheel.clock else {
+            panic!("advance on a real-time mock (see with_manual_clock)");
+        };
+        *now_us += duration.as_micros() as u64;
+        wheel.catch_up();
+    }
+
+    pub fn serial(&self) -> &str {
+        &self.serial
+    }
+
+    /// Speeds of the valid speed commands received so far, as sent (before clamping)
+    pub fn commands(&self) -> Vec<i16> {
+        self.lock().commands.clone()
+    }
+
+    pub fn last_command(&self) -> Option<i16> {
+        self.lock().commands.last().copied()
+    }
+
+    /// Speed the simulated wheel turns at now
+    pub fn rpm(&self) -> f32 {
+        let mut wheel = self.lock();
+        wheel.catch_up();
+        wheel.model.rpm()
+    }
+
+    /// Speed the controller is driving (after slew limiting), as in the status report
+    pub fn applied(&self) -> MotorSpeed {
+        let mut wheel = self.lock();
+        wheel.catch_up();
+        wheel.controller.applied()
+    }
+
+    /// Unplug (false) or replug the simulated wheel; reads and writes fail while unplugged
+    pub fn set_connected(&self, connected: bool) {
+        self.lock().connected = connected;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockWheel> {
+        // A panicking test thread leaves the model usable for the others
+        self.wheel.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn check_connected(&self, wheel: &MockWheel) -> Result<(), Error> {
+        if wheel.connected {
+            Ok(())
+        } else {
+            Err(Error::Disconnected(self.serial.clone()))
+        }
+    }
+}
+
+impl Transport for MockTransport {
+    fn write(&mut self, report: &[u8]) -> Result<(), Error> {
+        let mut wheel = self.lock();
+        self.check_connected(&wheel)?;
+        wheel.catch_up();
+        let wheel = &mut *wheel;
+        // Like the firmware, a rejected report is dropped without an error on the bus
+        if let CommandResult::Command(command) =
+            handle_command(report, &CommandGate::OPEN, &mut wheel.controller, &wheel.config)
+        {
+            wheel.commands.push(command.raw_speed_normalized);
+            wheel.last_command_us = Some(wheel.now_us);
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> Result<usize, Error> {
+        let wait = {
+            let mut wheel = self.lock();
+            self.check_connected(&wheel)?;
+            wheel.catch_up();
+            let wait = Duration::from_micros(wheel.next_status.remaining_us(wheel.clock.now_us())).min(timeout);
+            if let Clock::Manual(now_us) = &mut wheel.clock {
+                *now_us += wait.as_micros() as u64;
+                Duration::ZERO
+            } else {
+                wait
+            }
+        };
+        // Sleep unlocked so clones can inspect the wheel meanwhile
+        std::thread::sleep(wait);
+
+        let mut wheel = self.lock();
+        self.check_connected(&wheel)?;
+        wheel.catch_up();
+        let now_us = wheel.clock.now_us();
+        if !wheel.next_status.is_elapsed(now_us) {
+            return Ok(0);
+        }
+        wheel.next_status = wheel.next_status.next(MOCK_STATUS_INTERVAL.as_micros() as u64, now_us);
+        let report = wheel.status_report();
+        let len = report.len().min(buf.len());
+        buf[..len].copy_from_slice(&report[..len]);
+        Ok(len)
+    }
+
+    fn is_connected(&self) -> bool {
+        self.lock().connected
+    }
+
+    fn ensure_connected(&mut self) -> Result<(), Error> {
+        let wheel = self.lock();
+        self.check_connected(&wheel)
+    }
+}
@@ -0,0 +1,99 @@
+//! Report transport under `ReactionWheel`
+//!
+//! `ReactionWheel` builds and parses the reports; a `Transport` only moves
+//! them. `HidTransport` is the USB HID link to a real wheel and `mock::MockTransport`
+//! a simulated one, so code written against `ReactionWheel<T>` runs on either.
+
+use std::time::{Duration, Instant};
+
+use hidapi::{HidApi, HidDevice};
+
+use crate::{open_device, Error, RECONNECT_INTERVAL};
+
+/// Moves whole reports (report ID first) to and from one wheel
+pub trait Transport {
+    /// Write one output report
+    fn write(&mut self, report: &[u8]) -> Result<(), Error>;
+
+    /// Wait up to `timeout` for one input report and copy it into `buf`; 0 on timeout
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> Result<usize, Error>;
+
+    fn is_connected(&self) -> bool;
+
+    /// Make sure the link is up, reopening a lost one if the transport can
+    fn ensure_connected(&mut self) -> Result<(), Error>;
+}
+
+/// USB HID link to a wheel, identified by its serial number
+///
+/// A failed read or write marks the link lost; the next call tries to reopen
+/// the device by serial, at most once per `RECONNECT_INTERVAL`.
+pub struct HidTransport {
+    serial: String,
+    // HID interface number, None for the first one found (single-motor firmware)
+    interface: Option<i32>,
+    device: Option<HidDevice>,
+    last_attempt: Instant,
+}
+
+impl HidTransport {
+    pub(crate) fn open(api: &HidApi, serial: &str, interface: Option<i32>) -> Result<Self, Error> {
+        let device = open_device(api, serial, interface).ok_or_else(|| Error::NotFound(serial.to_owned()))?;
+        Ok(Self {
+            serial: serial.to_owned(),
+            interface,
+            device: Some(device),
+            last_attempt: Instant::now(),
+        })
+    }
+
+    fn reconnect(&mut self) -> Result<(), Error> {
+        if self.last_attempt.elapsed() < RECONNECT_INTERVAL {
+            return Err(Error::Disconnected(self.serial.clone()));
+        }
+        self.last_attempt = Instant::now();
+        let device = open_device(&HidApi::new()?, &self.serial, self.interface)
+            .ok_or_else(|| Error::Disconnected(self.serial.clone()))?;
+        self.device = Some(device);
+        Ok(())
+    }
+}
+
+impl Transport for HidTransport {
+    fn write(&mut self, report: &[u8]) -> Result<(), Error> {
+        self.ensure_connected()?;
+        let Some(device) = &self.device else {
+            return Err(Error::Disconnected(self.serial.clone()));
+        };
+        if device.write(report).is_err() {
+            self.device = None;
+            return Err(Error::Disconnected(self.serial.clone()));
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, buf: &mut [u8], timeout: Duration) -> Result<usize, Error> {
+        self.ensure_connected()?;
+        let Some(device) = &self.device else {
+            return Err(Error::Disconnected(self.serial.clone()));
+        };
+        match device.read_timeout(buf, timeout.as_millis().try_into().unwrap_or(i32::MAX)) {
+            Ok(len) => Ok(len),
+            Err(_) => {
+                self.device = None;
+                Err(Error::Disconnected(self.serial.clone()))
+            }
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        self.device.is_some()
+    }
+
+    fn ensure_connected(&mut self) -> Result<(), Error> {
+        if self.device.is_none() {
+            self.reconnect()?;
+        }
+        Ok(())
+    }
+}
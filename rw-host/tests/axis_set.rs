@@ -0,0 +1,15 @@
+use rw_host::{AxisSet, Error};
+
+#[test]
+fn set_all_needs_every_axis() {
+    let mut wheels = AxisSet::default();
+    assert!(matches!(wheels.set_all(10.0, 20.0, 30.0), Err(Error::NotFound(serial)) if serial == "RW-X"));
+}
+
+#[test]
+fn set_all_drives_every_axis() {
+    let mut wheels = AxisSet::mock();
+    wheels.set_all(10.0, -20.0, 30.0).unwrap();
+    let sent = [&wheels.x, &wheels.y, &wheels.z].map(|wheel| wheel.as_ref().unwrap().transport().last_command());
+    assert_eq!(sent, [Some(3277), Some(-6553), Some(9830)]);
+}
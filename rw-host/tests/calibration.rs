@@ -0,0 +1,56 @@
+use rw_host::calibration::{CalibrationError, Direction, DirectionCalibration, PROBE_MIN_RPM};
+
+#[test]
+fn reversed_negates_commands() {
+    assert_eq!(Direction::Normal.apply(16384), 16384);
+    assert_eq!(Direction::Reversed.apply(16384), -16384);
+    assert_eq!(Direction::Reversed.apply(-32767), 32767);
+    assert_eq!(Direction::Reversed.apply(i16::MIN), 32767);
+}
+
+#[test]
+fn probe_rpm_sign_gives_the_direction() {
+    assert_eq!(Direction::from_probe_rpm(1200), Some(Direction::Normal));
+    assert_eq!(Direction::from_probe_rpm(-1200), Some(Direction::Reversed));
+    assert_eq!(Direction::from_probe_rpm(PROBE_MIN_RPM - 1), None);
+    assert_eq!(Direction::from_probe_rpm(0), None);
+}
+
+#[test]
+fn file_round_trips() {
+    let mut calibration = DirectionCalibration::new();
+    calibration.set("RW-Y", Direction::Reversed);
+    calibration.set("RW-X", Direction::Normal);
+    let text = calibration.to_text();
+    assert!(text.ends_with("RW-X = normal\nRW-Y = reversed\n"));
+    assert_eq!(DirectionCalibration::parse(&text).unwrap(), calibration);
+}
+
+#[test]
+fn uncalibrated_wheels_are_normal() {
+    let calibration = DirectionCalibration::parse("# bench\n\n  RW-Z=reversed  # flipped mount\n").unwrap();
+    assert_eq!(calibration.direction("RW-Z"), Direction::Reversed);
+    assert_eq!(calibration.direction("RW-X"), Direction::Normal);
+    assert!(!calibration.is_calibrated("RW-X"));
+}
+
+#[test]
+fn bad_lines_are_rejected() {
+    for text in ["RW-X = backwards", "RW-X", " = normal"] {
+        let error = DirectionCalibration::parse(&format!("RW-Y = normal\n{text}\n")).unwrap_err();
+        assert!(matches!(error, CalibrationError::Parse { line: 2, .. }), "{text:?}");
+    }
+}
+
+#[test]
+fn missing_file_is_empty_and_save_creates_the_directory() {
+    let dir = std::env::temp_dir().join(format!("rw-host-calibration-{}", std::process::id()));
+    let path = dir.join("nested").join("directions.conf");
+    assert_eq!(DirectionCalibration::load(&path).unwrap(), DirectionCalibration::new());
+
+    let mut calibration = DirectionCalibration::new();
+    calibration.set("RW-X", Direction::Reversed);
+    calibration.save(&path).unwrap();
+    assert_eq!(DirectionCalibration::load(&path).unwrap(), calibration);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,105 @@
+use std::pin::Pin;
+use std::time::Duration;
+
+use futures_core::Stream;
+use rw_core::motor::MotorSpeed;
+use rw_host::calibration::{probe_rpm, Direction, PROBE_PERCENT};
+use rw_host::mock::{MockReactionWheel, MockTransport, MOCK_STATUS_INTERVAL};
+use rw_host::{AsyncReactionWheel, Error, ReactionWheel};
+
+// Mock on a manual clock, so the tests need not wait for the model
+fn manual_mock(serial: &str) -> MockReactionWheel {
+    ReactionWheel::with_transport(serial, MockTransport::new(serial).with_manual_clock())
+}
+
+#[test]
+fn commands_reach_the_mock() {
+    let mut wheel = ReactionWheel::mock("RW-X");
+    wheel.set_speed_percent(50.0).unwrap();
+    wheel.set_direction(Direction::Reversed);
+    wheel.set_speed_percent(25.0).unwrap();
+    assert_eq!(wheel.transport().commands(), [16384, -8192]);
+}
+
+#[test]
+fn mock_wheel_spins_up_and_reports_it() {
+    let mut wheel = manual_mock("RW-X");
+    let handle = wheel.transport().clone();
+    let mut last = None;
+    // Each read waits out the report interval on the mock's clock
+    for _ in 0..7 {
+        wheel.set_speed_percent(60.0).unwrap();
+        last = wheel.read_telemetry(MOCK_STATUS_INTERVAL).unwrap();
+    }
+    let telemetry = last.expect("no status report");
+    assert_eq!(telemetry.speed_normalized, 19660);
+    // 60% of 2500 counts is 1499, reported truncated
+    assert_eq!(telemetry.duty, 59);
+    assert!(telemetry.rpm > 0, "{telemetry:?}");
+    assert_eq!(telemetry.uptime_ms, 600);
+    assert!(handle.rpm() > 0.0);
+}
+
+#[test]
+fn status_reports_are_paced() {
+    let mut wheel = manual_mock("RW-X");
+    // The first report is due at once, the next one a full interval later
+    assert!(wheel.read_telemetry(Duration::ZERO).unwrap().is_some());
+    assert!(wheel.read_telemetry(Duration::from_millis(10)).unwrap().is_none());
+    let telemetry = wheel.read_telemetry(MOCK_STATUS_INTERVAL).unwrap().expect("no status report");
+    assert_eq!(telemetry.uptime_ms, MOCK_STATUS_INTERVAL.as_millis() as u32);
+}
+
+#[test]
+fn mock_stops_without_commands() {
+    let mut wheel = manual_mock("RW-X");
+    wheel.set_speed_percent(50.0).unwrap();
+    wheel.transport().advance(Duration::from_millis(100));
+    assert_ne!(wheel.transport().applied(), MotorSpeed::STOP);
+    // Past the firmware's 500ms command timeout
+    wheel.transport().advance(Duration::from_millis(500));
+    assert_eq!(wheel.transport().applied(), MotorSpeed::STOP);
+}
+
+#[test]
+fn unplugged_mock_is_disconnected() {
+    let mut wheel = manual_mock("RW-Y");
+    wheel.transport().set_connected(false);
+    assert!(!wheel.is_connected());
+    assert!(matches!(wheel.set_speed_percent(10.0), Err(Error::Disconnected(serial)) if serial == "RW-Y"));
+    assert!(matches!(wheel.read_telemetry(Duration::ZERO), Err(Error::Disconnected(_))));
+
+    wheel.transport().set_connected(true);
+    wheel.set_speed_percent(10.0).unwrap();
+    assert_eq!(wheel.transport().commands(), [3277]);
+}
+
+#[test]
+fn direction_probe_on_a_mock() {
+    // Real time: the probe paces itself by the wall clock
+    let mut wheel = ReactionWheel::mock("RW-Z");
+    let rpm = probe_rpm(&mut wheel, PROBE_PERCENT, Duration::from_millis(600)).unwrap();
+    assert_eq!(rpm.and_then(Direction::from_probe_rpm), Some(Direction::Normal), "{rpm:?}");
+    // Stopped after the probe
+    assert_eq!(wheel.transport().last_command(), Some(0));
+}
+
+#[test]
+#[should_panic(expected = "real-time mock")]
+fn real_time_mock_cannot_be_advanced() {
+    MockTransport::new("RW-X").advance(Duration::from_millis(1));
+}
+
+#[tokio::test]
+async fn async_driver_runs_on_a_mock() {
+    let mock = ReactionWheel::mock("RW-X");
+    let handle = mock.transport().clone();
+    let mut wheel = AsyncReactionWheel::from_wheel(mock).await.unwrap();
+    assert_eq!(wheel.serial(), "RW-X");
+    wheel.set_speed_percent(-50.0).await.unwrap();
+    assert_eq!(handle.last_command(), Some(-16384));
+
+    let mut stream = wheel.telemetry().unwrap();
+    let telemetry = std::future::poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await.unwrap();
+    assert!(telemetry.speed_normalized <= 0, "{telemetry:?}");
+}
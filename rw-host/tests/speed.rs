@@ -0,0 +1,16 @@
+use rw_host::percent_to_normalized;
+
+#[test]
+fn percent_maps_to_full_scale() {
+    assert_eq!(percent_to_normalized(100.0), 32767);
+    assert_eq!(percent_to_normalized(-100.0), -32767);
+    assert_eq!(percent_to_normalized(0.0), 0);
+    assert_eq!(percent_to_normalized(50.0), 16384);
+}
+
+#[test]
+fn out_of_range_is_clamped() {
+    assert_eq!(percent_to_normalized(150.0), 32767);
+    assert_eq!(percent_to_normalized(-1e9), -32767);
+    assert_eq!(percent_to_normalized(f32::NAN), 0);
+}
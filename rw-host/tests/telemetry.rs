@@ -0,0 +1,72 @@
+use rw_core::control::MotorOutput;
+use rw_core::fault::Fault;
+use rw_core::motor::MotorSpeed;
+use rw_core::protocol::RWStatusReport;
+use rw_host::csv::{csv_row, LogEvent, CSV_HEADER};
+use rw_host::{AsyncReactionWheel, Error, Telemetry};
+
+#[test]
+fn telemetry_from_status_report() {
+    let flags = RWStatusReport::FLAG_CLOSED_LOOP;
+    let status = RWStatusReport::new(MotorSpeed::new(-16384), MotorOutput::Reverse(1250), -5400, 423, flags)
+        .with_faults(Fault::STALL)
+        .with_liveness(7, 1500);
+    let telemetry = Telemetry::from_report(&status.to_report()).unwrap();
+    assert_eq!(
+        telemetry,
+        Telemetry {
+            speed_normalized: -16384,
+            duty: 50,
+            flags,
+            rpm: -5400,
+            temperature_c: 42.3,
+            heartbeat: 7,
+            uptime_ms: 1500,
+            faults: Fault::STALL,
+        }
+    );
+}
+
+#[test]
+fn telemetry_ignores_other_reports() {
+    assert_eq!(Telemetry::from_report(&[]), None);
+    assert_eq!(Telemetry::from_report(&[7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]), None);
+    // Status report cut short
+    assert_eq!(Telemetry::from_report(&[1, 0, 0, 0]), None);
+}
+
+#[test]
+fn csv_rows_match_the_header() {
+    let telemetry = Telemetry {
+        speed_normalized: -16384,
+        duty: 50,
+        flags: RWStatusReport::FLAG_CLOSED_LOOP,
+        rpm: -5400,
+        temperature_c: 42.3,
+        heartbeat: 7,
+        uptime_ms: 1500,
+        faults: Fault::STALL,
+    };
+    let columns = CSV_HEADER.split(',').count();
+    let row = csv_row(1.5, "RW-X", LogEvent::Report, Some(&telemetry));
+    assert_eq!(row, format!("1.500,RW-X,,-16384,50,-5400,42.3,32,{},7,1500", RWStatusReport::FLAG_CLOSED_LOOP));
+    assert_eq!(row.split(',').count(), columns);
+
+    let reconnected = csv_row(2.0, "RW-Y", LogEvent::Reconnected, Some(&telemetry));
+    assert!(reconnected.starts_with("2.000,RW-Y,reconnected,-16384,"));
+
+    let gap = csv_row(0.25, "RW-Z", LogEvent::Disconnected, None);
+    assert_eq!(gap, "0.250,RW-Z,disconnected,,,,,,,,");
+    assert_eq!(gap.split(',').count(), columns);
+}
+
+#[tokio::test]
+async fn async_open_reports_missing_wheel() {
+    match AsyncReactionWheel::open("RW-NONE").await {
+        // No HID backend in some build sandboxes
+        Err(Error::NotFound(serial)) => assert_eq!(serial, "RW-NONE"),
+        Err(Error::Hid(_)) => {}
+        Err(e) => panic!("unexpected error {e}"),
+        Ok(_) => panic!("opened a wheel that does not exist"),
+    }
+}
@@ -0,0 +1,315 @@
+//! Rigid-body attitude simulator driven by the three reaction wheels
+//!
+//! The spacecraft is a rigid body with one wheel along each body axis and no
+//! external torque, so total angular momentum is conserved: spinning a wheel up
+//! turns the body the other way. Wheels follow their commanded speed with a
+//! first-order lag, standing in for the firmware's speed loop.
+//!
+//! Body dynamics (body frame, `h` = wheel momentum):
+//!     I ω̇ = -ω × (I ω + h) - ḣ
+//!     q̇ = ½ q ⊗ [ω, 0]
+
+pub mod math;
+
+use rw_core::axis::{Axis, WheelSpec};
+use rw_core::motor::SPEED_NORMALIZED_MAX;
+
+pub use math::{Mat3, Quat, Vec3};
+
+/// rad/s per RPM
+const RPM_TO_RAD_S: f64 = core::f64::consts::TAU / 60.0;
+
+/// Fraction of `WheelParams::max_speed` from which a wheel counts as close to saturation
+pub const SATURATION_WARNING: f64 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub enum SimError {
+    #[error("spacecraft inertia tensor is singular")]
+    SingularInertia,
+    #[error("time step must be positive and finite, got {0}")]
+    TimeStep(f64),
+}
+
+/// One reaction wheel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WheelParams {
+    /// Rotor moment of inertia about its spin axis [kg·m²]
+    pub inertia: f64,
+    /// Wheel speed at full-scale normalized command [rad/s]
+    pub max_speed: f64,
+    /// Speed response time constant [s] (0 = follows the command instantly)
+    pub time_constant: f64,
+}
+
+/// Default wheel speed response time constant [s]
+pub const WHEEL_TIME_CONSTANT: f64 = 0.05;
+
+impl WheelParams {
+    /// Wheel with the inertia and closed-loop full scale of `spec`, as the firmware drives it
+    pub fn from_spec(spec: WheelSpec) -> Self {
+        Self {
+            inertia: spec.inertia_kg_m2 as f64,
+            max_speed: spec.max_rpm as f64 * RPM_TO_RAD_S,
+            time_constant: WHEEL_TIME_CONSTANT,
+        }
+    }
+
+    /// Wheel of the firmware's table for `axis` (`rw_core::axis::WHEEL_SPECS`)
+    pub fn for_axis(axis: Axis) -> Self {
+        Self::from_spec(axis.wheel_spec())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpacecraftParams {
+    /// Body inertia tensor, wheels excluded [kg·m²]
+    pub inertia: Mat3,
+    /// Wheels along body X, Y and Z
+    pub wheels: [WheelParams; 3],
+}
+
+impl SpacecraftParams {
+    /// Wheel on `axis` (None for `Axis::Test`)
+    pub fn wheel(&self, axis: Axis) -> Option<&WheelParams> {
+        match axis {
+            Axis::X => Some(&self.wheels[0]),
+            Axis::Y => Some(&self.wheels[1]),
+            Axis::Z => Some(&self.wheels[2]),
+            Axis::Test => None,
+        }
+    }
+
+    /// Rotor inertia of each wheel as a vector (X, Y, Z) [kg·m²]
+    fn wheel_inertia(&self) -> Vec3 {
+        Vec3::new(self.wheels[0].inertia, self.wheels[1].inertia, self.wheels[2].inertia)
+    }
+}
+
+impl Default for SpacecraftParams {
+    /// 6U CubeSat defaults from the backend config
+    fn default() -> Self {
+        Self {
+            inertia: Mat3::diagonal(0.05, 0.05, 0.02),
+            wheels: [Axis::X, Axis::Y, Axis::Z].map(WheelParams::for_axis),
+        }
+    }
+}
+
+/// Angular momentum stored in the wheels, for momentum management
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WheelMomentum {
+    /// Wheel momentum in the body frame [N·m·s]
+    pub momentum: Vec3,
+    /// |wheel speed| / `WheelParams::max_speed` per wheel (X, Y, Z); 1.0 = no more torque that way
+    pub saturation: [f64; 3],
+}
+
+impl WheelMomentum {
+    /// Whether any wheel is at `SATURATION_WARNING` or beyond (time to desaturate)
+    pub fn near_saturation(&self) -> bool {
+        self.saturation.iter().any(|&fraction| fraction >= SATURATION_WARNING)
+    }
+}
+
+/// Integrated spacecraft and wheel state
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct State {
+    attitude: Quat,
+    /// Body angular velocity [rad/s]
+    omega: Vec3,
+    /// Wheel speeds along body X/Y/Z [rad/s]
+    wheel_speed: Vec3,
+}
+
+pub struct Simulator {
+    params: SpacecraftParams,
+    inertia_inv: Mat3,
+    state: State,
+    /// Commanded wheel speeds [rad/s]
+    wheel_command: Vec3,
+    time: f64,
+}
+
+impl Simulator {
+    /// Spacecraft at rest, identity attitude, wheels stopped
+    pub fn new(params: SpacecraftParams) -> Result<Self, SimError> {
+        let inertia_inv = params.inertia.inverse().ok_or(SimError::SingularInertia)?;
+        Ok(Self {
+            params,
+            inertia_inv,
+            state: State {
+                attitude: Quat::IDENTITY,
+                omega: Vec3::ZERO,
+                wheel_speed: Vec3::ZERO,
+            },
+            wheel_command: Vec3::ZERO,
+            time: 0.0,
+        })
+    }
+
+    pub fn params(&self) -> &SpacecraftParams {
+        &self.params
+    }
+
+    /// Simulated time since start [s]
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Body-to-inertial attitude
+    pub fn attitude(&self) -> Quat {
+        self.state.attitude
+    }
+
+    /// Body angular velocity [rad/s]
+    pub fn angular_velocity(&self) -> Vec3 {
+        self.state.omega
+    }
+
+    /// Wheel speeds along body X/Y/Z [rad/s]
+    pub fn wheel_speeds(&self) -> Vec3 {
+        self.state.wheel_speed
+    }
+
+    pub fn set_attitude(&mut self, attitude: Quat) {
+        self.state.attitude = attitude.normalized();
+    }
+
+    pub fn set_angular_velocity(&mut self, omega: Vec3) {
+        self.state.omega = omega;
+    }
+
+    /// Command one wheel with the normalized speed sent over HID (-32767..=32767)
+    ///
+    /// `Axis::Test` is not mounted on the spacecraft and is ignored.
+    pub fn set_command_normalized(&mut self, axis: Axis, speed_normalized: i16) {
+        let Some(&wheel) = self.params.wheel(axis) else {
+            return;
+        };
+        let speed = speed_normalized as f64 / SPEED_NORMALIZED_MAX as f64 * wheel.max_speed;
+        if wheel.time_constant <= 0.0 {
+            self.set_wheel_speed(axis, speed);
+        } else if let Some(command) = axis_mut(&mut self.wheel_command, axis) {
+            *command = speed;
+        }
+    }
+
+    /// Command all three wheels (X, Y, Z) with normalized speeds
+    pub fn set_commands_normalized(&mut self, speeds: [i16; 3]) {
+        for (axis, speed) in [Axis::X, Axis::Y, Axis::Z].into_iter().zip(speeds) {
+            self.set_command_normalized(axis, speed);
+        }
+    }
+
+    /// Set one wheel to a measured speed (status report `rpm`), bypassing the lag
+    pub fn set_measured_rpm(&mut self, axis: Axis, rpm: i16) {
+        self.set_wheel_speed(axis, rpm as f64 * RPM_TO_RAD_S);
+    }
+
+    /// Jump one wheel (and its command) to `speed`, passing the momentum change to the body
+    fn set_wheel_speed(&mut self, axis: Axis, speed: f64) {
+        let mut speeds = self.state.wheel_speed;
+        let Some(wheel) = axis_mut(&mut speeds, axis) else {
+            return;
+        };
+        *wheel = speed;
+        if let Some(command) = axis_mut(&mut self.wheel_command, axis) {
+            *command = speed;
+        }
+        let delta_h = (speeds - self.state.wheel_speed).component_mul(self.params.wheel_inertia());
+        self.state.omega = self.state.omega - self.inertia_inv * delta_h;
+        self.state.wheel_speed = speeds;
+    }
+
+    /// Total angular momentum in the inertial frame [N·m·s] (constant up to integration error)
+    pub fn total_momentum(&self) -> Vec3 {
+        let s = &self.state;
+        let body = self.params.inertia * s.omega + s.wheel_speed.component_mul(self.params.wheel_inertia());
+        s.attitude.rotate(body)
+    }
+
+    /// Momentum stored in the wheels and how close each is to its top speed
+    pub fn wheel_momentum(&self) -> WheelMomentum {
+        let wheels = &self.params.wheels;
+        let speeds = self.state.wheel_speed;
+        WheelMomentum {
+            momentum: speeds.component_mul(self.params.wheel_inertia()),
+            saturation: [
+                speeds.x.abs() / wheels[0].max_speed,
+                speeds.y.abs() / wheels[1].max_speed,
+                speeds.z.abs() / wheels[2].max_speed,
+            ],
+        }
+    }
+
+    /// Advance by `dt` seconds (one RK4 step)
+    pub fn step(&mut self, dt: f64) -> Result<(), SimError> {
+        if !(dt > 0.0 && dt.is_finite()) {
+            return Err(SimError::TimeStep(dt));
+        }
+        let s0 = self.state;
+        let k1 = self.derivative(&s0);
+        let k2 = self.derivative(&s0.advance(&k1, dt / 2.0));
+        let k3 = self.derivative(&s0.advance(&k2, dt / 2.0));
+        let k4 = self.derivative(&s0.advance(&k3, dt));
+
+        let sum = |f: fn(&State) -> Vec3| (f(&k1) + (f(&k2) + f(&k3)) * 2.0 + f(&k4)) * (dt / 6.0);
+        let attitude = k1
+            .attitude
+            .add(k2.attitude.add(k3.attitude).scale(2.0))
+            .add(k4.attitude)
+            .scale(dt / 6.0);
+        self.state = State {
+            attitude: s0.attitude.add(attitude).normalized(),
+            omega: s0.omega + sum(|s| s.omega),
+            wheel_speed: s0.wheel_speed + sum(|s| s.wheel_speed),
+        };
+        self.time += dt;
+        Ok(())
+    }
+
+    /// State derivative (reusing `State` to hold rates)
+    fn derivative(&self, s: &State) -> State {
+        let lag = |wheel: &WheelParams, command: f64, speed: f64| {
+            if wheel.time_constant > 0.0 {
+                (command - speed) / wheel.time_constant
+            } else {
+                0.0
+            }
+        };
+        let [x, y, z] = &self.params.wheels;
+        let wheel_accel = Vec3::new(
+            lag(x, self.wheel_command.x, s.wheel_speed.x),
+            lag(y, self.wheel_command.y, s.wheel_speed.y),
+            lag(z, self.wheel_command.z, s.wheel_speed.z),
+        );
+        let wheel_inertia = self.params.wheel_inertia();
+        let h_total = self.params.inertia * s.omega + s.wheel_speed.component_mul(wheel_inertia);
+        let torque = -s.omega.cross(h_total) - wheel_accel.component_mul(wheel_inertia);
+        State {
+            attitude: s.attitude.derivative(s.omega),
+            omega: self.inertia_inv * torque,
+            wheel_speed: wheel_accel,
+        }
+    }
+}
+
+/// Component of a per-wheel vector (None for `Axis::Test`)
+fn axis_mut(v: &mut Vec3, axis: Axis) -> Option<&mut f64> {
+    match axis {
+        Axis::X => Some(&mut v.x),
+        Axis::Y => Some(&mut v.y),
+        Axis::Z => Some(&mut v.z),
+        Axis::Test => None,
+    }
+}
+
+impl State {
+    fn advance(&self, rate: &State, dt: f64) -> State {
+        State {
+            attitude: self.attitude.add(rate.attitude.scale(dt)),
+            omega: self.omega + rate.omega * dt,
+            wheel_speed: self.wheel_speed + rate.wheel_speed * dt,
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Minimal 3D vector, matrix and quaternion types for the attitude dynamics
+
+use core::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec3 {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Vec3 {
+    pub const ZERO: Self = Self::new(0.0, 0.0, 0.0);
+
+    pub const fn new(x: f64, y: f64, z: f64) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn dot(self, other: Self) -> f64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(self, other: Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn norm(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// Element-wise product
+    pub fn component_mul(self, other: Self) -> Self {
+        Self::new(self.x * other.x, self.y * other.y, self.z * other.z)
+    }
+}
+
+impl Add for Vec3 {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vec3 {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f64> for Vec3 {
+    type Output = Self;
+    fn mul(self, k: f64) -> Self {
+        Self::new(self.x * k, self.y * k, self.z * k)
+    }
+}
+
+impl Neg for Vec3 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self * -1.0
+    }
+}
+
+/// Row-major 3x3 matrix
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mat3(pub [[f64; 3]; 3]);
+
+impl Mat3 {
+    pub const fn diagonal(x: f64, y: f64, z: f64) -> Self {
+        Self([[x, 0.0, 0.0], [0.0, y, 0.0], [0.0, 0.0, z]])
+    }
+
+    /// Inverse, or None if the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        let m = &self.0;
+        let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+        // Transposed cofactor matrix (adjugate)
+        let adj = [
+            [cofactor(1, 2, 1, 2), -cofactor(0, 2, 1, 2), cofactor(0, 1, 1, 2)],
+            [-cofactor(1, 2, 0, 2), cofactor(0, 2, 0, 2), -cofactor(0, 1, 0, 2)],
+            [cofactor(1, 2, 0, 1), -cofactor(0, 2, 0, 1), cofactor(0, 1, 0, 1)],
+        ];
+        let det = m[0][0] * adj[0][0] + m[0][1] * adj[1][0] + m[0][2] * adj[2][0];
+        if det.abs() < f64::EPSILON {
+            return None;
+        }
+        Some(Self(adj.map(|row| row.map(|v| v / det))))
+    }
+}
+
+impl Mul<Vec3> for Mat3 {
+    type Output = Vec3;
+    fn mul(self, v: Vec3) -> Vec3 {
+        let row = |r: [f64; 3]| r[0] * v.x + r[1] * v.y + r[2] * v.z;
+        Vec3::new(row(self.0[0]), row(self.0[1]), row(self.0[2]))
+    }
+}
+
+/// Unit quaternion, scalar-last (`[x, y, z, w]`, Hamilton convention as in the backend)
+///
+/// Rotates body-frame vectors into the inertial frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quat {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
+}
+
+impl Quat {
+    pub const IDENTITY: Self = Self { x: 0.0, y: 0.0, z: 0.0, w: 1.0 };
+
+    /// Rotation of `angle` radians about `axis` (identity for a zero axis)
+    pub fn from_axis_angle(axis: Vec3, angle: f64) -> Self {
+        let norm = axis.norm();
+        if norm == 0.0 {
+            return Self::IDENTITY;
+        }
+        let (sin, cos) = (angle / 2.0).sin_cos();
+        let v = axis * (sin / norm);
+        Self { x: v.x, y: v.y, z: v.z, w: cos }
+    }
+
+    pub fn conjugate(self) -> Self {
+        Self { x: -self.x, y: -self.y, z: -self.z, w: self.w }
+    }
+
+    pub fn norm(self) -> f64 {
+        (self.x * self.x + self.y * self.y + self.z * self.z + self.w * self.w).sqrt()
+    }
+
+    pub fn normalized(self) -> Self {
+        let norm = self.norm();
+        if norm == 0.0 {
+            return Self::IDENTITY;
+        }
+        self.scale(1.0 / norm)
+    }
+
+    /// Rotation angle in radians (0..=π)
+    pub fn angle(self) -> f64 {
+        let v = Vec3::new(self.x, self.y, self.z).norm();
+        2.0 * v.atan2(self.w.abs())
+    }
+
+    /// Rotate body-frame vector `v` into the inertial frame
+    pub fn rotate(self, v: Vec3) -> Vec3 {
+        let p = self * Quat { x: v.x, y: v.y, z: v.z, w: 0.0 } * self.conjugate();
+        Vec3::new(p.x, p.y, p.z)
+    }
+
+    /// Time derivative for body angular velocity `omega`: q̇ = ½ q ⊗ [ω, 0]
+    pub(crate) fn derivative(self, omega: Vec3) -> Self {
+        (self * Quat { x: omega.x, y: omega.y, z: omega.z, w: 0.0 }).scale(0.5)
+    }
+
+    pub(crate) fn scale(self, k: f64) -> Self {
+        Self { x: self.x * k, y: self.y * k, z: self.z * k, w: self.w * k }
+    }
+
+    pub(crate) fn add(self, other: Self) -> Self {
+        Self { x: self.x + other.x, y: self.y + other.y, z: self.z + other.z, w: self.w + other.w }
+    }
+}
+
+impl Mul for Quat {
+    type Output = Self;
+    /// Hamilton product: `a * b` is rotation `b` followed by `a`
+    fn mul(self, b: Self) -> Self {
+        let a = self;
+        Self {
+            x: a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            y: a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            z: a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+            w: a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+        }
+    }
+}
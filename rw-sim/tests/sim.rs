@@ -0,0 +1,198 @@
+use std::f64::consts::TAU;
+
+use rw_core::axis::{Axis, WheelSpec, WHEEL_SPECS};
+use rw_core::pid::normalized_to_rpm;
+use rw_host::mock::{MockReactionWheel, MockTransport, MOCK_STATUS_INTERVAL};
+use rw_host::ReactionWheel;
+use rw_sim::{Mat3, Quat, SimError, Simulator, SpacecraftParams, Vec3, WheelParams, SATURATION_WARNING};
+
+const DT: f64 = 0.001;
+
+fn run(sim: &mut Simulator, seconds: f64) {
+    for _ in 0..(seconds / DT).round() as usize {
+        sim.step(DT).unwrap();
+    }
+}
+
+#[test]
+fn spinning_a_wheel_turns_the_body_the_other_way() {
+    let params = SpacecraftParams::default();
+    let mut sim = Simulator::new(params).unwrap();
+    sim.set_command_normalized(Axis::Z, 16384);
+    run(&mut sim, 1.0);
+
+    // Momentum exchange: I_zz ω_z = -I_w Ω_z, with no coupling into X/Y
+    let omega = sim.angular_velocity();
+    let wheel = sim.wheel_speeds();
+    assert!(wheel.z > 0.0);
+    let expected = -params.wheels[2].inertia * wheel.z / params.inertia.0[2][2];
+    assert!((omega.z - expected).abs() < 1e-9, "{omega:?} vs {expected}");
+    assert!(omega.x.abs() < 1e-12 && omega.y.abs() < 1e-12);
+}
+
+#[test]
+fn wheel_follows_the_command_with_a_lag() {
+    let params = SpacecraftParams::default();
+    let mut sim = Simulator::new(params).unwrap();
+    sim.set_command_normalized(Axis::X, 32767);
+
+    run(&mut sim, params.wheels[0].time_constant);
+    let ratio = sim.wheel_speeds().x / params.wheels[0].max_speed;
+    assert!((ratio - (1.0 - (-1.0f64).exp())).abs() < 1e-3, "{ratio}");
+
+    run(&mut sim, 1.0);
+    assert!((sim.wheel_speeds().x - params.wheels[0].max_speed).abs() < 1e-3);
+}
+
+#[test]
+fn instant_wheels_exchange_momentum_on_command() {
+    let mut params = SpacecraftParams::default();
+    params.wheels[1].time_constant = 0.0;
+    let mut sim = Simulator::new(params).unwrap();
+    sim.set_command_normalized(Axis::Y, -32767);
+
+    let wheel = params.wheels[1];
+    assert_eq!(sim.wheel_speeds().y, -wheel.max_speed);
+    let expected = wheel.inertia * wheel.max_speed / params.inertia.0[1][1];
+    assert!((sim.angular_velocity().y - expected).abs() < 1e-12);
+}
+
+#[test]
+fn default_wheels_follow_the_firmware_table() {
+    let params = SpacecraftParams::default();
+    for (wheel, spec) in params.wheels.iter().zip(WHEEL_SPECS) {
+        // Full scale in the sim is the firmware's closed-loop target for a full-scale command
+        assert!((wheel.max_speed * 60.0 / TAU - normalized_to_rpm(32767, spec.max_rpm) as f64).abs() < 1e-9);
+        assert_eq!(wheel.inertia, spec.inertia_kg_m2 as f64);
+    }
+    assert_eq!(params.wheel(Axis::Test), None);
+}
+
+#[test]
+fn wheels_can_differ_per_axis() {
+    let mut params = SpacecraftParams::default();
+    params.wheels[0] = WheelParams::from_spec(WheelSpec { max_rpm: 6000, inertia_kg_m2: 1e-5 });
+    for wheel in &mut params.wheels {
+        wheel.time_constant = 0.0;
+    }
+    let mut sim = Simulator::new(params).unwrap();
+    sim.set_commands_normalized([32767, 32767, 0]);
+
+    let speeds = sim.wheel_speeds();
+    assert!((speeds.x - speeds.y / 2.0).abs() < 1e-9, "{speeds:?}");
+    let momentum = sim.wheel_momentum().momentum;
+    assert!((momentum.x - params.wheels[0].inertia * speeds.x).abs() < 1e-15);
+    assert!(params.wheels[0].inertia > 2.0 * params.wheels[1].inertia);
+    assert!((momentum.y - params.wheels[1].inertia * speeds.y).abs() < 1e-15);
+    // Saturated in both cases: each wheel is at its own top speed
+    assert_eq!(sim.wheel_momentum().saturation[..2], [1.0, 1.0]);
+    assert!(sim.total_momentum().norm() < 1e-12);
+}
+
+#[test]
+fn attitude_integrates_constant_rate() {
+    let mut sim = Simulator::new(SpacecraftParams::default()).unwrap();
+    sim.set_angular_velocity(Vec3::new(0.0, 0.0, 0.1));
+    run(&mut sim, 2.0);
+
+    let expected = Quat::from_axis_angle(Vec3::new(0.0, 0.0, 1.0), 0.2);
+    let q = sim.attitude();
+    assert!((q.z - expected.z).abs() < 1e-9 && (q.w - expected.w).abs() < 1e-9, "{q:?}");
+    assert!((sim.time() - 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn tumbling_conserves_inertial_momentum() {
+    let params = SpacecraftParams {
+        inertia: Mat3([[0.05, 0.002, 0.0], [0.002, 0.04, 0.001], [0.0, 0.001, 0.02]]),
+        ..SpacecraftParams::default()
+    };
+    let mut sim = Simulator::new(params).unwrap();
+    sim.set_angular_velocity(Vec3::new(0.3, -0.2, 0.5));
+    sim.set_commands_normalized([10000, -20000, 30000]);
+    let h0 = sim.total_momentum();
+
+    run(&mut sim, 10.0);
+    let drift = (sim.total_momentum() - h0).norm();
+    assert!(drift < 1e-9 * h0.norm().max(1.0), "drift {drift}");
+    assert!((sim.attitude().norm() - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn measured_rpm_sets_wheel_speed() {
+    let mut sim = Simulator::new(SpacecraftParams::default()).unwrap();
+    sim.set_command_normalized(Axis::Y, 32767);
+    let h0 = sim.total_momentum();
+    sim.set_measured_rpm(Axis::X, 6000);
+
+    assert!((sim.wheel_speeds().x - 6000.0 * core::f64::consts::TAU / 60.0).abs() < 1e-9);
+    assert!(sim.angular_velocity().x < 0.0);
+    assert!((sim.total_momentum() - h0).norm() < 1e-15);
+
+    // Held there, not pulled back toward the previous command; other axes keep theirs
+    run(&mut sim, 0.5);
+    assert!((sim.wheel_speeds().x - 6000.0 * core::f64::consts::TAU / 60.0).abs() < 1e-9);
+    assert!(sim.wheel_speeds().y > 0.9 * sim.params().wheels[1].max_speed);
+}
+
+#[test]
+fn test_axis_is_ignored() {
+    let mut sim = Simulator::new(SpacecraftParams::default()).unwrap();
+    sim.set_command_normalized(Axis::Test, 32767);
+    run(&mut sim, 0.5);
+    assert_eq!(sim.wheel_speeds(), Vec3::ZERO);
+}
+
+#[test]
+fn invalid_parameters_are_rejected() {
+    let params = SpacecraftParams { inertia: Mat3::diagonal(0.05, 0.0, 0.02), ..SpacecraftParams::default() };
+    assert_eq!(Simulator::new(params).err(), Some(SimError::SingularInertia));
+
+    let mut sim = Simulator::new(SpacecraftParams::default()).unwrap();
+    assert_eq!(sim.step(0.0), Err(SimError::TimeStep(0.0)));
+    assert!(sim.step(f64::NAN).is_err());
+}
+
+#[test]
+fn wheel_momentum_and_saturation() {
+    let params = SpacecraftParams::default();
+    let mut sim = Simulator::new(params).unwrap();
+    sim.set_measured_rpm(Axis::X, 6000);
+    sim.set_measured_rpm(Axis::Z, -11400);
+
+    let budget = sim.wheel_momentum();
+    assert_eq!(budget.momentum.x, sim.wheel_speeds().x * params.wheels[0].inertia);
+    assert_eq!(budget.momentum.y, 0.0);
+    assert!(budget.momentum.z < 0.0);
+    assert!((budget.saturation[0] - 0.5).abs() < 1e-9);
+    assert!((budget.saturation[2] - 0.95).abs() < 1e-9);
+    assert!(budget.saturation[2] >= SATURATION_WARNING);
+    assert!(budget.near_saturation());
+
+    sim.set_measured_rpm(Axis::Z, 0);
+    assert!(!sim.wheel_momentum().near_saturation());
+}
+
+#[test]
+fn mock_wheel_in_the_loop_turns_the_body() {
+    // The firmware's control logic on a mock wheel stands in for the hardware:
+    // each status report's RPM is fed to the simulator, as with a wheel on the bus
+    let mut wheel: MockReactionWheel =
+        ReactionWheel::with_transport(Axis::Z.serial(), MockTransport::new(Axis::Z.serial()).with_manual_clock());
+    let mut sim = Simulator::new(SpacecraftParams::default()).unwrap();
+    let h0 = sim.total_momentum();
+    let mut last_rpm = 0;
+    for _ in 0..10 {
+        wheel.set_speed_percent(50.0).unwrap();
+        let telemetry = wheel.read_telemetry(MOCK_STATUS_INTERVAL).unwrap().expect("no status report");
+        sim.set_measured_rpm(Axis::Z, telemetry.rpm);
+        run(&mut sim, MOCK_STATUS_INTERVAL.as_secs_f64());
+        last_rpm = telemetry.rpm;
+    }
+
+    assert!(last_rpm > 0);
+    assert!(sim.wheel_speeds().z > 0.0);
+    assert!(sim.angular_velocity().z < 0.0);
+    assert!(sim.attitude().angle() > 0.0);
+    assert!((sim.total_momentum() - h0).norm() < 1e-12);
+}